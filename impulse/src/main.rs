@@ -1,39 +1,981 @@
-// use impulse_actuator::Actuator;
-// use impulse_interface::Interface;
+mod output;
 
-use clap::{App, SubCommand};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use system::config::{ActuatorArgs, InterfaceArgs};
+use system::external_client::External;
+use system::impulse::external::v010::event::Kind;
+use system::impulse::external::v010::{
+    BatchLaunchRequest, EventFilter, Group as GroupMessage, LaunchRequest, Placement, Preference,
+    Profile as ProfileMessage,
+};
+use system::impulse::shared::v010::{Drive, MachineSpec, NetworkInterface, RestartPolicy};
+use system::labels;
+use system::profiles;
+use system::runtime;
+
+use crate::output::Output;
+
+#[derive(Debug, Parser)]
+#[command(name = "impulse", version, about = "MicroVM runner")]
+struct Impulse {
+    #[command(flatten)]
+    connection: Connection,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = Output::Table,
+        help = "Output format"
+    )]
+    output: Output,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Args)]
+struct Connection {
+    #[arg(
+        long,
+        global = true,
+        env = "IMPULSE_ENDPOINT",
+        default_value = "http://127.0.0.1:1284",
+        help = "Endpoint of the external interface"
+    )]
+    endpoint: String,
+    #[arg(
+        long,
+        global = true,
+        env = "IMPULSE_CA_CERTIFICATE",
+        help = "PEM certificate authority used to verify the interface"
+    )]
+    ca_certificate: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        env = "IMPULSE_TOKEN",
+        hide_env_values = true,
+        help = "Bearer token for the external interface"
+    )]
+    token: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(subcommand, about = "Run the interface or actuator in process")]
+    Start(Start),
+    #[command(subcommand, about = "Manage MicroVMs")]
+    Vm(Vm),
+    #[command(subcommand, about = "Inspect and manage nodes")]
+    Node(Node),
+    #[command(subcommand, about = "Manage named MicroVM profiles")]
+    Profile(Profile),
+    #[command(subcommand, about = "Manage declarative MicroVM groups")]
+    Group(Group),
+    #[command(about = "Query recent audit log entries")]
+    Audit {
+        #[arg(long, help = "Only entries for this MicroVM uuid")]
+        vm_id: Option<String>,
+        #[arg(long, help = "Only entries from this caller")]
+        principal: Option<String>,
+        #[arg(long, default_value_t = 100, help = "Maximum number of entries")]
+        limit: u32,
+    },
+    #[command(about = "Show MicroVM usage and quotas per tenant")]
+    Usage {
+        #[arg(long, help = "Tenant to show instead of your own (admin only)")]
+        tenant: Option<String>,
+        #[arg(long, help = "Show every tenant (admin only)")]
+        all: bool,
+    },
+    #[command(about = "Stream VM and node events")]
+    Events {
+        #[arg(long, help = "Only events for this MicroVM uuid")]
+        vm_id: Option<String>,
+        #[arg(long, help = "Only events for this node")]
+        node_id: Option<String>,
+        #[arg(long, value_enum, help = "Only events of this kind (repeatable)")]
+        kind: Vec<EventKind>,
+        #[arg(long, help = "Continue after the event with this resume token")]
+        resume_token: Option<u64>,
+    },
+    #[command(about = "Show system status")]
+    Status,
+    #[command(about = "Show system version")]
+    Version,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum EventKind {
+    VmStateChanged,
+    NodeJoined,
+    NodeLeft,
+    TaskFailed,
+}
+
+impl From<EventKind> for Kind {
+    fn from(kind: EventKind) -> Kind {
+        match kind {
+            EventKind::VmStateChanged => Kind::VmStateChanged,
+            EventKind::NodeJoined => Kind::NodeJoined,
+            EventKind::NodeLeft => Kind::NodeLeft,
+            EventKind::TaskFailed => Kind::TaskFailed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Restart {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl From<Restart> for RestartPolicy {
+    fn from(restart: Restart) -> RestartPolicy {
+        match restart {
+            Restart::Never => RestartPolicy::Never,
+            Restart::OnFailure => RestartPolicy::OnFailure,
+            Restart::Always => RestartPolicy::Always,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Start {
+    #[command(about = "Run the interface")]
+    Interface(Box<InterfaceArgs>),
+    #[command(about = "Run the actuator")]
+    Actuator(Box<ActuatorArgs>),
+}
+
+#[derive(Debug, Subcommand)]
+enum Vm {
+    #[command(about = "Launch a MicroVM")]
+    Launch(Box<LaunchArgs>),
+    #[command(about = "Launch many identical MicroVMs in parallel")]
+    LaunchBatch(Box<BatchArgs>),
+    #[command(about = "Shutdown a MicroVM")]
+    Shutdown {
+        #[arg(help = "Uuid or name of the MicroVM")]
+        name: String,
+    },
+    #[command(about = "Shutdown every MicroVM matching a selector")]
+    ShutdownMatching {
+        #[arg(
+            long,
+            short = 'l',
+            help = "Label selector, e.g. env=prod,zone in (a,b)"
+        )]
+        selector: String,
+    },
+    #[command(about = "Set or remove labels on every MicroVM matching a selector")]
+    Label {
+        #[arg(
+            long,
+            short = 'l',
+            help = "Label selector, e.g. env=prod,zone in (a,b)"
+        )]
+        selector: String,
+        #[arg(
+            long = "set",
+            value_parser = labels::parse_label,
+            help = "Label key=value to set (repeatable)"
+        )]
+        set: Vec<(String, String)>,
+        #[arg(long, help = "Label key to remove (repeatable)")]
+        remove: Vec<String>,
+    },
+    #[command(about = "List MicroVMs")]
+    List {
+        #[arg(
+            long,
+            short = 'l',
+            help = "Label selector, e.g. env=prod,zone in (a,b)"
+        )]
+        selector: Option<String>,
+    },
+}
+
+#[derive(Debug, Args)]
+struct LaunchArgs {
+    #[arg(long, help = "Idempotency key; repeating it returns the same MicroVM")]
+    request_id: Option<String>,
+    #[arg(long, help = "Unique name for the MicroVM")]
+    name: Option<String>,
+    #[command(flatten)]
+    template: TemplateArgs,
+}
+
+#[derive(Debug, Args)]
+struct TemplateArgs {
+    #[arg(
+        long = "label",
+        value_parser = labels::parse_label,
+        help = "Label key=value for the MicroVM (repeatable)"
+    )]
+    labels: Vec<(String, String)>,
+    #[arg(
+        long = "annotation",
+        value_parser = labels::parse_annotation,
+        help = "Annotation key=value for the MicroVM (repeatable)"
+    )]
+    annotations: Vec<(String, String)>,
+    #[arg(
+        long,
+        help = "Node labels required to host the MicroVM, e.g. zone in (a,b)"
+    )]
+    node_selector: Option<String>,
+    #[arg(
+        long,
+        value_parser = preference,
+        help = "Prefer nodes matching [WEIGHT:]SELECTOR (repeatable)"
+    )]
+    prefer: Vec<(String, u32)>,
+    #[arg(
+        long,
+        help = "Only place next to MicroVMs matching this selector (repeatable)"
+    )]
+    affinity: Vec<String>,
+    #[arg(
+        long,
+        help = "Never place next to MicroVMs matching this selector (repeatable)"
+    )]
+    anti_affinity: Vec<String>,
+    #[arg(long, help = "Profile the MicroVM is built from")]
+    profile: Option<String>,
+    #[command(flatten)]
+    overrides: MachineArgs,
+}
+
+#[derive(Debug, Args)]
+struct MachineArgs {
+    #[arg(long, help = "Kernel image file name")]
+    kernel_image: Option<String>,
+    #[arg(long, help = "Initrd image file name")]
+    initrd: Option<String>,
+    #[arg(long, help = "Root filesystem image file name")]
+    root_fs: Option<String>,
+    #[arg(long, help = "Kernel command line")]
+    boot_args: Option<String>,
+    #[arg(long, help = "Number of vCPUs")]
+    vcpus: Option<u32>,
+    #[arg(long, help = "Memory in MiB")]
+    memory_mib: Option<u32>,
+    #[arg(
+        long = "drive",
+        value_parser = drive,
+        help = "Extra drive ID=IMAGE[:ro] (repeatable)"
+    )]
+    drives: Vec<Drive>,
+    #[arg(
+        long = "network",
+        value_parser = network_interface,
+        help = "Network interface IFACE=HOST_DEV[/MAC] (repeatable)"
+    )]
+    network_interfaces: Vec<NetworkInterface>,
+    #[arg(long, value_enum, help = "Restart the MicroVM when it exits")]
+    restart: Option<Restart>,
+}
+
+impl From<MachineArgs> for MachineSpec {
+    fn from(args: MachineArgs) -> MachineSpec {
+        MachineSpec {
+            kernel_image: args.kernel_image.unwrap_or_default(),
+            initrd: args.initrd.unwrap_or_default(),
+            root_fs: args.root_fs.unwrap_or_default(),
+            boot_args: args.boot_args.unwrap_or_default(),
+            vcpus: args.vcpus.unwrap_or_default(),
+            memory_mib: args.memory_mib.unwrap_or_default(),
+            drives: args.drives,
+            network_interfaces: args.network_interfaces,
+            restart_policy: args
+                .restart
+                .map(RestartPolicy::from)
+                .unwrap_or(RestartPolicy::Unspecified) as i32,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct BatchArgs {
+    #[arg(long, help = "Number of MicroVMs to launch")]
+    count: u32,
+    #[arg(long, help = "Shut down every MicroVM of the batch if any fails")]
+    atomic: bool,
+    #[command(flatten)]
+    launch: LaunchArgs,
+}
+
+impl From<BatchArgs> for BatchLaunchRequest {
+    fn from(args: BatchArgs) -> BatchLaunchRequest {
+        BatchLaunchRequest {
+            template: Some(LaunchRequest::from(args.launch)),
+            count: args.count,
+            launches: Vec::new(),
+            atomic: args.atomic,
+        }
+    }
+}
+
+impl From<LaunchArgs> for LaunchRequest {
+    fn from(args: LaunchArgs) -> LaunchRequest {
+        LaunchRequest {
+            request_id: args.request_id.unwrap_or_default(),
+            name: args.name.unwrap_or_default(),
+            ..LaunchRequest::from(args.template)
+        }
+    }
+}
+
+impl From<TemplateArgs> for LaunchRequest {
+    fn from(args: TemplateArgs) -> LaunchRequest {
+        LaunchRequest {
+            request_id: String::new(),
+            name: String::new(),
+            labels: args.labels.into_iter().collect(),
+            annotations: args.annotations.into_iter().collect(),
+            placement: Some(Placement {
+                node_selector: args.node_selector.unwrap_or_default(),
+                preferences: args
+                    .prefer
+                    .into_iter()
+                    .map(|(selector, weight)| Preference { selector, weight })
+                    .collect(),
+                affinity: args.affinity,
+                anti_affinity: args.anti_affinity,
+            }),
+            profile: args.profile.unwrap_or_default(),
+            overrides: Some(MachineSpec::from(args.overrides)),
+        }
+    }
+}
+
+fn overlay_template(template: LaunchRequest, args: TemplateArgs) -> LaunchRequest {
+    let mut template = template;
+    let mut placement = template.placement.unwrap_or_default();
+
+    template.labels.extend(args.labels);
+    template.annotations.extend(args.annotations);
+
+    if let Some(node_selector) = args.node_selector {
+        placement.node_selector = node_selector;
+    }
+
+    if !args.prefer.is_empty() {
+        placement.preferences = args
+            .prefer
+            .into_iter()
+            .map(|(selector, weight)| Preference { selector, weight })
+            .collect();
+    }
+
+    if !args.affinity.is_empty() {
+        placement.affinity = args.affinity;
+    }
+
+    if !args.anti_affinity.is_empty() {
+        placement.anti_affinity = args.anti_affinity;
+    }
+
+    if let Some(profile) = args.profile {
+        template.profile = profile;
+    }
+
+    template.placement = Some(placement);
+    template.overrides = Some(profiles::overlay(
+        &template.overrides.unwrap_or_default(),
+        &MachineSpec::from(args.overrides),
+    ));
+
+    template
+}
+
+fn preference(value: &str) -> Result<(String, u32), String> {
+    match value.split_once(':') {
+        Some((weight, selector)) => match weight.trim().parse() {
+            Ok(weight) => Ok((selector.trim().to_string(), weight)),
+            Err(_) => Err(format!("Weight {} is not a number!", weight)),
+        },
+        None => Ok((value.to_string(), 1)),
+    }
+}
+
+fn drive(value: &str) -> Result<Drive, String> {
+    let (drive_id, image) = match value.split_once('=') {
+        Some((drive_id, image)) if !drive_id.is_empty() && !image.is_empty() => (drive_id, image),
+        _ => return Err(format!("Drive {} is not ID=IMAGE[:ro]!", value)),
+    };
+    let (image, read_only) = match image.strip_suffix(":ro") {
+        Some(image) => (image, true),
+        None => (image, false),
+    };
+
+    Ok(Drive {
+        drive_id: drive_id.to_string(),
+        image: image.to_string(),
+        read_only,
+    })
+}
+
+fn network_interface(value: &str) -> Result<NetworkInterface, String> {
+    let (iface_id, host_dev_name) = match value.split_once('=') {
+        Some((iface_id, host_dev_name)) if !iface_id.is_empty() && !host_dev_name.is_empty() => {
+            (iface_id, host_dev_name)
+        }
+        _ => return Err(format!("Network {} is not IFACE=HOST_DEV[/MAC]!", value)),
+    };
+    let (host_dev_name, guest_mac) = host_dev_name.split_once('/').unwrap_or((host_dev_name, ""));
+
+    Ok(NetworkInterface {
+        iface_id: iface_id.to_string(),
+        host_dev_name: host_dev_name.to_string(),
+        guest_mac: guest_mac.to_string(),
+    })
+}
+
+#[derive(Debug, Subcommand)]
+enum Profile {
+    #[command(about = "List profiles")]
+    List,
+    #[command(about = "Show a profile")]
+    Get {
+        #[arg(help = "Name of the profile")]
+        name: String,
+    },
+    #[command(about = "Create a profile")]
+    Create {
+        #[arg(help = "Name of the profile")]
+        name: String,
+        #[arg(long, default_value = "", help = "What the profile is for")]
+        description: String,
+        #[command(flatten)]
+        machine: MachineArgs,
+    },
+    #[command(about = "Change fields of a profile, keeping the rest")]
+    Update {
+        #[arg(help = "Name of the profile")]
+        name: String,
+        #[arg(long, help = "What the profile is for")]
+        description: Option<String>,
+        #[command(flatten)]
+        machine: MachineArgs,
+    },
+    #[command(about = "Delete a profile")]
+    Delete {
+        #[arg(help = "Name of the profile")]
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Group {
+    #[command(about = "List groups")]
+    List,
+    #[command(about = "Show a group and its rollout status")]
+    Get {
+        #[arg(help = "Name of the group")]
+        name: String,
+    },
+    #[command(about = "Create a group of identical MicroVMs")]
+    Create {
+        #[arg(help = "Name of the group")]
+        name: String,
+        #[arg(long, help = "Number of MicroVMs to keep running")]
+        replicas: u32,
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "MicroVMs launched or stopped at a time while rolling"
+        )]
+        max_surge: u32,
+        #[command(flatten)]
+        template: Box<TemplateArgs>,
+    },
+    #[command(about = "Change fields of a group and roll its MicroVMs over")]
+    Update {
+        #[arg(help = "Name of the group")]
+        name: String,
+        #[arg(long, help = "Number of MicroVMs to keep running")]
+        replicas: Option<u32>,
+        #[arg(long, help = "MicroVMs launched or stopped at a time while rolling")]
+        max_surge: Option<u32>,
+        #[command(flatten)]
+        template: Box<TemplateArgs>,
+    },
+    #[command(about = "Change the number of MicroVMs of a group")]
+    Scale {
+        #[arg(help = "Name of the group")]
+        name: String,
+        #[arg(help = "Number of MicroVMs to keep running")]
+        replicas: u32,
+    },
+    #[command(about = "Delete a group and shut down its MicroVMs")]
+    Delete {
+        #[arg(help = "Name of the group")]
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Node {
+    #[command(about = "List registered nodes")]
+    List {
+        #[arg(long, short = 'l', help = "Label selector, e.g. zone=a,!gpu")]
+        selector: Option<String>,
+    },
+    #[command(about = "Stop scheduling new MicroVMs on a node")]
+    Drain {
+        #[arg(help = "Id of the node")]
+        node_id: String,
+        #[arg(long, help = "Resume scheduling on the node")]
+        undo: bool,
+    },
+    #[command(about = "Remove a node from the registry")]
+    Delist {
+        #[arg(help = "Id of the node")]
+        node_id: String,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let name = env!("CARGO_PKG_NAME");
-    let author = env!("CARGO_PKG_AUTHORS");
-    let version = env!("CARGO_PKG_VERSION");
+    let impulse = Impulse::parse();
 
-    let start = SubCommand::with_name("start")
-        .display_order(1)
-        .about("Start");
-    let shutdown = SubCommand::with_name("shutdown")
-        .display_order(2)
-        .about("Shutdown");
+    match impulse.command {
+        Command::Start(Start::Interface(args)) => runtime::interface(args.load().await?).await,
+        Command::Start(Start::Actuator(args)) => runtime::actuator(args.load().await?).await,
+        command => request(&impulse.connection, impulse.output, command).await,
+    }
+}
 
-    let impulse = App::new(name)
-        .author(author)
-        .version(version)
-        .about("MicroVM runner")
-        .subcommand(start)
-        .subcommand(shutdown)
-        .get_matches();
+async fn request(
+    connection: &Connection,
+    output: Output,
+    command: Command,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = External::init(
+        &connection.endpoint,
+        connection.ca_certificate.as_deref(),
+        connection.token.as_deref(),
+    )
+    .await?;
+
+    let rendered = match command {
+        Command::Vm(Vm::Launch(args)) => {
+            output.launch(&client.launch_vm(LaunchRequest::from(*args)).await?)
+        }
+        Command::Vm(Vm::LaunchBatch(args)) => {
+            let count = args.count;
+            let mut results = client.launch_vms(BatchLaunchRequest::from(*args)).await?;
+            let mut launched = HashMap::with_capacity(count as usize);
 
-    match impulse.subcommand_name() {
-        Some("start") => {
-            println!("do starty things");
+            while let Some(result) = results.message().await? {
+                println!("{}", output.batch_result(&result));
 
-            // Actuator::init().await?;
-            // Interface::init().await?;
+                launched.insert(result.index, result.launched);
+            }
+
+            let failed = count as usize - launched.values().filter(|launched| **launched).count();
+
+            return match failed {
+                0 => Ok(()),
+                failed => {
+                    Err(format!("{} of {} MicroVMs were not launched!", failed, count).into())
+                }
+            };
         }
-        Some("shutdown") => println!("do shutdowny things"),
-        _ => println!("{}", impulse.usage()),
-    }
+        Command::Vm(Vm::Shutdown { name }) => output.shutdown(&client.shutdown_vm(&name).await?),
+        Command::Vm(Vm::ShutdownMatching { selector }) => {
+            output.bulk(&client.shutdown_vms(&selector).await?)
+        }
+        Command::Vm(Vm::Label {
+            selector,
+            set,
+            remove,
+        }) => output.bulk(
+            &client
+                .label_vms(&selector, set.into_iter().collect(), remove)
+                .await?,
+        ),
+        Command::Vm(Vm::List { selector }) => {
+            output.vms(&client.list_vms(selector.as_deref()).await?)
+        }
+        Command::Node(Node::List { selector }) => {
+            output.nodes(&client.list_nodes(selector.as_deref()).await?)
+        }
+        Command::Node(Node::Drain { node_id, undo }) => {
+            output.node(client.drain_node(&node_id, !undo).await?)
+        }
+        Command::Node(Node::Delist { node_id }) => output.node(client.delist_node(&node_id).await?),
+        Command::Profile(Profile::List) => output.profiles(&client.list_profiles().await?),
+        Command::Profile(Profile::Get { name }) => output.profile(client.get_profile(&name).await?),
+        Command::Profile(Profile::Create {
+            name,
+            description,
+            machine,
+        }) => {
+            let profile = ProfileMessage {
+                name,
+                description,
+                machine: Some(MachineSpec::from(machine)),
+                ..ProfileMessage::default()
+            };
+
+            output.profile(client.create_profile(profile).await?)
+        }
+        Command::Profile(Profile::Update {
+            name,
+            description,
+            machine,
+        }) => {
+            let mut profile = client.get_profile(&name).await?;
+
+            profile.machine = Some(profiles::overlay(
+                &profile.machine.unwrap_or_default(),
+                &MachineSpec::from(machine),
+            ));
+
+            if let Some(description) = description {
+                profile.description = description;
+            }
+
+            output.profile(client.update_profile(profile).await?)
+        }
+        Command::Profile(Profile::Delete { name }) => {
+            output.profile(client.delete_profile(&name).await?)
+        }
+        Command::Group(Group::List) => output.groups(&client.list_groups().await?),
+        Command::Group(Group::Get { name }) => output.group(client.get_group(&name).await?),
+        Command::Group(Group::Create {
+            name,
+            replicas,
+            max_surge,
+            template,
+        }) => {
+            let group = GroupMessage {
+                name,
+                replicas,
+                max_surge,
+                template: Some(LaunchRequest::from(*template)),
+                ..GroupMessage::default()
+            };
+
+            output.group(client.create_group(group).await?)
+        }
+        Command::Group(Group::Update {
+            name,
+            replicas,
+            max_surge,
+            template,
+        }) => {
+            let mut group = client.get_group(&name).await?;
+
+            group.template = Some(overlay_template(
+                group.template.unwrap_or_default(),
+                *template,
+            ));
+
+            if let Some(replicas) = replicas {
+                group.replicas = replicas;
+            }
+
+            if let Some(max_surge) = max_surge {
+                group.max_surge = max_surge;
+            }
+
+            output.group(client.update_group(group).await?)
+        }
+        Command::Group(Group::Scale { name, replicas }) => {
+            output.group(client.scale_group(&name, replicas).await?)
+        }
+        Command::Group(Group::Delete { name }) => output.group(client.delete_group(&name).await?),
+        Command::Audit {
+            vm_id,
+            principal,
+            limit,
+        } => output.audit(
+            &client
+                .query_audit(vm_id.as_deref(), principal.as_deref(), limit)
+                .await?,
+        ),
+        Command::Usage { tenant, all } => {
+            output.usage(&client.query_usage(tenant.as_deref(), all).await?)
+        }
+        Command::Events {
+            vm_id,
+            node_id,
+            kind,
+            resume_token,
+        } => {
+            let filter = EventFilter {
+                vm_id: vm_id.unwrap_or_default(),
+                node_id: node_id.unwrap_or_default(),
+                kinds: kind
+                    .into_iter()
+                    .map(|kind| Kind::from(kind) as i32)
+                    .collect(),
+                resume_token: resume_token.unwrap_or_default(),
+            };
+            let mut events = client.watch_events(filter).await?;
+
+            while let Some(event) = events.message().await? {
+                println!("{}", output.event(&event));
+            }
+
+            return Ok(());
+        }
+        Command::Status => output.status(&client.system_status().await?),
+        Command::Version => output.version(&client.system_version().await?),
+        Command::Start(_) => return Ok(()),
+    };
+
+    println!("{}", rendered);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let test_impulse =
+            Impulse::try_parse_from(["impulse", "vm", "shutdown", "test_uuid", "--output", "json"])
+                .unwrap();
+        assert_eq!(test_impulse.output, Output::Json);
+        assert_eq!(
+            test_impulse.connection.endpoint.as_str(),
+            "http://127.0.0.1:1284"
+        );
+        assert!(matches!(
+            test_impulse.command,
+            Command::Vm(Vm::Shutdown { name }) if name == "test_uuid"
+        ));
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "start",
+            "interface",
+            "--external",
+            "unix:/run/impulse/external.sock",
+        ])
+        .unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Start(Start::Interface(_))
+        ));
+        let test_impulse =
+            Impulse::try_parse_from(["impulse", "node", "drain", "test_node", "--undo"]).unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Node(Node::Drain { node_id, undo: true }) if node_id == "test_node"
+        ));
+        let test_impulse =
+            Impulse::try_parse_from(["impulse", "audit", "--vm-id", "test_uuid"]).unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Audit { vm_id: Some(vm_id), principal: None, limit: 100 } if vm_id == "test_uuid"
+        ));
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "events",
+            "--kind",
+            "node-joined",
+            "--kind",
+            "task-failed",
+            "--resume-token",
+            "7",
+        ])
+        .unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Events { vm_id: None, kind, resume_token: Some(7), .. }
+                if kind == vec![EventKind::NodeJoined, EventKind::TaskFailed]
+        ));
+        assert!(Impulse::try_parse_from(["impulse", "vm", "shutdown"]).is_err());
+        let test_impulse =
+            Impulse::try_parse_from(["impulse", "usage", "--tenant", "test_team"]).unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Usage { tenant: Some(tenant), all: false } if tenant == "test_team"
+        ));
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "vm",
+            "label",
+            "-l",
+            "env=prod,zone in (a,b)",
+            "--set",
+            "owner=ops",
+            "--remove",
+            "tier",
+        ])
+        .unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Vm(Vm::Label { selector, set, remove })
+                if selector == "env=prod,zone in (a,b)"
+                    && set == vec![(String::from("owner"), String::from("ops"))]
+                    && remove == vec![String::from("tier")]
+        ));
+        assert!(Impulse::try_parse_from(["impulse", "vm", "launch", "--label", "env"]).is_err());
+        assert!(Impulse::try_parse_from(["impulse", "vm", "shutdown-matching"]).is_err());
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "vm",
+            "launch",
+            "--label",
+            "app=web",
+            "--node-selector",
+            "zone in (a,b)",
+            "--prefer",
+            "5:gpu",
+            "--prefer",
+            "zone=a",
+            "--anti-affinity",
+            "app=web",
+        ])
+        .unwrap();
+        let test_launch = match test_impulse.command {
+            Command::Vm(Vm::Launch(test_args)) => LaunchRequest::from(*test_args),
+            _ => panic!("expected vm launch"),
+        };
+        let test_placement = test_launch.placement.unwrap();
+        assert_eq!(test_placement.node_selector.as_str(), "zone in (a,b)");
+        assert_eq!(test_placement.preferences[0].selector.as_str(), "gpu");
+        assert_eq!(test_placement.preferences[0].weight, 5);
+        assert_eq!(test_placement.preferences[1].weight, 1);
+        assert_eq!(test_placement.anti_affinity, vec![String::from("app=web")]);
+        assert!(Impulse::try_parse_from(["impulse", "vm", "launch", "--prefer", "x:gpu"]).is_err());
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "vm",
+            "launch",
+            "--profile",
+            "small",
+            "--vcpus",
+            "4",
+            "--drive",
+            "data=data.ext4:ro",
+            "--network",
+            "eth0=tap0/06:00:ac:10:00:02",
+            "--restart",
+            "on-failure",
+        ])
+        .unwrap();
+        let test_launch = match test_impulse.command {
+            Command::Vm(Vm::Launch(test_args)) => LaunchRequest::from(*test_args),
+            _ => panic!("expected vm launch"),
+        };
+        let test_overrides = test_launch.overrides.unwrap();
+        assert_eq!(test_launch.profile.as_str(), "small");
+        assert_eq!(test_overrides.vcpus, 4);
+        assert_eq!(test_overrides.memory_mib, 0);
+        assert_eq!(
+            test_overrides.restart_policy,
+            RestartPolicy::OnFailure as i32,
+        );
+        assert_eq!(test_overrides.drives[0].drive_id.as_str(), "data");
+        assert_eq!(test_overrides.drives[0].image.as_str(), "data.ext4");
+        assert!(test_overrides.drives[0].read_only);
+        assert_eq!(
+            test_overrides.network_interfaces[0].host_dev_name.as_str(),
+            "tap0"
+        );
+        assert_eq!(
+            test_overrides.network_interfaces[0].guest_mac.as_str(),
+            "06:00:ac:10:00:02"
+        );
+        assert!(Impulse::try_parse_from(["impulse", "vm", "launch", "--drive", "data"]).is_err());
+        assert!(
+            Impulse::try_parse_from(["impulse", "vm", "launch", "--restart", "sometimes"]).is_err()
+        );
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "vm",
+            "launch-batch",
+            "--count",
+            "12",
+            "--atomic",
+            "--name",
+            "ci",
+            "--profile",
+            "small",
+        ])
+        .unwrap();
+        let test_batch = match test_impulse.command {
+            Command::Vm(Vm::LaunchBatch(test_args)) => BatchLaunchRequest::from(*test_args),
+            _ => panic!("expected vm launch-batch"),
+        };
+        assert_eq!(test_batch.count, 12);
+        assert!(test_batch.atomic);
+        assert_eq!(test_batch.template.unwrap().profile.as_str(), "small");
+        assert!(Impulse::try_parse_from(["impulse", "vm", "launch-batch"]).is_err());
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "profile",
+            "update",
+            "small",
+            "--memory-mib",
+            "2048",
+        ])
+        .unwrap();
+        assert!(matches!(
+            test_impulse.command,
+            Command::Profile(Profile::Update { name, description: None, machine })
+                if name == "small" && machine.memory_mib == Some(2048)
+        ));
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "group",
+            "create",
+            "web",
+            "--replicas",
+            "3",
+            "--profile",
+            "small",
+            "--label",
+            "tier=web",
+        ])
+        .unwrap();
+        let test_template = match test_impulse.command {
+            Command::Group(Group::Create {
+                name,
+                replicas: 3,
+                max_surge: 1,
+                template,
+            }) if name == "web" => LaunchRequest::from(*template),
+            _ => panic!("expected group create"),
+        };
+        assert!(test_template.name.is_empty());
+        assert_eq!(test_template.profile.as_str(), "small");
+        assert!(Impulse::try_parse_from(["impulse", "group", "create", "web"]).is_err());
+        assert!(Impulse::try_parse_from(["impulse", "group", "scale", "web"]).is_err());
+        let test_impulse = Impulse::try_parse_from([
+            "impulse",
+            "group",
+            "update",
+            "web",
+            "--memory-mib",
+            "2048",
+            "--label",
+            "version=2",
+        ])
+        .unwrap();
+        let test_updated = match test_impulse.command {
+            Command::Group(Group::Update {
+                replicas: None,
+                template,
+                ..
+            }) => overlay_template(test_template, *template),
+            _ => panic!("expected group update"),
+        };
+        assert_eq!(test_updated.profile.as_str(), "small");
+        assert_eq!(test_updated.labels.len(), 2);
+        assert_eq!(test_updated.overrides.unwrap().memory_mib, 2048);
+    }
+}
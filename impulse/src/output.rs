@@ -0,0 +1,1018 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+
+use serde_json::{json, Value};
+
+use system::impulse::external::v010::{
+    AuditList, BatchLaunchResult, BulkResult, Event, Group, GroupList, MicroVmList, Node, NodeList,
+    Profile, ProfileList, SystemStatusResponse, SystemVersionResponse, UsageList,
+};
+use system::impulse::shared::v010::{MicroVmLaunch, MicroVmShutdown};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Output {
+    #[default]
+    Table,
+    Json,
+}
+
+impl Output {
+    pub fn status(&self, status: &SystemStatusResponse) -> String {
+        let nodes = status.nodes.to_owned().unwrap_or_default();
+        let vms = status.vms.to_owned().unwrap_or_default();
+        let capacity = status.capacity.to_owned().unwrap_or_default();
+
+        match self {
+            Output::Table => {
+                let fields = [
+                    ("status", status.status.to_owned()),
+                    ("system_id", status.system_id.to_owned()),
+                    ("version", status.version.to_owned()),
+                    ("uptime_seconds", status.uptime_seconds.to_string()),
+                    ("nodes_registered", nodes.registered.to_string()),
+                    ("nodes_healthy", nodes.healthy.to_string()),
+                    ("nodes_stale", nodes.stale.to_string()),
+                    ("nodes_draining", nodes.draining.to_string()),
+                    ("vms_total", vms.total.to_string()),
+                    ("vms_pending", vms.pending.to_string()),
+                    ("vms_running", vms.running.to_string()),
+                    ("vms_failed", vms.failed.to_string()),
+                    ("vms_shutdown", vms.shutdown.to_string()),
+                    ("in_flight_tasks", status.in_flight_tasks.to_string()),
+                    ("queue_depth", status.queue_depth.to_string()),
+                    ("cpu_count", capacity.cpu_count.to_string()),
+                    ("memory_used", capacity.memory_used.to_string()),
+                    ("memory_free", capacity.memory_free.to_string()),
+                    ("disk_used", capacity.disk_used.to_string()),
+                    ("disk_free", capacity.disk_free.to_string()),
+                ];
+                let rows = fields
+                    .into_iter()
+                    .map(|(field, value)| vec![field.to_uppercase(), value])
+                    .collect();
+
+                table(&["FIELD", "VALUE"], rows)
+            }
+            Output::Json => render(json!({
+                "status": status.status,
+                "system_id": status.system_id,
+                "version": status.version,
+                "uptime_seconds": status.uptime_seconds,
+                "nodes": {
+                    "registered": nodes.registered,
+                    "healthy": nodes.healthy,
+                    "stale": nodes.stale,
+                    "draining": nodes.draining,
+                },
+                "vms": {
+                    "total": vms.total,
+                    "pending": vms.pending,
+                    "running": vms.running,
+                    "failed": vms.failed,
+                    "shutdown": vms.shutdown,
+                },
+                "in_flight_tasks": status.in_flight_tasks,
+                "queue_depth": status.queue_depth,
+                "capacity": {
+                    "cpu_count": capacity.cpu_count,
+                    "memory_total": capacity.memory_total,
+                    "memory_used": capacity.memory_used,
+                    "memory_free": capacity.memory_free,
+                    "disk_total": capacity.disk_total,
+                    "disk_used": capacity.disk_used,
+                    "disk_free": capacity.disk_free,
+                },
+            })),
+        }
+    }
+
+    pub fn event(&self, event: &Event) -> String {
+        let kind = event.kind().as_str_name().trim_start_matches("KIND_");
+        let state = event.state().as_str_name().trim_start_matches("STATE_");
+
+        match self {
+            Output::Table => [
+                event.resume_token.to_string(),
+                event.timestamp.to_string(),
+                kind.to_owned(),
+                format!("vm={}", event.vm_id),
+                format!("node={}", event.node_id),
+                format!("state={}", state),
+                format!("details={}", event.details),
+            ]
+            .join("  "),
+            Output::Json => json!({
+                "resume_token": event.resume_token,
+                "timestamp": event.timestamp,
+                "kind": kind.to_lowercase(),
+                "vm_id": event.vm_id,
+                "node_id": event.node_id,
+                "state": state.to_lowercase(),
+                "details": event.details,
+            })
+            .to_string(),
+        }
+    }
+
+    pub fn version(&self, version: &SystemVersionResponse) -> String {
+        match self {
+            Output::Table => table(&["VERSION"], vec![vec![version.version.to_owned()]]),
+            Output::Json => render(json!({ "version": version.version })),
+        }
+    }
+
+    pub fn launch(&self, launch: &MicroVmLaunch) -> String {
+        match self {
+            Output::Table => table(
+                &["UUID", "NODE", "LAUNCHED", "DETAILS"],
+                vec![vec![
+                    launch.uuid.to_owned(),
+                    launch.node_id.to_owned(),
+                    launch.launched.to_owned(),
+                    launch.details.to_owned(),
+                ]],
+            ),
+            Output::Json => render(json!({
+                "uuid": launch.uuid,
+                "node_id": launch.node_id,
+                "launched": launch.launched == "true",
+                "details": launch.details,
+            })),
+        }
+    }
+
+    pub fn batch_result(&self, result: &BatchLaunchResult) -> String {
+        let state = match (result.launched, result.rolled_back) {
+            (_, true) => "rolled_back",
+            (true, false) => "launched",
+            (false, false) => "failed",
+        };
+
+        match self {
+            Output::Table => [
+                result.index.to_string(),
+                state.to_uppercase(),
+                format!("uuid={}", result.uuid),
+                format!("name={}", result.name),
+                format!("node={}", result.node_id),
+                format!("details={}", result.details),
+            ]
+            .join("  "),
+            Output::Json => json!({
+                "index": result.index,
+                "state": state,
+                "uuid": result.uuid,
+                "name": result.name,
+                "node_id": result.node_id,
+                "details": result.details,
+            })
+            .to_string(),
+        }
+    }
+
+    pub fn shutdown(&self, shutdown: &MicroVmShutdown) -> String {
+        match self {
+            Output::Table => table(
+                &["UUID", "NODE", "SHUTDOWN", "DETAILS"],
+                vec![vec![
+                    shutdown.uuid.to_owned(),
+                    shutdown.node_id.to_owned(),
+                    shutdown.shutdown.to_owned(),
+                    shutdown.details.to_owned(),
+                ]],
+            ),
+            Output::Json => render(json!({
+                "uuid": shutdown.uuid,
+                "node_id": shutdown.node_id,
+                "shutdown": shutdown.shutdown == "true",
+                "details": shutdown.details,
+            })),
+        }
+    }
+
+    pub fn bulk(&self, bulk: &BulkResult) -> String {
+        match self {
+            Output::Table => {
+                let rows = bulk
+                    .results
+                    .iter()
+                    .map(|result| {
+                        vec![
+                            result.uuid.to_owned(),
+                            result.name.to_owned(),
+                            result.node_id.to_owned(),
+                            result.ok.to_string(),
+                            result.details.to_owned(),
+                        ]
+                    })
+                    .collect();
+
+                table(&["UUID", "NAME", "NODE", "OK", "DETAILS"], rows)
+            }
+            Output::Json => {
+                let results: Vec<Value> = bulk
+                    .results
+                    .iter()
+                    .map(|result| {
+                        json!({
+                            "uuid": result.uuid,
+                            "name": result.name,
+                            "node_id": result.node_id,
+                            "ok": result.ok,
+                            "details": result.details,
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "results": results }))
+            }
+        }
+    }
+
+    pub fn node(&self, node: Node) -> String {
+        self.nodes(&NodeList { nodes: vec![node] })
+    }
+
+    pub fn nodes(&self, list: &NodeList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .nodes
+                    .iter()
+                    .map(|node| {
+                        let inventory = node.inventory.to_owned().unwrap_or_default();
+
+                        vec![
+                            node.node_id.to_owned(),
+                            node.session_id.to_owned(),
+                            inventory.cpu_count.to_string(),
+                            inventory.memory_available.to_string(),
+                            inventory.disk_free.to_string(),
+                            inventory.images.len().to_string(),
+                            node.draining.to_string(),
+                            node.registered_at.to_string(),
+                            labels(&node.labels),
+                        ]
+                    })
+                    .collect();
+
+                table(
+                    &[
+                        "NODE",
+                        "SESSION",
+                        "CPUS",
+                        "MEMORY_AVAILABLE",
+                        "DISK_FREE",
+                        "IMAGES",
+                        "DRAINING",
+                        "REGISTERED_AT",
+                        "LABELS",
+                    ],
+                    rows,
+                )
+            }
+            Output::Json => {
+                let nodes: Vec<Value> = list
+                    .nodes
+                    .iter()
+                    .map(|node| {
+                        let inventory = node.inventory.to_owned().unwrap_or_default();
+
+                        json!({
+                            "node_id": node.node_id,
+                            "session_id": node.session_id,
+                            "registered_at": node.registered_at,
+                            "draining": node.draining,
+                            "labels": node.labels,
+                            "inventory": {
+                                "cpu_count": inventory.cpu_count,
+                                "memory_total": inventory.memory_total,
+                                "memory_available": inventory.memory_available,
+                                "disk_total": inventory.disk_total,
+                                "disk_free": inventory.disk_free,
+                                "images": inventory.images,
+                                "firecracker_version": inventory.firecracker_version,
+                                "jailer_version": inventory.jailer_version,
+                            },
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "nodes": nodes }))
+            }
+        }
+    }
+
+    pub fn vms(&self, list: &MicroVmList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .vms
+                    .iter()
+                    .map(|vm| {
+                        vec![
+                            vm.uuid.to_owned(),
+                            vm.name.to_owned(),
+                            vm.node_id.to_owned(),
+                            vm.state()
+                                .as_str_name()
+                                .trim_start_matches("STATE_")
+                                .to_owned(),
+                            vm.created_at.to_string(),
+                            vm.updated_at.to_string(),
+                            vm.details.to_owned(),
+                            labels(&vm.labels),
+                        ]
+                    })
+                    .collect();
+
+                table(
+                    &[
+                        "UUID",
+                        "NAME",
+                        "NODE",
+                        "STATE",
+                        "CREATED_AT",
+                        "UPDATED_AT",
+                        "DETAILS",
+                        "LABELS",
+                    ],
+                    rows,
+                )
+            }
+            Output::Json => {
+                let vms: Vec<Value> = list
+                    .vms
+                    .iter()
+                    .map(|vm| {
+                        json!({
+                            "uuid": vm.uuid,
+                            "name": vm.name,
+                            "request_id": vm.request_id,
+                            "tenant": vm.tenant,
+                            "profile": vm.profile,
+                            "group": vm.group,
+                            "machine": vm.machine,
+                            "labels": vm.labels,
+                            "annotations": vm.annotations,
+                            "node_id": vm.node_id,
+                            "state": vm.state().as_str_name().trim_start_matches("STATE_").to_lowercase(),
+                            "details": vm.details,
+                            "created_at": vm.created_at,
+                            "updated_at": vm.updated_at,
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "vms": vms }))
+            }
+        }
+    }
+
+    pub fn usage(&self, list: &UsageList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .tenants
+                    .iter()
+                    .map(|usage| {
+                        let quota = usage.quota.to_owned().unwrap_or_default();
+
+                        vec![
+                            usage.tenant.to_owned(),
+                            used(usage.vms, quota.vms),
+                            used(usage.vcpus, quota.vcpus),
+                            used(usage.memory_mib, quota.memory_mib),
+                            used(usage.disk_mib, quota.disk_mib),
+                        ]
+                    })
+                    .collect();
+
+                table(&["TENANT", "VMS", "VCPUS", "MEMORY_MIB", "DISK_MIB"], rows)
+            }
+            Output::Json => {
+                let tenants: Vec<Value> = list
+                    .tenants
+                    .iter()
+                    .map(|usage| {
+                        let quota = usage.quota.to_owned().unwrap_or_default();
+
+                        json!({
+                            "tenant": usage.tenant,
+                            "used": {
+                                "vms": usage.vms,
+                                "vcpus": usage.vcpus,
+                                "memory_mib": usage.memory_mib,
+                                "disk_mib": usage.disk_mib,
+                            },
+                            "quota": {
+                                "vms": quota.vms,
+                                "vcpus": quota.vcpus,
+                                "memory_mib": quota.memory_mib,
+                                "disk_mib": quota.disk_mib,
+                            },
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "tenants": tenants }))
+            }
+        }
+    }
+
+    pub fn profile(&self, profile: Profile) -> String {
+        self.profiles(&ProfileList {
+            profiles: vec![profile],
+        })
+    }
+
+    pub fn profiles(&self, list: &ProfileList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .profiles
+                    .iter()
+                    .map(|profile| {
+                        let machine = profile.machine.to_owned().unwrap_or_default();
+                        let source = match profile.configured {
+                            true => "config",
+                            false => "api",
+                        };
+
+                        vec![
+                            profile.name.to_owned(),
+                            source.to_string(),
+                            machine.vcpus.to_string(),
+                            machine.memory_mib.to_string(),
+                            machine.kernel_image,
+                            machine.root_fs,
+                            machine.drives.len().to_string(),
+                            profile.description.to_owned(),
+                        ]
+                    })
+                    .collect();
+
+                table(
+                    &[
+                        "NAME",
+                        "SOURCE",
+                        "VCPUS",
+                        "MEMORY_MIB",
+                        "KERNEL",
+                        "ROOT_FS",
+                        "DRIVES",
+                        "DESCRIPTION",
+                    ],
+                    rows,
+                )
+            }
+            Output::Json => {
+                let profiles: Vec<Value> = list
+                    .profiles
+                    .iter()
+                    .map(|profile| {
+                        json!({
+                            "name": profile.name,
+                            "description": profile.description,
+                            "configured": profile.configured,
+                            "machine": profile.machine.to_owned().unwrap_or_default(),
+                            "created_at": profile.created_at,
+                            "updated_at": profile.updated_at,
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "profiles": profiles }))
+            }
+        }
+    }
+
+    pub fn group(&self, group: Group) -> String {
+        self.groups(&GroupList {
+            groups: vec![group],
+        })
+    }
+
+    pub fn groups(&self, list: &GroupList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .groups
+                    .iter()
+                    .map(|group| {
+                        let status = group.status.to_owned().unwrap_or_default();
+                        let template = group.template.to_owned().unwrap_or_default();
+
+                        vec![
+                            group.name.to_owned(),
+                            group.tenant.to_owned(),
+                            group.replicas.to_string(),
+                            status.updated.to_string(),
+                            status.running.to_string(),
+                            status.pending.to_string(),
+                            group.generation.to_string(),
+                            template.profile,
+                        ]
+                    })
+                    .collect();
+
+                table(
+                    &[
+                        "NAME",
+                        "TENANT",
+                        "REPLICAS",
+                        "UPDATED",
+                        "RUNNING",
+                        "PENDING",
+                        "GENERATION",
+                        "PROFILE",
+                    ],
+                    rows,
+                )
+            }
+            Output::Json => {
+                let groups: Vec<Value> = list
+                    .groups
+                    .iter()
+                    .map(|group| {
+                        let status = group.status.to_owned().unwrap_or_default();
+                        let template = group.template.to_owned().unwrap_or_default();
+                        let placement = template.placement.unwrap_or_default();
+
+                        json!({
+                            "name": group.name,
+                            "tenant": group.tenant,
+                            "replicas": group.replicas,
+                            "max_surge": group.max_surge,
+                            "generation": group.generation,
+                            "template": {
+                                "labels": template.labels,
+                                "annotations": template.annotations,
+                                "node_selector": placement.node_selector,
+                                "affinity": placement.affinity,
+                                "anti_affinity": placement.anti_affinity,
+                                "profile": template.profile,
+                                "overrides": template.overrides.unwrap_or_default(),
+                            },
+                            "status": {
+                                "running": status.running,
+                                "pending": status.pending,
+                                "updated": status.updated,
+                            },
+                            "created_at": group.created_at,
+                            "updated_at": group.updated_at,
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "groups": groups }))
+            }
+        }
+    }
+
+    pub fn audit(&self, list: &AuditList) -> String {
+        match self {
+            Output::Table => {
+                let rows = list
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        vec![
+                            entry.timestamp.to_string(),
+                            entry.principal.to_owned(),
+                            entry.role.to_owned(),
+                            entry.operation.to_owned(),
+                            entry.vm_id.to_owned(),
+                            entry.node_id.to_owned(),
+                            entry.result.to_owned(),
+                            entry.latency_ms.to_string(),
+                        ]
+                    })
+                    .collect();
+
+                table(
+                    &[
+                        "TIMESTAMP",
+                        "PRINCIPAL",
+                        "ROLE",
+                        "OPERATION",
+                        "VM",
+                        "NODE",
+                        "RESULT",
+                        "LATENCY_MS",
+                    ],
+                    rows,
+                )
+            }
+            Output::Json => {
+                let entries: Vec<Value> = list
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        json!({
+                            "timestamp": entry.timestamp,
+                            "principal": entry.principal,
+                            "role": entry.role,
+                            "operation": entry.operation,
+                            "arguments": serde_json::from_str::<Value>(&entry.arguments).unwrap_or_default(),
+                            "vm_id": entry.vm_id,
+                            "node_id": entry.node_id,
+                            "allowed": entry.allowed,
+                            "result": entry.result,
+                            "error": entry.error,
+                            "latency_ms": entry.latency_ms,
+                        })
+                    })
+                    .collect();
+
+                render(json!({ "entries": entries }))
+            }
+        }
+    }
+}
+
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+
+    for row in std::iter::once(headers).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+
+        lines.push(cells.join("  ").trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+fn labels(labels: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+
+    pairs.sort();
+
+    pairs.join(",")
+}
+
+fn used(used: u64, quota: Option<u64>) -> String {
+    match quota {
+        Some(quota) => format!("{}/{}", used, quota),
+        None => used.to_string(),
+    }
+}
+
+fn render(value: Value) -> String {
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use system::impulse::external::v010::event::Kind;
+    use system::impulse::external::v010::micro_vm_record::State;
+    use system::impulse::external::v010::{
+        AuditRecord, ClusterCapacity, GroupStatus, LaunchRequest, MicroVmRecord, NodeSummary,
+        TenantQuota, TenantUsage, VmResult, VmSummary,
+    };
+    use system::impulse::shared::v010::{Drive, MachineSpec, NodeInventory};
+
+    #[test]
+    fn table_columns() {
+        let test_table = table(
+            &["UUID", "STATE"],
+            vec![
+                vec![String::from("test_uuid"), String::from("RUNNING")],
+                vec![String::from("a"), String::from("PENDING")],
+            ],
+        );
+        let test_lines: Vec<&str> = test_table.lines().collect();
+        assert_eq!(test_lines.len(), 3);
+        assert_eq!(test_lines[0], "UUID       STATE");
+        assert_eq!(test_lines[1], "test_uuid  RUNNING");
+        assert_eq!(test_lines[2], "a          PENDING");
+    }
+
+    #[test]
+    fn status() {
+        let test_status = SystemStatusResponse {
+            status: String::from("ready"),
+            system_id: String::from("test_system_id"),
+            version: String::from("v0.1.0"),
+            uptime_seconds: 42,
+            nodes: Some(NodeSummary {
+                registered: 2,
+                healthy: 1,
+                stale: 0,
+                draining: 1,
+            }),
+            vms: Some(VmSummary {
+                total: 3,
+                running: 2,
+                failed: 1,
+                ..VmSummary::default()
+            }),
+            in_flight_tasks: 1,
+            queue_depth: 0,
+            capacity: Some(ClusterCapacity {
+                cpu_count: 8,
+                memory_used: 1024,
+                ..ClusterCapacity::default()
+            }),
+        };
+        let test_table = Output::Table.status(&test_status);
+        assert!(test_table.starts_with("FIELD"));
+        assert!(test_table.contains("STATUS            ready"));
+        assert!(test_table.contains("VMS_RUNNING       2"));
+        let test_json: Value = serde_json::from_str(&Output::Json.status(&test_status)).unwrap();
+        assert_eq!(test_json["status"], "ready");
+        assert_eq!(test_json["uptime_seconds"], 42);
+        assert_eq!(test_json["nodes"]["draining"], 1);
+        assert_eq!(test_json["vms"]["failed"], 1);
+        assert_eq!(test_json["capacity"]["memory_used"], 1024);
+    }
+
+    #[test]
+    fn event() {
+        let test_event = Event {
+            resume_token: 7,
+            timestamp: 1,
+            kind: Kind::VmStateChanged as i32,
+            vm_id: String::from("test_uuid"),
+            node_id: String::from("test_node"),
+            state: State::Running as i32,
+            details: String::from("success!"),
+        };
+        assert_eq!(
+            Output::Table.event(&test_event).as_str(),
+            "7  1  VM_STATE_CHANGED  vm=test_uuid  node=test_node  state=RUNNING  details=success!",
+        );
+        let test_json = Output::Json.event(&test_event);
+        assert_eq!(test_json.lines().count(), 1);
+        let test_json: Value = serde_json::from_str(&test_json).unwrap();
+        assert_eq!(test_json["kind"], "vm_state_changed");
+        assert_eq!(test_json["state"], "running");
+    }
+
+    #[test]
+    fn vms() {
+        let test_list = MicroVmList {
+            vms: vec![MicroVmRecord {
+                uuid: String::from("test_uuid"),
+                node_id: String::from("test_node"),
+                state: State::Running as i32,
+                details: String::from("success!"),
+                created_at: 1,
+                updated_at: 2,
+                name: String::from("test_name"),
+                request_id: String::from("test_request"),
+                labels: HashMap::from([
+                    (String::from("tier"), String::from("web")),
+                    (String::from("env"), String::from("prod")),
+                ]),
+                annotations: HashMap::new(),
+                tenant: String::from("test_team"),
+                profile: String::from("test_profile"),
+                machine: Some(MachineSpec {
+                    vcpus: 4,
+                    ..MachineSpec::default()
+                }),
+                group: String::from("test_group"),
+            }],
+        };
+        let test_table = Output::Table.vms(&test_list);
+        assert!(test_table.starts_with("UUID"));
+        assert!(test_table.contains("test_uuid  test_name  test_node  RUNNING"));
+        assert!(test_table.ends_with("env=prod,tier=web"));
+        let test_json: Value = serde_json::from_str(&Output::Json.vms(&test_list)).unwrap();
+        assert_eq!(test_json["vms"][0]["uuid"], "test_uuid");
+        assert_eq!(test_json["vms"][0]["name"], "test_name");
+        assert_eq!(test_json["vms"][0]["labels"]["env"], "prod");
+        assert_eq!(test_json["vms"][0]["state"], "running");
+        assert_eq!(test_json["vms"][0]["updated_at"], 2);
+        assert_eq!(test_json["vms"][0]["tenant"], "test_team");
+        assert_eq!(test_json["vms"][0]["profile"], "test_profile");
+        assert_eq!(test_json["vms"][0]["machine"]["vcpus"], 4);
+        assert_eq!(test_json["vms"][0]["group"], "test_group");
+    }
+
+    #[test]
+    fn profiles() {
+        let test_list = ProfileList {
+            profiles: vec![Profile {
+                name: String::from("test_small"),
+                description: String::from("Small test MicroVM"),
+                machine: Some(MachineSpec {
+                    kernel_image: String::from("vmlinux"),
+                    root_fs: String::from("rootfs.ext4"),
+                    vcpus: 1,
+                    memory_mib: 512,
+                    drives: vec![Drive {
+                        drive_id: String::from("data"),
+                        image: String::from("data.ext4"),
+                        read_only: true,
+                    }],
+                    ..MachineSpec::default()
+                }),
+                configured: true,
+                created_at: 1,
+                updated_at: 2,
+            }],
+        };
+        let test_table = Output::Table.profiles(&test_list);
+        assert!(test_table.starts_with("NAME"));
+        assert!(test_table.ends_with(
+            "test_small  config  1      512         vmlinux  rootfs.ext4  1       Small test MicroVM"
+        ));
+        let test_json: Value = serde_json::from_str(&Output::Json.profiles(&test_list)).unwrap();
+        assert_eq!(test_json["profiles"][0]["name"], "test_small");
+        assert_eq!(test_json["profiles"][0]["configured"], true);
+        assert_eq!(test_json["profiles"][0]["machine"]["memory_mib"], 512);
+        assert_eq!(
+            test_json["profiles"][0]["machine"]["drives"][0]["read_only"],
+            true
+        );
+    }
+
+    #[test]
+    fn groups() {
+        let test_list = GroupList {
+            groups: vec![Group {
+                name: String::from("test_web"),
+                replicas: 3,
+                max_surge: 1,
+                template: Some(LaunchRequest {
+                    profile: String::from("test_small"),
+                    labels: HashMap::from([(String::from("tier"), String::from("web"))]),
+                    ..LaunchRequest::default()
+                }),
+                tenant: String::from("test_team"),
+                generation: 2,
+                status: Some(GroupStatus {
+                    running: 3,
+                    pending: 1,
+                    updated: 2,
+                }),
+                created_at: 1,
+                updated_at: 2,
+            }],
+        };
+        let test_table = Output::Table.groups(&test_list);
+        assert!(test_table.starts_with("NAME"));
+        assert!(test_table.ends_with(
+            "test_web  test_team  3         2        3        1        2           test_small"
+        ));
+        let test_json: Value = serde_json::from_str(&Output::Json.groups(&test_list)).unwrap();
+        assert_eq!(test_json["groups"][0]["name"], "test_web");
+        assert_eq!(test_json["groups"][0]["max_surge"], 1);
+        assert_eq!(test_json["groups"][0]["template"]["labels"]["tier"], "web");
+        assert_eq!(test_json["groups"][0]["status"]["updated"], 2);
+    }
+
+    #[test]
+    fn batch_result() {
+        let test_result = BatchLaunchResult {
+            index: 3,
+            uuid: String::from("test_uuid"),
+            name: String::from("test_name-3"),
+            node_id: String::from("test_node"),
+            launched: true,
+            details: String::from("success!"),
+            rolled_back: false,
+        };
+        assert_eq!(
+            Output::Table.batch_result(&test_result),
+            "3  LAUNCHED  uuid=test_uuid  name=test_name-3  node=test_node  details=success!"
+        );
+        let test_json: Value =
+            serde_json::from_str(&Output::Json.batch_result(&BatchLaunchResult {
+                launched: false,
+                rolled_back: true,
+                ..test_result
+            }))
+            .unwrap();
+        assert_eq!(test_json["index"], 3);
+        assert_eq!(test_json["state"], "rolled_back");
+        assert_eq!(test_json["uuid"], "test_uuid");
+    }
+
+    #[test]
+    fn usage() {
+        let test_list = UsageList {
+            tenants: vec![TenantUsage {
+                tenant: String::from("test_team"),
+                vms: 2,
+                vcpus: 4,
+                memory_mib: 2048,
+                disk_mib: 2048,
+                quota: Some(TenantQuota {
+                    vms: Some(5),
+                    vcpus: None,
+                    memory_mib: Some(4096),
+                    disk_mib: None,
+                }),
+            }],
+        };
+        let test_table = Output::Table.usage(&test_list);
+        assert!(test_table.starts_with("TENANT"));
+        assert!(test_table.ends_with("test_team  2/5  4      2048/4096   2048"));
+        let test_json: Value = serde_json::from_str(&Output::Json.usage(&test_list)).unwrap();
+        assert_eq!(test_json["tenants"][0]["tenant"], "test_team");
+        assert_eq!(test_json["tenants"][0]["used"]["vcpus"], 4);
+        assert_eq!(test_json["tenants"][0]["quota"]["vms"], 5);
+        assert!(test_json["tenants"][0]["quota"]["vcpus"].is_null());
+    }
+
+    #[test]
+    fn nodes() {
+        let test_list = NodeList {
+            nodes: vec![Node {
+                node_id: String::from("test_node"),
+                inventory: Some(NodeInventory {
+                    cpu_count: 4,
+                    ..NodeInventory::default()
+                }),
+                session_id: String::from("test_session"),
+                registered_at: 1,
+                draining: false,
+                labels: HashMap::from([(String::from("zone"), String::from("a"))]),
+            }],
+        };
+        let test_table = Output::Table.nodes(&test_list);
+        assert!(test_table.contains("test_node"));
+        let test_json: Value = serde_json::from_str(&Output::Json.nodes(&test_list)).unwrap();
+        assert_eq!(test_json["nodes"][0]["inventory"]["cpu_count"], 4);
+        assert_eq!(test_json["nodes"][0]["session_id"], "test_session");
+        assert_eq!(test_json["nodes"][0]["draining"], false);
+        assert_eq!(test_json["nodes"][0]["labels"]["zone"], "a");
+    }
+
+    #[test]
+    fn bulk() {
+        let test_bulk = BulkResult {
+            results: vec![VmResult {
+                uuid: String::from("test_uuid"),
+                name: String::from("test_name"),
+                node_id: String::from("test_node"),
+                ok: false,
+                details: String::from("test_failure"),
+            }],
+        };
+        let test_table = Output::Table.bulk(&test_bulk);
+        assert!(test_table.contains("test_uuid  test_name  test_node  false  test_failure"));
+        let test_json: Value = serde_json::from_str(&Output::Json.bulk(&test_bulk)).unwrap();
+        assert_eq!(test_json["results"][0]["ok"], false);
+        assert_eq!(test_json["results"][0]["details"], "test_failure");
+    }
+
+    #[test]
+    fn launch() {
+        let test_launch = MicroVmLaunch {
+            uuid: String::from("test_uuid"),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        };
+        let test_json: Value = serde_json::from_str(&Output::Json.launch(&test_launch)).unwrap();
+        assert_eq!(test_json["launched"], true);
+        assert_eq!(test_json["node_id"], "test_node");
+    }
+
+    #[test]
+    fn audit() {
+        let test_list = AuditList {
+            entries: vec![AuditRecord {
+                timestamp: 1,
+                principal: String::from("test_operator"),
+                role: String::from("operator"),
+                operation: String::from("shutdown_vm"),
+                arguments: String::from("{\"name\":\"test_uuid\"}"),
+                vm_id: String::from("test_uuid"),
+                node_id: String::from("test_node"),
+                allowed: true,
+                result: String::from("ok"),
+                error: String::new(),
+                latency_ms: 12,
+            }],
+        };
+        let test_table = Output::Table.audit(&test_list);
+        assert!(test_table.contains("test_operator  operator  shutdown_vm  test_uuid  test_node"));
+        let test_json: Value = serde_json::from_str(&Output::Json.audit(&test_list)).unwrap();
+        assert_eq!(test_json["entries"][0]["arguments"]["name"], "test_uuid");
+        assert_eq!(test_json["entries"][0]["latency_ms"], 12);
+    }
+}
@@ -1,5 +1,29 @@
+use std::env;
+use std::path::PathBuf;
+
+const SERDE_TYPES: &[&str] = &[
+    ".impulse.shared.v010.NodeInventory",
+    ".impulse.shared.v010.Task",
+    ".impulse.shared.v010.MachineSpec",
+    ".impulse.shared.v010.Drive",
+    ".impulse.shared.v010.NetworkInterface",
+];
+
+const SERDE_DEFAULTS: &[&str] = &[
+    ".impulse.shared.v010.Task",
+    ".impulse.shared.v010.MachineSpec",
+    ".impulse.shared.v010.Drive",
+    ".impulse.shared.v010.NetworkInterface",
+];
+
+const SERDE_FIELDS: &[(&str, &str)] = &[(
+    ".impulse.shared.v010.MachineSpec.restart_policy",
+    "#[serde(with = \"crate::profiles::restart_policy\")]",
+)];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=build.rs");
+    build_proto("cluster", "v010", true, true)?;
     build_proto("external", "v010", true, true)?;
     build_proto("internal", "v010", true, true)?;
     build_proto("shared", "v010", true, true)?;
@@ -14,10 +38,25 @@ fn build_proto(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let proto_file = format!("../proto/impulse/impulse_{}_{}.proto", name, version);
     println!("cargo:rerun-if-changed={}", proto_file);
-    tonic_build::configure()
+    let descriptor_file =
+        PathBuf::from(env::var("OUT_DIR")?).join(format!("impulse_{}_{}.bin", name, version));
+    let mut builder = tonic_build::configure()
         .build_client(client)
         .build_server(server)
-        .out_dir("../proto")
-        .compile(&[proto_file.as_str()], &["../proto/impulse"])?;
+        .file_descriptor_set_path(descriptor_file)
+        .out_dir("../proto");
+    for serde_type in SERDE_TYPES {
+        builder = builder.type_attribute(
+            serde_type,
+            "#[derive(serde::Serialize, serde::Deserialize)]",
+        );
+    }
+    for serde_default in SERDE_DEFAULTS {
+        builder = builder.message_attribute(serde_default, "#[serde(default)]");
+    }
+    for (serde_field, attribute) in SERDE_FIELDS {
+        builder = builder.field_attribute(serde_field, attribute);
+    }
+    builder.compile(&[proto_file.as_str()], &["../proto/impulse"])?;
     Ok(())
 }
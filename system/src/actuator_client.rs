@@ -1,35 +1,219 @@
-use tonic::transport::Channel;
-use tonic::{Request, Response, Status, Streaming};
+use std::collections::{HashMap, VecDeque};
+
+use tokio::time::{sleep, Duration};
+
+use tonic::codegen::InterceptedService;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Response, Status, Streaming};
+
+use tracing::{info, warn};
 
 use uuid::Uuid;
 
+use crate::actuator_engine::{Exit, Inventory};
+use crate::auth::{certificate_identity, client_tls, BearerToken};
+use crate::cluster::LEADER_METADATA;
+use crate::config::TlsConfig;
 use crate::impulse::internal::v010::interface_client::InterfaceClient;
-use crate::impulse::internal::v010::{NodeId, SystemId};
-use crate::impulse::shared::v010::{MicroVmLaunch, MicroVmShutdown, Task};
+use crate::impulse::internal::v010::{MicroVmExit, NodeId, NodeRegistration, SystemId, TaskAck};
+use crate::impulse::shared::v010::{MicroVmLaunch, MicroVmShutdown, NodeInventory, Task};
+use crate::telemetry;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PendingResult {
+    Launch(MicroVmLaunch, String),
+    Shutdown(MicroVmShutdown, String),
+    Exit(MicroVmExit, String),
+    Ack(TaskAck),
+}
+
+struct Backoff {
+    initial: Duration,
+    maximum: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    async fn init(initial: Duration, maximum: Duration) -> Backoff {
+        Backoff {
+            initial,
+            maximum,
+            current: initial,
+        }
+    }
+
+    async fn next(&mut self) -> Duration {
+        let delay = self.current;
+
+        self.current = (self.current * 2).min(self.maximum);
+
+        delay
+    }
+
+    async fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
 
 pub struct Internal {
-    transport: InterfaceClient<Channel>,
-    pub node_id: Uuid,
+    transports: Vec<(
+        String,
+        InterfaceClient<InterceptedService<Channel, BearerToken>>,
+    )>,
+    current: usize,
+    transport: InterfaceClient<InterceptedService<Channel, BearerToken>>,
+    pub node_id: String,
+    pub labels: HashMap<String, String>,
+    backoff: Backoff,
+    pending: VecDeque<PendingResult>,
 }
 
 impl Internal {
-    pub async fn init(endpoint: &'static str) -> Result<Internal, Box<dyn std::error::Error>> {
-        let transport = InterfaceClient::connect(endpoint).await?;
-        let node_id = Uuid::new_v4();
+    pub async fn init(
+        endpoint: &str,
+        failover_endpoints: &[String],
+        tls: Option<&TlsConfig>,
+        token: Option<&str>,
+    ) -> Result<Internal, Box<dyn std::error::Error>> {
+        let node_id = match tls {
+            Some(tls) => certificate_identity(&tls.certificate).await?,
+            None => Uuid::new_v4().to_string(),
+        };
+
+        let mut transports = Vec::with_capacity(failover_endpoints.len() + 1);
+
+        for address in [endpoint]
+            .into_iter()
+            .chain(failover_endpoints.iter().map(String::as_str))
+        {
+            let mut endpoint = Endpoint::from_shared(address.to_string())?;
+
+            if let Some(tls) = tls {
+                endpoint = endpoint.tls_config(client_tls(tls).await?)?;
+            }
+
+            let transport = InterfaceClient::with_interceptor(
+                endpoint.connect_lazy(),
+                BearerToken::init(token)?,
+            );
+
+            transports.push((address.to_string(), transport));
+        }
+
+        let transport = transports[0].1.clone();
+        let backoff = Backoff::init(Duration::from_millis(500), Duration::from_secs(30)).await;
+        let pending = VecDeque::with_capacity(20);
+
+        Ok(Internal {
+            transports,
+            current: 0,
+            transport,
+            node_id,
+            labels: HashMap::with_capacity(0),
+            backoff,
+            pending,
+        })
+    }
+
+    pub async fn session(&mut self, inventory: Inventory) -> Streaming<Task> {
+        loop {
+            match self.open_session(inventory.to_owned()).await {
+                Ok(controller) => {
+                    self.backoff.reset().await;
+
+                    return controller;
+                }
+                Err(error) => {
+                    if self.failover(&error).await {
+                        continue;
+                    }
+
+                    let delay = self.backoff.next().await;
 
-        Ok(Internal { transport, node_id })
+                    warn!(
+                        ?delay,
+                        error = error.message(),
+                        "Session unavailable... retrying",
+                    );
+
+                    sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    pub fn endpoint(&self) -> &str {
+        self.transports[self.current].0.as_str()
+    }
+
+    async fn failover(&mut self, error: &Status) -> bool {
+        let leader = error
+            .metadata()
+            .get(LEADER_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|leader| {
+                self.transports
+                    .iter()
+                    .position(|(endpoint, _)| endpoint == leader)
+            });
+
+        let next = match leader {
+            Some(leader) if leader != self.current => leader,
+            _ => (self.current + 1) % self.transports.len(),
+        };
+
+        if next != self.current {
+            self.current = next;
+            self.transport = self.transports[next].1.clone();
+
+            info!(endpoint = self.endpoint(), "Failing over to interface");
+        }
+
+        leader.is_some_and(|leader| leader == next)
     }
 
-    pub async fn register(&mut self) -> Result<tonic::Response<SystemId>, tonic::Status> {
+    async fn open_session(&mut self, inventory: Inventory) -> Result<Streaming<Task>, Status> {
+        let register = self.register(inventory).await?;
+
+        info!(system_id = %register.get_ref().system_id, "Registered with interface");
+
+        self.flush_pending().await?;
+
+        let controller = self.controller().await?;
+
+        Ok(controller.into_inner())
+    }
+
+    pub async fn register(
+        &mut self,
+        inventory: Inventory,
+    ) -> Result<tonic::Response<SystemId>, tonic::Status> {
         let mut transport = self.transport.clone();
-        let request = Request::new(NodeId {
+        let request = Request::new(NodeRegistration {
             node_id: self.node_id.to_string(),
+            inventory: Some(NodeInventory::from(inventory)),
+            labels: self.labels.to_owned(),
         });
         let response = transport.register(request).await?;
 
         Ok(response)
     }
 
+    pub async fn update_inventory(
+        &mut self,
+        inventory: Inventory,
+    ) -> Result<Response<SystemId>, Status> {
+        let mut transport = self.transport.clone();
+        let request = Request::new(NodeRegistration {
+            node_id: self.node_id.to_string(),
+            inventory: Some(NodeInventory::from(inventory)),
+            labels: HashMap::with_capacity(0),
+        });
+        let response = transport.update_inventory(request).await?;
+
+        Ok(response)
+    }
+
     pub async fn controller(&mut self) -> Result<Response<Streaming<Task>>, Status> {
         let mut transport = self.transport.clone();
         let request = Request::new(NodeId {
@@ -45,16 +229,19 @@ impl Internal {
         uuid: &str,
         launched: bool,
         details: String,
+        trace_id: &str,
     ) -> Result<Response<SystemId>, Status> {
-        let mut transport = self.transport.clone();
-        let request = Request::new(MicroVmLaunch {
-            uuid: uuid.to_string(),
-            launched: launched.to_string(),
-            details: details.to_string(),
-        });
-        let response = transport.launch_result(request).await?;
+        let result = PendingResult::Launch(
+            MicroVmLaunch {
+                uuid: uuid.to_string(),
+                launched: launched.to_string(),
+                details: details.to_string(),
+                node_id: self.node_id.to_string(),
+            },
+            trace_id.to_string(),
+        );
 
-        Ok(response)
+        self.report(result).await
     }
 
     pub async fn shutdown_result(
@@ -62,16 +249,49 @@ impl Internal {
         uuid: &str,
         shutdown: bool,
         details: String,
+        trace_id: &str,
     ) -> Result<Response<SystemId>, Status> {
-        let mut transport = self.transport.clone();
-        let request = Request::new(MicroVmShutdown {
-            uuid: uuid.to_string(),
-            shutdown: shutdown.to_string(),
-            details: details.to_string(),
+        let result = PendingResult::Shutdown(
+            MicroVmShutdown {
+                uuid: uuid.to_string(),
+                shutdown: shutdown.to_string(),
+                details: details.to_string(),
+                node_id: self.node_id.to_string(),
+            },
+            trace_id.to_string(),
+        );
+
+        self.report(result).await
+    }
+
+    pub async fn exit_report(
+        &mut self,
+        exit: &Exit,
+        trace_id: &str,
+    ) -> Result<Response<SystemId>, Status> {
+        let result = PendingResult::Exit(
+            MicroVmExit {
+                node_id: self.node_id.to_string(),
+                uuid: exit.uuid.to_string(),
+                reason: exit.reason.to_string(),
+                exit_code: exit.exit_code,
+                failed: exit.failed,
+                restarting: exit.restarting,
+                restarts: exit.restarts,
+            },
+            trace_id.to_string(),
+        );
+
+        self.report(result).await
+    }
+
+    pub async fn acknowledge(&mut self, task_id: &str) -> Result<Response<SystemId>, Status> {
+        let result = PendingResult::Ack(TaskAck {
+            node_id: self.node_id.to_string(),
+            task_id: task_id.to_string(),
         });
-        let response = transport.shutdown_result(request).await?;
 
-        Ok(response)
+        self.report(result).await
     }
 
     pub async fn delist(&mut self) -> Result<Response<SystemId>, Status> {
@@ -83,10 +303,330 @@ impl Internal {
 
         Ok(response)
     }
+
+    async fn report(&mut self, result: PendingResult) -> Result<Response<SystemId>, Status> {
+        match self.send(result.to_owned()).await {
+            Ok(response) => Ok(response),
+            Err(error) if rejected(&error) => {
+                warn!(
+                    ?result,
+                    error = error.message(),
+                    "Result rejected by interface"
+                );
+
+                Err(error)
+            }
+            Err(error) => {
+                warn!(?result, "Result held until reconnect");
+
+                self.pending.push_back(result);
+
+                Err(error)
+            }
+        }
+    }
+
+    async fn flush_pending(&mut self) -> Result<(), Status> {
+        while let Some(result) = self.pending.pop_front() {
+            match self.send(result.to_owned()).await {
+                Ok(_) => info!(?result, "Held result reported"),
+                Err(error) if rejected(&error) => {
+                    warn!(
+                        ?result,
+                        error = error.message(),
+                        "Held result rejected... dropping it"
+                    );
+                }
+                Err(error) => {
+                    self.pending.push_front(result);
+
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send(&mut self, result: PendingResult) -> Result<Response<SystemId>, Status> {
+        let mut transport = self.transport.clone();
+        let response = match result {
+            PendingResult::Launch(launch, trace_id) => {
+                let mut request = Request::new(launch);
+
+                telemetry::inject(request.metadata_mut(), &trace_id).await;
+
+                transport.launch_result(request).await?
+            }
+            PendingResult::Shutdown(shutdown, trace_id) => {
+                let mut request = Request::new(shutdown);
+
+                telemetry::inject(request.metadata_mut(), &trace_id).await;
+
+                transport.shutdown_result(request).await?
+            }
+            PendingResult::Exit(exit, trace_id) => {
+                let mut request = Request::new(exit);
+
+                telemetry::inject(request.metadata_mut(), &trace_id).await;
+
+                transport.exit_report(request).await?
+            }
+            PendingResult::Ack(ack) => transport.acknowledge(Request::new(ack)).await?,
+        };
+
+        Ok(response)
+    }
+}
+
+pub fn rejected(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::NotFound | Code::PermissionDenied | Code::InvalidArgument | Code::FailedPrecondition
+    )
+}
+
+impl From<Inventory> for NodeInventory {
+    fn from(inventory: Inventory) -> NodeInventory {
+        NodeInventory {
+            cpu_count: inventory.cpu_count,
+            memory_total: inventory.memory_total,
+            memory_available: inventory.memory_available,
+            disk_total: inventory.disk_total,
+            disk_free: inventory.disk_free,
+            images: inventory.images,
+            firecracker_version: inventory.firecracker_version.unwrap_or_default(),
+            jailer_version: inventory.jailer_version.unwrap_or_default(),
+            vm_ids: inventory.vm_ids,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::config::{EventsConfig, TaskQueueConfig};
+    use crate::events::EventLog;
+    use crate::internal_interface::{InterfaceServer, Internal as InternalInterface};
+    use crate::node_registry::NodeRegistry;
+    use crate::task_queue::TaskQueues;
+    use crate::tenants::Claim;
+    use crate::vm_registry::{VmRegistry, VmSpec, VmState};
+    use std::sync::Arc;
+    use tokio::sync::broadcast::channel;
+    use tonic::transport::Server;
+
+    const TEST_UNREACHABLE_ENDPOINT: &str = "http://127.0.0.1:1";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn backoff() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_backoff =
+            Backoff::init(Duration::from_millis(500), Duration::from_secs(2)).await;
+        assert_eq!(test_backoff.next().await, Duration::from_millis(500));
+        assert_eq!(test_backoff.next().await, Duration::from_secs(1));
+        assert_eq!(test_backoff.next().await, Duration::from_secs(2));
+        assert_eq!(test_backoff.next().await, Duration::from_secs(2));
+        test_backoff.reset().await;
+        assert_eq!(test_backoff.next().await, Duration::from_millis(500));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_internal = Internal::init(TEST_UNREACHABLE_ENDPOINT, &[], None, None).await?;
+        assert_eq!(
+            Uuid::parse_str(&test_internal.node_id)?.get_version_num(),
+            4
+        );
+        assert!(test_internal.pending.is_empty());
+        assert_eq!(test_internal.endpoint(), TEST_UNREACHABLE_ENDPOINT);
+        assert!(Internal::init("not an endpoint", &[], None, None)
+            .await
+            .is_err());
+        assert!(Internal::init(
+            TEST_UNREACHABLE_ENDPOINT,
+            &[String::from("not an endpoint")],
+            None,
+            None,
+        )
+        .await
+        .is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pending_results() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_internal = Internal::init(TEST_UNREACHABLE_ENDPOINT, &[], None, None).await?;
+        let test_launch_result = test_internal
+            .launch_result("test_uuid", true, String::from("launched"), "test_trace_id")
+            .await;
+        assert!(test_launch_result.is_err());
+        let test_shutdown_result = test_internal
+            .shutdown_result("test_uuid", true, String::from("shutdown"), "test_trace_id")
+            .await;
+        assert!(test_shutdown_result.is_err());
+        let test_exit = Exit {
+            uuid: String::from("test_uuid"),
+            reason: String::from("exited with status 1"),
+            exit_code: 1,
+            failed: true,
+            restarting: false,
+            restarts: 0,
+        };
+        let test_exit_report = test_internal.exit_report(&test_exit, "test_trace_id").await;
+        assert!(test_exit_report.is_err());
+        assert!(matches!(
+            test_internal.pending.back(),
+            Some(PendingResult::Exit(
+                MicroVmExit {
+                    exit_code: 1,
+                    failed: true,
+                    ..
+                },
+                _
+            )),
+        ));
+        let test_acknowledge = test_internal.acknowledge("test_uuid").await;
+        assert!(test_acknowledge.is_err());
+        assert_eq!(test_internal.pending.len(), 4);
+        assert!(test_internal.flush_pending().await.is_err());
+        assert_eq!(test_internal.pending.len(), 4);
+        assert_eq!(
+            test_internal.pending.back(),
+            Some(&PendingResult::Ack(TaskAck {
+                node_id: test_internal.node_id.to_string(),
+                task_id: String::from("test_uuid"),
+            })),
+        );
+        assert_eq!(
+            test_internal.pending.front(),
+            Some(&PendingResult::Launch(
+                MicroVmLaunch {
+                    uuid: String::from("test_uuid"),
+                    launched: String::from("true"),
+                    details: String::from("launched"),
+                    node_id: test_internal.node_id.to_string(),
+                },
+                String::from("test_trace_id"),
+            )),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejected() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(super::rejected(&Status::not_found("test_uuid")));
+        assert!(super::rejected(&Status::permission_denied("test_uuid")));
+        assert!(!super::rejected(&Status::unavailable("test_unreachable")));
+        assert!(!super::rejected(&Status::deadline_exceeded("test_slow")));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn flush_rejected() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let (test_launch_result_sender, _) = channel(4);
+        let (test_shutdown_result_sender, _) = channel(4);
+        let test_interface = InternalInterface::init(
+            Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?),
+            test_launch_result_sender,
+            test_shutdown_result_sender,
+            Arc::new(NodeRegistry::init().await?),
+            test_vm_registry.to_owned(),
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let (test_shutdown, test_shutdown_signal) = tokio::sync::oneshot::channel::<()>();
+        let test_server = tokio::spawn(
+            Server::builder()
+                .add_service(InterfaceServer::new(test_interface))
+                .serve_with_shutdown("127.0.0.1:48214".parse()?, async {
+                    test_shutdown_signal.await.ok();
+                }),
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut test_internal = Internal::init("http://127.0.0.1:48214", &[], None, None).await?;
+        test_vm_registry
+            .pending("test_known_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        for test_uuid in ["test_unknown_uuid", "test_known_uuid"] {
+            test_internal.pending.push_back(PendingResult::Launch(
+                MicroVmLaunch {
+                    uuid: String::from(test_uuid),
+                    launched: true.to_string(),
+                    details: String::from("launched"),
+                    node_id: test_internal.node_id.to_string(),
+                },
+                String::from("test_trace_id"),
+            ));
+        }
+        test_internal.flush_pending().await?;
+        assert!(test_internal.pending.is_empty());
+        let test_record = test_vm_registry.get("test_known_uuid").await.unwrap();
+        assert_eq!(test_record.state, VmState::Running);
+        assert!(test_vm_registry.get("test_unknown_uuid").await.is_none());
+        test_shutdown.send(()).ok();
+        test_server.await??;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failover() -> Result<(), Box<dyn std::error::Error>> {
+        let test_failover_endpoints = [
+            String::from("http://127.0.0.2:1"),
+            String::from("http://127.0.0.3:1"),
+        ];
+        let mut test_internal = Internal::init(
+            TEST_UNREACHABLE_ENDPOINT,
+            &test_failover_endpoints,
+            None,
+            None,
+        )
+        .await?;
+        let test_unreachable = Status::unavailable("test_unreachable");
+        assert!(!test_internal.failover(&test_unreachable).await);
+        assert_eq!(test_internal.endpoint(), "http://127.0.0.2:1");
+        let mut test_redirect = Status::unavailable("test_redirect");
+        test_redirect
+            .metadata_mut()
+            .insert(LEADER_METADATA, "http://127.0.0.3:1".parse()?);
+        assert!(test_internal.failover(&test_redirect).await);
+        assert_eq!(test_internal.endpoint(), "http://127.0.0.3:1");
+        assert!(!test_internal.failover(&test_redirect).await);
+        assert_eq!(test_internal.endpoint(), TEST_UNREACHABLE_ENDPOINT);
+        let mut test_single = Internal::init(TEST_UNREACHABLE_ENDPOINT, &[], None, None).await?;
+        assert!(!test_single.failover(&test_unreachable).await);
+        assert_eq!(test_single.endpoint(), TEST_UNREACHABLE_ENDPOINT);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn node_inventory_from() -> Result<(), Box<dyn std::error::Error>> {
+        let test_inventory = Inventory {
+            cpu_count: 4,
+            memory_total: 1024,
+            memory_available: 512,
+            disk_total: 2048,
+            disk_free: 1024,
+            images: vec![String::from("test_root_fs")],
+            firecracker_version: Some(String::from("Firecracker v1.4.1")),
+            jailer_version: None,
+            vm_ids: vec![String::from("test_uuid")],
+        };
+        let test_node_inventory = NodeInventory::from(test_inventory);
+        assert_eq!(test_node_inventory.cpu_count, 4);
+        assert_eq!(test_node_inventory.memory_available, 512);
+        assert_eq!(test_node_inventory.disk_free, 1024);
+        assert_eq!(test_node_inventory.images.len(), 1);
+        assert_eq!(
+            test_node_inventory.firecracker_version.as_str(),
+            "Firecracker v1.4.1",
+        );
+        assert!(test_node_inventory.jailer_version.is_empty());
+        assert_eq!(test_node_inventory.vm_ids, vec![String::from("test_uuid")]);
+        Ok(())
+    }
+
     // use super::*;
     // use std::str::FromStr;
     //
@@ -1,30 +1,51 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::Stdio;
+use std::process::{Output, Stdio};
+use std::time::{Duration, Instant};
 
 use tokio::fs;
 use tokio::process::Command;
 
+use tracing::{debug, info, warn};
+
 // use uuid::adapter::Simple;
 use uuid::fmt::Simple;
 use uuid::Uuid;
 
-use crate::IMPULSE_ACTUATOR;
+use crate::config::{ActuatorConfig, BootImages};
+use crate::impulse::shared::v010::{MachineSpec, RestartPolicy};
+use crate::metrics::ACTUATOR;
+use crate::profiles;
+pub use inventory::Inventory;
 use layer2::Layer2;
 use layer3::Layer3;
 use micro_vm::MicroVM;
+pub use unit::Exit;
+use unit::UnitStatus;
 
+mod inventory;
 mod layer2;
 mod layer3;
 mod micro_vm;
+mod unit;
+
+pub enum Supervised {
+    Exited(Exit),
+    Restarted { uuid: String, details: String },
+}
 
 pub struct Engine {
     pub firecracker_binary: PathBuf,
     pub jailer_binary: PathBuf,
+    pub systemd_run_binary: PathBuf,
+    pub systemctl_binary: PathBuf,
     pub config_base: PathBuf,
     pub socket_base: PathBuf,
     pub working_base: PathBuf,
     pub images_base: PathBuf,
+    pub boot_images: BootImages,
+    pub restart_backoff: Duration,
+    pub max_restart_backoff: Duration,
     pub launched_vms: HashMap<Simple, MicroVM>,
     pub layer2: Layer2,
     pub layer3: Layer3,
@@ -32,22 +53,29 @@ pub struct Engine {
 }
 
 impl Engine {
-    pub async fn init() -> Result<Engine, Box<dyn std::error::Error>> {
-        let firecracker_binary = PathBuf::from("/usr/bin/firecracker");
-        let jailer_binary = PathBuf::from("/usr/bin/jailer");
+    pub async fn init(config: &ActuatorConfig) -> Result<Engine, Box<dyn std::error::Error>> {
+        let firecracker_binary = config.firecracker_binary.to_owned();
+        let jailer_binary = config.jailer_binary.to_owned();
+        let systemd_run_binary = config.systemd_run_binary.to_owned();
+        let systemctl_binary = config.systemctl_binary.to_owned();
 
-        let config_base = PathBuf::from("/var/lib/impulse_actuator/machine");
+        let config_base = config.config_base.to_owned();
         fs::create_dir_all(&config_base).await?;
 
-        let socket_base = PathBuf::from("/tmp/impulse_actuator/socket");
+        let socket_base = config.socket_base.to_owned();
         fs::create_dir_all(&socket_base).await?;
 
-        let working_base = PathBuf::from("/srv/impulse_actuator/");
+        let working_base = config.working_base.to_owned();
         fs::create_dir_all(&working_base).await?;
 
-        let images_base = PathBuf::from("/var/lib/impulse_actuator/images");
+        let images_base = config.images_base.to_owned();
         fs::create_dir_all(&images_base).await?;
 
+        let boot_images = config.boot_images.to_owned();
+
+        let restart_backoff = Duration::from_millis(config.restart.backoff_ms);
+        let max_restart_backoff = Duration::from_millis(config.restart.max_backoff_ms);
+
         let launched_vms = HashMap::with_capacity(20);
 
         let layer2 = Layer2::init().await?;
@@ -56,10 +84,15 @@ impl Engine {
         Ok(Engine {
             firecracker_binary,
             jailer_binary,
+            systemd_run_binary,
+            systemctl_binary,
             config_base,
             socket_base,
             working_base,
             images_base,
+            boot_images,
+            restart_backoff,
+            max_restart_backoff,
             launched_vms,
             layer2,
             layer3,
@@ -70,62 +103,59 @@ impl Engine {
     pub async fn launch_vm(
         &mut self,
         uuid: &str,
+        machine: &MachineSpec,
     ) -> Result<(bool, String), Box<dyn std::error::Error>> {
-        println!(
-            "{} Preparing to launch new VM | {:?}",
-            IMPULSE_ACTUATOR, uuid,
-        );
+        info!(vm_uuid = uuid, "Preparing to launch new VM");
+
+        let parsed_uuid = Self::parse_uuid(uuid).await?;
+
+        if self.launched_vms.contains_key(&parsed_uuid) {
+            info!(vm_uuid = uuid, "VM is already running... skipping launch");
+
+            return Ok((true, String::from("MicroVM is already running")));
+        }
+
+        let machine = profiles::overlay(&self.boot_images.machine(), machine);
+
+        profiles::validate(&machine)?;
 
         let micro_vm = MicroVM::init(
             uuid,
             self.socket_base.as_path(),
             self.working_base.as_path(),
+            self.config_base.as_path(),
+            &machine,
         )
         .await?;
 
-        println!(
-            "{} Launching new VM with socket | {:?}",
-            IMPULSE_ACTUATOR, &micro_vm.api_socket,
-        );
-        println!(
-            "{} Launching new VM with base | {:?}",
-            IMPULSE_ACTUATOR, &micro_vm.base,
+        info!(
+            api_socket = ?micro_vm.api_socket,
+            base = ?micro_vm.base,
+            config_path = ?micro_vm.config_path,
+            vcpus = machine.vcpus,
+            memory_mib = machine.memory_mib,
+            "Launching new VM",
         );
 
-        println!(
-            "{} Launching new VM with config | {:?}",
-            IMPULSE_ACTUATOR, &micro_vm.config_path,
-        );
+        if let Err(error) = micro_vm.ready_boot(&self.images_base, &machine).await {
+            warn!(vm_uuid = uuid, %error, "MicroVM images could not be prepared");
 
-        micro_vm.ready_boot(&self.images_base).await?;
+            Self::run_cleanup(&micro_vm).await?;
 
-        let stdin = Stdio::null();
-        let stdout = Stdio::null();
-        let stderr = Stdio::null();
+            let details = format!("MicroVM images could not be prepared... {}!", error);
 
-        let command = Command::new("/usr/bin/systemd-run")
-            .stdin(stdin)
-            .stdout(stdout)
-            .stderr(stderr)
-            .arg(&micro_vm.unit_name)
-            .arg(&micro_vm.unit_slice)
-            .arg(&self.firecracker_binary)
-            .arg("--api-sock")
-            .arg(&micro_vm.api_socket)
-            .arg("--config-file")
-            .arg(&micro_vm.config_path)
-            .output()
-            .await?;
+            return Ok((false, details));
+        }
 
-        println!("{:?}", &command);
+        let command = self.start(&micro_vm).await?;
 
         if command.status.success() {
-            let uuid = Self::parse_uuid(uuid).await?;
-
-            if self.launched_vms.insert(uuid, micro_vm).is_none() {
-                println!("{} Launched!", IMPULSE_ACTUATOR);
+            if self.launched_vms.insert(parsed_uuid, micro_vm).is_none() {
+                info!("Launched!");
             }
 
+            ACTUATOR.running_vms.set(self.launched_vms.len() as i64);
+
             Ok((command.status.success(), String::from_utf8(command.stdout)?))
         } else {
             Self::run_cleanup(&micro_vm).await?;
@@ -142,9 +172,9 @@ impl Engine {
 
         match self.launched_vms.contains_key(&simple_uuid) {
             true => {
-                println!("{} Shutting down VM | {:?}", IMPULSE_ACTUATOR, uuid);
+                info!(vm_uuid = uuid, "Shutting down VM");
 
-                let command = Command::new("/usr/bin/systemctl")
+                let command = Command::new(&self.systemctl_binary)
                     .arg("stop")
                     .arg(format!("{}.slice", &simple_uuid))
                     .output()
@@ -154,8 +184,11 @@ impl Engine {
                     if let Some(micro_vm) = self.launched_vms.remove(&simple_uuid) {
                         Self::run_cleanup(&micro_vm).await?;
 
-                        println!("{} MicroVM has been shutdown!", IMPULSE_ACTUATOR);
+                        info!("MicroVM has been shutdown!");
                     }
+
+                    ACTUATOR.running_vms.set(self.launched_vms.len() as i64);
+
                     Ok((command.status.success(), String::from_utf8(command.stdout)?))
                 } else {
                     Ok((command.status.success(), String::from_utf8(command.stderr)?))
@@ -165,6 +198,64 @@ impl Engine {
         }
     }
 
+    pub async fn supervise(&mut self) -> Vec<Supervised> {
+        let mut supervised = Vec::with_capacity(0);
+        let mut uuids: Vec<Simple> = self.launched_vms.keys().copied().collect();
+
+        uuids.sort();
+
+        for uuid in uuids {
+            let restart_at = match self.launched_vms.get(&uuid) {
+                Some(micro_vm) => micro_vm.restart_at,
+                None => continue,
+            };
+
+            match restart_at {
+                Some(restart_at) if restart_at <= Instant::now() => {
+                    match self.restart(uuid).await {
+                        Ok(Some(result)) => supervised.push(result),
+                        Ok(None) => (),
+                        Err(error) => warn!(vm_uuid = %uuid, %error, "Unable to restart MicroVM"),
+                    }
+                }
+                Some(_) => (),
+                None => match self.poll(uuid).await {
+                    Ok(Some(exit)) => supervised.push(Supervised::Exited(exit)),
+                    Ok(None) => (),
+                    Err(error) => warn!(vm_uuid = %uuid, %error, "Unable to poll MicroVM"),
+                },
+            }
+        }
+
+        supervised
+    }
+
+    pub async fn inventory(&self) -> Result<Inventory, Box<dyn std::error::Error>> {
+        let mut inventory = Inventory::collect(
+            &self.working_base,
+            &self.images_base,
+            &self.firecracker_binary,
+            &self.jailer_binary,
+        )
+        .await?;
+
+        inventory.vm_ids = self.vm_ids();
+
+        Ok(inventory)
+    }
+
+    pub fn vm_ids(&self) -> Vec<String> {
+        let mut vm_ids: Vec<String> = self
+            .launched_vms
+            .keys()
+            .map(|uuid| uuid.to_string())
+            .collect();
+
+        vm_ids.sort();
+
+        vm_ids
+    }
+
     pub async fn shutdown(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if self.active {
             self.active = false;
@@ -173,6 +264,193 @@ impl Engine {
         Ok(())
     }
 
+    async fn start(&self, micro_vm: &MicroVM) -> Result<Output, Box<dyn std::error::Error>> {
+        let stdin = Stdio::null();
+        let stdout = Stdio::null();
+        let stderr = Stdio::null();
+
+        let command = Command::new(&self.systemd_run_binary)
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(stderr)
+            .arg(&micro_vm.unit_name)
+            .arg(&micro_vm.unit_slice)
+            .arg(&self.firecracker_binary)
+            .arg("--api-sock")
+            .arg(&micro_vm.api_socket)
+            .arg("--config-file")
+            .arg(&micro_vm.config_path)
+            .output()
+            .await?;
+
+        debug!(?command, "systemd-run finished");
+
+        Ok(command)
+    }
+
+    async fn poll(&mut self, uuid: Simple) -> Result<Option<Exit>, Box<dyn std::error::Error>> {
+        let command = match Command::new(&self.systemctl_binary)
+            .arg("show")
+            .arg(format!("{}.service", uuid))
+            .arg("--property=LoadState,ActiveState,Result,ExecMainStatus")
+            .output()
+            .await
+        {
+            Ok(command) => command,
+            Err(error) => {
+                warn!(vm_uuid = %uuid, %error, "Unable to query MicroVM unit");
+
+                return Ok(None);
+            }
+        };
+
+        if !command.status.success() {
+            warn!(
+                vm_uuid = %uuid,
+                stderr = %String::from_utf8_lossy(&command.stderr),
+                "Unable to query MicroVM unit",
+            );
+
+            return Ok(None);
+        }
+
+        let status = UnitStatus::parse(&String::from_utf8_lossy(&command.stdout));
+
+        match status.exited() {
+            Some((reason, failed)) => match self.launched_vms.remove(&uuid) {
+                Some(micro_vm) => {
+                    let exit = self
+                        .exited(uuid, micro_vm, reason, status.exit_code, failed)
+                        .await?;
+
+                    Ok(Some(exit))
+                }
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn restart(
+        &mut self,
+        uuid: Simple,
+    ) -> Result<Option<Supervised>, Box<dyn std::error::Error>> {
+        let mut micro_vm = match self.launched_vms.remove(&uuid) {
+            Some(micro_vm) => micro_vm,
+            None => return Ok(None),
+        };
+
+        info!(vm_uuid = %uuid, restarts = micro_vm.restarts, "Restarting MicroVM");
+
+        micro_vm.started = Instant::now();
+        micro_vm.restart_at = None;
+
+        let (started, details) = match micro_vm.cleanup_api_socket().await {
+            Ok(()) => match self.start(&micro_vm).await {
+                Ok(command) if command.status.success() => (true, String::new()),
+                Ok(command) => (false, String::from_utf8_lossy(&command.stderr).into_owned()),
+                Err(error) => (false, error.to_string()),
+            },
+            Err(error) => (false, error.to_string()),
+        };
+
+        if started {
+            let details = format!("MicroVM restarted (restart {})", micro_vm.restarts);
+
+            self.launched_vms.insert(uuid, micro_vm);
+
+            Ok(Some(Supervised::Restarted {
+                uuid: uuid.to_string(),
+                details,
+            }))
+        } else {
+            let reason = format!("failed to restart: {}", details.trim());
+            let exit = self.exited(uuid, micro_vm, reason, -1, true).await?;
+
+            Ok(Some(Supervised::Exited(exit)))
+        }
+    }
+
+    async fn exited(
+        &mut self,
+        uuid: Simple,
+        mut micro_vm: MicroVM,
+        reason: String,
+        exit_code: i32,
+        failed: bool,
+    ) -> Result<Exit, Box<dyn std::error::Error>> {
+        if micro_vm.started.elapsed() >= self.max_restart_backoff {
+            micro_vm.restarts = 0;
+        }
+
+        let restarting = match micro_vm.restart_policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => failed,
+            RestartPolicy::Never | RestartPolicy::Unspecified => false,
+        };
+
+        if let Err(error) = Command::new(&self.systemctl_binary)
+            .arg("reset-failed")
+            .arg(format!("{}.service", uuid))
+            .output()
+            .await
+        {
+            debug!(vm_uuid = %uuid, %error, "Unable to reset MicroVM unit");
+        }
+
+        let outcome = match (failed, restarting) {
+            (_, true) => "restarting",
+            (true, false) => "failed",
+            (false, false) => "stopped",
+        };
+
+        ACTUATOR.exits.with_label_values(&[outcome]).inc();
+
+        if restarting {
+            let delay = self
+                .restart_backoff
+                .saturating_mul(1 << micro_vm.restarts.min(16))
+                .min(self.max_restart_backoff);
+
+            micro_vm.restarts += 1;
+            micro_vm.restart_at = Some(Instant::now() + delay);
+
+            info!(
+                vm_uuid = %uuid,
+                reason = reason.as_str(),
+                restarts = micro_vm.restarts,
+                ?delay,
+                "MicroVM exited... restart scheduled",
+            );
+        } else {
+            info!(
+                vm_uuid = %uuid,
+                reason = reason.as_str(),
+                failed,
+                "MicroVM exited",
+            );
+        }
+
+        let exit = Exit {
+            uuid: uuid.to_string(),
+            reason,
+            exit_code,
+            failed,
+            restarting,
+            restarts: micro_vm.restarts,
+        };
+
+        if restarting {
+            self.launched_vms.insert(uuid, micro_vm);
+        } else {
+            Self::run_cleanup(&micro_vm).await?;
+        }
+
+        ACTUATOR.running_vms.set(self.launched_vms.len() as i64);
+
+        Ok(exit)
+    }
+
     async fn parse_uuid(uuid: &str) -> Result<Simple, Box<dyn std::error::Error>> {
         let parsed_uuid = Uuid::parse_str(uuid)?;
 
@@ -181,19 +459,13 @@ impl Engine {
 
     async fn run_cleanup(micro_vm: &MicroVM) -> Result<(), Box<dyn std::error::Error>> {
         micro_vm.cleanup_api_socket().await?;
-        println!(
-            "{} Removing socket | {:?}",
-            IMPULSE_ACTUATOR, &micro_vm.api_socket,
-        );
+        debug!(api_socket = ?micro_vm.api_socket, "Removed socket");
 
         micro_vm.cleanup_base().await?;
-        println!("{} Removing base | {:?}", IMPULSE_ACTUATOR, &micro_vm.base);
+        debug!(base = ?micro_vm.base, "Removed base");
 
         micro_vm.cleanup_config_path().await?;
-        println!(
-            "{} Removing config file | {:?}",
-            IMPULSE_ACTUATOR, &micro_vm.base,
-        );
+        debug!(config_path = ?micro_vm.config_path, "Removed config file");
 
         Ok(())
     }
@@ -207,7 +479,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn init() -> Result<(), Box<dyn std::error::Error>> {
-        let test_engine = Engine::init().await?;
+        let test_engine = Engine::init(&ActuatorConfig::default()).await?;
         assert_eq!(
             test_engine.firecracker_binary.to_str().unwrap(),
             "/usr/bin/firecracker",
@@ -252,27 +524,251 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn launch_vm() -> Result<(), Box<dyn std::error::Error>> {
-        let mut test_engine = Engine::init().await?;
-        let test_engine_boot = test_engine
-            .launch_vm(TEST_LAUNCH_VM_UUID.simple().to_string().as_str())
-            .await;
-        assert!(test_engine_boot.is_err());
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await?;
+        let (test_launched, test_details) = test_engine
+            .launch_vm(
+                TEST_LAUNCH_VM_UUID.simple().to_string().as_str(),
+                &MachineSpec::default(),
+            )
+            .await?;
+        assert!(!test_launched);
+        assert!(test_details.starts_with("MicroVM images could not be prepared"));
         assert!(test_engine.launched_vms.is_empty());
+        assert!(fs::metadata(
+            test_engine
+                .working_base
+                .join(TEST_LAUNCH_VM_UUID.simple().to_string())
+        )
+        .await
+        .is_err());
+        let test_invalid = test_engine
+            .launch_vm(
+                TEST_LAUNCH_VM_UUID.simple().to_string().as_str(),
+                &MachineSpec {
+                    root_fs: String::from("../etc/shadow"),
+                    ..MachineSpec::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(test_invalid.to_string().contains("not a plain file name"));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_vm_running() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await?;
+        let test_uuid = Uuid::parse_str("00000000000000000000000000000001")?.simple();
+        let test_micro_vm = MicroVM::init(
+            test_uuid.to_string().as_str(),
+            test_engine.socket_base.as_path(),
+            test_engine.working_base.as_path(),
+            test_engine.config_base.as_path(),
+            &test_engine.boot_images.machine(),
+        )
+        .await?;
+        test_engine.launched_vms.insert(test_uuid, test_micro_vm);
+        let (test_launched, test_details) = test_engine
+            .launch_vm(test_uuid.to_string().as_str(), &MachineSpec::default())
+            .await?;
+        assert!(test_launched);
+        assert_eq!(test_details.as_str(), "MicroVM is already running");
+        assert_eq!(test_engine.launched_vms.len(), 1);
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn shutdown_vm() {
-        let mut test_engine = Engine::init().await.unwrap();
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await.unwrap();
         let test_engine_shutdown_vm = test_engine
             .shutdown_vm(TEST_LAUNCH_VM_UUID.simple().to_string().as_str())
             .await;
         assert!(test_engine_shutdown_vm.is_ok());
     }
 
+    async fn test_restarting_vm(
+        test_engine: &Engine,
+        test_uuid: Simple,
+        test_restart_policy: RestartPolicy,
+    ) -> Result<MicroVM, Box<dyn std::error::Error>> {
+        let test_machine = MachineSpec {
+            restart_policy: test_restart_policy as i32,
+            ..test_engine.boot_images.machine()
+        };
+
+        MicroVM::init(
+            test_uuid.to_string().as_str(),
+            test_engine.socket_base.as_path(),
+            test_engine.working_base.as_path(),
+            test_engine.config_base.as_path(),
+            &test_machine,
+        )
+        .await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exited() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_engine = Engine::init(&ActuatorConfig {
+            systemctl_binary: PathBuf::from("/nonexistent/systemctl"),
+            ..ActuatorConfig::default()
+        })
+        .await?;
+        let test_never = Uuid::parse_str("00000000000000000000000000000010")?.simple();
+        let test_micro_vm =
+            test_restarting_vm(&test_engine, test_never, RestartPolicy::Never).await?;
+        let test_exit = test_engine
+            .exited(
+                test_never,
+                test_micro_vm,
+                String::from("exited with status 1"),
+                1,
+                true,
+            )
+            .await?;
+        assert!(test_exit.failed);
+        assert!(!test_exit.restarting);
+        assert!(!test_engine.launched_vms.contains_key(&test_never));
+        let test_on_failure = Uuid::parse_str("00000000000000000000000000000011")?.simple();
+        let test_micro_vm =
+            test_restarting_vm(&test_engine, test_on_failure, RestartPolicy::OnFailure).await?;
+        let test_exit = test_engine
+            .exited(
+                test_on_failure,
+                test_micro_vm,
+                String::from("exited cleanly"),
+                0,
+                false,
+            )
+            .await?;
+        assert!(!test_exit.restarting);
+        let test_micro_vm =
+            test_restarting_vm(&test_engine, test_on_failure, RestartPolicy::OnFailure).await?;
+        let test_exit = test_engine
+            .exited(
+                test_on_failure,
+                test_micro_vm,
+                String::from("was killed by signal 9"),
+                9,
+                true,
+            )
+            .await?;
+        assert!(test_exit.restarting);
+        assert_eq!(test_exit.restarts, 1);
+        let test_restart_at = test_engine.launched_vms[&test_on_failure].restart_at;
+        assert!(test_restart_at.is_some());
+        let test_micro_vm = test_engine.launched_vms.remove(&test_on_failure).unwrap();
+        let test_exit = test_engine
+            .exited(
+                test_on_failure,
+                test_micro_vm,
+                String::from("was killed by signal 9"),
+                9,
+                true,
+            )
+            .await?;
+        assert_eq!(test_exit.restarts, 2);
+        assert!(test_engine.launched_vms[&test_on_failure].restart_at > test_restart_at);
+        let test_always = Uuid::parse_str("00000000000000000000000000000012")?.simple();
+        let test_micro_vm =
+            test_restarting_vm(&test_engine, test_always, RestartPolicy::Always).await?;
+        let test_exit = test_engine
+            .exited(
+                test_always,
+                test_micro_vm,
+                String::from("exited cleanly"),
+                0,
+                false,
+            )
+            .await?;
+        assert!(test_exit.restarting);
+        assert!(test_engine.launched_vms.contains_key(&test_always));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn supervise() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_engine = Engine::init(&ActuatorConfig {
+            systemctl_binary: PathBuf::from("/nonexistent/systemctl"),
+            systemd_run_binary: PathBuf::from("/bin/true"),
+            ..ActuatorConfig::default()
+        })
+        .await?;
+        let test_uuid = Uuid::parse_str("00000000000000000000000000000013")?.simple();
+        let mut test_micro_vm =
+            test_restarting_vm(&test_engine, test_uuid, RestartPolicy::Always).await?;
+        test_micro_vm.restarts = 1;
+        test_micro_vm.restart_at = Some(Instant::now());
+        test_engine.launched_vms.insert(test_uuid, test_micro_vm);
+        let test_supervised = test_engine.supervise().await;
+        assert_eq!(test_supervised.len(), 1);
+        match &test_supervised[0] {
+            Supervised::Restarted { uuid, details } => {
+                assert_eq!(uuid, &test_uuid.to_string());
+                assert_eq!(details.as_str(), "MicroVM restarted (restart 1)");
+            }
+            Supervised::Exited(_) => panic!("expected a restart"),
+        }
+        assert!(test_engine.launched_vms[&test_uuid].restart_at.is_none());
+        assert!(test_engine.supervise().await.is_empty());
+        assert!(test_engine.launched_vms.contains_key(&test_uuid));
+        test_engine.systemd_run_binary = PathBuf::from("/bin/false");
+        test_engine
+            .launched_vms
+            .get_mut(&test_uuid)
+            .unwrap()
+            .restart_policy = RestartPolicy::OnFailure;
+        test_engine
+            .launched_vms
+            .get_mut(&test_uuid)
+            .unwrap()
+            .restart_at = Some(Instant::now());
+        let test_supervised = test_engine.supervise().await;
+        match &test_supervised[0] {
+            Supervised::Exited(test_exit) => {
+                assert!(test_exit.failed);
+                assert!(test_exit.restarting);
+                assert!(test_exit.reason.starts_with("failed to restart"));
+            }
+            Supervised::Restarted { .. } => panic!("expected an exit"),
+        }
+        test_engine
+            .launched_vms
+            .get_mut(&test_uuid)
+            .unwrap()
+            .restart_policy = RestartPolicy::Never;
+        test_engine
+            .launched_vms
+            .get_mut(&test_uuid)
+            .unwrap()
+            .restart_at = Some(Instant::now());
+        let test_supervised = test_engine.supervise().await;
+        match &test_supervised[0] {
+            Supervised::Exited(test_exit) => assert!(!test_exit.restarting),
+            Supervised::Restarted { .. } => panic!("expected an exit"),
+        }
+        assert!(test_engine.launched_vms.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn inventory() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = ActuatorConfig {
+            firecracker_binary: PathBuf::from("/var/lib/test_impulse_actuator/missing_firecracker"),
+            jailer_binary: PathBuf::from("/var/lib/test_impulse_actuator/missing_jailer"),
+            ..ActuatorConfig::default()
+        };
+        let test_engine = Engine::init(&test_config).await?;
+        let test_inventory = test_engine.inventory().await?;
+        assert!(test_inventory.cpu_count >= 1);
+        assert!(test_inventory.firecracker_version.is_none());
+        assert!(test_inventory.jailer_version.is_none());
+        assert!(test_inventory.vm_ids.is_empty());
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn shutdown() -> Result<(), Box<dyn std::error::Error>> {
-        let mut test_engine = Engine::init().await?;
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await?;
         assert!(test_engine.active);
         test_engine.shutdown().await?;
         assert!(!test_engine.active);
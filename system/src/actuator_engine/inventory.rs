@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use nix::sys::statvfs::statvfs;
+
+use tokio::fs::{read_dir, read_to_string};
+use tokio::process::Command;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inventory {
+    pub cpu_count: u32,
+    pub memory_total: u64,
+    pub memory_available: u64,
+    pub disk_total: u64,
+    pub disk_free: u64,
+    pub images: Vec<String>,
+    pub firecracker_version: Option<String>,
+    pub jailer_version: Option<String>,
+    pub vm_ids: Vec<String>,
+}
+
+impl Inventory {
+    pub async fn collect(
+        working_base: &Path,
+        images_base: &Path,
+        firecracker_binary: &Path,
+        jailer_binary: &Path,
+    ) -> Result<Inventory, Box<dyn std::error::Error>> {
+        let cpuinfo = read_to_string("/proc/cpuinfo").await?;
+        let cpu_count = Self::parse_cpu_count(&cpuinfo).await;
+
+        let meminfo = read_to_string("/proc/meminfo").await?;
+        let (memory_total, memory_available) = Self::parse_memory(&meminfo).await;
+
+        let (disk_total, disk_free) = Self::disk(working_base).await?;
+        let images = Self::images(images_base).await?;
+
+        let firecracker_version = Self::version(firecracker_binary).await;
+        let jailer_version = Self::version(jailer_binary).await;
+
+        Ok(Inventory {
+            cpu_count,
+            memory_total,
+            memory_available,
+            disk_total,
+            disk_free,
+            images,
+            firecracker_version,
+            jailer_version,
+            vm_ids: Vec::with_capacity(0),
+        })
+    }
+
+    async fn parse_cpu_count(cpuinfo: &str) -> u32 {
+        let processors = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("processor"))
+            .count() as u32;
+
+        processors.max(1)
+    }
+
+    async fn parse_memory(meminfo: &str) -> (u64, u64) {
+        let mut memory_total = 0;
+        let mut memory_available = 0;
+
+        for line in meminfo.lines() {
+            let mut fields = line.split_whitespace();
+            let key = fields.next();
+            let kib = fields.next().and_then(|value| value.parse::<u64>().ok());
+
+            match (key, kib) {
+                (Some("MemTotal:"), Some(kib)) => memory_total = kib * 1024,
+                (Some("MemAvailable:"), Some(kib)) => memory_available = kib * 1024,
+                _ => (),
+            }
+        }
+
+        (memory_total, memory_available)
+    }
+
+    async fn disk(working_base: &Path) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let stat = statvfs(working_base)?;
+        let fragment_size = stat.fragment_size() as u64;
+        let disk_total = stat.blocks() as u64 * fragment_size;
+        let disk_free = stat.blocks_available() as u64 * fragment_size;
+
+        Ok((disk_total, disk_free))
+    }
+
+    async fn images(images_base: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut images = Vec::with_capacity(10);
+        let mut entries = read_dir(images_base).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                images.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+
+        images.sort();
+
+        Ok(images)
+    }
+
+    async fn version(binary: &Path) -> Option<String> {
+        match Command::new(binary).arg("--version").output().await {
+            Ok(output) if output.status.success() => Self::parse_version(&output.stdout).await,
+            _ => None,
+        }
+    }
+
+    async fn parse_version(output: &[u8]) -> Option<String> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .next()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn collect() -> Result<(), Box<dyn std::error::Error>> {
+        let test_images_base = Path::new("/var/lib/test_impulse_actuator/inventory_images");
+        tokio::fs::create_dir_all(test_images_base).await?;
+        tokio::fs::write(test_images_base.join("test_root_fs"), b"test root fs").await?;
+        let test_inventory = Inventory::collect(
+            Path::new("/tmp"),
+            test_images_base,
+            &test_images_base.join("missing_firecracker"),
+            &test_images_base.join("missing_jailer"),
+        )
+        .await?;
+        assert!(test_inventory.cpu_count >= 1);
+        assert!(test_inventory.memory_total > 0);
+        assert!(test_inventory.disk_total >= test_inventory.disk_free);
+        assert!(test_inventory
+            .images
+            .contains(&String::from("test_root_fs")));
+        assert!(test_inventory.firecracker_version.is_none());
+        assert!(test_inventory.jailer_version.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_cpu_count() -> Result<(), Box<dyn std::error::Error>> {
+        let test_cpuinfo = "processor\t: 0\nmodel name\t: test\n\nprocessor\t: 1\n";
+        assert_eq!(Inventory::parse_cpu_count(test_cpuinfo).await, 2);
+        assert_eq!(Inventory::parse_cpu_count("").await, 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_memory() -> Result<(), Box<dyn std::error::Error>> {
+        let test_meminfo =
+            "MemTotal:        2048 kB\nMemFree:          512 kB\nMemAvailable:    1024 kB\n";
+        let (test_total, test_available) = Inventory::parse_memory(test_meminfo).await;
+        assert_eq!(test_total, 2048 * 1024);
+        assert_eq!(test_available, 1024 * 1024);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_version() -> Result<(), Box<dyn std::error::Error>> {
+        let test_output =
+            b"Firecracker v1.4.1\n\nSupported snapshot data format versions: v1.0.0\n";
+        assert_eq!(
+            Inventory::parse_version(test_output).await,
+            Some(String::from("Firecracker v1.4.1")),
+        );
+        assert!(Inventory::parse_version(b"").await.is_none());
+        Ok(())
+    }
+}
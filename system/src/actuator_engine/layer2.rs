@@ -1,21 +1,62 @@
+use std::collections::HashSet;
+
 use rand::distributions::{Distribution, Uniform};
 use rand::rngs::ThreadRng;
 use rand::thread_rng;
 
+use crate::metrics::ACTUATOR;
+use crate::system_error::SystemError;
+
+const MAC_ADDRESS_SPACE: i64 = 16_i64.pow(11);
+
 pub struct Layer2 {
     rng: ThreadRng,
     uniform: Uniform<u32>,
+    assigned: HashSet<String>,
 }
 
 impl Layer2 {
     pub async fn init() -> Result<Layer2, Box<dyn std::error::Error>> {
         let rng = thread_rng();
         let uniform = Uniform::new_inclusive(0, 15);
+        let assigned = HashSet::with_capacity(256);
 
-        Ok(Layer2 { rng, uniform })
+        ACTUATOR.free_mac_addresses.set(MAC_ADDRESS_SPACE);
+
+        Ok(Layer2 {
+            rng,
+            uniform,
+            assigned,
+        })
     }
 
     pub async fn generate_mac_address(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        for _ in 0..=15 {
+            let mac_address = self.random_mac_address().await;
+
+            if self.assigned.insert(mac_address.to_owned()) {
+                ACTUATOR
+                    .free_mac_addresses
+                    .set(MAC_ADDRESS_SPACE - self.assigned.len() as i64);
+
+                return Ok(mac_address);
+            }
+        }
+
+        let error = SystemError::new("Unable to generate a unique MAC address");
+
+        Err(Box::new(error))
+    }
+
+    pub async fn reclaim_mac_address(&mut self, mac_address: &str) {
+        if self.assigned.remove(mac_address) {
+            ACTUATOR
+                .free_mac_addresses
+                .set(MAC_ADDRESS_SPACE - self.assigned.len() as i64);
+        }
+    }
+
+    async fn random_mac_address(&mut self) -> String {
         let (first_octet_digit_one, _) = self.generate_digit().await;
         let (second_octet_digit_one, second_octet_digit_two) = self.generate_digit().await;
         let (third_octet_digit_one, third_octet_digit_two) = self.generate_digit().await;
@@ -38,7 +79,7 @@ impl Layer2 {
             sixth_octet_digit_two,
         );
 
-        Ok(mac_address)
+        mac_address
     }
 
     async fn generate_digit(&mut self) -> (String, String) {
@@ -74,6 +115,17 @@ mod tests {
         let mut test_layer2 = Layer2::init().await?;
         let test_mac_address = test_layer2.generate_mac_address().await?;
         assert_eq!(test_mac_address.len(), 17);
+        assert!(test_layer2.assigned.contains(&test_mac_address));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reclaim_mac_address() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_layer2 = Layer2::init().await?;
+        let test_mac_address = test_layer2.generate_mac_address().await?;
+        assert_eq!(test_layer2.assigned.len(), 1);
+        test_layer2.reclaim_mac_address(&test_mac_address).await;
+        assert!(test_layer2.assigned.is_empty());
         Ok(())
     }
 
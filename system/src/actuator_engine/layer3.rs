@@ -2,11 +2,12 @@ use rand::seq::SliceRandom;
 use rand::thread_rng;
 
 use std::net::Ipv4Addr;
-use std::u8::{MAX, MIN};
 use std::vec::Vec;
 
+use crate::metrics::ACTUATOR;
 use crate::system_error::SystemError;
 
+#[allow(dead_code)]
 #[derive(Debug, PartialEq)]
 enum Class {
     A,
@@ -26,7 +27,7 @@ impl Class {
     async fn generate(&self) -> Vec<Ipv4Addr> {
         let mut range = Vec::with_capacity(256);
 
-        for address in MIN..=MAX {
+        for address in u8::MIN..=u8::MAX {
             let new = match self {
                 Class::A => Ipv4Addr::new(10, 10, 10, address),
                 Class::B => Ipv4Addr::new(172, 31, 10, address),
@@ -38,6 +39,7 @@ impl Class {
     }
 }
 
+#[allow(dead_code)]
 pub struct Layer3 {
     dhcp_enabled: bool,
     class: Class,
@@ -54,6 +56,8 @@ impl Layer3 {
         let pool = class.generate().await;
         let assigned = Vec::with_capacity(256);
 
+        ACTUATOR.free_ip_addresses.set(pool.len() as i64);
+
         Ok(Layer3 {
             dhcp_enabled,
             class,
@@ -74,11 +78,14 @@ impl Layer3 {
                 }
                 false => {
                     self.assigned.push(random_address);
+                    self.observe_free().await;
                     return Ok(random_address);
                 }
             }
         }
 
+        ACTUATOR.address_pool_exhausted.inc();
+
         let error = SystemError::new("The address pool is exhausted");
 
         Err(Box::new(error))
@@ -94,9 +101,17 @@ impl Layer3 {
             }
         }
 
+        self.observe_free().await;
+
         Ok(())
     }
 
+    async fn observe_free(&self) {
+        let free = self.pool.len().saturating_sub(self.assigned.len());
+
+        ACTUATOR.free_ip_addresses.set(free as i64);
+    }
+
     async fn choose_random_address(&self) -> Ipv4Addr {
         let mut rng = thread_rng();
         *self.pool.choose(&mut rng).unwrap()
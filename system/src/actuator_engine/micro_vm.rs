@@ -1,9 +1,11 @@
 use std::path::Path;
+use std::time::Instant;
 
 use tokio::fs::{copy, create_dir_all, metadata, remove_dir_all, remove_file};
 
 use std::path::PathBuf;
 
+use crate::impulse::shared::v010::{MachineSpec, RestartPolicy};
 use config_file::ConfigFile;
 
 mod config_file;
@@ -15,6 +17,10 @@ pub struct MicroVM {
     pub base: PathBuf,
     pub unit_name: String,
     pub unit_slice: String,
+    pub restart_policy: RestartPolicy,
+    pub restarts: u32,
+    pub started: Instant,
+    pub restart_at: Option<Instant>,
 }
 
 impl MicroVM {
@@ -22,13 +28,15 @@ impl MicroVM {
         uuid: &str,
         socket_base: &Path,
         working_base: &Path,
+        config_base: &Path,
+        machine: &MachineSpec,
     ) -> Result<MicroVM, Box<dyn std::error::Error>> {
         let mut api_socket = socket_base.to_path_buf();
         api_socket.push(uuid);
         api_socket.set_extension("socket");
 
-        let config_file = ConfigFile::build(uuid).await?;
-        let config_path = config_file.write(uuid).await?;
+        let config_file = ConfigFile::build(uuid, working_base, machine).await?;
+        let config_path = config_file.write(uuid, config_base).await?;
 
         let mut base = working_base.to_path_buf();
         base.push(uuid);
@@ -38,6 +46,11 @@ impl MicroVM {
         let unit_name = format!("--unit={}", uuid);
         let unit_slice = format!("--slice={}", uuid);
 
+        let restart_policy = match RestartPolicy::from_i32(machine.restart_policy) {
+            Some(RestartPolicy::Unspecified) | None => RestartPolicy::Never,
+            Some(restart_policy) => restart_policy,
+        };
+
         Ok(MicroVM {
             api_socket,
             config_file,
@@ -45,13 +58,21 @@ impl MicroVM {
             base,
             unit_name,
             unit_slice,
+            restart_policy,
+            restarts: 0,
+            started: Instant::now(),
+            restart_at: None,
         })
     }
 
-    pub async fn ready_boot(&self, images: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        let kernel_image_name = "some_kernel_image";
-        let initrd_name = "some_initrd";
-        let root_fs_name = "some_root_fs";
+    pub async fn ready_boot(
+        &self,
+        images: &Path,
+        machine: &MachineSpec,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let kernel_image_name = &machine.kernel_image;
+        let initrd_name = &machine.initrd;
+        let root_fs_name = &machine.root_fs;
 
         let base_kernel_image = images.join(kernel_image_name);
         let base_initrd = images.join(initrd_name);
@@ -65,6 +86,10 @@ impl MicroVM {
         copy(base_initrd, running_initrd).await?;
         copy(base_root_fs, running_root_fs).await?;
 
+        for drive in &machine.drives {
+            copy(images.join(&drive.image), self.base.join(&drive.image)).await?;
+        }
+
         Ok(())
     }
 
@@ -103,9 +128,12 @@ impl MicroVM {
 mod tests {
     use super::*;
 
+    use crate::config::BootImages;
+
     const TEST_MICROVM_UUID: uuid::Uuid = uuid::Uuid::nil();
     const TEST_SOCKET_BASE: &str = "/tmp/test_impulse_actuator/socket";
     const TEST_WORKING_BASE: &str = "/srv/test_impulse_actuator/";
+    const TEST_CONFIG_BASE: &str = "/var/lib/test_impulse_actuator/machine";
 
     #[tokio::test(flavor = "multi_thread")]
     async fn init() -> Result<(), Box<dyn std::error::Error>> {
@@ -113,6 +141,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         let test_micro_vm_srv_metadata = metadata(&test_micro_vm.base).await?;
@@ -137,6 +167,9 @@ mod tests {
             test_micro_vm.unit_slice.as_str(),
             "--slice=00000000000000000000000000000000",
         );
+        assert_eq!(test_micro_vm.restart_policy, RestartPolicy::Never);
+        assert_eq!(test_micro_vm.restarts, 0);
+        assert!(test_micro_vm.restart_at.is_none());
         Ok(())
     }
 
@@ -146,6 +179,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         let test_images_base = Path::new("/var/lib/test_impulse_actuator/images");
@@ -157,7 +192,9 @@ mod tests {
         .await?;
         tokio::fs::write(test_images_base.join("some_initrd"), b"test initrd").await?;
         tokio::fs::write(test_images_base.join("some_root_fs"), b"test root fs").await?;
-        let test_ready_boot = test_micro_vm.ready_boot(test_images_base).await;
+        let test_ready_boot = test_micro_vm
+            .ready_boot(test_images_base, &BootImages::default().machine())
+            .await;
         assert!(test_ready_boot.is_ok());
         let test_kernel_image_md =
             metadata(&test_micro_vm.base.as_path().join("some_kernel_image")).await?;
@@ -182,6 +219,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         tokio::fs::write(&test_micro_vm.api_socket, b"test socket").await?;
@@ -198,6 +237,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         let test_cleanup_api_socket = test_micro_vm.cleanup_api_socket().await;
@@ -211,6 +252,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         tokio::fs::write(&test_micro_vm.base.join("test_file_1"), b"test base file 1").await?;
@@ -229,6 +272,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         assert!(metadata(&test_micro_vm.base).await.is_ok());
@@ -245,6 +290,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         assert!(metadata(&test_micro_vm.config_path).await.is_ok());
@@ -260,6 +307,8 @@ mod tests {
             TEST_MICROVM_UUID.simple().to_string().as_str(),
             Path::new(TEST_SOCKET_BASE),
             Path::new(TEST_WORKING_BASE),
+            Path::new(TEST_CONFIG_BASE),
+            &BootImages::default().machine(),
         )
         .await?;
         assert!(metadata(&test_micro_vm.config_path).await.is_ok());
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +7,9 @@ use serde_json::to_string_pretty;
 use tokio::fs::create_dir_all;
 use tokio::fs::write;
 
+use crate::impulse::shared::v010::{MachineSpec, NetworkInterface};
+use crate::profiles::ROOT_DRIVE_ID;
+
 #[derive(Deserialize, Serialize)]
 pub struct ConfigFile {
     #[serde(rename = "boot-source")]
@@ -31,21 +34,39 @@ pub struct ConfigFile {
 }
 
 impl ConfigFile {
-    pub async fn build(uuid: &str) -> Result<ConfigFile, Box<dyn std::error::Error>> {
-        let boot_source = BootSource::build(uuid).await?;
-        let mut drives = Vec::with_capacity(3);
-        let drive = Drive::build(false, true, uuid).await?;
+    pub async fn build(
+        uuid: &str,
+        working_base: &Path,
+        machine: &MachineSpec,
+    ) -> Result<ConfigFile, Box<dyn std::error::Error>> {
+        let base = working_base.join(uuid);
+        let boot_source = BootSource::build(&base, machine).await?;
+        let mut drives = Vec::with_capacity(machine.drives.len() + 1);
+        let drive = Drive::build(false, true, &base, ROOT_DRIVE_ID, &machine.root_fs).await?;
 
         drives.push(drive);
 
-        let machine_config = MachineConfig::build().await?;
+        for drive in &machine.drives {
+            drives.push(
+                Drive::build(drive.read_only, false, &base, &drive.drive_id, &drive.image).await?,
+            );
+        }
+
+        let machine_config = MachineConfig::build(machine).await?;
+
+        let mut network_interfaces = Vec::with_capacity(machine.network_interfaces.len());
+
+        for interface in &machine.network_interfaces {
+            network_interfaces.push(NetworkInterfaces::build(interface).await?);
+        }
 
         Ok(ConfigFile {
             boot_source,
             drives,
             machine_config,
             balloon: None,
-            network_interfaces: None,
+            network_interfaces: Some(network_interfaces)
+                .filter(|interfaces| !interfaces.is_empty()),
             vsock: None,
             logger: None,
             metrics: None,
@@ -53,8 +74,12 @@ impl ConfigFile {
         })
     }
 
-    pub async fn write(&self, uuid: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let mut config_file = PathBuf::from("/var/lib/impulse_actuator/machine/");
+    pub async fn write(
+        &self,
+        uuid: &str,
+        config_base: &Path,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let mut config_file = config_base.to_path_buf();
         config_file.push(uuid);
         create_dir_all(&config_file).await?;
         config_file.push("config_file");
@@ -74,13 +99,13 @@ struct BootSource {
 }
 
 impl BootSource {
-    async fn build(uuid: &str) -> Result<BootSource, Box<dyn std::error::Error>> {
-        let kernel_image = "some_kernel_image";
-        let kernel_image_path =
-            PathBuf::from(format!("/srv/impulse_actuator/{}/{}", uuid, kernel_image));
-        let boot_args = String::from("console=ttyS0 reboot=k panic=1 pci=off");
-        let initrd = "some_initrd";
-        let initrd_path = PathBuf::from(format!("/srv/impulse_actuator/{}/{}", uuid, initrd));
+    async fn build(
+        base: &Path,
+        machine: &MachineSpec,
+    ) -> Result<BootSource, Box<dyn std::error::Error>> {
+        let kernel_image_path = base.join(&machine.kernel_image);
+        let boot_args = machine.boot_args.to_owned();
+        let initrd_path = base.join(&machine.initrd);
 
         Ok(BootSource {
             kernel_image_path,
@@ -102,11 +127,12 @@ impl Drive {
     async fn build(
         is_read_only: bool,
         is_root_device: bool,
-        uuid: &str,
+        base: &Path,
+        drive_id: &str,
+        image: &str,
     ) -> Result<Drive, Box<dyn std::error::Error>> {
-        let drive_id = String::from("some_drive_id");
-        let drive = String::from("some_root_fs");
-        let path_on_host = PathBuf::from(format!("/srv/impulse_actuator/{}/{}", uuid, drive));
+        let drive_id = drive_id.to_string();
+        let path_on_host = base.join(image);
 
         Ok(Drive {
             drive_id,
@@ -120,14 +146,14 @@ impl Drive {
 #[derive(Deserialize, Serialize)]
 struct MachineConfig {
     ht_enabled: bool,
-    mem_size_mib: u16,
-    vcpu_count: u8,
+    mem_size_mib: u32,
+    vcpu_count: u32,
 }
 
 impl MachineConfig {
-    async fn build() -> Result<MachineConfig, Box<dyn std::error::Error>> {
-        let mem_size_mib = 1024;
-        let vcpu_count = 2;
+    async fn build(machine: &MachineSpec) -> Result<MachineConfig, Box<dyn std::error::Error>> {
+        let mem_size_mib = machine.memory_mib;
+        let vcpu_count = machine.vcpus;
 
         Ok(MachineConfig {
             ht_enabled: true,
@@ -141,14 +167,17 @@ impl MachineConfig {
 struct NetworkInterfaces {
     host_dev_name: String,
     iface_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     guest_mac: String,
 }
 
 impl NetworkInterfaces {
-    async fn build(mac_address: &str) -> Result<NetworkInterfaces, Box<dyn std::error::Error>> {
-        let host_dev_name = String::from("tap0");
-        let iface_id = String::from("eth0");
-        let guest_mac = mac_address.to_string();
+    async fn build(
+        interface: &NetworkInterface,
+    ) -> Result<NetworkInterfaces, Box<dyn std::error::Error>> {
+        let host_dev_name = interface.host_dev_name.to_owned();
+        let iface_id = interface.iface_id.to_owned();
+        let guest_mac = interface.guest_mac.to_owned();
 
         Ok(NetworkInterfaces {
             host_dev_name,
@@ -162,11 +191,21 @@ impl NetworkInterfaces {
 mod tests {
     use super::*;
 
+    use crate::config::BootImages;
+    use crate::impulse::shared::v010::Drive as DriveSpec;
+
     const TEST_UUID: uuid::Uuid = uuid::Uuid::nil();
+    const TEST_WORKING_BASE: &str = "/srv/impulse_actuator/";
+    const TEST_CONFIG_BASE: &str = "/var/lib/impulse_actuator/machine";
 
     #[tokio::test(flavor = "multi_thread")]
     async fn build() -> Result<(), Box<dyn std::error::Error>> {
-        let test_config_file = ConfigFile::build(TEST_UUID.simple().to_string().as_str()).await?;
+        let test_config_file = ConfigFile::build(
+            TEST_UUID.simple().to_string().as_str(),
+            Path::new(TEST_WORKING_BASE),
+            &BootImages::default().machine(),
+        )
+        .await?;
         assert_eq!(
             test_config_file
                 .boot_source
@@ -185,7 +224,7 @@ mod tests {
         );
 
         for drive in test_config_file.drives {
-            assert_eq!(drive.drive_id.as_str(), "some_drive_id");
+            assert_eq!(drive.drive_id.as_str(), ROOT_DRIVE_ID);
             assert!(!drive.is_read_only);
             assert!(drive.is_root_device);
             assert_eq!(
@@ -201,11 +240,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn build_machine() -> Result<(), Box<dyn std::error::Error>> {
+        let test_machine = MachineSpec {
+            vcpus: 4,
+            memory_mib: 4096,
+            boot_args: String::from("console=ttyS0"),
+            drives: vec![DriveSpec {
+                drive_id: String::from("data"),
+                image: String::from("test_data"),
+                read_only: true,
+            }],
+            network_interfaces: vec![NetworkInterface {
+                iface_id: String::from("eth0"),
+                host_dev_name: String::from("tap0"),
+                guest_mac: String::new(),
+            }],
+            ..BootImages::default().machine()
+        };
+        let test_config_file = ConfigFile::build(
+            TEST_UUID.simple().to_string().as_str(),
+            Path::new(TEST_WORKING_BASE),
+            &test_machine,
+        )
+        .await?;
+        assert_eq!(
+            test_config_file.boot_source.boot_args.as_str(),
+            "console=ttyS0"
+        );
+        assert_eq!(test_config_file.drives.len(), 2);
+        assert!(test_config_file.drives[0].is_root_device);
+        assert_eq!(test_config_file.drives[1].drive_id.as_str(), "data");
+        assert!(test_config_file.drives[1].is_read_only);
+        assert!(!test_config_file.drives[1].is_root_device);
+        assert_eq!(
+            test_config_file.drives[1].path_on_host.to_str().unwrap(),
+            "/srv/impulse_actuator/00000000000000000000000000000000/test_data",
+        );
+        assert_eq!(test_config_file.machine_config.mem_size_mib, 4096);
+        assert_eq!(test_config_file.machine_config.vcpu_count, 4);
+        let test_json = serde_json::to_value(&test_config_file)?;
+        assert_eq!(test_json["network-interfaces"][0]["host_dev_name"], "tap0");
+        assert!(test_json["network-interfaces"][0]
+            .get("guest_mac")
+            .is_none());
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn write() -> Result<(), Box<dyn std::error::Error>> {
-        let test_config_file = ConfigFile::build(TEST_UUID.simple().to_string().as_str()).await?;
+        let test_config_file = ConfigFile::build(
+            TEST_UUID.simple().to_string().as_str(),
+            Path::new(TEST_WORKING_BASE),
+            &BootImages::default().machine(),
+        )
+        .await?;
         test_config_file
-            .write(TEST_UUID.simple().to_string().as_str())
+            .write(
+                TEST_UUID.simple().to_string().as_str(),
+                Path::new(TEST_CONFIG_BASE),
+            )
             .await?;
         let test_config_file_metadata = tokio::fs::metadata(
             "/var/lib/impulse_actuator/machine/00000000000000000000000000000000/config_file.json",
@@ -231,7 +325,7 @@ mod tests {
         );
 
         for drive in test_json.drives {
-            assert_eq!(drive.drive_id.as_str(), "some_drive_id");
+            assert_eq!(drive.drive_id.as_str(), ROOT_DRIVE_ID);
             assert!(!drive.is_read_only);
             assert!(drive.is_root_device);
             assert_eq!(
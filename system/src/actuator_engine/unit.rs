@@ -0,0 +1,110 @@
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnitStatus {
+    pub load_state: String,
+    pub active_state: String,
+    pub result: String,
+    pub exit_code: i32,
+}
+
+impl UnitStatus {
+    pub fn parse(output: &str) -> UnitStatus {
+        let mut status = UnitStatus::default();
+
+        for line in output.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                match key.trim() {
+                    "LoadState" => status.load_state = value.trim().to_owned(),
+                    "ActiveState" => status.active_state = value.trim().to_owned(),
+                    "Result" => status.result = value.trim().to_owned(),
+                    "ExecMainStatus" => status.exit_code = value.trim().parse().unwrap_or(0),
+                    _ => (),
+                }
+            }
+        }
+
+        status
+    }
+
+    pub fn exited(&self) -> Option<(String, bool)> {
+        match self.active_state.as_str() {
+            "active" | "activating" | "deactivating" | "reloading" | "" => None,
+            "failed" => {
+                let reason = match self.result.as_str() {
+                    "exit-code" => format!("exited with status {}", self.exit_code),
+                    "signal" => format!("was killed by signal {}", self.exit_code),
+                    "core-dump" => format!("dumped core on signal {}", self.exit_code),
+                    "oom-kill" => String::from("was killed for running out of memory"),
+                    "timeout" => String::from("timed out"),
+                    result => format!("failed with result {}", result),
+                };
+
+                Some((reason, true))
+            }
+            _ => match self.exit_code {
+                0 => Some((String::from("exited cleanly"), false)),
+                exit_code => Some((format!("exited with status {}", exit_code), true)),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exit {
+    pub uuid: String,
+    pub reason: String,
+    pub exit_code: i32,
+    pub failed: bool,
+    pub restarting: bool,
+    pub restarts: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() -> Result<(), Box<dyn std::error::Error>> {
+        let test_status = UnitStatus::parse(
+            "LoadState=loaded\nActiveState=failed\nResult=exit-code\nExecMainStatus=3\n",
+        );
+        assert_eq!(test_status.load_state.as_str(), "loaded");
+        assert_eq!(test_status.active_state.as_str(), "failed");
+        assert_eq!(test_status.result.as_str(), "exit-code");
+        assert_eq!(test_status.exit_code, 3);
+        assert_eq!(
+            test_status.exited(),
+            Some((String::from("exited with status 3"), true)),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exited() -> Result<(), Box<dyn std::error::Error>> {
+        let test_running =
+            UnitStatus::parse("LoadState=loaded\nActiveState=active\nResult=success");
+        assert_eq!(test_running.exited(), None);
+        assert_eq!(UnitStatus::parse("").exited(), None);
+        let test_gone =
+            UnitStatus::parse("LoadState=not-found\nActiveState=inactive\nExecMainStatus=0");
+        assert_eq!(
+            test_gone.exited(),
+            Some((String::from("exited cleanly"), false)),
+        );
+        let test_killed = UnitStatus::parse("ActiveState=failed\nResult=signal\nExecMainStatus=9");
+        assert_eq!(
+            test_killed.exited(),
+            Some((String::from("was killed by signal 9"), true)),
+        );
+        let test_oom = UnitStatus::parse("ActiveState=failed\nResult=oom-kill");
+        assert_eq!(
+            test_oom.exited(),
+            Some((String::from("was killed for running out of memory"), true)),
+        );
+        let test_other = UnitStatus::parse("ActiveState=failed\nResult=start-limit-hit");
+        assert_eq!(
+            test_other.exited(),
+            Some((String::from("failed with result start-limit-hit"), true)),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use tokio::fs::{create_dir_all, metadata, remove_file, rename, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use tracing::error;
+
+use crate::config::AuditConfig;
+use crate::policy::{Operation, Principal, Role};
+
+pub trait Audited {
+    fn arguments(&self) -> Value {
+        Value::Null
+    }
+
+    fn vm_id(&self) -> Option<String> {
+        None
+    }
+
+    fn node_id(&self) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub principal: String,
+    pub role: Role,
+    pub operation: Operation,
+    pub arguments: Value,
+    pub vm_id: Option<String>,
+    pub node_id: Option<String>,
+    pub allowed: bool,
+    pub result: String,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+impl AuditEntry {
+    pub async fn init<T: Audited>(
+        principal: &Principal,
+        operation: Operation,
+        request: &T,
+    ) -> AuditEntry {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        AuditEntry {
+            timestamp,
+            principal: principal.name.to_owned(),
+            role: principal.role,
+            operation,
+            arguments: request.arguments(),
+            vm_id: request.vm_id(),
+            node_id: request.node_id(),
+            allowed: false,
+            result: String::from("pending"),
+            error: None,
+            latency_ms: 0,
+        }
+    }
+
+    pub async fn complete<T: Audited>(&mut self, result: Result<&T, &Status>, latency: Duration) {
+        match result {
+            Ok(response) => {
+                self.vm_id = self.vm_id.take().or_else(|| response.vm_id());
+                self.node_id = self.node_id.take().or_else(|| response.node_id());
+                self.result = String::from("ok");
+            }
+            Err(status) => {
+                self.result = format!("{:?}", status.code());
+                self.error = Some(status.message().to_owned());
+            }
+        }
+
+        self.latency_ms = latency.as_millis() as u64;
+    }
+
+    pub async fn matches(&self, vm_id: Option<&str>, principal: Option<&str>) -> bool {
+        let vm_id_matches = vm_id.is_none_or(|vm_id| self.vm_id.as_deref() == Some(vm_id));
+        let principal_matches = principal.is_none_or(|principal| self.principal == principal);
+
+        vm_id_matches && principal_matches
+    }
+}
+
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    capacity: usize,
+    max_bytes: u64,
+    max_files: usize,
+    entries: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl AuditLog {
+    pub async fn init(config: &AuditConfig) -> Result<AuditLog, Box<dyn std::error::Error>> {
+        if let Some(parent) = config.path.as_ref().and_then(|path| path.parent()) {
+            create_dir_all(parent).await?;
+        }
+
+        let entries = Mutex::new(VecDeque::with_capacity(config.capacity));
+
+        Ok(AuditLog {
+            path: config.path.to_owned(),
+            capacity: config.capacity,
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            entries,
+        })
+    }
+
+    pub async fn record(&self, entry: AuditEntry) {
+        let mut entries = self.entries.lock().await;
+
+        if let Some(path) = &self.path {
+            if let Err(error) = self.append(path, &entry).await {
+                error!(path = %path.display(), %error, "Unable to write audit log");
+            }
+        }
+
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+
+        entries.push_back(entry);
+    }
+
+    pub async fn recent(&self) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().await;
+
+        entries.iter().cloned().collect()
+    }
+
+    pub async fn query(
+        &self,
+        vm_id: Option<&str>,
+        principal: Option<&str>,
+        limit: usize,
+    ) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().await;
+        let mut matched = Vec::with_capacity(limit.min(entries.len()));
+
+        for entry in entries.iter().rev() {
+            if matched.len() == limit {
+                break;
+            }
+
+            if entry.matches(vm_id, principal).await {
+                matched.push(entry.to_owned());
+            }
+        }
+
+        matched
+    }
+
+    async fn append(
+        &self,
+        path: &Path,
+        entry: &AuditEntry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        if let Ok(current) = metadata(path).await {
+            if current.len() > 0 && current.len() + line.len() as u64 > self.max_bytes {
+                self.rotate(path).await?;
+            }
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        file.write_all(&line).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    async fn rotate(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if self.max_files == 0 {
+            remove_file(path).await?;
+
+            return Ok(());
+        }
+
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", path.display(), index));
+
+        if metadata(rotated(self.max_files)).await.is_ok() {
+            remove_file(rotated(self.max_files)).await?;
+        }
+
+        for index in (1..self.max_files).rev() {
+            if metadata(rotated(index)).await.is_ok() {
+                rename(rotated(index), rotated(index + 1)).await?;
+            }
+        }
+
+        rename(path, rotated(1)).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestRequest(Option<&'static str>);
+
+    impl Audited for TestRequest {
+        fn vm_id(&self) -> Option<String> {
+            self.0.map(String::from)
+        }
+
+        fn node_id(&self) -> Option<String> {
+            Some(String::from("test_node"))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record() -> Result<(), Box<dyn std::error::Error>> {
+        let test_path = PathBuf::from("/tmp/test_impulse/audit/record.log");
+        tokio::fs::remove_file(&test_path).await.ok();
+        let test_config = AuditConfig {
+            path: Some(test_path.to_owned()),
+            capacity: 2,
+            max_bytes: 1024 * 1024,
+            max_files: 1,
+        };
+        let test_audit_log = AuditLog::init(&test_config).await?;
+        let test_principal = Principal {
+            name: String::from("test_reader"),
+            role: Role::Reader,
+            tenant: String::from("default"),
+        };
+        for (test_operation, test_allowed) in [
+            (Operation::ListVms, true),
+            (Operation::LaunchVm, false),
+            (Operation::SystemStatus, true),
+        ] {
+            let mut test_entry =
+                AuditEntry::init(&test_principal, test_operation, &TestRequest(None)).await;
+            test_entry.allowed = test_allowed;
+            test_audit_log.record(test_entry).await;
+        }
+        let test_recent = test_audit_log.recent().await;
+        assert_eq!(test_recent.len(), 2);
+        assert_eq!(test_recent[0].operation, Operation::LaunchVm);
+        assert!(!test_recent[0].allowed);
+        let test_lines = tokio::fs::read_to_string(&test_path).await?;
+        assert_eq!(test_lines.lines().count(), 3);
+        let test_first: serde_json::Value =
+            serde_json::from_str(test_lines.lines().next().unwrap())?;
+        assert_eq!(test_first["principal"], "test_reader");
+        assert_eq!(test_first["role"], "reader");
+        assert_eq!(test_first["operation"], "list_vms");
+        assert_eq!(test_first["node_id"], "test_node");
+        assert_eq!(test_first["allowed"], true);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn complete() -> Result<(), Box<dyn std::error::Error>> {
+        let test_principal = Principal {
+            name: String::from("test_operator"),
+            role: Role::Operator,
+            tenant: String::from("default"),
+        };
+        let mut test_entry =
+            AuditEntry::init(&test_principal, Operation::LaunchVm, &TestRequest(None)).await;
+        test_entry
+            .complete(
+                Ok(&TestRequest(Some("test_uuid"))),
+                Duration::from_millis(7),
+            )
+            .await;
+        assert_eq!(test_entry.vm_id.as_deref(), Some("test_uuid"));
+        assert_eq!(test_entry.result.as_str(), "ok");
+        assert_eq!(test_entry.latency_ms, 7);
+        let mut test_entry =
+            AuditEntry::init(&test_principal, Operation::DrainNode, &TestRequest(None)).await;
+        let test_status = Status::permission_denied("test_denied");
+        test_entry
+            .complete::<TestRequest>(Err(&test_status), Duration::from_millis(1))
+            .await;
+        assert_eq!(test_entry.result.as_str(), "PermissionDenied");
+        assert_eq!(test_entry.error.as_deref(), Some("test_denied"));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn query() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = AuditConfig {
+            path: None,
+            capacity: 10,
+            max_bytes: 0,
+            max_files: 0,
+        };
+        let test_audit_log = AuditLog::init(&test_config).await?;
+        for (test_name, test_vm_id) in [
+            ("test_operator", Some("test_uuid_a")),
+            ("test_reader", None),
+            ("test_operator", Some("test_uuid_b")),
+            ("test_admin", Some("test_uuid_a")),
+        ] {
+            let test_principal = Principal {
+                name: String::from(test_name),
+                role: Role::Admin,
+                tenant: String::from("default"),
+            };
+            let test_entry = AuditEntry::init(
+                &test_principal,
+                Operation::ShutdownVm,
+                &TestRequest(test_vm_id),
+            )
+            .await;
+            test_audit_log.record(test_entry).await;
+        }
+        let test_vm = test_audit_log.query(Some("test_uuid_a"), None, 10).await;
+        assert_eq!(test_vm.len(), 2);
+        assert_eq!(test_vm[0].principal.as_str(), "test_admin");
+        let test_caller = test_audit_log.query(None, Some("test_operator"), 1).await;
+        assert_eq!(test_caller.len(), 1);
+        assert_eq!(test_caller[0].vm_id.as_deref(), Some("test_uuid_b"));
+        let test_both = test_audit_log
+            .query(Some("test_uuid_a"), Some("test_operator"), 10)
+            .await;
+        assert_eq!(test_both.len(), 1);
+        assert_eq!(test_audit_log.query(None, None, 10).await.len(), 4);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rotate() -> Result<(), Box<dyn std::error::Error>> {
+        let test_path = PathBuf::from("/tmp/test_impulse/audit/rotate.log");
+        for test_index in ["", ".1", ".2", ".3"] {
+            let test_file = format!("{}{}", test_path.display(), test_index);
+            tokio::fs::remove_file(test_file).await.ok();
+        }
+        let test_config = AuditConfig {
+            path: Some(test_path.to_owned()),
+            capacity: 10,
+            max_bytes: 1,
+            max_files: 2,
+        };
+        let test_audit_log = AuditLog::init(&test_config).await?;
+        let test_principal = Principal {
+            name: String::from("test_admin"),
+            role: Role::Admin,
+            tenant: String::from("default"),
+        };
+        for _ in 0..4 {
+            let test_entry =
+                AuditEntry::init(&test_principal, Operation::ListNodes, &TestRequest(None)).await;
+            test_audit_log.record(test_entry).await;
+        }
+        let test_current = tokio::fs::read_to_string(&test_path).await?;
+        assert_eq!(test_current.lines().count(), 1);
+        assert!(metadata(format!("{}.1", test_path.display())).await.is_ok());
+        assert!(metadata(format!("{}.2", test_path.display())).await.is_ok());
+        assert!(metadata(format!("{}.3", test_path.display()))
+            .await
+            .is_err());
+        Ok(())
+    }
+}
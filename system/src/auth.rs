@@ -0,0 +1,630 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::fs::read;
+
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo, UdsConnectInfo};
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+use tonic::{Request, Status};
+
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::config::{ClusterConfig, ListenerConfig, TlsConfig};
+use crate::system_error::SystemError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PeerIdentity(pub String);
+
+#[derive(Clone, Debug, Default)]
+pub struct BearerTokens {
+    tokens: Arc<HashSet<String>>,
+}
+
+impl BearerTokens {
+    pub fn init(tokens: &[String]) -> BearerTokens {
+        let tokens = Arc::new(tokens.iter().cloned().collect());
+
+        BearerTokens { tokens }
+    }
+}
+
+impl Interceptor for BearerTokens {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if self.tokens.is_empty() {
+            return Ok(request);
+        }
+
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match token {
+            Some(token) if self.tokens.contains(token) => Ok(request),
+            Some(_) => Err(Status::unauthenticated("Bearer token is not valid!")),
+            None => Err(Status::unauthenticated(
+                "Bearer token is missing... please provide a token!",
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BearerToken {
+    value: Option<MetadataValue<Ascii>>,
+}
+
+impl BearerToken {
+    pub fn init(token: Option<&str>) -> Result<BearerToken, Box<dyn std::error::Error>> {
+        let value = match token {
+            Some(token) => Some(format!("Bearer {}", token).parse()?),
+            None => None,
+        };
+
+        Ok(BearerToken { value })
+    }
+}
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(value) = &self.value {
+            request
+                .metadata_mut()
+                .insert("authorization", value.to_owned());
+        }
+
+        Ok(request)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NodeIdentity {
+    required: bool,
+    allowed: Option<Arc<HashSet<String>>>,
+}
+
+impl NodeIdentity {
+    pub fn init(required: bool) -> NodeIdentity {
+        NodeIdentity {
+            required,
+            allowed: None,
+        }
+    }
+
+    pub fn allowed(identities: &[String]) -> NodeIdentity {
+        NodeIdentity {
+            required: true,
+            allowed: Some(Arc::new(identities.iter().cloned().collect())),
+        }
+    }
+}
+
+impl Interceptor for NodeIdentity {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if !self.required {
+            return Ok(request);
+        }
+
+        let tcp_certs = request
+            .extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|info| info.peer_certs());
+        let uds_certs = request
+            .extensions()
+            .get::<TlsConnectInfo<UdsConnectInfo>>()
+            .and_then(|info| info.peer_certs());
+
+        let identity = tcp_certs
+            .or(uds_certs)
+            .and_then(|certs| certs.first().and_then(|cert| der_identity(cert.get_ref())));
+
+        if let (Some(identity), Some(allowed)) = (&identity, &self.allowed) {
+            if !allowed.contains(identity) {
+                let message = format!(
+                    "Client certificate identity {} is not allowed on this listener!",
+                    identity,
+                );
+
+                return Err(Status::permission_denied(message));
+            }
+        }
+
+        match identity {
+            Some(identity) => {
+                request.extensions_mut().insert(PeerIdentity(identity));
+
+                Ok(request)
+            }
+            None => Err(Status::unauthenticated(
+                "Client certificate is missing... please connect with mTLS!",
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ListenerAuth {
+    bearer_tokens: BearerTokens,
+    node_identity: NodeIdentity,
+}
+
+impl ListenerAuth {
+    pub fn init(listener: &ListenerConfig, node_identity: bool) -> ListenerAuth {
+        ListenerAuth {
+            bearer_tokens: BearerTokens::init(&listener.tokens),
+            node_identity: NodeIdentity::init(node_identity && listener.tls.is_some()),
+        }
+    }
+
+    pub fn cluster(cluster: &ClusterConfig) -> Result<ListenerAuth, SystemError> {
+        let mut tokens = cluster.listener.tokens.to_owned();
+
+        tokens.extend(cluster.token.to_owned());
+
+        let node_identity =
+            match &cluster.listener.tls {
+                Some(_) => {
+                    let peers: Vec<String> = cluster
+                        .peers
+                        .iter()
+                        .map(|peer| peer.id.to_owned())
+                        .collect();
+
+                    NodeIdentity::allowed(&peers)
+                }
+                None if !tokens.is_empty() => NodeIdentity::init(false),
+                None => return Err(SystemError::new(
+                    "Cluster listener has no authentication... please configure tls or a token!",
+                )),
+            };
+
+        Ok(ListenerAuth {
+            bearer_tokens: BearerTokens::init(&tokens),
+            node_identity,
+        })
+    }
+}
+
+impl Interceptor for ListenerAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let request = self.bearer_tokens.call(request)?;
+
+        self.node_identity.call(request)
+    }
+}
+
+pub(crate) async fn authorize_node<T>(request: &Request<T>, node_id: &str) -> Result<(), Status> {
+    match request.extensions().get::<PeerIdentity>() {
+        Some(PeerIdentity(identity)) if identity != node_id => {
+            let message = format!(
+                "Node {} does not match the client certificate identity {}!",
+                node_id, identity,
+            );
+
+            Err(Status::permission_denied(message))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) async fn server_tls(
+    tls: &TlsConfig,
+) -> Result<ServerTlsConfig, Box<dyn std::error::Error>> {
+    let certificate = read(&tls.certificate).await?;
+    let key = read(&tls.key).await?;
+    let ca_certificate = read(&tls.ca_certificate).await?;
+
+    let server_tls = ServerTlsConfig::new()
+        .identity(Identity::from_pem(certificate, key))
+        .client_ca_root(Certificate::from_pem(ca_certificate))
+        .client_auth_optional(true);
+
+    Ok(server_tls)
+}
+
+pub(crate) async fn client_tls(
+    tls: &TlsConfig,
+) -> Result<ClientTlsConfig, Box<dyn std::error::Error>> {
+    let certificate = read(&tls.certificate).await?;
+    let key = read(&tls.key).await?;
+
+    let client_tls = ca_tls(&tls.ca_certificate)
+        .await?
+        .identity(Identity::from_pem(certificate, key));
+
+    Ok(client_tls)
+}
+
+pub(crate) async fn ca_tls(
+    ca_certificate: &Path,
+) -> Result<ClientTlsConfig, Box<dyn std::error::Error>> {
+    let ca_certificate = read(ca_certificate).await?;
+
+    Ok(ClientTlsConfig::new().ca_certificate(Certificate::from_pem(ca_certificate)))
+}
+
+pub(crate) async fn certificate_identity(
+    certificate: &Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let pem = read(certificate).await?;
+    let (_, pem) = parse_x509_pem(&pem)?;
+
+    match der_identity(&pem.contents) {
+        Some(identity) => Ok(identity),
+        None => {
+            let message = format!("{} has no common name!", certificate.display());
+
+            Err(Box::new(SystemError::new(&message)))
+        }
+    }
+}
+
+fn der_identity(der: &[u8]) -> Option<String> {
+    let (_, certificate) = X509Certificate::from_der(der).ok()?;
+    let common_name = certificate.subject().iter_common_name().next()?;
+
+    common_name.as_str().ok().map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, SanType};
+
+    use tonic::transport::Endpoint;
+
+    use tokio::sync::broadcast::channel;
+
+    use uuid::Uuid;
+
+    use crate::actuator_client::Internal as InternalClient;
+    use crate::actuator_engine::Inventory;
+    use crate::audit::AuditLog;
+    use crate::cluster::{ClusterServer, Raft};
+    use crate::config::{
+        AuditConfig, EventsConfig, ListenAddress, PeerConfig, PolicyConfig, TaskQueueConfig,
+    };
+    use crate::events::EventLog;
+    use crate::external_client::External as ExternalClient;
+    use crate::external_interface::{External, InterfaceServer as ExternalInterfaceServer};
+    use crate::groups::GroupRegistry;
+    use crate::impulse::cluster::v010::cluster_client::ClusterClient;
+    use crate::impulse::cluster::v010::VoteRequest;
+    use crate::internal_interface::{InterfaceServer as InternalInterfaceServer, Internal};
+    use crate::node_registry::NodeRegistry;
+    use crate::policy::Policy;
+    use crate::profiles::ProfileRegistry;
+    use crate::task_queue::TaskQueues;
+    use crate::vm_registry::VmRegistry;
+
+    const TEST_TLS_BASE: &str = "/tmp/test_impulse/tls";
+
+    async fn test_certificate(
+        common_name: &str,
+        ca: Option<&rcgen::Certificate>,
+    ) -> Result<rcgen::Certificate, Box<dyn std::error::Error>> {
+        let mut test_params = CertificateParams::default();
+        test_params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+
+        match ca {
+            Some(_) => {
+                test_params.subject_alt_names = vec![
+                    SanType::DnsName(String::from("localhost")),
+                    SanType::IpAddress("127.0.0.1".parse()?),
+                ];
+            }
+            None => test_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained),
+        }
+
+        Ok(rcgen::Certificate::from_params(test_params)?)
+    }
+
+    async fn test_tls(
+        test_ca: &rcgen::Certificate,
+        scope: &str,
+        common_name: &str,
+    ) -> Result<TlsConfig, Box<dyn std::error::Error>> {
+        let test_base = PathBuf::from(TEST_TLS_BASE).join(scope);
+        let test_certificate = test_certificate(common_name, Some(test_ca)).await?;
+        let test_tls = TlsConfig {
+            certificate: test_base.join(format!("{}.pem", common_name)),
+            key: test_base.join(format!("{}.key", common_name)),
+            ca_certificate: test_base.join("test_ca.pem"),
+        };
+        tokio::fs::create_dir_all(&test_base).await?;
+        tokio::fs::write(&test_tls.ca_certificate, test_ca.serialize_pem()?).await?;
+        tokio::fs::write(
+            &test_tls.certificate,
+            test_certificate.serialize_pem_with_signer(test_ca)?,
+        )
+        .await?;
+        tokio::fs::write(&test_tls.key, test_certificate.serialize_private_key_pem()).await?;
+        Ok(test_tls)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bearer_tokens() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_open = BearerTokens::init(&[]);
+        assert!(test_open.call(Request::new(())).is_ok());
+        let mut test_bearer_tokens = BearerTokens::init(&[String::from("test_token")]);
+        let test_missing = test_bearer_tokens.call(Request::new(())).unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::Unauthenticated);
+        let mut test_bearer_token = BearerToken::init(Some("test_wrong_token"))?;
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        let test_wrong = test_bearer_tokens.call(test_request).unwrap_err();
+        assert_eq!(test_wrong.message(), "Bearer token is not valid!");
+        let mut test_bearer_token = BearerToken::init(Some("test_token"))?;
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        assert!(test_bearer_tokens.call(test_request).is_ok());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn node_identity() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_not_required = NodeIdentity::init(false);
+        let test_request = test_not_required.call(Request::new(()))?;
+        assert!(authorize_node(&test_request, "test_node").await.is_ok());
+        let mut test_required = NodeIdentity::init(true);
+        let test_missing = test_required.call(Request::new(())).unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::Unauthenticated);
+        let mut test_request = Request::new(());
+        test_request
+            .extensions_mut()
+            .insert(PeerIdentity(String::from("test_node")));
+        assert!(authorize_node(&test_request, "test_node").await.is_ok());
+        let test_mismatch = authorize_node(&test_request, "test_other_node")
+            .await
+            .unwrap_err();
+        assert_eq!(test_mismatch.code(), tonic::Code::PermissionDenied);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn listener_auth() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_listener = ListenerConfig::from("127.0.0.1:1284".parse::<ListenAddress>()?);
+        let mut test_open = ListenerAuth::init(&test_listener, true);
+        assert!(test_open.call(Request::new(())).is_ok());
+        test_listener.tokens = vec![String::from("test_token")];
+        let mut test_tokens = ListenerAuth::init(&test_listener, true);
+        assert!(test_tokens.call(Request::new(())).is_err());
+        let mut test_bearer_token = BearerToken::init(Some("test_token"))?;
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        assert!(test_tokens.call(test_request).is_ok());
+        test_listener.tls = Some(TlsConfig {
+            certificate: PathBuf::from("/etc/impulse/interface.pem"),
+            key: PathBuf::from("/etc/impulse/interface.key"),
+            ca_certificate: PathBuf::from("/etc/impulse/ca.pem"),
+        });
+        let mut test_external = ListenerAuth::init(&test_listener, false);
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        assert!(test_external.call(test_request).is_ok());
+        let mut test_internal = ListenerAuth::init(&test_listener, true);
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        let test_missing = test_internal.call(test_request).unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::Unauthenticated);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cluster_auth() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_cluster = ClusterConfig {
+            id: String::from("test_interface_a"),
+            peers: vec![PeerConfig {
+                id: String::from("test_interface_b"),
+                endpoint: String::from("http://127.0.0.1:1"),
+                external: String::from("http://127.0.0.1:1/external"),
+                internal: String::from("http://127.0.0.1:1/internal"),
+            }],
+            ..ClusterConfig::default()
+        };
+        assert!(ListenerAuth::cluster(&test_cluster).is_err());
+        test_cluster.token = Some(String::from("test_token"));
+        let mut test_tokens = ListenerAuth::cluster(&test_cluster)?;
+        let test_missing = test_tokens.call(Request::new(())).unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::Unauthenticated);
+        let mut test_bearer_token = BearerToken::init(Some("test_token"))?;
+        let test_request = test_bearer_token.call(Request::new(()))?;
+        assert!(test_tokens.call(test_request).is_ok());
+        test_cluster.token = None;
+        test_cluster.listener.tls = Some(TlsConfig {
+            certificate: PathBuf::from("/etc/impulse/cluster.pem"),
+            key: PathBuf::from("/etc/impulse/cluster.key"),
+            ca_certificate: PathBuf::from("/etc/impulse/ca.pem"),
+        });
+        let mut test_identity = ListenerAuth::cluster(&test_cluster)?;
+        let test_missing = test_identity.call(Request::new(())).unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::Unauthenticated);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cluster_mutual_tls() -> Result<(), Box<dyn std::error::Error>> {
+        let test_ca = test_certificate("test_ca", None).await?;
+        let test_cluster = ClusterConfig {
+            id: String::from("test_interface_a"),
+            listener: ListenerConfig {
+                tls: Some(test_tls(&test_ca, "cluster", "test_interface_a").await?),
+                ..ListenerConfig::from("127.0.0.1:48215".parse::<ListenAddress>()?)
+            },
+            peers: vec![PeerConfig {
+                id: String::from("test_interface_b"),
+                endpoint: String::from("http://127.0.0.1:1"),
+                external: String::from("http://127.0.0.1:1/external"),
+                internal: String::from("http://127.0.0.1:1/internal"),
+            }],
+            ..ClusterConfig::default()
+        };
+        let test_raft =
+            Raft::init(&test_cluster, sled::Config::new().temporary(true).open()?).await?;
+        let (test_shutdown, test_shutdown_signal) = tokio::sync::oneshot::channel::<()>();
+        let test_server = tonic::transport::Server::builder()
+            .tls_config(server_tls(test_cluster.listener.tls.as_ref().unwrap()).await?)?
+            .add_service(ClusterServer::with_interceptor(
+                test_raft,
+                ListenerAuth::cluster(&test_cluster)?,
+            ))
+            .serve_with_shutdown("127.0.0.1:48215".parse()?, async {
+                test_shutdown_signal.await.ok();
+            });
+        let test_server = tokio::spawn(test_server);
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+        let test_vote = VoteRequest {
+            term: 1,
+            candidate_id: String::from("test_interface_b"),
+            last_log_index: 0,
+            last_log_term: 0,
+        };
+        for (test_identity, test_candidate, test_code) in [
+            (
+                "test_node",
+                "test_node",
+                Some(tonic::Code::PermissionDenied),
+            ),
+            (
+                "test_interface_b",
+                "test_interface_c",
+                Some(tonic::Code::PermissionDenied),
+            ),
+            ("test_interface_b", "test_interface_b", None),
+        ] {
+            let test_endpoint = Endpoint::from_static("https://127.0.0.1:48215").tls_config(
+                client_tls(&test_tls(&test_ca, "cluster", test_identity).await?).await?,
+            )?;
+            let mut test_client = ClusterClient::new(test_endpoint.connect().await?);
+            let test_voted = test_client
+                .request_vote(VoteRequest {
+                    candidate_id: test_candidate.to_string(),
+                    ..test_vote.to_owned()
+                })
+                .await;
+            assert_eq!(
+                test_voted.err().map(|test_error| test_error.code()),
+                test_code
+            );
+        }
+
+        test_shutdown.send(()).ok();
+        test_server.await??;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mutual_tls() -> Result<(), Box<dyn std::error::Error>> {
+        let test_ca = test_certificate("test_ca", None).await?;
+        let test_server_tls = test_tls(&test_ca, "mutual", "test_interface").await?;
+        let test_client_tls = test_tls(&test_ca, "mutual", "test_node").await?;
+        assert_eq!(
+            certificate_identity(&test_client_tls.certificate).await?,
+            "test_node",
+        );
+
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_launch_result_sender, _) = channel(4);
+        let (test_shutdown_result_sender, _) = channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = External::init(
+            Uuid::new_v4(),
+            test_task_queues.to_owned(),
+            test_launch_result_sender.to_owned(),
+            test_shutdown_result_sender.to_owned(),
+            test_node_registry.to_owned(),
+            test_vm_registry.to_owned(),
+            Arc::new(ProfileRegistry::init(&HashMap::new()).await?),
+            Arc::new(GroupRegistry::init().await?),
+            Arc::new(Policy::init(&PolicyConfig::default()).await?),
+            Arc::new(
+                AuditLog::init(&AuditConfig {
+                    path: None,
+                    capacity: 10,
+                    max_bytes: 0,
+                    max_files: 0,
+                })
+                .await?,
+            ),
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_launch_result_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let (test_shutdown, test_shutdown_signal) = tokio::sync::oneshot::channel::<()>();
+        let test_server = tonic::transport::Server::builder()
+            .tls_config(server_tls(&test_server_tls).await?)?
+            .add_service(ExternalInterfaceServer::with_interceptor(
+                test_external,
+                BearerTokens::init(&[String::from("test_token")]),
+            ))
+            .add_service(InternalInterfaceServer::with_interceptor(
+                test_internal,
+                NodeIdentity::init(true),
+            ))
+            .serve_with_shutdown("127.0.0.1:48211".parse()?, async {
+                test_shutdown_signal.await.ok();
+            });
+        let test_server = tokio::spawn(test_server);
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+        let mut test_node =
+            InternalClient::init("https://127.0.0.1:48211", &[], Some(&test_client_tls), None)
+                .await?;
+        assert_eq!(test_node.node_id.as_str(), "test_node");
+        assert!(test_node.register(Inventory::default()).await.is_ok());
+        test_node.node_id = String::from("test_other_node");
+        let test_mismatch = test_node.register(Inventory::default()).await.unwrap_err();
+        assert_eq!(test_mismatch.code(), tonic::Code::PermissionDenied);
+
+        let test_missing_certificate = TlsConfig {
+            certificate: PathBuf::from(TEST_TLS_BASE).join("missing.pem"),
+            ..test_client_tls.to_owned()
+        };
+        assert!(InternalClient::init(
+            "https://127.0.0.1:48211",
+            &[],
+            Some(&test_missing_certificate),
+            None
+        )
+        .await
+        .is_err());
+
+        let mut test_operator = ExternalClient::init(
+            "https://127.0.0.1:48211",
+            Some(&test_client_tls.ca_certificate),
+            None,
+        )
+        .await?;
+        let test_unauthenticated = test_operator.list_nodes(None).await.unwrap_err();
+        assert_eq!(test_unauthenticated.code(), tonic::Code::Unauthenticated);
+        let mut test_operator = ExternalClient::init(
+            "https://127.0.0.1:48211",
+            Some(&test_client_tls.ca_certificate),
+            Some("test_token"),
+        )
+        .await?;
+        let test_nodes = test_operator.list_nodes(None).await?;
+        assert_eq!(test_nodes.nodes.len(), 1);
+        assert_eq!(test_nodes.nodes[0].node_id.as_str(), "test_node");
+
+        test_shutdown.send(()).ok();
+        test_server.await??;
+        Ok(())
+    }
+}
@@ -0,0 +1,1275 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use prost::Message;
+
+use rand::distributions::{Distribution, Uniform};
+use rand::thread_rng;
+
+use sled::{Db, Tree};
+
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, sleep_until, timeout, Duration, Instant};
+
+use tonic::codegen::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status};
+
+use tracing::{error, info, warn};
+
+use crate::auth::{authorize_node, client_tls, BearerToken};
+use crate::config::{ClusterConfig, PeerConfig};
+use crate::impulse::cluster::v010::cluster_client::ClusterClient;
+use crate::impulse::cluster::v010::cluster_server::Cluster;
+use crate::impulse::cluster::v010::{
+    AppendRequest, AppendResponse, Command, Entry, SnapshotRequest, VoteRequest, VoteResponse,
+};
+use crate::store::STATE_TREES;
+use crate::system_error::SystemError;
+
+pub use crate::impulse::cluster::v010::cluster_server::ClusterServer;
+
+pub const LEADER_METADATA: &str = "impulse-leader";
+
+pub(crate) const RAFT: &str = "raft";
+pub(crate) const RAFT_LOG: &str = "raft_log";
+
+const TERM: &[u8] = b"term";
+const VOTED_FOR: &[u8] = b"voted_for";
+const SNAPSHOT_INDEX: &[u8] = b"snapshot_index";
+const SNAPSHOT_TERM: &[u8] = b"snapshot_term";
+const MAX_ENTRIES: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Leadership {
+    Leader,
+    Follower(Option<String>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+enum Replication {
+    Append(AppendRequest),
+    Snapshot(SnapshotRequest),
+}
+
+struct Peer {
+    config: PeerConfig,
+    client: ClusterClient<InterceptedService<Channel, BearerToken>>,
+}
+
+struct RaftState {
+    role: Role,
+    term: u64,
+    voted_for: Option<String>,
+    leader_id: Option<String>,
+    commit_index: u64,
+    last_applied: u64,
+    snapshot_index: u64,
+    snapshot_term: u64,
+    last_index: u64,
+    last_term: u64,
+    barrier: u64,
+    deadline: Instant,
+    next_index: HashMap<String, u64>,
+    match_index: HashMap<String, u64>,
+    contacted: HashMap<String, Instant>,
+}
+
+pub struct Raft {
+    pub id: String,
+    peers: Vec<Peer>,
+    election_timeout: Duration,
+    heartbeat: Duration,
+    snapshot_threshold: u64,
+    db: Db,
+    meta: Tree,
+    log: Tree,
+    state: Mutex<RaftState>,
+    leadership: watch::Sender<Leadership>,
+    committed: watch::Sender<u64>,
+    appended: watch::Sender<u64>,
+}
+
+impl Raft {
+    pub async fn init(config: &ClusterConfig, db: Db) -> Result<Raft, Box<dyn std::error::Error>> {
+        if config.id.is_empty() {
+            let error = SystemError::new("Cluster member id is missing... please configure one!");
+
+            return Err(Box::new(error));
+        }
+
+        let mut ids = HashSet::with_capacity(config.peers.len() + 1);
+
+        ids.insert(config.id.as_str());
+
+        for peer in &config.peers {
+            if !ids.insert(peer.id.as_str()) {
+                let message = format!("Cluster member id {} is configured twice!", peer.id);
+
+                return Err(Box::new(SystemError::new(&message)));
+            }
+        }
+
+        let election_timeout = Duration::from_millis(config.election_timeout_ms.max(1));
+        let heartbeat = Duration::from_millis(config.heartbeat_ms.max(1));
+        let mut peers = Vec::with_capacity(config.peers.len());
+
+        for peer in &config.peers {
+            let mut endpoint = Endpoint::from_shared(peer.endpoint.to_owned())?
+                .connect_timeout(election_timeout)
+                .timeout(election_timeout);
+
+            if let Some(tls) = &config.tls {
+                endpoint = endpoint.tls_config(client_tls(tls).await?)?;
+            }
+
+            let client = ClusterClient::with_interceptor(
+                endpoint.connect_lazy(),
+                BearerToken::init(config.token.as_deref())?,
+            );
+
+            peers.push(Peer {
+                config: peer.to_owned(),
+                client,
+            });
+        }
+
+        let meta = db.open_tree(RAFT)?;
+        let log = db.open_tree(RAFT_LOG)?;
+
+        let term = Self::load_u64(&meta, TERM)?;
+        let voted_for = match meta.get(VOTED_FOR)? {
+            Some(voted_for) => Some(String::from_utf8(voted_for.to_vec())?),
+            None => None,
+        };
+        let snapshot_index = Self::load_u64(&meta, SNAPSHOT_INDEX)?;
+        let snapshot_term = Self::load_u64(&meta, SNAPSHOT_TERM)?;
+        let (last_index, last_term) = match log.last()? {
+            Some((_, entry)) => {
+                let entry = Entry::decode(entry.as_ref())?;
+
+                (entry.index, entry.term)
+            }
+            None => (snapshot_index, snapshot_term),
+        };
+
+        let state = RaftState {
+            role: Role::Follower,
+            term,
+            voted_for,
+            leader_id: None,
+            commit_index: snapshot_index,
+            last_applied: snapshot_index,
+            snapshot_index,
+            snapshot_term,
+            last_index,
+            last_term,
+            barrier: 0,
+            deadline: Instant::now() + election_timeout,
+            next_index: HashMap::with_capacity(peers.len()),
+            match_index: HashMap::with_capacity(peers.len()),
+            contacted: HashMap::with_capacity(peers.len()),
+        };
+
+        let (leadership, _) = watch::channel(Leadership::Follower(None));
+        let (committed, _) = watch::channel(snapshot_index);
+        let (appended, _) = watch::channel(last_index);
+
+        info!(
+            id = %config.id,
+            peers = peers.len(),
+            term,
+            last_index,
+            "Cluster member initialized",
+        );
+
+        Ok(Raft {
+            id: config.id.to_owned(),
+            peers,
+            election_timeout,
+            heartbeat,
+            snapshot_threshold: config.snapshot_threshold.max(1) as u64,
+            db,
+            meta,
+            log,
+            state: Mutex::new(state),
+            leadership,
+            committed,
+            appended,
+        })
+    }
+
+    fn load_u64(tree: &Tree, key: &[u8]) -> Result<u64, Box<dyn std::error::Error>> {
+        match tree.get(key)? {
+            Some(value) => Ok(u64::from_be_bytes(value.as_ref().try_into()?)),
+            None => Ok(0),
+        }
+    }
+
+    pub fn leadership(&self) -> watch::Receiver<Leadership> {
+        self.leadership.subscribe()
+    }
+
+    pub fn peers(&self) -> Vec<PeerConfig> {
+        self.peers
+            .iter()
+            .map(|peer| peer.config.to_owned())
+            .collect()
+    }
+
+    pub async fn run(
+        self: Arc<Self>,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut replicators = JoinSet::new();
+
+        for index in 0..self.peers.len() {
+            replicators.spawn(self.clone().replicate(index, shutdown.clone()));
+        }
+
+        loop {
+            let (role, deadline) = {
+                let state = self.state.lock().await;
+
+                (state.role, state.deadline)
+            };
+
+            if role != Role::Leader && Instant::now() >= deadline {
+                self.campaign().await;
+
+                continue;
+            }
+
+            if role == Role::Leader {
+                self.check_quorum().await;
+            }
+
+            let wake = match role {
+                Role::Leader => Instant::now() + self.heartbeat,
+                _ => deadline,
+            };
+
+            tokio::select! {
+                _ = sleep_until(wake) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        replicators.shutdown().await;
+
+        info!(id = %self.id, "Cluster member stopped");
+
+        Ok(())
+    }
+
+    pub(crate) async fn propose(&self, commands: Vec<Command>) -> Result<(), Status> {
+        let (index, term) = {
+            let mut state = self.state.lock().await;
+
+            if state.role != Role::Leader {
+                return Err(Status::unavailable(
+                    "This replica is not the leader... please retry against the leader!",
+                ));
+            }
+
+            let entry = Entry {
+                term: state.term,
+                index: state.last_index + 1,
+                commands,
+            };
+            let appended = (entry.index, entry.term);
+
+            self.append(&mut state, vec![entry]).await;
+
+            if self.peers.is_empty() {
+                self.advance_commit(&mut state).await;
+            }
+
+            appended
+        };
+
+        self.appended.send_replace(index);
+
+        let mut committed = self.committed.subscribe();
+        let waited = timeout(self.election_timeout * 2, async {
+            while *committed.borrow_and_update() < index {
+                if committed.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+        .await;
+
+        let state = self.state.lock().await;
+
+        match waited.is_ok() && state.commit_index >= index && self.term_at(&state, index) == term {
+            true => Ok(()),
+            false => Err(Status::unavailable(
+                "Write was not committed by a majority of the cluster... please retry later!",
+            )),
+        }
+    }
+
+    fn deadline(&self) -> Instant {
+        let timeout = self.election_timeout.as_millis() as u64;
+        let jitter = Uniform::from(0..=timeout).sample(&mut thread_rng());
+
+        Instant::now() + self.election_timeout + Duration::from_millis(jitter)
+    }
+
+    fn majority(&self) -> usize {
+        let members = self.peers.len() + 1;
+
+        members / 2 + 1
+    }
+
+    async fn campaign(&self) {
+        let request = {
+            let mut state = self.state.lock().await;
+
+            state.term += 1;
+            state.role = Role::Candidate;
+            state.voted_for = Some(self.id.to_owned());
+            state.leader_id = None;
+            state.deadline = self.deadline();
+
+            self.persist_vote(&state).await;
+            self.publish(Leadership::Follower(None));
+
+            VoteRequest {
+                term: state.term,
+                candidate_id: self.id.to_owned(),
+                last_log_index: state.last_index,
+                last_log_term: state.last_term,
+            }
+        };
+
+        info!(id = %self.id, term = request.term, "Starting election");
+
+        let mut ballots = JoinSet::new();
+
+        for peer in &self.peers {
+            let mut client = peer.client.clone();
+            let request = request.to_owned();
+
+            ballots.spawn(async move { client.request_vote(Request::new(request)).await });
+        }
+
+        let mut votes = 1;
+
+        while votes < self.majority() {
+            let response = match ballots.join_next().await {
+                Some(Ok(Ok(response))) => response.into_inner(),
+                Some(_) => continue,
+                None => break,
+            };
+
+            if response.term > request.term {
+                let mut state = self.state.lock().await;
+
+                self.step_down(&mut state, response.term).await;
+
+                return;
+            }
+
+            if response.granted {
+                votes += 1;
+            }
+        }
+
+        ballots.abort_all();
+
+        let mut state = self.state.lock().await;
+
+        if votes >= self.majority() && state.role == Role::Candidate && state.term == request.term {
+            self.lead(&mut state).await;
+        }
+    }
+
+    async fn lead(&self, state: &mut RaftState) {
+        state.role = Role::Leader;
+        state.leader_id = Some(self.id.to_owned());
+        state.next_index.clear();
+        state.match_index.clear();
+        state.contacted.clear();
+
+        for peer in &self.peers {
+            state
+                .next_index
+                .insert(peer.config.id.to_owned(), state.last_index + 1);
+            state.match_index.insert(peer.config.id.to_owned(), 0);
+            state
+                .contacted
+                .insert(peer.config.id.to_owned(), Instant::now());
+        }
+
+        let entry = Entry {
+            term: state.term,
+            index: state.last_index + 1,
+            commands: Vec::with_capacity(0),
+        };
+
+        state.barrier = entry.index;
+
+        info!(id = %self.id, term = state.term, votes_needed = self.majority(), "Elected leader");
+
+        self.append(state, vec![entry]).await;
+        self.appended.send_replace(state.last_index);
+
+        if self.peers.is_empty() {
+            self.advance_commit(state).await;
+        }
+    }
+
+    async fn step_down(&self, state: &mut RaftState, term: u64) {
+        if term > state.term {
+            state.term = term;
+            state.voted_for = None;
+
+            self.persist_vote(state).await;
+        }
+
+        if state.role == Role::Leader {
+            info!(id = %self.id, term, "Stepping down as leader");
+
+            state.leader_id = None;
+        }
+
+        state.role = Role::Follower;
+        state.deadline = self.deadline();
+
+        self.publish(Leadership::Follower(state.leader_id.to_owned()));
+    }
+
+    async fn check_quorum(&self) {
+        let mut state = self.state.lock().await;
+
+        if state.role != Role::Leader {
+            return;
+        }
+
+        let contacted = 1 + state
+            .contacted
+            .values()
+            .filter(|contacted| contacted.elapsed() < self.election_timeout)
+            .count();
+
+        if contacted < self.majority() {
+            warn!(
+                id = %self.id,
+                term = state.term,
+                contacted,
+                "Lost contact with a majority of the cluster",
+            );
+
+            let term = state.term;
+
+            self.step_down(&mut state, term).await;
+        }
+    }
+
+    async fn follow(&self, state: &mut RaftState, term: u64, leader_id: &str) {
+        self.step_down(state, term).await;
+
+        if state.leader_id.as_deref() != Some(leader_id) {
+            info!(id = %self.id, term, leader = leader_id, "Following leader");
+
+            state.leader_id = Some(leader_id.to_owned());
+        }
+
+        self.publish(Leadership::Follower(state.leader_id.to_owned()));
+    }
+
+    fn publish(&self, leadership: Leadership) {
+        self.leadership.send_if_modified(|current| {
+            let modified = *current != leadership;
+
+            *current = leadership;
+
+            modified
+        });
+    }
+
+    async fn replicate(self: Arc<Self>, index: usize, mut shutdown: watch::Receiver<bool>) {
+        let mut appended = self.appended.subscribe();
+
+        loop {
+            if self.sync_peer(index).await {
+                continue;
+            }
+
+            tokio::select! {
+                _ = sleep(self.heartbeat) => {}
+                _ = appended.changed() => {}
+                _ = shutdown.changed() => return,
+            }
+        }
+    }
+
+    async fn sync_peer(&self, index: usize) -> bool {
+        let peer = &self.peers[index];
+
+        let (term, replication) = {
+            let state = self.state.lock().await;
+
+            if state.role != Role::Leader {
+                return false;
+            }
+
+            let next_index = state
+                .next_index
+                .get(&peer.config.id)
+                .copied()
+                .unwrap_or(state.last_index + 1);
+
+            let replication = match next_index <= state.snapshot_index {
+                true => Replication::Snapshot(self.snapshot(&state)),
+                false => {
+                    let prev_log_index = next_index - 1;
+
+                    Replication::Append(AppendRequest {
+                        term: state.term,
+                        leader_id: self.id.to_owned(),
+                        prev_log_index,
+                        prev_log_term: self.term_at(&state, prev_log_index),
+                        entries: self.entries(next_index),
+                        leader_commit: state.commit_index,
+                    })
+                }
+            };
+
+            (state.term, replication)
+        };
+
+        let mut client = peer.client.clone();
+        let response = match replication {
+            Replication::Append(request) => client.append_entries(Request::new(request)).await,
+            Replication::Snapshot(request) => {
+                info!(peer = %peer.config.id, index = request.last_included_index, "Sending snapshot");
+
+                client.install_snapshot(Request::new(request)).await
+            }
+        };
+
+        let response = match response {
+            Ok(response) => response.into_inner(),
+            Err(_) => return false,
+        };
+
+        let mut state = self.state.lock().await;
+
+        if response.term > state.term {
+            self.step_down(&mut state, response.term).await;
+
+            return false;
+        }
+
+        if state.role != Role::Leader || state.term != term {
+            return false;
+        }
+
+        state
+            .contacted
+            .insert(peer.config.id.to_owned(), Instant::now());
+
+        match response.success {
+            true => {
+                let match_index = state
+                    .match_index
+                    .get(&peer.config.id)
+                    .copied()
+                    .unwrap_or(0)
+                    .max(response.match_index);
+
+                state
+                    .match_index
+                    .insert(peer.config.id.to_owned(), match_index);
+                state
+                    .next_index
+                    .insert(peer.config.id.to_owned(), match_index + 1);
+
+                self.advance_commit(&mut state).await;
+
+                match_index < state.last_index
+            }
+            false => {
+                let next_index = state.next_index.get(&peer.config.id).copied().unwrap_or(1);
+                let next_index = (next_index - 1).min(response.match_index + 1).max(1);
+
+                state
+                    .next_index
+                    .insert(peer.config.id.to_owned(), next_index);
+
+                true
+            }
+        }
+    }
+
+    async fn advance_commit(&self, state: &mut RaftState) {
+        let mut index = state.last_index;
+
+        while index > state.commit_index && self.term_at(state, index) == state.term {
+            let replicas = 1 + state
+                .match_index
+                .values()
+                .filter(|match_index| **match_index >= index)
+                .count();
+
+            if replicas >= self.majority() {
+                state.commit_index = index;
+
+                break;
+            }
+
+            index -= 1;
+        }
+
+        self.apply_committed(state).await;
+    }
+
+    async fn apply_committed(&self, state: &mut RaftState) {
+        if state.last_applied < state.commit_index {
+            while state.last_applied < state.commit_index {
+                let index = state.last_applied + 1;
+
+                if let Some(entry) = self.entry(index) {
+                    self.apply(&entry.commands);
+                }
+
+                state.last_applied = index;
+            }
+
+            self.flush().await;
+            self.committed.send_replace(state.commit_index);
+        }
+
+        if state.role == Role::Leader && state.last_applied >= state.barrier {
+            self.publish(Leadership::Leader);
+        }
+
+        if state.last_applied - state.snapshot_index > self.snapshot_threshold {
+            self.compact(state).await;
+        }
+    }
+
+    fn apply(&self, commands: &[Command]) {
+        for command in commands {
+            let applied = match self.db.open_tree(&command.tree) {
+                Ok(tree) if command.remove => tree.remove(&command.key).map(|_| ()),
+                Ok(tree) => tree
+                    .insert(&command.key, command.value.as_slice())
+                    .map(|_| ()),
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = applied {
+                error!(tree = %command.tree, %error, "Unable to apply replicated command");
+            }
+        }
+    }
+
+    async fn compact(&self, state: &mut RaftState) {
+        let snapshot_term = self.term_at(state, state.last_applied);
+
+        for key in self.log.range(..=state.last_applied.to_be_bytes()).keys() {
+            let removed = match key {
+                Ok(key) => self.log.remove(key).map(|_| ()),
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = removed {
+                error!(%error, "Unable to compact cluster log");
+
+                return;
+            }
+        }
+
+        state.snapshot_index = state.last_applied;
+        state.snapshot_term = snapshot_term;
+
+        self.persist_snapshot(state).await;
+
+        info!(index = state.snapshot_index, "Cluster log compacted");
+    }
+
+    fn snapshot(&self, state: &RaftState) -> SnapshotRequest {
+        let mut records = Vec::with_capacity(100);
+
+        for name in STATE_TREES {
+            let tree = match self.db.open_tree(name) {
+                Ok(tree) => tree,
+                Err(error) => {
+                    error!(tree = name, %error, "Unable to read tree for snapshot");
+
+                    continue;
+                }
+            };
+
+            for (key, value) in tree.iter().flatten() {
+                records.push(Command {
+                    tree: name.to_string(),
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                    remove: false,
+                });
+            }
+        }
+
+        SnapshotRequest {
+            term: state.term,
+            leader_id: self.id.to_owned(),
+            last_included_index: state.last_applied,
+            last_included_term: self.term_at(state, state.last_applied),
+            records,
+        }
+    }
+
+    fn term_at(&self, state: &RaftState, index: u64) -> u64 {
+        if index == state.snapshot_index {
+            return state.snapshot_term;
+        }
+
+        self.entry(index).map(|entry| entry.term).unwrap_or(0)
+    }
+
+    fn entry(&self, index: u64) -> Option<Entry> {
+        let entry = self.log.get(index.to_be_bytes()).ok()??;
+
+        Entry::decode(entry.as_ref()).ok()
+    }
+
+    fn entries(&self, from: u64) -> Vec<Entry> {
+        self.log
+            .range(from.to_be_bytes()..)
+            .values()
+            .take(MAX_ENTRIES)
+            .flatten()
+            .filter_map(|entry| Entry::decode(entry.as_ref()).ok())
+            .collect()
+    }
+
+    async fn append(&self, state: &mut RaftState, entries: Vec<Entry>) {
+        for entry in entries {
+            if let Err(error) = self
+                .log
+                .insert(entry.index.to_be_bytes(), entry.encode_to_vec())
+            {
+                error!(index = entry.index, %error, "Unable to append to cluster log");
+
+                return;
+            }
+
+            state.last_index = entry.index;
+            state.last_term = entry.term;
+        }
+
+        self.flush().await;
+    }
+
+    async fn truncate(&self, state: &mut RaftState, from: u64) {
+        for key in self.log.range(from.to_be_bytes()..).keys().flatten() {
+            if let Err(error) = self.log.remove(key) {
+                error!(%error, "Unable to truncate cluster log");
+            }
+        }
+
+        state.last_index = from - 1;
+        state.last_term = self.term_at(state, state.last_index);
+    }
+
+    async fn persist_vote(&self, state: &RaftState) {
+        let mut persisted = self.meta.insert(TERM, &state.term.to_be_bytes());
+
+        if persisted.is_ok() {
+            persisted = match &state.voted_for {
+                Some(voted_for) => self.meta.insert(VOTED_FOR, voted_for.as_bytes()),
+                None => self.meta.remove(VOTED_FOR),
+            };
+        }
+
+        if let Err(error) = persisted {
+            error!(%error, "Unable to persist cluster vote");
+        }
+
+        self.flush().await;
+    }
+
+    async fn persist_snapshot(&self, state: &RaftState) {
+        let persisted = self
+            .meta
+            .insert(SNAPSHOT_INDEX, &state.snapshot_index.to_be_bytes())
+            .and_then(|_| {
+                self.meta
+                    .insert(SNAPSHOT_TERM, &state.snapshot_term.to_be_bytes())
+            });
+
+        if let Err(error) = persisted {
+            error!(%error, "Unable to persist cluster snapshot");
+        }
+
+        self.flush().await;
+    }
+
+    async fn flush(&self) {
+        if let Err(error) = self.db.flush_async().await {
+            error!(%error, "Unable to flush cluster log");
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Cluster for Raft {
+    async fn request_vote(
+        &self,
+        request: Request<VoteRequest>,
+    ) -> Result<Response<VoteResponse>, Status> {
+        authorize_node(&request, &request.get_ref().candidate_id).await?;
+
+        let request = request.into_inner();
+        let mut state = self.state.lock().await;
+
+        if request.term > state.term {
+            self.step_down(&mut state, request.term).await;
+        }
+
+        let up_to_date =
+            (request.last_log_term, request.last_log_index) >= (state.last_term, state.last_index);
+        let available = match &state.voted_for {
+            Some(voted_for) => voted_for == &request.candidate_id,
+            None => true,
+        };
+        let granted = request.term == state.term && available && up_to_date;
+
+        if granted {
+            state.voted_for = Some(request.candidate_id.to_owned());
+            state.deadline = self.deadline();
+
+            self.persist_vote(&state).await;
+        }
+
+        Ok(Response::new(VoteResponse {
+            term: state.term,
+            granted,
+        }))
+    }
+
+    async fn append_entries(
+        &self,
+        request: Request<AppendRequest>,
+    ) -> Result<Response<AppendResponse>, Status> {
+        authorize_node(&request, &request.get_ref().leader_id).await?;
+
+        let request = request.into_inner();
+        let mut state = self.state.lock().await;
+
+        let rejected = |state: &RaftState, match_index: u64| AppendResponse {
+            term: state.term,
+            success: false,
+            match_index,
+        };
+
+        if request.term < state.term {
+            return Ok(Response::new(rejected(&state, state.last_index)));
+        }
+
+        self.follow(&mut state, request.term, &request.leader_id)
+            .await;
+
+        if request.prev_log_index > state.last_index {
+            return Ok(Response::new(rejected(&state, state.last_index)));
+        }
+
+        if request.prev_log_index >= state.snapshot_index
+            && self.term_at(&state, request.prev_log_index) != request.prev_log_term
+        {
+            return Ok(Response::new(rejected(
+                &state,
+                request.prev_log_index.saturating_sub(1),
+            )));
+        }
+
+        let mut last_new = request.prev_log_index;
+        let mut entries = Vec::with_capacity(request.entries.len());
+
+        for entry in request.entries {
+            last_new = entry.index;
+
+            if entry.index <= state.snapshot_index {
+                continue;
+            }
+
+            if entry.index <= state.last_index {
+                if self.term_at(&state, entry.index) == entry.term {
+                    continue;
+                }
+
+                self.truncate(&mut state, entry.index).await;
+            }
+
+            entries.push(entry);
+        }
+
+        if !entries.is_empty() {
+            self.append(&mut state, entries).await;
+        }
+
+        if request.leader_commit > state.commit_index {
+            state.commit_index = request.leader_commit.min(last_new).max(state.commit_index);
+
+            self.apply_committed(&mut state).await;
+        }
+
+        Ok(Response::new(AppendResponse {
+            term: state.term,
+            success: true,
+            match_index: last_new,
+        }))
+    }
+
+    async fn install_snapshot(
+        &self,
+        request: Request<SnapshotRequest>,
+    ) -> Result<Response<AppendResponse>, Status> {
+        authorize_node(&request, &request.get_ref().leader_id).await?;
+
+        let request = request.into_inner();
+        let mut state = self.state.lock().await;
+
+        if request.term < state.term {
+            return Ok(Response::new(AppendResponse {
+                term: state.term,
+                success: false,
+                match_index: state.last_index,
+            }));
+        }
+
+        self.follow(&mut state, request.term, &request.leader_id)
+            .await;
+
+        if request.last_included_index > state.last_applied {
+            for name in STATE_TREES {
+                let cleared = self.db.open_tree(name).and_then(|tree| tree.clear());
+
+                if let Err(error) = cleared {
+                    error!(tree = name, %error, "Unable to clear tree for snapshot");
+                }
+            }
+
+            self.apply(&request.records);
+
+            if let Err(error) = self.log.clear() {
+                error!(%error, "Unable to clear cluster log");
+            }
+
+            state.snapshot_index = request.last_included_index;
+            state.snapshot_term = request.last_included_term;
+            state.last_index = request.last_included_index;
+            state.last_term = request.last_included_term;
+            state.commit_index = request.last_included_index;
+            state.last_applied = request.last_included_index;
+
+            self.persist_snapshot(&state).await;
+            self.committed.send_replace(state.commit_index);
+
+            info!(
+                index = request.last_included_index,
+                records = request.records.len(),
+                "Snapshot installed",
+            );
+        }
+
+        Ok(Response::new(AppendResponse {
+            term: state.term,
+            success: true,
+            match_index: request.last_included_index,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LeaderGate {
+    leadership: watch::Receiver<Leadership>,
+    redirects: Arc<HashMap<String, String>>,
+}
+
+impl LeaderGate {
+    pub fn init(
+        leadership: watch::Receiver<Leadership>,
+        redirects: HashMap<String, String>,
+    ) -> LeaderGate {
+        LeaderGate {
+            leadership,
+            redirects: Arc::new(redirects),
+        }
+    }
+}
+
+impl Interceptor for LeaderGate {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let leader = match &*self.leadership.borrow() {
+            Leadership::Leader => return Ok(request),
+            Leadership::Follower(leader) => leader.to_owned(),
+        };
+
+        let endpoint = leader.and_then(|leader| self.redirects.get(&leader).cloned());
+
+        match endpoint {
+            Some(endpoint) => {
+                let message = format!(
+                    "This replica is not the leader... please retry against {}!",
+                    endpoint,
+                );
+                let mut status = Status::unavailable(message);
+
+                match endpoint.parse() {
+                    Ok(value) => {
+                        status.metadata_mut().insert(LEADER_METADATA, value);
+                    }
+                    Err(_) => warn!(%endpoint, "Leader endpoint is not valid metadata"),
+                }
+
+                Err(status)
+            }
+            None => Err(Status::unavailable(
+                "No leader is elected yet... please retry later!",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tonic::transport::Server;
+
+    use crate::config::{ListenAddress, ListenerConfig, StoreConfig};
+    use crate::impulse::shared::v010::Task;
+    use crate::store::Store;
+
+    const TEST_MEMBERS: &[(&str, &str)] = &[
+        ("test_interface_a", "127.0.0.1:48421"),
+        ("test_interface_b", "127.0.0.1:48422"),
+        ("test_interface_c", "127.0.0.1:48423"),
+    ];
+
+    struct TestMember {
+        store: Store,
+        shutdown: watch::Sender<bool>,
+    }
+
+    fn test_config(member: usize) -> Result<ClusterConfig, Box<dyn std::error::Error>> {
+        let (id, address) = TEST_MEMBERS[member];
+        let peers = TEST_MEMBERS
+            .iter()
+            .filter(|(peer_id, _)| *peer_id != id)
+            .map(|(peer_id, peer_address)| PeerConfig {
+                id: peer_id.to_string(),
+                endpoint: format!("http://{}", peer_address),
+                external: format!("http://{}/external", peer_address),
+                internal: format!("http://{}/internal", peer_address),
+            })
+            .collect();
+
+        Ok(ClusterConfig {
+            id: id.to_string(),
+            listener: ListenerConfig::from(address.parse::<ListenAddress>()?),
+            peers,
+            election_timeout_ms: 300,
+            heartbeat_ms: 50,
+            snapshot_threshold: 8,
+            ..ClusterConfig::default()
+        })
+    }
+
+    async fn test_member(member: usize) -> Result<TestMember, Box<dyn std::error::Error>> {
+        let test_config = test_config(member)?;
+        let store = Store::clustered(&StoreConfig { path: None }, &test_config).await?;
+        let raft = store.raft().unwrap();
+        let (shutdown, _) = watch::channel(false);
+        let mut signal = shutdown.subscribe();
+        let address = TEST_MEMBERS[member].1.parse()?;
+
+        tokio::spawn(
+            Server::builder()
+                .add_service(ClusterServer::from_arc(raft.clone()))
+                .serve_with_shutdown(address, async move {
+                    while !*signal.borrow_and_update() {
+                        if signal.changed().await.is_err() {
+                            break;
+                        }
+                    }
+                }),
+        );
+        tokio::spawn(raft.run(shutdown.subscribe()));
+
+        Ok(TestMember { store, shutdown })
+    }
+
+    async fn test_elected(members: &[&TestMember]) -> Option<usize> {
+        for _ in 0..100 {
+            let leaders: Vec<usize> = members
+                .iter()
+                .enumerate()
+                .filter(|(_, member)| *member.store.leadership().borrow() == Leadership::Leader)
+                .map(|(index, _)| index)
+                .collect();
+
+            if leaders.len() == 1 {
+                return leaders.first().copied();
+            }
+
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        None
+    }
+
+    async fn test_replicated(member: &TestMember, node_id: &str) -> bool {
+        for _ in 0..100 {
+            let replicated = member
+                .store
+                .tasks()
+                .await
+                .is_ok_and(|tasks| tasks.iter().any(|(id, _)| id == node_id));
+
+            if replicated {
+                return true;
+            }
+
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        false
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_db = sled::Config::new().temporary(true).open()?;
+        let test_anonymous = ClusterConfig::default();
+        assert!(Raft::init(&test_anonymous, test_db.clone()).await.is_err());
+        let mut test_duplicate = test_config(0)?;
+        test_duplicate.peers[1].id = String::from("test_interface_a");
+        assert!(Raft::init(&test_duplicate, test_db.clone()).await.is_err());
+        let test_raft = Raft::init(&test_config(0)?, test_db).await?;
+        assert_eq!(test_raft.peers().len(), 2);
+        assert_eq!(*test_raft.leadership().borrow(), Leadership::Follower(None));
+        assert!(test_raft.propose(Vec::with_capacity(0)).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn replicate() -> Result<(), Box<dyn std::error::Error>> {
+        let test_a = test_member(0).await?;
+        let test_b = test_member(1).await?;
+        let test_leader_index = test_elected(&[&test_a, &test_b]).await.unwrap();
+        let (test_leader, test_follower) = match test_leader_index {
+            0 => (&test_a, &test_b),
+            _ => (&test_b, &test_a),
+        };
+        let test_leader_id = TEST_MEMBERS[test_leader_index].0;
+        for test_index in 0..12 {
+            let test_node_id = format!("test_node_{}", test_index);
+            test_leader
+                .store
+                .put_tasks(&test_node_id, &[Task::default()])
+                .await?;
+        }
+        assert!(test_replicated(test_follower, "test_node_11").await);
+        assert_eq!(
+            *test_follower.store.leadership().borrow(),
+            Leadership::Follower(Some(test_leader_id.to_string())),
+        );
+        let test_follower_raft = test_follower.store.raft().unwrap();
+        assert!(test_follower_raft
+            .propose(Vec::with_capacity(0))
+            .await
+            .is_err());
+        test_leader.store.remove_tasks("test_node_0").await?;
+
+        let test_c = test_member(2).await?;
+        assert!(test_replicated(&test_c, "test_node_11").await);
+        assert_eq!(test_c.store.tasks().await?.len(), 11);
+        let test_c_raft = test_c.store.raft().unwrap();
+        assert!(test_c_raft.state.lock().await.snapshot_index > 0);
+
+        test_leader.shutdown.send_replace(true);
+        let test_remaining = [test_follower, &test_c];
+        let test_new_leader = test_elected(&test_remaining).await.unwrap();
+        let test_new_follower = test_remaining[1 - test_new_leader];
+        test_remaining[test_new_leader]
+            .store
+            .put_tasks("test_node_failover", &[Task::default()])
+            .await?;
+        assert!(test_replicated(test_new_follower, "test_node_failover").await);
+
+        for test_member in [&test_a, &test_b, &test_c] {
+            test_member.shutdown.send_replace(true);
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn check_quorum() -> Result<(), Box<dyn std::error::Error>> {
+        let test_db = sled::Config::new().temporary(true).open()?;
+        let test_raft = Raft::init(&test_config(0)?, test_db).await?;
+        test_raft.lead(&mut *test_raft.state.lock().await).await;
+        test_raft.check_quorum().await;
+        assert_eq!(test_raft.state.lock().await.role, Role::Leader);
+        let test_silent = Instant::now() - test_raft.election_timeout * 2;
+        for test_contacted in test_raft.state.lock().await.contacted.values_mut() {
+            *test_contacted = test_silent;
+        }
+        test_raft
+            .state
+            .lock()
+            .await
+            .contacted
+            .insert(String::from("test_interface_b"), Instant::now());
+        test_raft.check_quorum().await;
+        assert_eq!(test_raft.state.lock().await.role, Role::Leader);
+        test_raft
+            .state
+            .lock()
+            .await
+            .contacted
+            .insert(String::from("test_interface_b"), test_silent);
+        test_raft.check_quorum().await;
+        let test_state = test_raft.state.lock().await;
+        assert_eq!(test_state.role, Role::Follower);
+        assert!(test_state.leader_id.is_none());
+        assert_eq!(*test_raft.leadership().borrow(), Leadership::Follower(None));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn leader_gate() -> Result<(), Box<dyn std::error::Error>> {
+        let (test_leadership, _) = watch::channel(Leadership::Leader);
+        let test_redirects = HashMap::from([(
+            String::from("test_interface_b"),
+            String::from("http://127.0.0.2:1285"),
+        )]);
+        let mut test_gate = LeaderGate::init(test_leadership.subscribe(), test_redirects);
+        assert!(test_gate.call(Request::new(())).is_ok());
+        test_leadership.send_replace(Leadership::Follower(Some(String::from("test_interface_b"))));
+        let test_redirect = test_gate.call(Request::new(())).unwrap_err();
+        assert_eq!(test_redirect.code(), tonic::Code::Unavailable);
+        assert_eq!(
+            test_redirect.metadata().get(LEADER_METADATA).unwrap(),
+            "http://127.0.0.2:1285",
+        );
+        test_leadership.send_replace(Leadership::Follower(None));
+        let test_unelected = test_gate.call(Request::new(())).unwrap_err();
+        assert_eq!(test_unelected.code(), tonic::Code::Unavailable);
+        assert!(test_unelected.metadata().get(LEADER_METADATA).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,1014 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+
+use serde::Deserialize;
+
+use tokio::fs::{metadata, read_to_string};
+
+use crate::impulse::shared::v010::{MachineSpec, RestartPolicy};
+use crate::labels;
+use crate::policy::Role;
+use crate::system_error::SystemError;
+use crate::tenants::{Quota, Resources};
+
+pub const DEFAULT_CONFIG: &str = "/etc/impulse/impulse.toml";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub interface: InterfaceConfig,
+    pub actuator: ActuatorConfig,
+}
+
+impl Config {
+    pub async fn load(path: Option<&Path>) -> Result<Config, Box<dyn std::error::Error>> {
+        match path {
+            Some(path) => Self::from_file(path).await,
+            None => match metadata(DEFAULT_CONFIG).await {
+                Ok(_) => Self::from_file(Path::new(DEFAULT_CONFIG)).await,
+                Err(_) => Ok(Config::default()),
+            },
+        }
+    }
+
+    async fn from_file(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let contents = read_to_string(path).await?;
+        let config = toml::from_str(&contents)?;
+
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterfaceConfig {
+    pub external: Vec<ListenerConfig>,
+    pub internal: Vec<ListenerConfig>,
+    pub policy: PolicyConfig,
+    pub profiles: HashMap<String, ProfileConfig>,
+    pub groups: GroupsConfig,
+    pub audit: AuditConfig,
+    pub events: EventsConfig,
+    pub tasks: TaskQueueConfig,
+    pub store: StoreConfig,
+    pub cluster: Option<ClusterConfig>,
+    pub log: LogConfig,
+    pub metrics: Option<SocketAddr>,
+}
+
+impl Default for InterfaceConfig {
+    fn default() -> InterfaceConfig {
+        let ipv4_addr = Ipv4Addr::new(0, 0, 0, 0);
+        let external = SocketAddr::new(IpAddr::V4(ipv4_addr), 1284);
+        let internal = SocketAddr::new(IpAddr::V4(ipv4_addr), 1285);
+
+        InterfaceConfig {
+            external: vec![ListenerConfig::from(ListenAddress::Tcp(external))],
+            internal: vec![ListenerConfig::from(ListenAddress::Tcp(internal))],
+            policy: PolicyConfig::default(),
+            profiles: HashMap::with_capacity(0),
+            groups: GroupsConfig::default(),
+            audit: AuditConfig::default(),
+            events: EventsConfig::default(),
+            tasks: TaskQueueConfig::default(),
+            store: StoreConfig::default(),
+            cluster: None,
+            log: LogConfig::default(),
+            metrics: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    pub anonymous_role: Role,
+    pub default_role: Role,
+    pub principals: Vec<PrincipalConfig>,
+    pub tenants: TenantsConfig,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> PolicyConfig {
+        PolicyConfig {
+            anonymous_role: Role::Reader,
+            default_role: Role::Reader,
+            principals: Vec::with_capacity(0),
+            tenants: TenantsConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PrincipalConfig {
+    pub name: String,
+    pub token: String,
+    pub role: Role,
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantsConfig {
+    pub default: String,
+    pub footprint: Resources,
+    pub default_quota: Quota,
+    pub quotas: HashMap<String, Quota>,
+}
+
+impl Default for TenantsConfig {
+    fn default() -> TenantsConfig {
+        TenantsConfig {
+            default: String::from("default"),
+            footprint: Resources {
+                vcpus: 2,
+                memory_mib: 1024,
+                disk_mib: 1024,
+            },
+            default_quota: Quota::default(),
+            quotas: HashMap::with_capacity(0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub description: String,
+    pub machine: MachineSpec,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupsConfig {
+    pub reconcile_ms: u64,
+}
+
+impl Default for GroupsConfig {
+    fn default() -> GroupsConfig {
+        GroupsConfig {
+            reconcile_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub path: Option<PathBuf>,
+    pub capacity: usize,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> AuditConfig {
+        AuditConfig {
+            path: Some(PathBuf::from("/var/log/impulse/audit.log")),
+            capacity: 1000,
+            max_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventsConfig {
+    pub capacity: usize,
+}
+
+impl Default for EventsConfig {
+    fn default() -> EventsConfig {
+        EventsConfig { capacity: 1024 }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskQueueConfig {
+    pub capacity: usize,
+}
+
+impl Default for TaskQueueConfig {
+    fn default() -> TaskQueueConfig {
+        TaskQueueConfig { capacity: 64 }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    pub path: Option<PathBuf>,
+}
+
+impl Default for StoreConfig {
+    fn default() -> StoreConfig {
+        StoreConfig {
+            path: Some(PathBuf::from("/var/lib/impulse/interface")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClusterConfig {
+    pub id: String,
+    pub listener: ListenerConfig,
+    pub peers: Vec<PeerConfig>,
+    pub tls: Option<TlsConfig>,
+    pub token: Option<String>,
+    pub election_timeout_ms: u64,
+    pub heartbeat_ms: u64,
+    pub snapshot_threshold: usize,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> ClusterConfig {
+        let cluster = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 1286);
+
+        ClusterConfig {
+            id: String::new(),
+            listener: ListenerConfig::from(ListenAddress::Tcp(cluster)),
+            peers: Vec::with_capacity(0),
+            tls: None,
+            token: None,
+            election_timeout_ms: 1500,
+            heartbeat_ms: 250,
+            snapshot_threshold: 1024,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PeerConfig {
+    pub id: String,
+    pub endpoint: String,
+    pub external: String,
+    pub internal: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> LogConfig {
+        LogConfig {
+            level: String::from("info"),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ListenerConfig {
+    pub address: ListenAddress,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub tokens: Vec<String>,
+}
+
+impl From<ListenAddress> for ListenerConfig {
+    fn from(address: ListenAddress) -> ListenerConfig {
+        ListenerConfig {
+            address,
+            tls: None,
+            tokens: Vec::with_capacity(0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = SystemError;
+
+    fn from_str(address: &str) -> Result<ListenAddress, SystemError> {
+        if let Some(path) = address.strip_prefix("unix:") {
+            return match path.is_empty() {
+                true => Err(SystemError::new("Unix socket path is empty!")),
+                false => Ok(ListenAddress::Unix(PathBuf::from(path))),
+            };
+        }
+
+        let socket_addr = address.strip_prefix("tcp://").unwrap_or(address);
+
+        match socket_addr.parse() {
+            Ok(socket_addr) => Ok(ListenAddress::Tcp(socket_addr)),
+            Err(_) => {
+                let message = format!("{} is not a socket address or unix: path!", address);
+
+                Err(SystemError::new(&message))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for ListenAddress {
+    type Error = SystemError;
+
+    fn try_from(address: String) -> Result<ListenAddress, SystemError> {
+        address.parse()
+    }
+}
+
+impl Display for ListenAddress {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ListenAddress::Tcp(socket_addr) => write!(f, "{}", socket_addr),
+            ListenAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActuatorConfig {
+    pub endpoint: String,
+    pub failover_endpoints: Vec<String>,
+    pub labels: HashMap<String, String>,
+    pub firecracker_binary: PathBuf,
+    pub jailer_binary: PathBuf,
+    pub systemd_run_binary: PathBuf,
+    pub systemctl_binary: PathBuf,
+    pub config_base: PathBuf,
+    pub socket_base: PathBuf,
+    pub working_base: PathBuf,
+    pub images_base: PathBuf,
+    pub boot_images: BootImages,
+    pub tls: Option<TlsConfig>,
+    pub token: Option<String>,
+    pub restart: RestartConfig,
+    pub log: LogConfig,
+    pub metrics: Option<SocketAddr>,
+}
+
+impl Default for ActuatorConfig {
+    fn default() -> ActuatorConfig {
+        ActuatorConfig {
+            endpoint: String::from("http://127.0.0.1:1285"),
+            failover_endpoints: Vec::with_capacity(0),
+            labels: HashMap::with_capacity(0),
+            firecracker_binary: PathBuf::from("/usr/bin/firecracker"),
+            jailer_binary: PathBuf::from("/usr/bin/jailer"),
+            systemd_run_binary: PathBuf::from("/usr/bin/systemd-run"),
+            systemctl_binary: PathBuf::from("/usr/bin/systemctl"),
+            config_base: PathBuf::from("/var/lib/impulse_actuator/machine"),
+            socket_base: PathBuf::from("/tmp/impulse_actuator/socket"),
+            working_base: PathBuf::from("/srv/impulse_actuator/"),
+            images_base: PathBuf::from("/var/lib/impulse_actuator/images"),
+            boot_images: BootImages::default(),
+            tls: None,
+            token: None,
+            restart: RestartConfig::default(),
+            log: LogConfig::default(),
+            metrics: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestartConfig {
+    pub watch_ms: u64,
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> RestartConfig {
+        RestartConfig {
+            watch_ms: 2_000,
+            backoff_ms: 1_000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BootImages {
+    pub kernel_image: String,
+    pub initrd: String,
+    pub root_fs: String,
+}
+
+impl BootImages {
+    pub fn machine(&self) -> MachineSpec {
+        MachineSpec {
+            kernel_image: self.kernel_image.to_owned(),
+            initrd: self.initrd.to_owned(),
+            root_fs: self.root_fs.to_owned(),
+            boot_args: String::from("console=ttyS0 reboot=k panic=1 pci=off"),
+            vcpus: 2,
+            memory_mib: 1024,
+            drives: Vec::with_capacity(0),
+            network_interfaces: Vec::with_capacity(0),
+            restart_policy: RestartPolicy::Unspecified as i32,
+        }
+    }
+}
+
+impl Default for BootImages {
+    fn default() -> BootImages {
+        BootImages {
+            kernel_image: String::from("some_kernel_image"),
+            initrd: String::from("some_initrd"),
+            root_fs: String::from("some_root_fs"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub certificate: PathBuf,
+    pub key: PathBuf,
+    pub ca_certificate: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Parser)]
+pub struct TlsArgs {
+    #[arg(
+        long,
+        env = "IMPULSE_TLS_CERTIFICATE",
+        requires_all = ["tls_key", "tls_ca_certificate"],
+        help = "PEM certificate presented to peers"
+    )]
+    pub tls_certificate: Option<PathBuf>,
+    #[arg(
+        long,
+        env = "IMPULSE_TLS_KEY",
+        requires = "tls_certificate",
+        help = "PEM private key for the certificate"
+    )]
+    pub tls_key: Option<PathBuf>,
+    #[arg(
+        long,
+        env = "IMPULSE_TLS_CA_CERTIFICATE",
+        requires = "tls_certificate",
+        help = "PEM certificate authority used to verify peers"
+    )]
+    pub tls_ca_certificate: Option<PathBuf>,
+}
+
+impl TlsArgs {
+    fn apply(&self, tls: &mut Option<TlsConfig>) {
+        if let (Some(certificate), Some(key), Some(ca_certificate)) = (
+            &self.tls_certificate,
+            &self.tls_key,
+            &self.tls_ca_certificate,
+        ) {
+            *tls = Some(TlsConfig {
+                certificate: certificate.to_owned(),
+                key: key.to_owned(),
+                ca_certificate: ca_certificate.to_owned(),
+            });
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Parser)]
+pub struct LogArgs {
+    #[arg(
+        long,
+        env = "IMPULSE_LOG_LEVEL",
+        help = "Log filter directives, e.g. info or system=debug"
+    )]
+    pub log_level: Option<String>,
+    #[arg(
+        long,
+        env = "IMPULSE_LOG_FORMAT",
+        value_enum,
+        help = "Log output format"
+    )]
+    pub log_format: Option<LogFormat>,
+}
+
+impl LogArgs {
+    fn apply(&self, log: &mut LogConfig) {
+        if let Some(level) = &self.log_level {
+            log.level = level.to_owned();
+        }
+
+        if let Some(format) = self.log_format {
+            log.format = format;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Parser)]
+pub struct InterfaceArgs {
+    #[arg(
+        long,
+        env = "IMPULSE_CONFIG",
+        help = "Path to the TOML configuration file"
+    )]
+    pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        env = "IMPULSE_INTERFACE_EXTERNAL",
+        value_delimiter = ',',
+        help = "Address to serve the external interface on, a socket address or unix:<path>"
+    )]
+    pub external: Vec<ListenAddress>,
+    #[arg(
+        long,
+        env = "IMPULSE_INTERFACE_INTERNAL",
+        value_delimiter = ',',
+        help = "Address to serve the internal interface on, a socket address or unix:<path>"
+    )]
+    pub internal: Vec<ListenAddress>,
+    #[arg(
+        long = "bearer-token",
+        env = "IMPULSE_INTERFACE_TOKENS",
+        value_delimiter = ',',
+        help = "Bearer token accepted on the external listeners"
+    )]
+    pub tokens: Vec<String>,
+    #[arg(
+        long,
+        env = "IMPULSE_INTERFACE_METRICS",
+        help = "Address to serve Prometheus metrics on at /metrics"
+    )]
+    pub metrics: Option<SocketAddr>,
+    #[command(flatten)]
+    pub tls: TlsArgs,
+    #[command(flatten)]
+    pub log: LogArgs,
+}
+
+impl InterfaceArgs {
+    pub async fn load(&self) -> Result<InterfaceConfig, Box<dyn std::error::Error>> {
+        let config = Config::load(self.config.as_deref()).await?;
+        let mut interface = config.interface;
+
+        if !self.external.is_empty() {
+            interface.external = Self::listeners(&self.external).await;
+        }
+
+        if !self.internal.is_empty() {
+            interface.internal = Self::listeners(&self.internal).await;
+        }
+
+        if !self.tokens.is_empty() {
+            for listener in interface.external.iter_mut() {
+                listener.tokens = self.tokens.to_owned();
+            }
+        }
+
+        for listener in interface
+            .external
+            .iter_mut()
+            .chain(interface.internal.iter_mut())
+        {
+            self.tls.apply(&mut listener.tls);
+        }
+
+        self.log.apply(&mut interface.log);
+
+        if let Some(metrics) = self.metrics {
+            interface.metrics = Some(metrics);
+        }
+
+        Ok(interface)
+    }
+
+    async fn listeners(addresses: &[ListenAddress]) -> Vec<ListenerConfig> {
+        addresses
+            .iter()
+            .cloned()
+            .map(ListenerConfig::from)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, Parser)]
+pub struct ActuatorArgs {
+    #[arg(
+        long,
+        env = "IMPULSE_CONFIG",
+        help = "Path to the TOML configuration file"
+    )]
+    pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        env = "IMPULSE_ACTUATOR_ENDPOINT",
+        help = "Endpoint of the internal interface"
+    )]
+    pub endpoint: Option<String>,
+    #[arg(
+        long = "failover-endpoint",
+        env = "IMPULSE_ACTUATOR_FAILOVER_ENDPOINTS",
+        value_delimiter = ',',
+        help = "Endpoint of another interface replica to fail over to"
+    )]
+    pub failover_endpoints: Vec<String>,
+    #[arg(
+        long = "label",
+        env = "IMPULSE_ACTUATOR_LABELS",
+        value_delimiter = ',',
+        value_parser = labels::parse_label,
+        help = "Label key=value reported for this node (repeatable)"
+    )]
+    pub labels: Vec<(String, String)>,
+    #[arg(
+        long = "bearer-token",
+        env = "IMPULSE_ACTUATOR_TOKEN",
+        hide_env_values = true,
+        help = "Bearer token for the internal interface"
+    )]
+    pub token: Option<String>,
+    #[arg(
+        long,
+        env = "IMPULSE_ACTUATOR_METRICS",
+        help = "Address to serve Prometheus metrics on at /metrics"
+    )]
+    pub metrics: Option<SocketAddr>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_FIRECRACKER_BINARY")]
+    pub firecracker_binary: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_JAILER_BINARY")]
+    pub jailer_binary: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_SYSTEMD_RUN_BINARY")]
+    pub systemd_run_binary: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_SYSTEMCTL_BINARY")]
+    pub systemctl_binary: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_CONFIG_BASE")]
+    pub config_base: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_SOCKET_BASE")]
+    pub socket_base: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_WORKING_BASE")]
+    pub working_base: Option<PathBuf>,
+    #[arg(long, env = "IMPULSE_ACTUATOR_IMAGES_BASE")]
+    pub images_base: Option<PathBuf>,
+    #[command(flatten)]
+    pub tls: TlsArgs,
+    #[command(flatten)]
+    pub log: LogArgs,
+}
+
+impl ActuatorArgs {
+    pub async fn load(&self) -> Result<ActuatorConfig, Box<dyn std::error::Error>> {
+        let config = Config::load(self.config.as_deref()).await?;
+        let mut actuator = config.actuator;
+
+        if let Some(endpoint) = &self.endpoint {
+            actuator.endpoint = endpoint.to_owned();
+        }
+
+        if !self.failover_endpoints.is_empty() {
+            actuator.failover_endpoints = self.failover_endpoints.to_owned();
+        }
+
+        actuator.labels.extend(self.labels.iter().cloned());
+        labels::validate(&actuator.labels)?;
+
+        if let Some(token) = &self.token {
+            actuator.token = Some(token.to_owned());
+        }
+
+        if let Some(metrics) = self.metrics {
+            actuator.metrics = Some(metrics);
+        }
+
+        let paths = [
+            (&self.firecracker_binary, &mut actuator.firecracker_binary),
+            (&self.jailer_binary, &mut actuator.jailer_binary),
+            (&self.systemd_run_binary, &mut actuator.systemd_run_binary),
+            (&self.systemctl_binary, &mut actuator.systemctl_binary),
+            (&self.config_base, &mut actuator.config_base),
+            (&self.socket_base, &mut actuator.socket_base),
+            (&self.working_base, &mut actuator.working_base),
+            (&self.images_base, &mut actuator.images_base),
+        ];
+
+        for (flag, path) in paths {
+            if let Some(flag) = flag {
+                *path = flag.to_owned();
+            }
+        }
+
+        self.tls.apply(&mut actuator.tls);
+        self.log.apply(&mut actuator.log);
+
+        Ok(actuator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CONFIG: &str = "/tmp/test_impulse/impulse.toml";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_default() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = Config::default();
+        assert_eq!(
+            test_config.interface.external[0]
+                .address
+                .to_string()
+                .as_str(),
+            "0.0.0.0:1284",
+        );
+        assert_eq!(
+            test_config.interface.internal[0]
+                .address
+                .to_string()
+                .as_str(),
+            "0.0.0.0:1285",
+        );
+        assert_eq!(
+            test_config.actuator.endpoint.as_str(),
+            "http://127.0.0.1:1285",
+        );
+        assert_eq!(
+            test_config.actuator.working_base.to_str().unwrap(),
+            "/srv/impulse_actuator/",
+        );
+        assert_eq!(
+            test_config.actuator.boot_images.root_fs.as_str(),
+            "some_root_fs",
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_load() -> Result<(), Box<dyn std::error::Error>> {
+        tokio::fs::create_dir_all("/tmp/test_impulse").await?;
+        tokio::fs::write(
+            TEST_CONFIG,
+            b"[interface]\nmetrics = \"127.0.0.1:9284\"\n\n[[interface.external]]\naddress = \"127.0.0.1:4821\"\ntokens = [\"test_token\"]\n\n[[interface.external]]\naddress = \"unix:/tmp/test_impulse/external.sock\"\n\n[[interface.internal]]\naddress = \"10.0.0.1:4822\"\n\n[interface.internal.tls]\ncertificate = \"/etc/impulse/interface.pem\"\nkey = \"/etc/impulse/interface.key\"\nca_certificate = \"/etc/impulse/ca.pem\"\n\n[interface.policy]\nanonymous_role = \"reader\"\n\n[[interface.policy.principals]]\nname = \"test_admin\"\ntoken = \"test_token\"\nrole = \"admin\"\ntenant = \"test_team\"\n\n[interface.policy.tenants.footprint]\nvcpus = 1\nmemory_mib = 512\ndisk_mib = 2048\n\n[interface.policy.tenants.quotas.test_team]\nvms = 4\nvcpus = 8\n\n[interface.profiles.small]\ndescription = \"test_small\"\n\n[interface.profiles.small.machine]\nvcpus = 1\nmemory_mib = 512\n\n[[interface.profiles.small.machine.drives]]\ndrive_id = \"data\"\nimage = \"test_data\"\n\n[interface.groups]\nreconcile_ms = 2000\n\n[interface.audit]\npath = \"/tmp/test_impulse/audit.log\"\nmax_files = 3\n\n[interface.events]\ncapacity = 64\n\n[interface.tasks]\ncapacity = 8\n\n[interface.store]\npath = \"/tmp/test_impulse/store\"\n\n[interface.cluster]\nid = \"test_interface_a\"\nelection_timeout_ms = 600\n\n[interface.cluster.listener]\naddress = \"127.0.0.1:4823\"\n\n[[interface.cluster.peers]]\nid = \"test_interface_b\"\nendpoint = \"http://127.0.0.2:4823\"\nexternal = \"http://127.0.0.2:4821\"\ninternal = \"http://127.0.0.2:4822\"\n\n[interface.log]\nlevel = \"system=debug\"\nformat = \"json\"\n\n[actuator]\nendpoint = \"https://127.0.0.1:4821\"\nfailover_endpoints = [\"https://127.0.0.2:4821\"]\nlabels = { zone = \"a\" }\nworking_base = \"/srv/test_impulse_actuator\"\nmetrics = \"0.0.0.0:9285\"\n\n[actuator.restart]\nbackoff_ms = 500\n\n[actuator.boot_images]\nroot_fs = \"test_root_fs\"\n\n[actuator.tls]\ncertificate = \"/etc/impulse/actuator.pem\"\nkey = \"/etc/impulse/actuator.key\"\nca_certificate = \"/etc/impulse/ca.pem\"\n",
+        )
+        .await?;
+        let test_config = Config::load(Some(Path::new(TEST_CONFIG))).await?;
+        assert_eq!(test_config.interface.external.len(), 2);
+        assert_eq!(
+            test_config.interface.external[0].address,
+            ListenAddress::Tcp("127.0.0.1:4821".parse()?),
+        );
+        assert_eq!(test_config.interface.external[0].tokens, ["test_token"]);
+        assert_eq!(
+            test_config.interface.external[1].address,
+            ListenAddress::Unix(PathBuf::from("/tmp/test_impulse/external.sock")),
+        );
+        assert!(test_config.interface.external[1].tokens.is_empty());
+        assert!(test_config.interface.external[1].tls.is_none());
+        assert_eq!(
+            test_config.interface.internal[0]
+                .address
+                .to_string()
+                .as_str(),
+            "10.0.0.1:4822",
+        );
+        assert_eq!(
+            test_config.actuator.endpoint.as_str(),
+            "https://127.0.0.1:4821",
+        );
+        assert_eq!(
+            test_config.actuator.failover_endpoints,
+            ["https://127.0.0.2:4821"],
+        );
+        assert_eq!(test_config.actuator.labels["zone"], "a");
+        assert_eq!(
+            test_config.actuator.working_base.to_str().unwrap(),
+            "/srv/test_impulse_actuator",
+        );
+        assert_eq!(
+            test_config.actuator.socket_base.to_str().unwrap(),
+            "/tmp/impulse_actuator/socket",
+        );
+        assert_eq!(test_config.actuator.restart.backoff_ms, 500);
+        assert_eq!(test_config.actuator.restart.max_backoff_ms, 60_000);
+        assert_eq!(
+            test_config.actuator.boot_images.root_fs.as_str(),
+            "test_root_fs",
+        );
+        assert_eq!(
+            test_config.actuator.boot_images.initrd.as_str(),
+            "some_initrd",
+        );
+        assert_eq!(
+            test_config.actuator.tls.unwrap().key.to_str().unwrap(),
+            "/etc/impulse/actuator.key",
+        );
+        assert!(test_config.interface.internal[0].tls.is_some());
+        assert_eq!(test_config.interface.policy.anonymous_role, Role::Reader);
+        assert_eq!(test_config.interface.policy.default_role, Role::Reader);
+        assert_eq!(test_config.interface.policy.principals[0].role, Role::Admin);
+        assert_eq!(
+            test_config.interface.policy.principals[0].tenant.as_deref(),
+            Some("test_team"),
+        );
+        let test_small = &test_config.interface.profiles["small"];
+        assert_eq!(test_small.description.as_str(), "test_small");
+        assert_eq!(test_small.machine.vcpus, 1);
+        assert!(test_small.machine.kernel_image.is_empty());
+        assert_eq!(test_small.machine.drives[0].image.as_str(), "test_data");
+        assert!(!test_small.machine.drives[0].read_only);
+        assert_eq!(test_config.interface.groups.reconcile_ms, 2000);
+        let test_tenants = &test_config.interface.policy.tenants;
+        assert_eq!(test_tenants.default.as_str(), "default");
+        assert_eq!(test_tenants.footprint.memory_mib, 512);
+        assert_eq!(test_tenants.quotas["test_team"].vcpus, Some(8));
+        assert_eq!(test_tenants.quotas["test_team"].memory_mib, None);
+        assert_eq!(
+            test_config.interface.audit.path.unwrap().to_str().unwrap(),
+            "/tmp/test_impulse/audit.log",
+        );
+        assert_eq!(test_config.interface.audit.capacity, 1000);
+        assert_eq!(test_config.interface.audit.max_files, 3);
+        assert_eq!(test_config.interface.events.capacity, 64);
+        assert_eq!(test_config.interface.tasks.capacity, 8);
+        assert_eq!(
+            test_config.interface.store.path,
+            Some(PathBuf::from("/tmp/test_impulse/store")),
+        );
+        let test_cluster = test_config.interface.cluster.unwrap();
+        assert_eq!(test_cluster.id.as_str(), "test_interface_a");
+        assert_eq!(test_cluster.election_timeout_ms, 600);
+        assert_eq!(test_cluster.heartbeat_ms, 250);
+        assert_eq!(
+            test_cluster.listener.address.to_string().as_str(),
+            "127.0.0.1:4823",
+        );
+        assert_eq!(
+            test_cluster.peers[0].internal.as_str(),
+            "http://127.0.0.2:4822"
+        );
+        assert_eq!(test_config.interface.log.level.as_str(), "system=debug");
+        assert_eq!(test_config.interface.log.format, LogFormat::Json);
+        assert_eq!(test_config.actuator.log, LogConfig::default());
+        assert_eq!(
+            test_config.interface.metrics,
+            Some("127.0.0.1:9284".parse()?)
+        );
+        assert_eq!(test_config.actuator.metrics, Some("0.0.0.0:9285".parse()?));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_load_error() -> Result<(), Box<dyn std::error::Error>> {
+        let test_missing = Config::load(Some(Path::new("/tmp/test_impulse/missing.toml"))).await;
+        assert!(test_missing.is_err());
+        tokio::fs::create_dir_all("/tmp/test_impulse").await?;
+        tokio::fs::write("/tmp/test_impulse/unknown.toml", b"[unknown]\n").await?;
+        let test_unknown = Config::load(Some(Path::new("/tmp/test_impulse/unknown.toml"))).await;
+        assert!(test_unknown.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interface_args() -> Result<(), Box<dyn std::error::Error>> {
+        let test_args = InterfaceArgs::try_parse_from([
+            "impulse_interface",
+            "--external",
+            "127.0.0.1:9999,unix:/tmp/test_impulse/external.sock",
+            "--internal",
+            "tcp://10.0.0.1:9998",
+        ])?;
+        let test_interface = test_args.load().await?;
+        assert_eq!(test_interface.external.len(), 2);
+        assert_eq!(
+            test_interface.external[1].address.to_string().as_str(),
+            "unix:/tmp/test_impulse/external.sock",
+        );
+        assert_eq!(
+            test_interface.internal[0].address.to_string().as_str(),
+            "10.0.0.1:9998",
+        );
+        assert!(test_interface.external[0].tls.is_none());
+        assert!(test_interface.external[0].tokens.is_empty());
+        let test_args = InterfaceArgs::try_parse_from([
+            "impulse_interface",
+            "--bearer-token",
+            "test_token_a,test_token_b",
+            "--tls-certificate",
+            "/tmp/test_impulse/interface.pem",
+            "--tls-key",
+            "/tmp/test_impulse/interface.key",
+            "--tls-ca-certificate",
+            "/tmp/test_impulse/ca.pem",
+        ])?;
+        let test_interface = test_args.load().await?;
+        assert_eq!(
+            test_interface.external[0].tokens,
+            ["test_token_a", "test_token_b"],
+        );
+        assert!(test_interface.internal[0].tokens.is_empty());
+        assert_eq!(
+            test_interface.internal[0]
+                .tls
+                .to_owned()
+                .unwrap()
+                .ca_certificate
+                .to_str()
+                .unwrap(),
+            "/tmp/test_impulse/ca.pem",
+        );
+        assert!(test_interface.external[0].tls.is_some());
+        let test_bad_address =
+            InterfaceArgs::try_parse_from(["impulse_interface", "--external", "not_an_address"]);
+        assert!(test_bad_address.is_err());
+        let test_partial_tls = InterfaceArgs::try_parse_from([
+            "impulse_interface",
+            "--tls-certificate",
+            "/tmp/test_impulse/interface.pem",
+        ]);
+        assert!(test_partial_tls.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn actuator_args() -> Result<(), Box<dyn std::error::Error>> {
+        let test_args = ActuatorArgs::try_parse_from([
+            "impulse_actuator",
+            "--endpoint",
+            "http://127.0.0.1:9999",
+            "--failover-endpoint",
+            "http://127.0.0.2:9999,http://127.0.0.3:9999",
+            "--label",
+            "zone=b",
+            "--label",
+            "gpu=",
+            "--working-base",
+            "/srv/test_impulse_actuator/",
+            "--firecracker-binary",
+            "/opt/firecracker",
+            "--log-level",
+            "debug",
+            "--log-format",
+            "json",
+            "--metrics",
+            "127.0.0.1:9285",
+        ])?;
+        let test_actuator = test_args.load().await?;
+        assert_eq!(test_actuator.endpoint.as_str(), "http://127.0.0.1:9999");
+        assert_eq!(test_actuator.failover_endpoints.len(), 2);
+        assert_eq!(test_actuator.labels["zone"], "b");
+        assert_eq!(test_actuator.labels["gpu"], "");
+        assert!(ActuatorArgs::try_parse_from(["impulse_actuator", "--label", "zone"]).is_err());
+        assert_eq!(
+            test_actuator.working_base.to_str().unwrap(),
+            "/srv/test_impulse_actuator/",
+        );
+        assert_eq!(
+            test_actuator.firecracker_binary.to_str().unwrap(),
+            "/opt/firecracker",
+        );
+        assert_eq!(
+            test_actuator.images_base.to_str().unwrap(),
+            "/var/lib/impulse_actuator/images",
+        );
+        assert_eq!(test_actuator.log.level.as_str(), "debug");
+        assert_eq!(test_actuator.log.format, LogFormat::Json);
+        assert_eq!(test_actuator.metrics, Some("127.0.0.1:9285".parse()?));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn listen_address() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            "0.0.0.0:1284".parse::<ListenAddress>()?,
+            ListenAddress::Tcp("0.0.0.0:1284".parse()?),
+        );
+        assert_eq!(
+            "tcp://[::1]:1284".parse::<ListenAddress>()?,
+            ListenAddress::Tcp("[::1]:1284".parse()?),
+        );
+        assert_eq!(
+            "unix:/run/impulse/external.sock".parse::<ListenAddress>()?,
+            ListenAddress::Unix(PathBuf::from("/run/impulse/external.sock")),
+        );
+        assert!("unix:".parse::<ListenAddress>().is_err());
+        assert!("localhost".parse::<ListenAddress>().is_err());
+        Ok(())
+    }
+}
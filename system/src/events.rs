@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use crate::config::EventsConfig;
+use crate::impulse::external::v010::event::Kind;
+use crate::impulse::external::v010::micro_vm_record::State;
+use crate::impulse::external::v010::{Event, EventFilter};
+
+struct Ring {
+    events: VecDeque<Event>,
+    last_token: u64,
+}
+
+pub struct EventLog {
+    capacity: usize,
+    ring: Mutex<Ring>,
+    sender: Sender<Event>,
+}
+
+impl EventLog {
+    pub async fn init(config: &EventsConfig) -> Result<EventLog, Box<dyn std::error::Error>> {
+        let capacity = config.capacity.max(1);
+        let ring = Mutex::new(Ring {
+            events: VecDeque::with_capacity(capacity),
+            last_token: 0,
+        });
+        let (sender, _) = channel(capacity);
+
+        Ok(EventLog {
+            capacity,
+            ring,
+            sender,
+        })
+    }
+
+    pub(crate) async fn vm_state(&self, vm_id: &str, node_id: &str, state: State, details: &str) {
+        self.publish(Kind::VmStateChanged, vm_id, node_id, Some(state), details)
+            .await;
+    }
+
+    pub(crate) async fn node_joined(&self, node_id: &str) {
+        self.publish(Kind::NodeJoined, "", node_id, None, "").await;
+    }
+
+    pub(crate) async fn node_left(&self, node_id: &str, details: &str) {
+        self.publish(Kind::NodeLeft, "", node_id, None, details)
+            .await;
+    }
+
+    pub(crate) async fn task_failed(&self, vm_id: &str, node_id: &str, details: &str) {
+        self.publish(Kind::TaskFailed, vm_id, node_id, None, details)
+            .await;
+    }
+
+    pub(crate) async fn subscribe(
+        &self,
+        resume_token: u64,
+    ) -> Result<(Vec<Event>, Receiver<Event>), Status> {
+        let ring = self.ring.lock().await;
+        let receiver = self.sender.subscribe();
+
+        if resume_token == 0 {
+            return Ok((Vec::with_capacity(0), receiver));
+        }
+
+        let oldest = ring
+            .events
+            .front()
+            .map_or(ring.last_token + 1, |event| event.resume_token);
+
+        if resume_token > ring.last_token || resume_token + 1 < oldest {
+            let message = format!(
+                "Resume token {} is no longer available... events {} to {} are buffered",
+                resume_token, oldest, ring.last_token,
+            );
+
+            return Err(Status::out_of_range(message));
+        }
+
+        let backlog = ring
+            .events
+            .iter()
+            .filter(|event| event.resume_token > resume_token)
+            .cloned()
+            .collect();
+
+        Ok((backlog, receiver))
+    }
+
+    async fn publish(
+        &self,
+        kind: Kind,
+        vm_id: &str,
+        node_id: &str,
+        state: Option<State>,
+        details: &str,
+    ) {
+        let mut ring = self.ring.lock().await;
+
+        ring.last_token += 1;
+
+        let event = Event {
+            resume_token: ring.last_token,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            kind: kind as i32,
+            vm_id: vm_id.to_owned(),
+            node_id: node_id.to_owned(),
+            state: state.unwrap_or(State::Unspecified) as i32,
+            details: details.to_owned(),
+        };
+
+        if ring.events.len() == self.capacity {
+            ring.events.pop_front();
+        }
+
+        ring.events.push_back(event.to_owned());
+
+        self.sender.send(event).ok();
+    }
+}
+
+pub(crate) async fn matches(filter: &EventFilter, event: &Event) -> bool {
+    let vm_id_matches = filter.vm_id.is_empty() || filter.vm_id == event.vm_id;
+    let node_id_matches = filter.node_id.is_empty() || filter.node_id == event.node_id;
+    let kind_matches = filter.kinds.is_empty() || filter.kinds.contains(&event.kind);
+
+    vm_id_matches && node_id_matches && kind_matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CONFIG: EventsConfig = EventsConfig { capacity: 3 };
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn publish() -> Result<(), Box<dyn std::error::Error>> {
+        let test_event_log = EventLog::init(&TEST_CONFIG).await?;
+        let (test_backlog, mut test_receiver) = test_event_log.subscribe(0).await?;
+        assert!(test_backlog.is_empty());
+        test_event_log.node_joined("test_node").await;
+        test_event_log
+            .vm_state("test_uuid", "test_node", State::Running, "success!")
+            .await;
+        let test_joined = test_receiver.recv().await?;
+        assert_eq!(test_joined.resume_token, 1);
+        assert_eq!(test_joined.kind, Kind::NodeJoined as i32);
+        assert_eq!(test_joined.node_id.as_str(), "test_node");
+        let test_state = test_receiver.recv().await?;
+        assert_eq!(test_state.resume_token, 2);
+        assert_eq!(test_state.state, State::Running as i32);
+        assert_eq!(test_state.details.as_str(), "success!");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn subscribe() -> Result<(), Box<dyn std::error::Error>> {
+        let test_event_log = EventLog::init(&TEST_CONFIG).await?;
+        assert!(test_event_log.subscribe(1).await.is_err());
+        for test_node in ["test_node_a", "test_node_b", "test_node_c", "test_node_d"] {
+            test_event_log.node_joined(test_node).await;
+        }
+        let (test_backlog, _) = test_event_log.subscribe(2).await?;
+        assert_eq!(test_backlog.len(), 2);
+        assert_eq!(test_backlog[0].node_id.as_str(), "test_node_c");
+        let (test_backlog, _) = test_event_log.subscribe(1).await?;
+        assert_eq!(test_backlog.len(), 3);
+        let (test_backlog, _) = test_event_log.subscribe(4).await?;
+        assert!(test_backlog.is_empty());
+        let test_unknown = test_event_log.subscribe(5).await.unwrap_err();
+        assert_eq!(test_unknown.code(), tonic::Code::OutOfRange);
+        test_event_log.node_left("test_node_a", "test_left").await;
+        let test_expired = test_event_log.subscribe(1).await.unwrap_err();
+        assert_eq!(test_expired.code(), tonic::Code::OutOfRange);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn filter() -> Result<(), Box<dyn std::error::Error>> {
+        let test_event = Event {
+            kind: Kind::TaskFailed as i32,
+            vm_id: String::from("test_uuid"),
+            node_id: String::from("test_node"),
+            ..Event::default()
+        };
+        assert!(matches(&EventFilter::default(), &test_event).await);
+        let test_filter = EventFilter {
+            vm_id: String::from("test_uuid"),
+            kinds: vec![Kind::TaskFailed as i32, Kind::NodeLeft as i32],
+            ..EventFilter::default()
+        };
+        assert!(matches(&test_filter, &test_event).await);
+        let test_filter = EventFilter {
+            node_id: String::from("test_other_node"),
+            ..EventFilter::default()
+        };
+        assert!(!matches(&test_filter, &test_event).await);
+        let test_filter = EventFilter {
+            kinds: vec![Kind::VmStateChanged as i32],
+            ..EventFilter::default()
+        };
+        assert!(!matches(&test_filter, &test_event).await);
+        Ok(())
+    }
+}
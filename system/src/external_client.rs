@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use tokio::net::UnixStream;
+
+use tonic::codegen::InterceptedService;
+use tonic::transport::{Channel, Endpoint, Uri};
+use tonic::{Request, Status, Streaming};
+
+use tower::service_fn;
+
+use crate::auth::{ca_tls, BearerToken};
+
+use crate::impulse::external::v010::interface_client::InterfaceClient;
+use crate::impulse::external::v010::{
+    AuditList, AuditQuery, BatchLaunchRequest, BatchLaunchResult, BulkResult, Event, EventFilter,
+    Group, GroupList, GroupScale, GroupSelector, LabelUpdate, LaunchRequest, ListFilter, MicroVm,
+    MicroVmList, Node, NodeDrain, NodeList, NodeSelector, Profile, ProfileList, ProfileSelector,
+    SystemStatusResponse, SystemVersionResponse, UsageList, UsageQuery, VmSelector,
+};
+use crate::impulse::shared::v010::{Empty, MicroVmLaunch, MicroVmShutdown};
+
+pub struct External {
+    transport: InterfaceClient<InterceptedService<Channel, BearerToken>>,
+}
+
+impl External {
+    pub async fn init(
+        endpoint: &str,
+        ca_certificate: Option<&Path>,
+        token: Option<&str>,
+    ) -> Result<External, Box<dyn std::error::Error>> {
+        let socket_path = endpoint.strip_prefix("unix:").map(PathBuf::from);
+
+        let mut endpoint = match socket_path {
+            Some(_) => Endpoint::from_static("http://localhost"),
+            None => Endpoint::from_shared(endpoint.to_string())?,
+        };
+
+        if let Some(ca_certificate) = ca_certificate {
+            endpoint = endpoint.tls_config(ca_tls(ca_certificate).await?)?;
+        }
+
+        let channel = match socket_path {
+            Some(socket_path) => {
+                endpoint
+                    .connect_with_connector(service_fn(move |_: Uri| {
+                        UnixStream::connect(socket_path.to_owned())
+                    }))
+                    .await?
+            }
+            None => endpoint.connect().await?,
+        };
+        let transport = InterfaceClient::with_interceptor(channel, BearerToken::init(token)?);
+
+        Ok(External { transport })
+    }
+
+    pub async fn system_status(&mut self) -> Result<SystemStatusResponse, Status> {
+        let request = Request::new(Empty {});
+        let response = self.transport.system_status(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn system_version(&mut self) -> Result<SystemVersionResponse, Status> {
+        let request = Request::new(Empty {});
+        let response = self.transport.system_version(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn launch_vm(&mut self, launch: LaunchRequest) -> Result<MicroVmLaunch, Status> {
+        let request = Request::new(launch);
+        let response = self.transport.launch_vm(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn launch_vms(
+        &mut self,
+        batch: BatchLaunchRequest,
+    ) -> Result<Streaming<BatchLaunchResult>, Status> {
+        let request = Request::new(batch);
+        let response = self.transport.launch_v_ms(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn shutdown_vm(&mut self, name: &str) -> Result<MicroVmShutdown, Status> {
+        let request = Request::new(MicroVm {
+            name: name.to_string(),
+        });
+        let response = self.transport.shutdown_vm(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn shutdown_vms(&mut self, selector: &str) -> Result<BulkResult, Status> {
+        let request = Request::new(VmSelector {
+            selector: selector.to_string(),
+        });
+        let response = self.transport.shutdown_v_ms(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn label_vms(
+        &mut self,
+        selector: &str,
+        labels: HashMap<String, String>,
+        remove: Vec<String>,
+    ) -> Result<BulkResult, Status> {
+        let request = Request::new(LabelUpdate {
+            selector: selector.to_string(),
+            labels,
+            remove,
+        });
+        let response = self.transport.label_v_ms(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn list_nodes(&mut self, selector: Option<&str>) -> Result<NodeList, Status> {
+        let request = Request::new(ListFilter {
+            selector: selector.unwrap_or_default().to_string(),
+        });
+        let response = self.transport.list_nodes(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn list_vms(&mut self, selector: Option<&str>) -> Result<MicroVmList, Status> {
+        let request = Request::new(ListFilter {
+            selector: selector.unwrap_or_default().to_string(),
+        });
+        let response = self.transport.list_v_ms(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn delist_node(&mut self, node_id: &str) -> Result<Node, Status> {
+        let request = Request::new(NodeSelector {
+            node_id: node_id.to_string(),
+        });
+        let response = self.transport.delist_node(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn drain_node(&mut self, node_id: &str, draining: bool) -> Result<Node, Status> {
+        let request = Request::new(NodeDrain {
+            node_id: node_id.to_string(),
+            draining,
+        });
+        let response = self.transport.drain_node(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn query_audit(
+        &mut self,
+        vm_id: Option<&str>,
+        principal: Option<&str>,
+        limit: u32,
+    ) -> Result<AuditList, Status> {
+        let request = Request::new(AuditQuery {
+            vm_id: vm_id.unwrap_or_default().to_string(),
+            principal: principal.unwrap_or_default().to_string(),
+            limit,
+        });
+        let response = self.transport.query_audit(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn query_usage(
+        &mut self,
+        tenant: Option<&str>,
+        all: bool,
+    ) -> Result<UsageList, Status> {
+        let request = Request::new(UsageQuery {
+            tenant: tenant.unwrap_or_default().to_string(),
+            all,
+        });
+        let response = self.transport.query_usage(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn create_profile(&mut self, profile: Profile) -> Result<Profile, Status> {
+        let request = Request::new(profile);
+        let response = self.transport.create_profile(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn update_profile(&mut self, profile: Profile) -> Result<Profile, Status> {
+        let request = Request::new(profile);
+        let response = self.transport.update_profile(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_profile(&mut self, name: &str) -> Result<Profile, Status> {
+        let request = Request::new(ProfileSelector {
+            name: name.to_string(),
+        });
+        let response = self.transport.delete_profile(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn get_profile(&mut self, name: &str) -> Result<Profile, Status> {
+        let request = Request::new(ProfileSelector {
+            name: name.to_string(),
+        });
+        let response = self.transport.get_profile(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn list_profiles(&mut self) -> Result<ProfileList, Status> {
+        let request = Request::new(Empty {});
+        let response = self.transport.list_profiles(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn create_group(&mut self, group: Group) -> Result<Group, Status> {
+        let request = Request::new(group);
+        let response = self.transport.create_group(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn update_group(&mut self, group: Group) -> Result<Group, Status> {
+        let request = Request::new(group);
+        let response = self.transport.update_group(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn scale_group(&mut self, name: &str, replicas: u32) -> Result<Group, Status> {
+        let request = Request::new(GroupScale {
+            name: name.to_string(),
+            replicas,
+        });
+        let response = self.transport.scale_group(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_group(&mut self, name: &str) -> Result<Group, Status> {
+        let request = Request::new(GroupSelector {
+            name: name.to_string(),
+        });
+        let response = self.transport.delete_group(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn get_group(&mut self, name: &str) -> Result<Group, Status> {
+        let request = Request::new(GroupSelector {
+            name: name.to_string(),
+        });
+        let response = self.transport.get_group(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn list_groups(&mut self) -> Result<GroupList, Status> {
+        let request = Request::new(Empty {});
+        let response = self.transport.list_groups(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn watch_events(&mut self, filter: EventFilter) -> Result<Streaming<Event>, Status> {
+        let request = Request::new(filter);
+        let response = self.transport.watch_events(request).await?;
+
+        Ok(response.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(External::init("not a valid endpoint", None, None)
+            .await
+            .is_err());
+        assert!(External::init("http://127.0.0.1:1", None, None)
+            .await
+            .is_err());
+        assert!(
+            External::init("http://127.0.0.1:1", None, Some("bad\ntoken"))
+                .await
+                .is_err()
+        );
+        assert!(
+            External::init("unix:/tmp/test_impulse/missing.sock", None, None)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -1,61 +1,397 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
 use tonic::{Request, Response, Status};
 
-use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::sync::Mutex;
+
+use tokio_stream::wrappers::ReceiverStream;
+
+use tracing::{debug, info, info_span, warn, Instrument};
 
 use uuid::Uuid;
 
-use crate::impulse::external::v010::{MicroVm, SystemStatusResponse, SystemVersionResponse};
+use crate::audit::{AuditEntry, AuditLog, Audited};
+use crate::events::{self, EventLog};
+use crate::groups::{self, GroupRecord, GroupRegistry, GroupTemplate};
+use crate::impulse::external::v010::micro_vm_record::State;
+use crate::impulse::external::v010::{
+    AuditList, AuditQuery, AuditRecord, BatchLaunchRequest, BatchLaunchResult, BulkResult,
+    ClusterCapacity, Event, EventFilter, Group, GroupList, GroupScale, GroupSelector, GroupStatus,
+    LabelUpdate, LaunchRequest, ListFilter, MicroVm, MicroVmList, MicroVmRecord, Node, NodeDrain,
+    NodeList, NodeSelector, NodeSummary, Placement, Preference, Profile, ProfileList,
+    ProfileSelector, SystemStatusResponse, SystemVersionResponse, TenantQuota, TenantUsage,
+    UsageList, UsageQuery, VmResult, VmSelector, VmSummary,
+};
 use crate::impulse::shared::v010::{Empty, MicroVmLaunch, MicroVmShutdown, Task};
-use crate::IMPULSE_INTERFACE;
+use crate::labels::{self, Selector};
+use crate::metrics::INTERFACE;
+use crate::node_registry::{NodeRecord, NodeRegistry};
+use crate::placement::{Candidate, Constraints, PlacementSpec, Resident};
+use crate::policy::{Operation, Policy, Principal, Role};
+use crate::profiles::{self, ProfileRecord, ProfileRegistry};
+use crate::task_queue::TaskQueues;
+use crate::telemetry;
+use crate::tenants::{Claim, Quota, Usage};
+use crate::vm_registry::{Reservation, VmRecord, VmRegistry, VmSpec, VmState};
 
 pub use crate::impulse::external::v010::interface_server::{Interface, InterfaceServer};
 
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+const MAX_BATCH: usize = 256;
+const RESULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct InFlight<'a>(&'a AtomicU32);
+
+impl<'a> InFlight<'a> {
+    fn start(counter: &'a AtomicU32) -> InFlight<'a> {
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        InFlight(counter)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct Batch {
+    atomic: bool,
+    trace_id: String,
+    started: Instant,
+    results: Vec<BatchLaunchResult>,
+    reserved: Vec<(u32, VmRecord)>,
+    awaiting: HashMap<String, (u32, VmRecord)>,
+    launched: Vec<(u32, VmRecord)>,
+}
+
+#[derive(Clone)]
 pub struct External {
-    status: String,
+    system_id: Uuid,
+    started: Instant,
+    in_flight: Arc<AtomicU32>,
+    placing: Arc<Mutex<()>>,
+    result_timeout: Duration,
     pub version: String,
-    task_sender: Sender<Task>,
+    task_queues: Arc<TaskQueues>,
     launch_result_sender_clone: Sender<MicroVmLaunch>,
     shutdown_result_sender_clone: Sender<MicroVmShutdown>,
+    node_registry: Arc<NodeRegistry>,
+    vm_registry: Arc<VmRegistry>,
+    profile_registry: Arc<ProfileRegistry>,
+    group_registry: Arc<GroupRegistry>,
+    policy: Arc<Policy>,
+    audit_log: Arc<AuditLog>,
+    event_log: Arc<EventLog>,
 }
 
 impl External {
+    #[allow(clippy::too_many_arguments)]
     pub async fn init(
-        task_sender: Sender<Task>,
+        system_id: Uuid,
+        task_queues: Arc<TaskQueues>,
         launch_result_sender_clone: Sender<MicroVmLaunch>,
         shutdown_result_sender_clone: Sender<MicroVmShutdown>,
+        node_registry: Arc<NodeRegistry>,
+        vm_registry: Arc<VmRegistry>,
+        profile_registry: Arc<ProfileRegistry>,
+        group_registry: Arc<GroupRegistry>,
+        policy: Arc<Policy>,
+        audit_log: Arc<AuditLog>,
+        event_log: Arc<EventLog>,
     ) -> Result<External, Box<dyn std::error::Error>> {
-        let status = String::from("Running!");
+        let started = Instant::now();
+        let in_flight = Arc::new(AtomicU32::new(0));
         let version = String::from("v0.1.0");
 
         Ok(External {
-            status,
+            system_id,
+            started,
+            in_flight,
+            placing: Arc::new(Mutex::new(())),
+            result_timeout: RESULT_TIMEOUT,
             version,
-            task_sender,
+            task_queues,
             launch_result_sender_clone,
             shutdown_result_sender_clone,
+            node_registry,
+            vm_registry,
+            profile_registry,
+            group_registry,
+            policy,
+            audit_log,
+            event_log,
         })
     }
-}
 
-#[tonic::async_trait]
-impl Interface for External {
-    async fn system_status(
+    async fn audited<T, R, F, H>(
+        &self,
+        mut request: Request<T>,
+        operation: Operation,
+        handler: H,
+    ) -> Result<Response<R>, Status>
+    where
+        T: Audited,
+        R: Audited,
+        F: Future<Output = Result<Response<R>, Status>>,
+        H: FnOnce(Request<T>) -> F,
+    {
+        let started = Instant::now();
+        let principal = self.policy.principal(&request).await;
+        let mut entry = AuditEntry::init(&principal, operation, request.get_ref()).await;
+        let span = info_span!(
+            "external",
+            %operation,
+            principal = %principal.name,
+            role = %principal.role,
+            tenant = %principal.tenant,
+        );
+
+        let result = match Policy::authorize(&principal, operation).await {
+            Ok(()) => {
+                entry.allowed = true;
+
+                request.extensions_mut().insert(principal);
+
+                handler(request).instrument(span).await
+            }
+            Err(status) => {
+                span.in_scope(|| warn!(error = status.message(), "Permission denied"));
+
+                Err(status)
+            }
+        };
+
+        entry
+            .complete(result.as_ref().map(Response::get_ref), started.elapsed())
+            .await;
+
+        self.audit_log.record(entry).await;
+
+        result
+    }
+
+    async fn caller<T>(&self, request: &Request<T>) -> Principal {
+        match request.extensions().get::<Principal>() {
+            Some(principal) => principal.to_owned(),
+            None => self.policy.principal(request).await,
+        }
+    }
+
+    async fn node_not_found(node_id: &str) -> Status {
+        let message = format!("Node {} was not found!", node_id);
+        Status::new(tonic::Code::NotFound, message)
+    }
+
+    async fn selector(selector: &str) -> Result<Selector, Status> {
+        selector
+            .parse::<Selector>()
+            .map_err(|error| Status::invalid_argument(error.to_string()))
+    }
+
+    async fn bulk_selector(selector: &str) -> Result<Selector, Status> {
+        let selector = Self::selector(selector).await?;
+
+        match selector.is_empty() {
+            true => {
+                let message = String::from(
+                    "Selector is empty and would match every MicroVM... please narrow it!",
+                );
+
+                Err(Status::invalid_argument(message))
+            }
+            false => Ok(selector),
+        }
+    }
+
+    async fn vm_result(record: &VmRecord, ok: bool, details: &str) -> VmResult {
+        VmResult {
+            uuid: record.uuid.to_owned(),
+            name: record.spec.name.to_owned(),
+            node_id: record.node_id.to_owned(),
+            ok,
+            details: details.to_string(),
+        }
+    }
+
+    async fn constraints(placement: &PlacementSpec) -> Result<Constraints, Status> {
+        Constraints::parse(placement).map_err(|error| Status::invalid_argument(error.to_string()))
+    }
+
+    async fn schedule(&self, record: &VmRecord) -> Result<String, Status> {
+        let constraints = Self::constraints(&record.spec.placement)
+            .await?
+            .require_images(profiles::images(&record.spec.machine));
+        let connected: HashMap<String, usize> =
+            self.task_queues.connected().await.into_iter().collect();
+        let nodes = self.node_registry.list().await;
+        let vms = self.vm_registry.list().await;
+
+        let candidates: Vec<Candidate> = nodes
+            .iter()
+            .map(|node| Candidate {
+                node_id: &node.node_id,
+                labels: &node.labels,
+                depth: connected.get(&node.node_id).copied(),
+                draining: node.draining,
+                images: &node.inventory.images,
+            })
+            .collect();
+        let residents: Vec<Resident> = vms
+            .iter()
+            .filter(|vm| vm.live() && !vm.node_id.is_empty() && vm.uuid != record.uuid)
+            .map(|vm| Resident {
+                uuid: &vm.uuid,
+                node_id: &vm.node_id,
+                labels: &vm.spec.labels,
+            })
+            .collect();
+
+        match constraints.place(&candidates, &residents, &record.spec.labels) {
+            Ok(node_id) => {
+                debug!(%node_id, depth = connected.get(&node_id), "Node selected");
+
+                Ok(node_id)
+            }
+            Err(blocked) => {
+                let available = candidates
+                    .iter()
+                    .any(|candidate| candidate.depth.is_some() && !candidate.draining);
+
+                if !available {
+                    let message = String::from(
+                        "No connected node is accepting launches... please retry later!",
+                    );
+
+                    return Err(Status::unavailable(message));
+                }
+
+                let reasons: Vec<String> = blocked
+                    .iter()
+                    .map(|(node_id, reason)| format!("{}: {}", node_id, reason))
+                    .collect();
+                let message = format!(
+                    "No node satisfies the placement of this MicroVM... {}!",
+                    reasons.join("; "),
+                );
+
+                Err(Status::failed_precondition(message))
+            }
+        }
+    }
+
+    async fn holders(&self, uuid: &str) -> Vec<String> {
+        match self.vm_registry.get(uuid).await {
+            Some(record) if !record.node_id.is_empty() => vec![record.node_id],
+            _ => self
+                .task_queues
+                .connected()
+                .await
+                .into_iter()
+                .map(|(node_id, _)| node_id)
+                .collect(),
+        }
+    }
+
+    async fn status(
         &self,
         request: Request<Empty>,
     ) -> Result<Response<SystemStatusResponse>, Status> {
-        println!("{:?}", request);
+        debug!(?request, "System status requested");
+
+        let nodes = self.node_registry.list().await;
+        let vms = self.vm_registry.list().await;
+
+        let ready = !nodes.is_empty() && !self.task_queues.connected().await.is_empty();
+        let status = match ready {
+            true => String::from("ready"),
+            false => String::from("not_ready"),
+        };
+
         let status = SystemStatusResponse {
-            status: self.status.to_owned(),
+            status,
+            system_id: self.system_id.to_string(),
+            version: self.version.to_owned(),
+            uptime_seconds: self.started.elapsed().as_secs(),
+            nodes: Some(Self::node_summary(&nodes).await),
+            vms: Some(Self::vm_summary(&vms).await),
+            in_flight_tasks: self.in_flight.load(Ordering::Relaxed),
+            queue_depth: self.task_queues.depth().await as u32,
+            capacity: Some(Self::capacity(&nodes).await),
         };
         let response = Response::new(status);
         Ok(response)
     }
 
-    async fn system_version(
+    async fn node_summary(nodes: &[NodeRecord]) -> NodeSummary {
+        let mut summary = NodeSummary {
+            registered: nodes.len() as u32,
+            ..NodeSummary::default()
+        };
+
+        for node in nodes {
+            match (node.stale().await, node.draining) {
+                (true, _) => summary.stale += 1,
+                (false, true) => summary.draining += 1,
+                (false, false) => summary.healthy += 1,
+            }
+        }
+
+        summary
+    }
+
+    async fn vm_summary(vms: &[VmRecord]) -> VmSummary {
+        let mut summary = VmSummary {
+            total: vms.len() as u32,
+            ..VmSummary::default()
+        };
+
+        for vm in vms {
+            match vm.state {
+                VmState::Pending => summary.pending += 1,
+                VmState::Running => summary.running += 1,
+                VmState::Failed => summary.failed += 1,
+                VmState::Shutdown => summary.shutdown += 1,
+            }
+        }
+
+        summary
+    }
+
+    async fn capacity(nodes: &[NodeRecord]) -> ClusterCapacity {
+        let mut capacity = ClusterCapacity::default();
+
+        for node in nodes {
+            let inventory = &node.inventory;
+
+            capacity.cpu_count += inventory.cpu_count;
+            capacity.memory_total += inventory.memory_total;
+            capacity.memory_free += inventory.memory_available;
+            capacity.memory_used += inventory
+                .memory_total
+                .saturating_sub(inventory.memory_available);
+            capacity.disk_total += inventory.disk_total;
+            capacity.disk_free += inventory.disk_free;
+            capacity.disk_used += inventory.disk_total.saturating_sub(inventory.disk_free);
+        }
+
+        capacity
+    }
+
+    async fn version(
         &self,
         request: Request<Empty>,
     ) -> Result<Response<SystemVersionResponse>, Status> {
-        println!("{:?}", request);
+        debug!(?request, "System version requested");
         let version = SystemVersionResponse {
             version: self.version.to_owned(),
         };
@@ -63,124 +399,2412 @@ impl Interface for External {
         Ok(response)
     }
 
-    async fn launch_vm(&self, request: Request<Empty>) -> Result<Response<MicroVmLaunch>, Status> {
-        println!(
-            "{} Incoming launch request | {:?}",
-            IMPULSE_INTERFACE,
-            request.get_ref(),
-        );
-        println!(
-            "{} Sending request to connected nodes | {:?}",
-            IMPULSE_INTERFACE,
-            &self.task_sender.receiver_count(),
-        );
-
+    async fn launch(
+        &self,
+        request: Request<LaunchRequest>,
+    ) -> Result<Response<MicroVmLaunch>, Status> {
+        let trace_id = telemetry::trace_id(request.metadata()).await;
+        let mut claim = self
+            .policy
+            .tenants
+            .claim(&self.caller(&request).await.tenant);
+        let request = request.into_inner();
+        let request_id = request.request_id.to_owned();
+        let spec = self.prepare(request, &mut claim).await?;
         let task = Task {
             action: 1,
             id: Uuid::new_v4().simple().to_string(),
+            trace_id,
+            machine: Some(spec.machine.to_owned()),
+        };
+        let span = info_span!(
+            "launch_vm",
+            vm_uuid = %task.id,
+            trace_id = %task.trace_id,
+            request_id = %request_id,
+            tenant = %claim.tenant,
+            profile = %spec.profile,
+        );
+        let started = Instant::now();
+
+        let launched = async move {
+            let _in_flight = InFlight::start(&self.in_flight);
+
+            let uuid = task.id.to_owned();
+            let trace_id = task.trace_id.to_owned();
+            let receiver = self.launch_result_sender_clone.subscribe();
+
+            let reservation = self
+                .vm_registry
+                .pending(&uuid, &request_id, spec, &claim)
+                .await;
+
+            let record = match reservation {
+                Ok(Reservation::Created(record)) => {
+                    debug!(name = %record.spec.name, "Reserved MicroVM");
+
+                    record
+                }
+                Ok(Reservation::Existing(record)) => {
+                    return self.existing(record, receiver, &trace_id, started).await;
+                }
+                Err(status) => {
+                    warn!(error = status.message(), "Launch rejected at reservation");
+
+                    INTERFACE
+                        .observe_launch("rejected", started.elapsed())
+                        .await;
+
+                    return Err(status);
+                }
+            };
+
+            if let Err(status) = self.dispatch(&record, task).await {
+                INTERFACE
+                    .observe_launch("rejected", started.elapsed())
+                    .await;
+
+                return Err(status);
+            }
+
+            self.await_launch(receiver, &uuid, &trace_id, started).await
         };
 
-        if let Ok(msg) = &self.task_sender.send(task) {
-            println!("{} Message sent | {:?}", IMPULSE_INTERFACE, &msg);
+        launched.instrument(span).await
+    }
+
+    async fn prepare(&self, request: LaunchRequest, claim: &mut Claim) -> Result<VmSpec, Status> {
+        if let Err(error) = labels::validate(&request.labels)
+            .and_then(|_| labels::validate_annotations(&request.annotations))
+        {
+            return Err(Status::invalid_argument(error.to_string()));
         }
 
-        let mut receiver = self.launch_result_sender_clone.subscribe();
+        let placement = request
+            .placement
+            .map(PlacementSpec::from)
+            .unwrap_or_default();
 
-        if let Ok(message) = receiver.recv().await {
-            let response = Response::new(message);
+        Self::constraints(&placement).await?;
 
-            Ok(response)
-        } else {
-            let message = String::from("Something went wrong!");
-            let status = Status::new(tonic::Code::NotFound, message);
-            Err(status)
+        let machine = self
+            .profile_registry
+            .resolve(&request.profile, &request.overrides.unwrap_or_default())
+            .await?;
+
+        // drives are sized by their images... disk is charged as the flat per-VM footprint
+        if machine.vcpus > 0 {
+            claim.resources.vcpus = u64::from(machine.vcpus);
+        }
+
+        if machine.memory_mib > 0 {
+            claim.resources.memory_mib = u64::from(machine.memory_mib);
         }
+
+        Ok(VmSpec {
+            name: request.name,
+            labels: request.labels,
+            annotations: request.annotations,
+            placement,
+            profile: request.profile,
+            machine,
+            ..VmSpec::default()
+        })
     }
 
-    async fn shutdown_vm(
+    async fn dispatch(&self, record: &VmRecord, task: Task) -> Result<String, Status> {
+        let scheduled = {
+            let _placing = self.placing.lock().await;
+
+            match self.schedule(record).await {
+                Ok(node_id) => {
+                    info!(%node_id, vm_uuid = %record.uuid, "Sending launch request to node");
+
+                    match self.vm_registry.assign(&record.uuid, &node_id).await {
+                        Ok(_) => match self.task_queues.enqueue(&node_id, task).await {
+                            Ok(depth) => Ok((node_id, depth)),
+                            Err(status) => Err(status),
+                        },
+                        Err(status) => Err(status),
+                    }
+                }
+                Err(status) => Err(status),
+            }
+        };
+
+        match scheduled {
+            Ok((node_id, depth)) => {
+                debug!(depth, "Task queued");
+
+                self.event_log
+                    .vm_state(&record.uuid, &node_id, State::Pending, "")
+                    .await;
+
+                Ok(node_id)
+            }
+            Err(status) => {
+                warn!(vm_uuid = %record.uuid, error = status.message(), "Task rejected");
+
+                if let Err(status) = self.vm_registry.remove(&record.uuid).await {
+                    warn!(vm_uuid = %record.uuid, error = status.message(), "Unable to release MicroVM");
+                }
+
+                Err(status)
+            }
+        }
+    }
+
+    async fn launch_batch(
         &self,
-        request: Request<MicroVm>,
-    ) -> Result<Response<MicroVmShutdown>, Status> {
-        let task = Task {
-            action: 2,
-            id: request.into_inner().name,
+        request: Request<BatchLaunchRequest>,
+    ) -> Result<Response<ReceiverStream<Result<BatchLaunchResult, Status>>>, Status> {
+        let trace_id = telemetry::trace_id(request.metadata()).await;
+        let claim = self
+            .policy
+            .tenants
+            .claim(&self.caller(&request).await.tenant);
+        let request = request.into_inner();
+        let atomic = request.atomic;
+        let launches = Self::expand(request).await?;
+        let span = info_span!(
+            "launch_vms",
+            count = launches.len(),
+            atomic,
+            trace_id = %trace_id,
+            tenant = %claim.tenant,
+        );
+        let started = Instant::now();
+        let mut batch = Batch {
+            atomic,
+            trace_id,
+            started,
+            results: Vec::with_capacity(launches.len()),
+            reserved: Vec::with_capacity(launches.len()),
+            awaiting: HashMap::with_capacity(launches.len()),
+            launched: Vec::with_capacity(launches.len()),
         };
 
-        if let Ok(task) = &self.task_sender.send(task) {
-            println!("Message sent - {:?}", task);
+        let receiver = async {
+            let receiver = self.launch_result_sender_clone.subscribe();
+
+            for (index, launch) in (0..).zip(launches) {
+                let uuid = Uuid::new_v4().simple().to_string();
+                let name = launch.name.to_owned();
+                let request_id = launch.request_id.to_owned();
+                let mut claim = claim.to_owned();
+
+                let reservation = match self.prepare(launch, &mut claim).await {
+                    Ok(spec) => {
+                        self.vm_registry
+                            .pending(&uuid, &request_id, spec, &claim)
+                            .await
+                    }
+                    Err(status) => Err(status),
+                };
+
+                match reservation {
+                    Ok(Reservation::Created(record)) => batch.reserved.push((index, record)),
+                    Ok(Reservation::Existing(record)) => match record.state {
+                        VmState::Pending => {
+                            batch
+                                .awaiting
+                                .insert(record.uuid.to_owned(), (index, record));
+                        }
+                        state => {
+                            INTERFACE
+                                .observe_launch("existing", started.elapsed())
+                                .await;
+
+                            let launched = state == VmState::Running;
+                            let result =
+                                Self::batch_result(index, &record, launched, &record.details).await;
+
+                            batch.results.push(result);
+
+                            match launched {
+                                true => batch.launched.push((index, record)),
+                                false if batch.atomic => {
+                                    self.release(&batch).await;
+
+                                    let message = format!(
+                                        "MicroVM {} of the batch already failed... use new request ids to retry!",
+                                        index,
+                                    );
+
+                                    return Err(Status::failed_precondition(message));
+                                }
+                                false => {}
+                            }
+                        }
+                    },
+                    Err(status) => {
+                        warn!(index, error = status.message(), "Launch rejected at reservation");
+
+                        INTERFACE
+                            .observe_launch("rejected", started.elapsed())
+                            .await;
+
+                        if batch.atomic {
+                            self.release(&batch).await;
+
+                            let message = format!(
+                                "MicroVM {} of the batch was rejected, nothing was launched: {}",
+                                index,
+                                status.message(),
+                            );
+
+                            return Err(Status::new(status.code(), message));
+                        }
+
+                        batch.results.push(BatchLaunchResult {
+                            index,
+                            name,
+                            details: status.message().to_string(),
+                            ..BatchLaunchResult::default()
+                        });
+                    }
+                }
+            }
+
+            info!(
+                reserved = batch.reserved.len(),
+                existing = batch.awaiting.len() + batch.launched.len(),
+                rejected = batch.results.len() - batch.launched.len(),
+                "Batch reserved",
+            );
+
+            Ok(receiver)
+        }
+        .instrument(span.to_owned())
+        .await?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let external = self.to_owned();
+
+        tokio::spawn(
+            async move {
+                let _in_flight = InFlight::start(&external.in_flight);
+
+                external.run_batch(batch, receiver, tx).await;
+            }
+            .instrument(span),
+        );
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn expand(request: BatchLaunchRequest) -> Result<Vec<LaunchRequest>, Status> {
+        let launches = match (request.template, request.launches.is_empty()) {
+            (Some(_), false) => {
+                let message =
+                    String::from("Pass either a template and a count or a list of launches!");
+
+                return Err(Status::invalid_argument(message));
+            }
+            (Some(template), true) => (0..request.count)
+                .map(|index| {
+                    let mut launch = template.to_owned();
+
+                    if !launch.name.is_empty() {
+                        launch.name = format!("{}-{}", launch.name, index);
+                    }
+
+                    if !launch.request_id.is_empty() {
+                        launch.request_id = format!("{}-{}", launch.request_id, index);
+                    }
+
+                    launch
+                })
+                .collect(),
+            (None, _) => request.launches,
+        };
+
+        match launches.len() {
+            0 => {
+                let message = String::from("Batch launches no MicroVMs!");
+
+                Err(Status::invalid_argument(message))
+            }
+            count if count > MAX_BATCH => {
+                let message = format!(
+                    "Batch asks for {} MicroVMs but at most {} are allowed!",
+                    count, MAX_BATCH,
+                );
+
+                Err(Status::invalid_argument(message))
+            }
+            _ => Ok(launches),
+        }
+    }
+
+    async fn release(&self, batch: &Batch) {
+        for (_, record) in &batch.reserved {
+            if let Err(status) = self.vm_registry.remove(&record.uuid).await {
+                warn!(vm_uuid = %record.uuid, error = status.message(), "Unable to release MicroVM");
+            }
+        }
+    }
+
+    async fn run_batch(
+        &self,
+        mut batch: Batch,
+        mut receiver: Receiver<MicroVmLaunch>,
+        tx: tokio::sync::mpsc::Sender<Result<BatchLaunchResult, Status>>,
+    ) {
+        let mut failed = batch.results.iter().any(|result| !result.launched);
+
+        for result in std::mem::take(&mut batch.results) {
+            let _ = tx.send(Ok(result)).await;
+        }
+
+        for (index, mut record) in std::mem::take(&mut batch.reserved) {
+            if batch.atomic && failed {
+                if let Err(status) = self.vm_registry.remove(&record.uuid).await {
+                    warn!(vm_uuid = %record.uuid, error = status.message(), "Unable to release MicroVM");
+                }
+
+                let result =
+                    Self::batch_result(index, &record, false, "Skipped... batch failed").await;
+                let _ = tx.send(Ok(result)).await;
+
+                continue;
+            }
+
+            let task = Task {
+                action: 1,
+                id: record.uuid.to_owned(),
+                trace_id: batch.trace_id.to_owned(),
+                machine: Some(record.spec.machine.to_owned()),
+            };
+
+            match self.dispatch(&record, task).await {
+                Ok(node_id) => {
+                    record.node_id = node_id;
+                    batch
+                        .awaiting
+                        .insert(record.uuid.to_owned(), (index, record));
+                }
+                Err(status) => {
+                    INTERFACE
+                        .observe_launch("rejected", batch.started.elapsed())
+                        .await;
+
+                    failed = true;
+
+                    let result = Self::batch_result(index, &record, false, status.message()).await;
+                    let _ = tx.send(Ok(result)).await;
+                }
+            }
+        }
+
+        let waiting = Instant::now();
+        let mut unsettled = "Something went wrong!";
+
+        while !batch.awaiting.is_empty() {
+            let remaining = self.result_timeout.saturating_sub(waiting.elapsed());
+
+            let messages = match tokio::time::timeout(remaining, receiver.recv()).await {
+                Ok(Ok(message)) => vec![message],
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        skipped,
+                        "Launch results lagged... reading them from the registry"
+                    );
+
+                    self.settled(&batch.awaiting).await
+                }
+                Ok(Err(RecvError::Closed)) => break,
+                Err(_) => {
+                    unsettled = "MicroVM did not report a launch result in time... please check its state later!";
+
+                    break;
+                }
+            };
+
+            for message in messages {
+                if let Some((index, mut record)) = batch.awaiting.remove(&message.uuid) {
+                    let launched = message.launched == "true";
+                    let result = if launched { "launched" } else { "failed" };
+
+                    INTERFACE
+                        .observe_launch(result, batch.started.elapsed())
+                        .await;
+
+                    record.node_id = message.node_id;
+
+                    let result =
+                        Self::batch_result(index, &record, launched, &message.details).await;
+                    let _ = tx.send(Ok(result)).await;
+
+                    match launched {
+                        true => batch.launched.push((index, record)),
+                        false => failed = true,
+                    }
+                }
+            }
+        }
+
+        for (index, record) in std::mem::take(&mut batch.awaiting).into_values() {
+            INTERFACE
+                .observe_launch("error", batch.started.elapsed())
+                .await;
+
+            failed = true;
+
+            let result = Self::batch_result(index, &record, false, unsettled).await;
+            let _ = tx.send(Ok(result)).await;
         }
 
+        let rolled_back = match batch.atomic && failed {
+            true => self.roll_back(&batch, &tx).await,
+            false => 0,
+        };
+
+        info!(
+            launched = batch.launched.len(),
+            failed, rolled_back, "Batch launch finished",
+        );
+    }
+
+    async fn settled(&self, awaiting: &HashMap<String, (u32, VmRecord)>) -> Vec<MicroVmLaunch> {
+        let mut settled = Vec::with_capacity(awaiting.len());
+
+        for uuid in awaiting.keys() {
+            if let Some(message) = self.settled_launch(uuid).await {
+                settled.push(message);
+            }
+        }
+
+        settled
+    }
+
+    async fn roll_back(
+        &self,
+        batch: &Batch,
+        tx: &tokio::sync::mpsc::Sender<Result<BatchLaunchResult, Status>>,
+    ) -> usize {
         let mut receiver = self.shutdown_result_sender_clone.subscribe();
+        let mut awaiting = HashMap::with_capacity(batch.launched.len());
+
+        warn!(
+            launched = batch.launched.len(),
+            "Batch failed... rolling back launched MicroVMs",
+        );
 
-        if let Ok(message) = receiver.recv().await {
-            let response = Response::new(message);
+        for (index, record) in &batch.launched {
+            let task = Task {
+                action: 2,
+                id: record.uuid.to_owned(),
+                trace_id: batch.trace_id.to_owned(),
+                machine: None,
+            };
 
-            Ok(response)
-        } else {
-            let message = String::from("Something went wrong!");
-            let status = Status::new(tonic::Code::NotFound, message);
-            Err(status)
+            match self.dispatch_shutdown(&task).await {
+                Ok(_) => {
+                    awaiting.insert(record.uuid.to_owned(), (*index, record));
+                }
+                Err(status) => {
+                    let details = format!("Rollback failed: {}", status.message());
+                    let result = Self::batch_result(*index, record, true, &details).await;
+                    let _ = tx.send(Ok(result)).await;
+                }
+            }
+        }
+
+        let mut rolled_back = 0;
+        let waiting = Instant::now();
+        let mut unsettled = "Something went wrong!";
+
+        while !awaiting.is_empty() {
+            let remaining = self.result_timeout.saturating_sub(waiting.elapsed());
+
+            let messages = match tokio::time::timeout(remaining, receiver.recv()).await {
+                Ok(Ok(message)) => vec![message],
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        skipped,
+                        "Shutdown results lagged... reading them from the registry"
+                    );
+
+                    let mut settled = Vec::with_capacity(awaiting.len());
+
+                    for uuid in awaiting.keys() {
+                        if let Some(message) = self.settled_shutdown(uuid).await {
+                            settled.push(message);
+                        }
+                    }
+
+                    settled
+                }
+                Ok(Err(RecvError::Closed)) => break,
+                Err(_) => {
+                    unsettled = "MicroVM did not report a shutdown result in time... please check its state later!";
+
+                    break;
+                }
+            };
+
+            for message in messages {
+                if let Some((index, record)) = awaiting.remove(&message.uuid) {
+                    let shutdown = message.shutdown == "true";
+                    let mut result = match shutdown {
+                        true => Self::batch_result(index, record, false, &message.details).await,
+                        false => {
+                            let details = format!("Rollback failed: {}", message.details);
+
+                            Self::batch_result(index, record, true, &details).await
+                        }
+                    };
+
+                    result.rolled_back = shutdown;
+                    rolled_back += usize::from(shutdown);
+
+                    let _ = tx.send(Ok(result)).await;
+                }
+            }
+        }
+
+        for (index, record) in awaiting.into_values() {
+            let details = format!("Rollback failed: {}", unsettled);
+            let result = Self::batch_result(index, record, true, &details).await;
+            let _ = tx.send(Ok(result)).await;
         }
+
+        rolled_back
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    async fn batch_result(
+        index: u32,
+        record: &VmRecord,
+        launched: bool,
+        details: &str,
+    ) -> BatchLaunchResult {
+        BatchLaunchResult {
+            index,
+            uuid: record.uuid.to_owned(),
+            name: record.spec.name.to_owned(),
+            node_id: record.node_id.to_owned(),
+            launched,
+            details: details.to_string(),
+            rolled_back: false,
+        }
+    }
+
+    async fn existing(
+        &self,
+        record: VmRecord,
+        receiver: Receiver<MicroVmLaunch>,
+        trace_id: &str,
+        started: Instant,
+    ) -> Result<Response<MicroVmLaunch>, Status> {
+        info!(
+            existing_uuid = %record.uuid,
+            state = ?record.state,
+            "Launch request already handled",
+        );
+
+        if record.state == VmState::Pending {
+            return self
+                .await_launch(receiver, &record.uuid, trace_id, started)
+                .await;
+        }
+
+        INTERFACE
+            .observe_launch("existing", started.elapsed())
+            .await;
+
+        let mut response = Response::new(MicroVmLaunch {
+            launched: (record.state == VmState::Running).to_string(),
+            uuid: record.uuid,
+            details: record.details,
+            node_id: record.node_id,
+        });
+
+        telemetry::inject(response.metadata_mut(), trace_id).await;
+
+        Ok(response)
+    }
+
+    async fn await_launch(
+        &self,
+        mut receiver: Receiver<MicroVmLaunch>,
+        uuid: &str,
+        trace_id: &str,
+        started: Instant,
+    ) -> Result<Response<MicroVmLaunch>, Status> {
+        let waiting = Instant::now();
+        let result = loop {
+            let remaining = self.result_timeout.saturating_sub(waiting.elapsed());
+
+            match tokio::time::timeout(remaining, receiver.recv()).await {
+                Ok(Ok(message)) if message.uuid == uuid => break Ok(message),
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        skipped,
+                        "Launch results lagged... reading it from the registry"
+                    );
+
+                    if let Some(message) = self.settled_launch(uuid).await {
+                        break Ok(message);
+                    }
+                }
+                Ok(Err(RecvError::Closed)) => {
+                    let message = String::from(
+                        "Launch results are not being received... please retry later!",
+                    );
+
+                    break Err(Status::unavailable(message));
+                }
+                Err(_) => {
+                    let message = format!(
+                        "MicroVM {} did not report a launch result in time... please check its state later!",
+                        uuid,
+                    );
+
+                    break Err(Status::deadline_exceeded(message));
+                }
+            }
+        };
+
+        match result {
+            Ok(message) => {
+                info!(node_id = %message.node_id, launched = %message.launched, "Launch result");
+
+                let result = if message.launched == "true" {
+                    "launched"
+                } else {
+                    "failed"
+                };
+
+                INTERFACE.observe_launch(result, started.elapsed()).await;
+
+                let mut response = Response::new(message);
+
+                telemetry::inject(response.metadata_mut(), trace_id).await;
+
+                Ok(response)
+            }
+            Err(status) => {
+                INTERFACE.observe_launch("error", started.elapsed()).await;
+
+                Err(status)
+            }
+        }
+    }
+
+    async fn settled_launch(&self, uuid: &str) -> Option<MicroVmLaunch> {
+        match self.vm_registry.get(uuid).await {
+            Some(record) if record.state != VmState::Pending => Some(MicroVmLaunch {
+                launched: (record.state == VmState::Running).to_string(),
+                uuid: record.uuid,
+                details: record.details,
+                node_id: record.node_id,
+            }),
+            _ => None,
+        }
+    }
+
+    async fn shutdown(
+        &self,
+        request: Request<MicroVm>,
+    ) -> Result<Response<MicroVmShutdown>, Status> {
+        let caller = self.caller(&request).await;
+        let trace_id = telemetry::trace_id(request.metadata()).await;
+        let name = request.into_inner().name;
+        let task = Task {
+            action: 2,
+            id: self.vm_registry.resolve(&name, &caller.tenant).await,
+            trace_id,
+            machine: None,
+        };
+
+        self.owned_vm(&caller, &task.id).await?;
+
+        let span = info_span!("shutdown_vm", vm_uuid = %task.id, trace_id = %task.trace_id);
+        let started = Instant::now();
+
+        let shutdown = async move {
+            let _in_flight = InFlight::start(&self.in_flight);
+
+            let uuid = task.id.to_owned();
+            let trace_id = task.trace_id.to_owned();
+            let receiver = self.shutdown_result_sender_clone.subscribe();
+
+            if let Err(status) = self.dispatch_shutdown(&task).await {
+                INTERFACE
+                    .observe_shutdown("rejected", started.elapsed())
+                    .await;
+
+                return Err(status);
+            }
+
+            self.await_shutdown(receiver, &uuid, &trace_id, started)
+                .await
+        };
+
+        shutdown.instrument(span).await
+    }
+
+    async fn await_shutdown(
+        &self,
+        mut receiver: Receiver<MicroVmShutdown>,
+        uuid: &str,
+        trace_id: &str,
+        started: Instant,
+    ) -> Result<Response<MicroVmShutdown>, Status> {
+        let waiting = Instant::now();
+        let result = loop {
+            let remaining = self.result_timeout.saturating_sub(waiting.elapsed());
+
+            match tokio::time::timeout(remaining, receiver.recv()).await {
+                Ok(Ok(message)) if message.uuid == uuid => break Ok(message),
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        skipped,
+                        "Shutdown results lagged... reading it from the registry"
+                    );
+
+                    if let Some(message) = self.settled_shutdown(uuid).await {
+                        break Ok(message);
+                    }
+                }
+                Ok(Err(RecvError::Closed)) => {
+                    let message = String::from(
+                        "Shutdown results are not being received... please retry later!",
+                    );
+
+                    break Err(Status::unavailable(message));
+                }
+                Err(_) => {
+                    let message = format!(
+                        "MicroVM {} did not report a shutdown result in time... please check its state later!",
+                        uuid,
+                    );
+
+                    break Err(Status::deadline_exceeded(message));
+                }
+            }
+        };
+
+        match result {
+            Ok(message) => {
+                info!(node_id = %message.node_id, shutdown = %message.shutdown, "Shutdown result");
+
+                let result = if message.shutdown == "true" {
+                    "shutdown"
+                } else {
+                    "failed"
+                };
+
+                INTERFACE.observe_shutdown(result, started.elapsed()).await;
+
+                let mut response = Response::new(message);
+
+                telemetry::inject(response.metadata_mut(), trace_id).await;
+
+                Ok(response)
+            }
+            Err(status) => {
+                INTERFACE.observe_shutdown("error", started.elapsed()).await;
+
+                Err(status)
+            }
+        }
+    }
+
+    async fn settled_shutdown(&self, uuid: &str) -> Option<MicroVmShutdown> {
+        match self.vm_registry.get(uuid).await {
+            Some(record) if record.state == VmState::Shutdown => Some(MicroVmShutdown {
+                shutdown: true.to_string(),
+                uuid: record.uuid,
+                details: record.details,
+                node_id: record.node_id,
+            }),
+            _ => None,
+        }
+    }
+
+    async fn dispatch_shutdown(&self, task: &Task) -> Result<usize, Status> {
+        let mut queued = 0;
+        let mut rejected = None;
+
+        for node_id in self.holders(&task.id).await {
+            match self.task_queues.enqueue(&node_id, task.to_owned()).await {
+                Ok(depth) => {
+                    debug!(%node_id, depth, "Task queued");
+
+                    queued += 1;
+                }
+                Err(status) => {
+                    warn!(%node_id, error = status.message(), "Task rejected");
+
+                    rejected = Some(status);
+                }
+            }
+        }
+
+        match queued {
+            0 => Err(rejected.unwrap_or_else(|| {
+                let message = String::from("No connected node holds this MicroVM!");
+
+                Status::unavailable(message)
+            })),
+            queued => Ok(queued),
+        }
+    }
+
+    async fn bulk_shutdown(
+        &self,
+        request: Request<VmSelector>,
+    ) -> Result<Response<BulkResult>, Status> {
+        let caller = self.caller(&request).await;
+        let trace_id = telemetry::trace_id(request.metadata()).await;
+        let selector = Self::bulk_selector(&request.into_inner().selector).await?;
+        let targets: Vec<VmRecord> = self
+            .vm_registry
+            .select(&selector)
+            .await
+            .into_iter()
+            .filter(|record| record.live() && Self::visible(&caller, record))
+            .collect();
+        let span = info_span!(
+            "shutdown_vms",
+            %selector,
+            matched = targets.len(),
+            trace_id = %trace_id,
+        );
+        let started = Instant::now();
+
+        let shutdown = async move {
+            let _in_flight = InFlight::start(&self.in_flight);
+
+            let receiver = self.shutdown_result_sender_clone.subscribe();
+            let mut results = Vec::with_capacity(targets.len());
+            let mut awaiting = HashMap::with_capacity(targets.len());
+
+            for record in targets {
+                let task = Task {
+                    action: 2,
+                    id: record.uuid.to_owned(),
+                    trace_id: trace_id.to_owned(),
+                    machine: None,
+                };
+
+                match self.dispatch_shutdown(&task).await {
+                    Ok(_) => {
+                        awaiting.insert(record.uuid.to_owned(), record);
+                    }
+                    Err(status) => {
+                        INTERFACE
+                            .observe_shutdown("rejected", started.elapsed())
+                            .await;
+
+                        results.push(Self::vm_result(&record, false, status.message()).await);
+                    }
+                }
+            }
+
+            results.extend(self.await_shutdowns(receiver, awaiting, started).await);
+
+            info!(
+                shutdown = results.iter().filter(|result| result.ok).count(),
+                failed = results.iter().filter(|result| !result.ok).count(),
+                "Bulk shutdown finished",
+            );
+
+            results.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+
+            let mut response = Response::new(BulkResult { results });
+
+            telemetry::inject(response.metadata_mut(), &trace_id).await;
+
+            Ok(response)
+        };
+
+        shutdown.instrument(span).await
+    }
+
+    async fn await_shutdowns(
+        &self,
+        mut receiver: Receiver<MicroVmShutdown>,
+        mut awaiting: HashMap<String, VmRecord>,
+        started: Instant,
+    ) -> Vec<VmResult> {
+        let mut results = Vec::with_capacity(awaiting.len());
+        let waiting = Instant::now();
+        let mut unsettled = "Something went wrong!";
+
+        while !awaiting.is_empty() {
+            let remaining = self.result_timeout.saturating_sub(waiting.elapsed());
+
+            let messages = match tokio::time::timeout(remaining, receiver.recv()).await {
+                Ok(Ok(message)) => vec![message],
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        skipped,
+                        "Shutdown results lagged... reading them from the registry"
+                    );
+
+                    let mut settled = Vec::with_capacity(awaiting.len());
+
+                    for uuid in awaiting.keys() {
+                        if let Some(message) = self.settled_shutdown(uuid).await {
+                            settled.push(message);
+                        }
+                    }
+
+                    settled
+                }
+                Ok(Err(RecvError::Closed)) => break,
+                Err(_) => {
+                    unsettled = "MicroVM did not report a shutdown result in time... please check its state later!";
+
+                    break;
+                }
+            };
+
+            for message in messages {
+                if let Some(mut record) = awaiting.remove(&message.uuid) {
+                    let shutdown = message.shutdown == "true";
+                    let result = if shutdown { "shutdown" } else { "failed" };
+
+                    INTERFACE.observe_shutdown(result, started.elapsed()).await;
+
+                    record.node_id = message.node_id;
+                    results.push(Self::vm_result(&record, shutdown, &message.details).await);
+                }
+            }
+        }
+
+        for record in awaiting.into_values() {
+            INTERFACE.observe_shutdown("error", started.elapsed()).await;
+
+            results.push(Self::vm_result(&record, false, unsettled).await);
+        }
+
+        results
+    }
+
+    async fn relabel(&self, request: Request<LabelUpdate>) -> Result<Response<BulkResult>, Status> {
+        let caller = self.caller(&request).await;
+        let update = request.into_inner();
+        let selector = Self::bulk_selector(&update.selector).await?;
+
+        if let Err(error) =
+            labels::validate(&update.labels).and_then(|_| labels::validate_keys(&update.remove))
+        {
+            return Err(Status::invalid_argument(error.to_string()));
+        }
+
+        let mut results = Vec::with_capacity(20);
+
+        for record in self
+            .vm_registry
+            .relabel(
+                &selector,
+                &update.labels,
+                &update.remove,
+                Self::scope(&caller),
+            )
+            .await?
+        {
+            results.push(Self::vm_result(&record, true, "Labels updated").await);
+        }
+
+        info!(%selector, relabeled = results.len(), "MicroVMs relabeled");
+
+        Ok(Response::new(BulkResult { results }))
+    }
+
+    async fn nodes(&self, request: Request<ListFilter>) -> Result<Response<NodeList>, Status> {
+        debug!(?request, "Nodes requested");
+
+        let selector = Self::selector(&request.into_inner().selector).await?;
+        let nodes = self
+            .node_registry
+            .list()
+            .await
+            .into_iter()
+            .filter(|record| selector.matches(&record.labels))
+            .map(Node::from)
+            .collect();
+
+        let response = Response::new(NodeList { nodes });
+
+        Ok(response)
+    }
+
+    async fn vms(&self, request: Request<ListFilter>) -> Result<Response<MicroVmList>, Status> {
+        debug!(?request, "MicroVMs requested");
+
+        let caller = self.caller(&request).await;
+        let selector = Self::selector(&request.into_inner().selector).await?;
+        let vms = self
+            .vm_registry
+            .select(&selector)
+            .await
+            .into_iter()
+            .filter(|record| Self::visible(&caller, record))
+            .map(MicroVmRecord::from)
+            .collect();
+
+        let response = Response::new(MicroVmList { vms });
+
+        Ok(response)
+    }
+
+    async fn delist(&self, request: Request<NodeSelector>) -> Result<Response<Node>, Status> {
+        let node_id = request.into_inner().node_id;
+
+        match self.node_registry.remove(&node_id).await? {
+            Some(record) => {
+                info!(%node_id, "Node delisted by admin");
+
+                for task in self.task_queues.close(&node_id).await? {
+                    self.event_log
+                        .task_failed(
+                            &task.id,
+                            &node_id,
+                            "Node delisted before acknowledging task",
+                        )
+                        .await;
+                }
+
+                self.event_log
+                    .node_left(&node_id, "Node delisted by admin")
+                    .await;
+
+                Ok(Response::new(Node::from(record)))
+            }
+            None => Err(Self::node_not_found(&node_id).await),
+        }
+    }
+
+    async fn drain(&self, request: Request<NodeDrain>) -> Result<Response<Node>, Status> {
+        let drain = request.into_inner();
+
+        match self
+            .node_registry
+            .drain(&drain.node_id, drain.draining)
+            .await?
+        {
+            Some(record) => {
+                info!(node_id = %drain.node_id, draining = drain.draining, "Node drain updated");
+
+                Ok(Response::new(Node::from(record)))
+            }
+            None => Err(Self::node_not_found(&drain.node_id).await),
+        }
+    }
+
+    async fn audit(&self, request: Request<AuditQuery>) -> Result<Response<AuditList>, Status> {
+        let query = request.into_inner();
+        let vm_id = Some(query.vm_id.as_str()).filter(|vm_id| !vm_id.is_empty());
+        let principal = Some(query.principal.as_str()).filter(|principal| !principal.is_empty());
+        let limit = match query.limit {
+            0 => DEFAULT_AUDIT_LIMIT,
+            limit => limit as usize,
+        };
+
+        let entries = self
+            .audit_log
+            .query(vm_id, principal, limit)
+            .await
+            .into_iter()
+            .map(AuditRecord::from)
+            .collect();
+
+        let response = Response::new(AuditList { entries });
+
+        Ok(response)
+    }
+
+    async fn usage(&self, request: Request<UsageQuery>) -> Result<Response<UsageList>, Status> {
+        let caller = self.caller(&request).await;
+        let query = request.into_inner();
+        let tenant = match query.tenant.is_empty() {
+            true => caller.tenant.to_owned(),
+            false => query.tenant,
+        };
+
+        if (query.all || tenant != caller.tenant) && caller.role < Role::Admin {
+            let message = format!(
+                "{} may only query the usage of tenant {}... admin role required!",
+                caller.name, caller.tenant,
+            );
+
+            return Err(Status::permission_denied(message));
+        }
+
+        let mut usage = self.vm_registry.usage().await;
+        let names = match query.all {
+            true => {
+                let mut names: Vec<String> = usage
+                    .keys()
+                    .filter(|tenant| !tenant.is_empty())
+                    .chain(self.policy.tenants.configured())
+                    .cloned()
+                    .collect();
+
+                names.sort();
+                names.dedup();
+                names
+            }
+            false => vec![tenant],
+        };
+
+        let tenants = names
+            .into_iter()
+            .map(|tenant| {
+                let used = usage.remove(&tenant).unwrap_or_default();
+                let quota = self.policy.tenants.quota(&tenant).to_owned();
+
+                Self::tenant_usage(tenant, used, quota)
+            })
+            .collect();
+
+        Ok(Response::new(UsageList { tenants }))
+    }
+
+    async fn create(&self, request: Request<Profile>) -> Result<Response<Profile>, Status> {
+        let profile = request.into_inner();
+        let record = self
+            .profile_registry
+            .create(
+                &profile.name,
+                &profile.description,
+                profile.machine.unwrap_or_default(),
+            )
+            .await?;
+
+        info!(profile = %record.name, "Profile created");
+
+        Ok(Response::new(Profile::from(record)))
+    }
+
+    async fn update(&self, request: Request<Profile>) -> Result<Response<Profile>, Status> {
+        let profile = request.into_inner();
+        let record = self
+            .profile_registry
+            .update(
+                &profile.name,
+                &profile.description,
+                profile.machine.unwrap_or_default(),
+            )
+            .await?;
+
+        info!(profile = %record.name, "Profile updated");
+
+        Ok(Response::new(Profile::from(record)))
+    }
+
+    async fn delete(&self, request: Request<ProfileSelector>) -> Result<Response<Profile>, Status> {
+        let record = self
+            .profile_registry
+            .remove(&request.into_inner().name)
+            .await?;
+
+        info!(profile = %record.name, "Profile deleted");
+
+        Ok(Response::new(Profile::from(record)))
+    }
+
+    async fn profile(
+        &self,
+        request: Request<ProfileSelector>,
+    ) -> Result<Response<Profile>, Status> {
+        let record = self
+            .profile_registry
+            .get(&request.into_inner().name)
+            .await?;
+
+        Ok(Response::new(Profile::from(record)))
+    }
+
+    async fn profiles(&self, request: Request<Empty>) -> Result<Response<ProfileList>, Status> {
+        debug!(?request, "Profile list requested");
+
+        let profiles = self
+            .profile_registry
+            .list()
+            .await
+            .into_iter()
+            .map(Profile::from)
+            .collect();
+
+        Ok(Response::new(ProfileList { profiles }))
+    }
+
+    async fn group_create(&self, request: Request<Group>) -> Result<Response<Group>, Status> {
+        let caller = self.caller(&request).await;
+        let group = request.into_inner();
+        let template = self.template(group.template, &caller.tenant).await?;
+        let record = self
+            .group_registry
+            .create(
+                &group.name,
+                &caller.tenant,
+                group.replicas,
+                group.max_surge,
+                template,
+            )
+            .await?;
+
+        info!(group = %record.name, replicas = record.replicas, "Group created");
+
+        Ok(Response::new(self.group_status(record).await))
+    }
+
+    async fn group_update(&self, request: Request<Group>) -> Result<Response<Group>, Status> {
+        let caller = self.caller(&request).await;
+        let group = request.into_inner();
+        let owned = self.owned_group(&caller, &group.name).await?;
+        let template = self.template(group.template, &owned.tenant).await?;
+        let record = self
+            .group_registry
+            .update(&group.name, group.replicas, group.max_surge, template)
+            .await?;
+
+        info!(
+            group = %record.name,
+            replicas = record.replicas,
+            generation = record.generation,
+            "Group updated",
+        );
+
+        Ok(Response::new(self.group_status(record).await))
+    }
+
+    async fn group_scale(&self, request: Request<GroupScale>) -> Result<Response<Group>, Status> {
+        let caller = self.caller(&request).await;
+        let scale = request.into_inner();
+
+        self.owned_group(&caller, &scale.name).await?;
+
+        let record = self
+            .group_registry
+            .scale(&scale.name, scale.replicas)
+            .await?;
+
+        info!(group = %record.name, replicas = record.replicas, "Group scaled");
+
+        Ok(Response::new(self.group_status(record).await))
+    }
+
+    async fn group_delete(
+        &self,
+        request: Request<GroupSelector>,
+    ) -> Result<Response<Group>, Status> {
+        let caller = self.caller(&request).await;
+        let name = request.into_inner().name;
+
+        self.owned_group(&caller, &name).await?;
+
+        let record = self.group_registry.remove(&name).await?;
+
+        info!(group = %record.name, "Group deleted... members will be shut down");
+
+        Ok(Response::new(self.group_status(record).await))
+    }
+
+    async fn group(&self, request: Request<GroupSelector>) -> Result<Response<Group>, Status> {
+        let caller = self.caller(&request).await;
+        let record = self
+            .owned_group(&caller, &request.into_inner().name)
+            .await?;
+
+        Ok(Response::new(self.group_status(record).await))
+    }
+
+    async fn groups(&self, request: Request<Empty>) -> Result<Response<GroupList>, Status> {
+        debug!(?request, "Group list requested");
+
+        let caller = self.caller(&request).await;
+        let scope = Self::scope(&caller);
+        let vms = self.vm_registry.list().await;
+        let mut groups = Vec::with_capacity(8);
+
+        for record in self
+            .group_registry
+            .list()
+            .await
+            .into_iter()
+            .filter(|record| scope.is_none_or(|tenant| record.tenant == tenant))
+        {
+            groups.push(Self::group_message(record, &vms).await);
+        }
+
+        Ok(Response::new(GroupList { groups }))
+    }
+
+    async fn template(
+        &self,
+        template: Option<LaunchRequest>,
+        tenant: &str,
+    ) -> Result<GroupTemplate, Status> {
+        let template = template.unwrap_or_default();
+
+        if !template.name.is_empty() || !template.request_id.is_empty() {
+            let message = String::from(
+                "Group templates take no name or request id... members are named after the group!",
+            );
+
+            return Err(Status::invalid_argument(message));
+        }
+
+        let mut claim = self.policy.tenants.claim(tenant);
+
+        self.prepare(template.to_owned(), &mut claim).await?;
+
+        Ok(GroupTemplate::from(template))
+    }
+
+    async fn owned_group(&self, caller: &Principal, name: &str) -> Result<GroupRecord, Status> {
+        let record = self.group_registry.get(name).await?;
+
+        if record.tenant != caller.tenant && caller.role < Role::Admin {
+            let message = format!(
+                "{} may only manage groups of tenant {}... admin role required!",
+                caller.name, caller.tenant,
+            );
+
+            return Err(Status::permission_denied(message));
+        }
+
+        Ok(record)
+    }
+
+    async fn owned_vm(&self, caller: &Principal, uuid: &str) -> Result<(), Status> {
+        match self.vm_registry.get(uuid).await {
+            Some(record) if !Self::visible(caller, &record) => {
+                let message = format!(
+                    "{} may only manage MicroVMs of tenant {}... admin role required!",
+                    caller.name, caller.tenant,
+                );
+
+                Err(Status::permission_denied(message))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn visible(caller: &Principal, record: &VmRecord) -> bool {
+        record.tenant == caller.tenant || caller.role >= Role::Admin
+    }
+
+    async fn observable(caller: &Principal, vm_registry: &VmRegistry, event: &Event) -> bool {
+        match event.vm_id.is_empty() || caller.role >= Role::Admin {
+            true => true,
+            false => vm_registry
+                .get(&event.vm_id)
+                .await
+                .is_some_and(|record| Self::visible(caller, &record)),
+        }
+    }
+
+    fn scope(caller: &Principal) -> Option<&str> {
+        match caller.role >= Role::Admin {
+            true => None,
+            false => Some(caller.tenant.as_str()),
+        }
+    }
+
+    async fn group_status(&self, record: GroupRecord) -> Group {
+        let vms = self.vm_registry.list().await;
+
+        Self::group_message(record, &vms).await
+    }
+
+    async fn group_message(record: GroupRecord, vms: &[VmRecord]) -> Group {
+        let mut status = GroupStatus::default();
+
+        for vm in vms
+            .iter()
+            .filter(|vm| vm.spec.group == record.name && vm.live())
+        {
+            match vm.state {
+                VmState::Running => status.running += 1,
+                _ => status.pending += 1,
+            }
+
+            if vm.spec.generation == record.generation {
+                status.updated += 1;
+            }
+        }
+
+        Group {
+            name: record.name,
+            replicas: record.replicas,
+            max_surge: record.max_surge,
+            template: Some(LaunchRequest::from(record.template)),
+            tenant: record.tenant,
+            generation: record.generation,
+            status: Some(status),
+            created_at: unix_seconds(record.created_at),
+            updated_at: unix_seconds(record.updated_at),
+        }
+    }
+
+    pub async fn reconcile(&self) {
+        let vms = self.vm_registry.list().await;
+        let mut healthy = HashSet::with_capacity(8);
+
+        for node in self.node_registry.list().await {
+            if !node.stale().await {
+                healthy.insert(node.node_id);
+            }
+        }
+
+        let live: HashSet<String> = vms
+            .iter()
+            .filter(|record| record.live())
+            .map(|record| record.uuid.to_owned())
+            .collect();
+        let retiring = self.group_registry.retiring(&live).await;
+        let trace_id = Uuid::new_v4().simple().to_string();
+        let groups = self.group_registry.list().await;
+
+        for group in &groups {
+            let members: Vec<VmRecord> = vms
+                .iter()
+                .filter(|record| record.spec.group == group.name)
+                .cloned()
+                .collect();
+            let plan = groups::plan(group, &members, &healthy, &retiring);
+
+            if plan == groups::Plan::default() {
+                continue;
+            }
+
+            info!(
+                group = %group.name,
+                lost = plan.lost.len(),
+                launch = plan.launch,
+                stop = plan.stop.len(),
+                %trace_id,
+                "Reconciling group",
+            );
+
+            for uuid in &plan.lost {
+                let details = "Node of the MicroVM was lost... replacing it";
+
+                match self.vm_registry.fail(uuid, details).await {
+                    Ok(Some(record)) => {
+                        self.event_log
+                            .vm_state(uuid, &record.node_id, State::Failed, details)
+                            .await;
+                    }
+                    Ok(None) => {}
+                    Err(status) => {
+                        warn!(vm_uuid = %uuid, error = status.message(), "Unable to fail lost member");
+                    }
+                }
+            }
+
+            for _ in 0..plan.launch {
+                if let Err(status) = self.launch_member(group, &trace_id).await {
+                    warn!(group = %group.name, error = status.message(), "Unable to launch member");
+
+                    break;
+                }
+            }
+
+            for uuid in &plan.stop {
+                self.retire(uuid, &trace_id).await;
+            }
+        }
+
+        for record in vms.iter().filter(|record| {
+            !record.spec.group.is_empty()
+                && record.state == VmState::Running
+                && !retiring.contains(&record.uuid)
+                && !groups.iter().any(|group| group.name == record.spec.group)
+        }) {
+            info!(group = %record.spec.group, vm_uuid = %record.uuid, "Retiring orphaned member");
+
+            self.retire(&record.uuid, &trace_id).await;
+        }
+    }
+
+    async fn launch_member(&self, group: &GroupRecord, trace_id: &str) -> Result<(), Status> {
+        let uuid = Uuid::new_v4().simple().to_string();
+        let mut claim = self.policy.tenants.claim(&group.tenant);
+        let mut request = LaunchRequest::from(group.template.to_owned());
+
+        request.name = format!("{}-{}", group.name, &uuid[..8]);
+
+        let mut spec = self.prepare(request, &mut claim).await?;
+
+        spec.group = group.name.to_owned();
+        spec.generation = group.generation;
+
+        let record = match self.vm_registry.pending(&uuid, "", spec, &claim).await? {
+            Reservation::Created(record) | Reservation::Existing(record) => record,
+        };
+        let task = Task {
+            action: 1,
+            id: uuid,
+            trace_id: trace_id.to_string(),
+            machine: Some(record.spec.machine.to_owned()),
+        };
+
+        let node_id = self.dispatch(&record, task).await?;
+
+        info!(group = %group.name, vm_uuid = %record.uuid, %node_id, "Launched member");
+
+        Ok(())
+    }
+
+    async fn retire(&self, uuid: &str, trace_id: &str) {
+        let task = Task {
+            action: 2,
+            id: uuid.to_string(),
+            trace_id: trace_id.to_string(),
+            machine: None,
+        };
+
+        match self.dispatch_shutdown(&task).await {
+            Ok(_) => self.group_registry.retire(uuid).await,
+            Err(status) => {
+                warn!(vm_uuid = %uuid, error = status.message(), "Unable to retire member");
+            }
+        }
+    }
+
+    fn tenant_usage(tenant: String, usage: Usage, quota: Quota) -> TenantUsage {
+        TenantUsage {
+            tenant,
+            vms: usage.vms,
+            vcpus: usage.vcpus,
+            memory_mib: usage.memory_mib,
+            disk_mib: usage.disk_mib,
+            quota: Some(TenantQuota::from(quota)),
+        }
+    }
+
+    async fn watch(
+        &self,
+        request: Request<EventFilter>,
+    ) -> Result<Response<ReceiverStream<Result<Event, Status>>>, Status> {
+        let caller = self.caller(&request).await;
+        let vm_registry = self.vm_registry.to_owned();
+        let filter = request.into_inner();
+        let (backlog, mut receiver) = self.event_log.subscribe(filter.resume_token).await?;
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let span = info_span!(
+            "watch_events",
+            vm_id = %filter.vm_id,
+            node_id = %filter.node_id,
+            resume_token = filter.resume_token,
+        );
+
+        tokio::spawn(
+            async move {
+                let mut last_token = filter.resume_token;
+
+                for event in backlog {
+                    last_token = event.resume_token;
+
+                    if events::matches(&filter, &event).await
+                        && Self::observable(&caller, &vm_registry, &event).await
+                        && tx.send(Ok(event)).await.is_err()
+                    {
+                        return;
+                    }
+                }
+
+                loop {
+                    let event = tokio::select! {
+                        _ = tx.closed() => break,
+                        received = receiver.recv() => match received {
+                            Ok(event) => event,
+                            Err(RecvError::Lagged(skipped)) => {
+                                let message = format!(
+                                    "Watcher fell {} events behind... resume from token {}",
+                                    skipped, last_token,
+                                );
+
+                                tx.send(Err(Status::out_of_range(message))).await.ok();
+
+                                break;
+                            }
+                            Err(RecvError::Closed) => break,
+                        },
+                    };
+
+                    last_token = event.resume_token;
+
+                    if events::matches(&filter, &event).await
+                        && Self::observable(&caller, &vm_registry, &event).await
+                        && tx.send(Ok(event)).await.is_err()
+                    {
+                        break;
+                    }
+                }
+
+                debug!(last_token, "Watcher disconnected");
+            }
+            .instrument(span),
+        );
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+#[tonic::async_trait]
+impl Interface for External {
+    async fn system_status(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<SystemStatusResponse>, Status> {
+        self.audited(request, Operation::SystemStatus, |request| {
+            self.status(request)
+        })
+        .await
+    }
+
+    async fn system_version(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<SystemVersionResponse>, Status> {
+        self.audited(request, Operation::SystemVersion, |request| {
+            self.version(request)
+        })
+        .await
+    }
+
+    async fn launch_vm(
+        &self,
+        request: Request<LaunchRequest>,
+    ) -> Result<Response<MicroVmLaunch>, Status> {
+        self.audited(request, Operation::LaunchVm, |request| self.launch(request))
+            .await
+    }
+
+    type LaunchVMsStream = ReceiverStream<Result<BatchLaunchResult, Status>>;
+
+    async fn launch_v_ms(
+        &self,
+        request: Request<BatchLaunchRequest>,
+    ) -> Result<Response<Self::LaunchVMsStream>, Status> {
+        self.audited(request, Operation::LaunchVms, |request| {
+            self.launch_batch(request)
+        })
+        .await
+    }
+
+    async fn shutdown_vm(
+        &self,
+        request: Request<MicroVm>,
+    ) -> Result<Response<MicroVmShutdown>, Status> {
+        self.audited(request, Operation::ShutdownVm, |request| {
+            self.shutdown(request)
+        })
+        .await
+    }
+
+    async fn shutdown_v_ms(
+        &self,
+        request: Request<VmSelector>,
+    ) -> Result<Response<BulkResult>, Status> {
+        self.audited(request, Operation::ShutdownVms, |request| {
+            self.bulk_shutdown(request)
+        })
+        .await
+    }
+
+    async fn label_v_ms(
+        &self,
+        request: Request<LabelUpdate>,
+    ) -> Result<Response<BulkResult>, Status> {
+        self.audited(request, Operation::LabelVms, |request| {
+            self.relabel(request)
+        })
+        .await
+    }
+
+    async fn list_nodes(&self, request: Request<ListFilter>) -> Result<Response<NodeList>, Status> {
+        self.audited(request, Operation::ListNodes, |request| self.nodes(request))
+            .await
+    }
+
+    async fn list_v_ms(
+        &self,
+        request: Request<ListFilter>,
+    ) -> Result<Response<MicroVmList>, Status> {
+        self.audited(request, Operation::ListVms, |request| self.vms(request))
+            .await
+    }
+
+    async fn delist_node(&self, request: Request<NodeSelector>) -> Result<Response<Node>, Status> {
+        self.audited(request, Operation::DelistNode, |request| {
+            self.delist(request)
+        })
+        .await
+    }
+
+    async fn drain_node(&self, request: Request<NodeDrain>) -> Result<Response<Node>, Status> {
+        self.audited(request, Operation::DrainNode, |request| self.drain(request))
+            .await
+    }
+
+    async fn query_audit(
+        &self,
+        request: Request<AuditQuery>,
+    ) -> Result<Response<AuditList>, Status> {
+        self.audited(request, Operation::QueryAudit, |request| {
+            self.audit(request)
+        })
+        .await
+    }
+
+    async fn query_usage(
+        &self,
+        request: Request<UsageQuery>,
+    ) -> Result<Response<UsageList>, Status> {
+        self.audited(request, Operation::QueryUsage, |request| {
+            self.usage(request)
+        })
+        .await
+    }
+
+    async fn create_profile(&self, request: Request<Profile>) -> Result<Response<Profile>, Status> {
+        self.audited(request, Operation::CreateProfile, |request| {
+            self.create(request)
+        })
+        .await
+    }
+
+    async fn update_profile(&self, request: Request<Profile>) -> Result<Response<Profile>, Status> {
+        self.audited(request, Operation::UpdateProfile, |request| {
+            self.update(request)
+        })
+        .await
+    }
+
+    async fn delete_profile(
+        &self,
+        request: Request<ProfileSelector>,
+    ) -> Result<Response<Profile>, Status> {
+        self.audited(request, Operation::DeleteProfile, |request| {
+            self.delete(request)
+        })
+        .await
+    }
+
+    async fn get_profile(
+        &self,
+        request: Request<ProfileSelector>,
+    ) -> Result<Response<Profile>, Status> {
+        self.audited(request, Operation::GetProfile, |request| {
+            self.profile(request)
+        })
+        .await
+    }
+
+    async fn list_profiles(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<ProfileList>, Status> {
+        self.audited(request, Operation::ListProfiles, |request| {
+            self.profiles(request)
+        })
+        .await
+    }
+
+    async fn create_group(&self, request: Request<Group>) -> Result<Response<Group>, Status> {
+        self.audited(request, Operation::CreateGroup, |request| {
+            self.group_create(request)
+        })
+        .await
+    }
+
+    async fn update_group(&self, request: Request<Group>) -> Result<Response<Group>, Status> {
+        self.audited(request, Operation::UpdateGroup, |request| {
+            self.group_update(request)
+        })
+        .await
+    }
+
+    async fn scale_group(&self, request: Request<GroupScale>) -> Result<Response<Group>, Status> {
+        self.audited(request, Operation::ScaleGroup, |request| {
+            self.group_scale(request)
+        })
+        .await
+    }
+
+    async fn delete_group(
+        &self,
+        request: Request<GroupSelector>,
+    ) -> Result<Response<Group>, Status> {
+        self.audited(request, Operation::DeleteGroup, |request| {
+            self.group_delete(request)
+        })
+        .await
+    }
+
+    async fn get_group(&self, request: Request<GroupSelector>) -> Result<Response<Group>, Status> {
+        self.audited(request, Operation::GetGroup, |request| self.group(request))
+            .await
+    }
+
+    async fn list_groups(&self, request: Request<Empty>) -> Result<Response<GroupList>, Status> {
+        self.audited(request, Operation::ListGroups, |request| {
+            self.groups(request)
+        })
+        .await
+    }
+
+    type WatchEventsStream = ReceiverStream<Result<Event, Status>>;
+
+    async fn watch_events(
+        &self,
+        request: Request<EventFilter>,
+    ) -> Result<Response<Self::WatchEventsStream>, Status> {
+        self.audited(request, Operation::WatchEvents, |request| {
+            self.watch(request)
+        })
+        .await
+    }
+}
+
+impl Audited for Empty {}
+
+impl Audited for ReceiverStream<Result<Event, Status>> {}
+
+impl Audited for EventFilter {
+    fn arguments(&self) -> Value {
+        json!({
+            "vm_id": self.vm_id,
+            "node_id": self.node_id,
+            "kinds": self.kinds,
+            "resume_token": self.resume_token,
+        })
+    }
+
+    fn vm_id(&self) -> Option<String> {
+        Some(self.vm_id.to_owned()).filter(|vm_id| !vm_id.is_empty())
+    }
+
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned()).filter(|node_id| !node_id.is_empty())
+    }
+}
+
+impl Audited for SystemStatusResponse {}
+
+impl Audited for SystemVersionResponse {}
+
+impl Audited for NodeList {}
+
+impl Audited for MicroVmList {}
+
+impl Audited for AuditList {}
+
+impl Audited for MicroVm {
+    fn arguments(&self) -> Value {
+        json!({ "name": self.name })
+    }
+
+    fn vm_id(&self) -> Option<String> {
+        Some(self.name.to_owned()).filter(|name| !name.is_empty())
+    }
+}
+
+impl Audited for LaunchRequest {
+    fn arguments(&self) -> Value {
+        json!({
+            "request_id": self.request_id,
+            "name": self.name,
+            "labels": self.labels,
+            "profile": self.profile,
+        })
+    }
+}
+
+impl Audited for BatchLaunchRequest {
+    fn arguments(&self) -> Value {
+        let template = self.template.to_owned().unwrap_or_default();
+
+        json!({
+            "count": self.count,
+            "launches": self.launches.len(),
+            "atomic": self.atomic,
+            "name": template.name,
+            "request_id": template.request_id,
+            "profile": template.profile,
+        })
+    }
+}
+
+impl Audited for ReceiverStream<Result<BatchLaunchResult, Status>> {}
+
+impl Audited for ListFilter {
+    fn arguments(&self) -> Value {
+        json!({ "selector": self.selector })
+    }
+}
+
+impl Audited for VmSelector {
+    fn arguments(&self) -> Value {
+        json!({ "selector": self.selector })
+    }
+}
+
+impl Audited for LabelUpdate {
+    fn arguments(&self) -> Value {
+        json!({
+            "selector": self.selector,
+            "labels": self.labels,
+            "remove": self.remove,
+        })
+    }
+}
+
+impl Audited for BulkResult {}
+
+impl Audited for MicroVmLaunch {
+    fn vm_id(&self) -> Option<String> {
+        Some(self.uuid.to_owned()).filter(|uuid| !uuid.is_empty())
+    }
+
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned()).filter(|node_id| !node_id.is_empty())
+    }
+}
+
+impl Audited for MicroVmShutdown {
+    fn vm_id(&self) -> Option<String> {
+        Some(self.uuid.to_owned()).filter(|uuid| !uuid.is_empty())
+    }
+
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned()).filter(|node_id| !node_id.is_empty())
+    }
+}
+
+impl Audited for Node {
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned())
+    }
+}
+
+impl Audited for NodeSelector {
+    fn arguments(&self) -> Value {
+        json!({ "node_id": self.node_id })
+    }
+
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned())
+    }
+}
+
+impl Audited for NodeDrain {
+    fn arguments(&self) -> Value {
+        json!({ "node_id": self.node_id, "draining": self.draining })
+    }
+
+    fn node_id(&self) -> Option<String> {
+        Some(self.node_id.to_owned())
+    }
+}
+
+impl Audited for AuditQuery {
+    fn arguments(&self) -> Value {
+        json!({
+            "vm_id": self.vm_id,
+            "principal": self.principal,
+            "limit": self.limit,
+        })
+    }
+}
+
+impl Audited for UsageQuery {
+    fn arguments(&self) -> Value {
+        json!({ "tenant": self.tenant, "all": self.all })
+    }
+}
+
+impl Audited for UsageList {}
+
+impl Audited for Profile {
+    fn arguments(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "machine": self.machine,
+        })
+    }
+}
+
+impl Audited for ProfileSelector {
+    fn arguments(&self) -> Value {
+        json!({ "name": self.name })
+    }
+}
+
+impl Audited for ProfileList {}
+
+impl Audited for Group {
+    fn arguments(&self) -> Value {
+        json!({
+            "name": self.name,
+            "replicas": self.replicas,
+            "max_surge": self.max_surge,
+            "template": self.template.as_ref().map(Audited::arguments),
+        })
+    }
+}
+
+impl Audited for GroupScale {
+    fn arguments(&self) -> Value {
+        json!({ "name": self.name, "replicas": self.replicas })
+    }
+}
+
+impl Audited for GroupSelector {
+    fn arguments(&self) -> Value {
+        json!({ "name": self.name })
+    }
+}
+
+impl Audited for GroupList {}
+
+impl From<AuditEntry> for AuditRecord {
+    fn from(entry: AuditEntry) -> AuditRecord {
+        AuditRecord {
+            timestamp: entry.timestamp,
+            principal: entry.principal,
+            role: entry.role.to_string(),
+            operation: entry.operation.to_string(),
+            arguments: entry.arguments.to_string(),
+            vm_id: entry.vm_id.unwrap_or_default(),
+            node_id: entry.node_id.unwrap_or_default(),
+            allowed: entry.allowed,
+            result: entry.result,
+            error: entry.error.unwrap_or_default(),
+            latency_ms: entry.latency_ms,
+        }
+    }
+}
+
+impl From<Placement> for PlacementSpec {
+    fn from(placement: Placement) -> PlacementSpec {
+        PlacementSpec {
+            node_selector: placement.node_selector,
+            preferences: placement
+                .preferences
+                .into_iter()
+                .map(|preference| (preference.selector, preference.weight.max(1)))
+                .collect(),
+            affinity: placement.affinity,
+            anti_affinity: placement.anti_affinity,
+        }
+    }
+}
+
+impl From<PlacementSpec> for Placement {
+    fn from(spec: PlacementSpec) -> Placement {
+        Placement {
+            node_selector: spec.node_selector,
+            preferences: spec
+                .preferences
+                .into_iter()
+                .map(|(selector, weight)| Preference { selector, weight })
+                .collect(),
+            affinity: spec.affinity,
+            anti_affinity: spec.anti_affinity,
+        }
+    }
+}
+
+impl From<LaunchRequest> for GroupTemplate {
+    fn from(request: LaunchRequest) -> GroupTemplate {
+        GroupTemplate {
+            labels: request.labels,
+            annotations: request.annotations,
+            placement: request
+                .placement
+                .map(PlacementSpec::from)
+                .unwrap_or_default(),
+            profile: request.profile,
+            overrides: request.overrides.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<GroupTemplate> for LaunchRequest {
+    fn from(template: GroupTemplate) -> LaunchRequest {
+        LaunchRequest {
+            labels: template.labels,
+            annotations: template.annotations,
+            placement: Some(Placement::from(template.placement)),
+            profile: template.profile,
+            overrides: Some(template.overrides),
+            ..LaunchRequest::default()
+        }
+    }
+}
+
+impl From<Quota> for TenantQuota {
+    fn from(quota: Quota) -> TenantQuota {
+        TenantQuota {
+            vms: quota.vms,
+            vcpus: quota.vcpus,
+            memory_mib: quota.memory_mib,
+            disk_mib: quota.disk_mib,
+        }
+    }
+}
+
+impl From<ProfileRecord> for Profile {
+    fn from(record: ProfileRecord) -> Profile {
+        Profile {
+            name: record.name,
+            description: record.description,
+            machine: Some(record.machine),
+            configured: record.configured,
+            created_at: unix_seconds(record.created_at),
+            updated_at: unix_seconds(record.updated_at),
+        }
+    }
+}
+
+impl From<NodeRecord> for Node {
+    fn from(record: NodeRecord) -> Node {
+        Node {
+            node_id: record.node_id,
+            inventory: Some(record.inventory),
+            session_id: record.session_id.to_string(),
+            registered_at: unix_seconds(record.registered_at),
+            draining: record.draining,
+            labels: record.labels,
+        }
+    }
+}
+
+impl From<VmState> for State {
+    fn from(state: VmState) -> State {
+        match state {
+            VmState::Pending => State::Pending,
+            VmState::Running => State::Running,
+            VmState::Failed => State::Failed,
+            VmState::Shutdown => State::Shutdown,
+        }
+    }
+}
+
+impl From<VmRecord> for MicroVmRecord {
+    fn from(record: VmRecord) -> MicroVmRecord {
+        MicroVmRecord {
+            uuid: record.uuid,
+            node_id: record.node_id,
+            state: State::from(record.state) as i32,
+            details: record.details,
+            created_at: unix_seconds(record.created_at),
+            updated_at: unix_seconds(record.updated_at),
+            name: record.spec.name,
+            request_id: record.request_id,
+            labels: record.spec.labels,
+            annotations: record.spec.annotations,
+            tenant: record.tenant,
+            profile: record.spec.profile,
+            machine: Some(record.spec.machine),
+            group: record.spec.group,
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio_stream::StreamExt;
+
+    use crate::config::{
+        AuditConfig, EventsConfig, PolicyConfig, PrincipalConfig, ProfileConfig, TaskQueueConfig,
+        TenantsConfig,
+    };
+    use crate::impulse::external::v010::event::Kind;
+    use crate::impulse::shared::v010::{Drive, MachineSpec};
+
+    const TEST_AUDIT_CONFIG: AuditConfig = AuditConfig {
+        path: None,
+        capacity: 10,
+        max_bytes: 0,
+        max_files: 0,
+    };
+    use crate::impulse::shared::v010::NodeInventory;
+
+    fn test_policy_config() -> PolicyConfig {
+        PolicyConfig {
+            anonymous_role: Role::Admin,
+            ..PolicyConfig::default()
+        }
+    }
+
+    async fn test_external() -> Result<External, Box<dyn std::error::Error>> {
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(1);
+        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
+        let test_external = External::init(
+            Uuid::new_v4(),
+            Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?),
+            test_response_sender,
+            test_shutdown_result_sender,
+            Arc::new(NodeRegistry::init().await?),
+            Arc::new(VmRegistry::init().await?),
+            Arc::new(ProfileRegistry::init(&HashMap::new()).await?),
+            Arc::new(GroupRegistry::init().await?),
+            Arc::new(Policy::init(&test_policy_config()).await?),
+            Arc::new(AuditLog::init(&TEST_AUDIT_CONFIG).await?),
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        Ok(test_external)
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn init() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_response_sender, _) = tokio::sync::broadcast::channel(1);
         let test_response_sender_clone = test_response_sender.clone();
         let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
         let test_shutdown_result_sender_clone = test_shutdown_result_sender.clone();
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_policy = Arc::new(Policy::init(&test_policy_config()).await?);
+        let test_audit_log = Arc::new(AuditLog::init(&TEST_AUDIT_CONFIG).await?);
+        let test_system_id = Uuid::new_v4();
         let test_external = External::init(
-            test_tx,
+            test_system_id,
+            test_task_queues,
             test_response_sender_clone,
             test_shutdown_result_sender_clone,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(ProfileRegistry::init(&HashMap::new()).await?),
+            Arc::new(GroupRegistry::init().await?),
+            test_policy,
+            test_audit_log,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
         )
         .await?;
-        assert_eq!(test_external.status.as_str(), "Running!");
+        assert_eq!(test_external.system_id, test_system_id);
+        assert_eq!(test_external.in_flight.load(Ordering::Relaxed), 0);
         assert_eq!(test_external.version.as_str(), "v0.1.0");
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn system_status() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _rx) = tokio::sync::broadcast::channel(1);
-        let (test_response_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_response_sender_clone = test_response_sender.clone();
-        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_shutdown_result_sender_clone = test_shutdown_result_sender.clone();
-        let test_external = External::init(
-            test_tx,
-            test_response_sender_clone,
-            test_shutdown_result_sender_clone,
-        )
-        .await?;
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_system_id = Uuid::new_v4();
+        let test_external = External {
+            system_id: test_system_id,
+            task_queues: test_task_queues.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
         let test_request = Request::new(Empty {});
         let test_external_system_status = test_external.system_status(test_request).await?;
         assert_eq!(
             test_external_system_status.get_ref().status.as_str(),
-            "Running!",
+            "not_ready",
         );
+
+        let test_inventory = NodeInventory {
+            cpu_count: 4,
+            memory_total: 1024,
+            memory_available: 256,
+            disk_total: 4096,
+            disk_free: 1024,
+            ..NodeInventory::default()
+        };
+        test_node_registry
+            .register("test_node_a", test_inventory.to_owned(), HashMap::new())
+            .await?;
+        test_node_registry
+            .register("test_node_b", test_inventory, HashMap::new())
+            .await?;
+        test_node_registry.drain("test_node_b", true).await?;
+        test_vm_registry
+            .pending(
+                "test_uuid_pending",
+                "",
+                VmSpec::default(),
+                &Claim::default(),
+            )
+            .await?;
+        test_vm_registry
+            .pending(
+                "test_uuid_running",
+                "",
+                VmSpec::default(),
+                &Claim::default(),
+            )
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_uuid_running"),
+                launched: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_node_a"),
+            })
+            .await?;
+        test_task_queues.open("test_node_a").await?;
+        test_task_queues.connect("test_node_a").await;
+        test_task_queues
+            .enqueue("test_node_a", Task::default())
+            .await?;
+        let test_request = Request::new(Empty {});
+        let test_status = test_external
+            .system_status(test_request)
+            .await?
+            .into_inner();
+        assert_eq!(test_status.status.as_str(), "ready");
+        assert_eq!(test_status.system_id, test_system_id.to_string());
+        assert_eq!(test_status.version.as_str(), "v0.1.0");
+        assert_eq!(test_status.queue_depth, 1);
+        assert_eq!(test_status.in_flight_tasks, 0);
+        let test_nodes = test_status.nodes.unwrap_or_default();
+        assert_eq!(test_nodes.registered, 2);
+        assert_eq!(test_nodes.healthy, 1);
+        assert_eq!(test_nodes.draining, 1);
+        assert_eq!(test_nodes.stale, 0);
+        let test_vms = test_status.vms.unwrap_or_default();
+        assert_eq!(test_vms.total, 2);
+        assert_eq!(test_vms.pending, 1);
+        assert_eq!(test_vms.running, 1);
+        let test_capacity = test_status.capacity.unwrap_or_default();
+        assert_eq!(test_capacity.cpu_count, 8);
+        assert_eq!(test_capacity.memory_used, 1536);
+        assert_eq!(test_capacity.memory_free, 512);
+        assert_eq!(test_capacity.disk_used, 6144);
+        assert_eq!(test_capacity.disk_free, 2048);
+        test_task_queues.close("test_node_a").await?;
+        let test_request = Request::new(Empty {});
+        let test_status = test_external
+            .system_status(test_request)
+            .await?
+            .into_inner();
+        assert_eq!(test_status.queue_depth, 0);
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn system_version() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _rx) = tokio::sync::broadcast::channel(1);
-        let (test_response_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_response_sender_clone = test_response_sender.clone();
-        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_shutdown_result_sender_clone = test_shutdown_result_sender.clone();
-        let test_external = External::init(
-            test_tx,
-            test_response_sender_clone,
-            test_shutdown_result_sender_clone,
-        )
-        .await?;
+        let test_external = test_external().await?;
         let test_request = Request::new(Empty {});
         let test_external_system_version = test_external.system_version(test_request).await?;
         assert_eq!(
@@ -192,61 +2816,247 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn launch_vm_response() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig { capacity: 1 }).await?);
         let (test_response_sender, _test_response_rx) = tokio::sync::broadcast::channel(1);
         let test_response_sender_clone = test_response_sender.clone();
         drop(_test_response_rx);
-        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_shutdown_result_sender_clone = test_shutdown_result_sender.clone();
-        let test_external = External::init(
-            test_tx,
-            test_response_sender_clone,
-            test_shutdown_result_sender_clone,
-        )
-        .await?;
-        let test_request = Request::new(Empty {});
-        let test_result = tokio::spawn(async move {
-            let test_external_launch_vm = test_external.launch_vm(test_request).await.unwrap();
-            assert_eq!(test_external_launch_vm.get_ref().uuid.as_str(), "test_uuid");
-            assert_eq!(test_external_launch_vm.get_ref().launched.as_str(), "true");
-            assert_eq!(
-                test_external_launch_vm.get_ref().details.as_str(),
-                "success!"
-            );
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = Arc::new(External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender_clone,
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
         });
-        if test_response_sender.receiver_count() == 0 {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-        }
+        let test_request = Request::new(LaunchRequest::default());
+        let test_unavailable = test_external.launch_vm(test_request).await.unwrap_err();
+        assert_eq!(test_unavailable.code(), tonic::Code::Unavailable);
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let test_launch = tokio::spawn({
+            let test_external = test_external.to_owned();
+            async move {
+                test_external
+                    .launch_vm(Request::new(LaunchRequest::default()))
+                    .await
+            }
+        });
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        assert_eq!(test_task.action, 1);
+        assert_eq!(test_vm_registry.list().await.len(), 1);
+        let test_request = Request::new(LaunchRequest::default());
+        let test_full = test_external.launch_vm(test_request).await.unwrap_err();
+        assert_eq!(test_full.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(test_vm_registry.list().await.len(), 1);
+        let test_other_instance = MicroVmLaunch {
+            uuid: String::from("test_other_uuid"),
+            launched: false.to_string(),
+            details: String::from("failed!"),
+            node_id: String::from("test_node"),
+        };
+        test_response_sender
+            .send(test_other_instance)
+            .expect("could not send!");
         let test_instance_start = MicroVmLaunch {
-            uuid: String::from("test_uuid"),
+            uuid: test_task.id.to_owned(),
             launched: true.to_string(),
             details: String::from("success!"),
+            node_id: String::from("test_node"),
         };
         test_response_sender
             .send(test_instance_start)
             .expect("could not send!");
-        assert!(test_result.await.is_ok());
+        let test_external_launch_vm = test_launch.await??;
+        assert_eq!(test_external_launch_vm.get_ref().uuid, test_task.id);
+        assert_eq!(test_external_launch_vm.get_ref().launched.as_str(), "true");
+        assert_eq!(
+            test_external_launch_vm.get_ref().details.as_str(),
+            "success!"
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn await_launch_lagged_and_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let mut test_external = External {
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        test_external.result_timeout = Duration::from_millis(200);
+        let test_launched = MicroVmLaunch {
+            uuid: String::from("test_uuid"),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        };
+        let test_receiver = test_response_sender.subscribe();
+        for test_index in 0..6 {
+            test_response_sender.send(MicroVmLaunch {
+                uuid: format!("test_other_uuid_{}", test_index),
+                ..test_launched.to_owned()
+            })?;
+        }
+        test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_vm_registry.launched(&test_launched).await?;
+        let test_response = test_external
+            .await_launch(test_receiver, "test_uuid", "test_trace_id", Instant::now())
+            .await?;
+        assert_eq!(test_response.get_ref().launched.as_str(), "true");
+        assert_eq!(test_response.get_ref().details.as_str(), "success!");
+        let test_receiver = test_response_sender.subscribe();
+        let test_timeout = test_external
+            .await_launch(
+                test_receiver,
+                "test_missing_uuid",
+                "test_trace_id",
+                Instant::now(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(test_timeout.code(), tonic::Code::DeadlineExceeded);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_vm_idempotent() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = Arc::new(External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        });
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let test_invalid = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                labels: HashMap::from([(String::from("env"), String::from("not valid"))]),
+                ..LaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        let test_request = LaunchRequest {
+            request_id: String::from("test_request"),
+            name: String::from("test_name"),
+            ..LaunchRequest::default()
+        };
+        let test_launch = tokio::spawn({
+            let test_external = test_external.to_owned();
+            let test_request = test_request.to_owned();
+            async move { test_external.launch_vm(Request::new(test_request)).await }
+        });
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        let test_retry = tokio::spawn({
+            let test_external = test_external.to_owned();
+            let test_request = test_request.to_owned();
+            async move { test_external.launch_vm(Request::new(test_request)).await }
+        });
+        let test_conflict = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                name: String::from("test_other_name"),
+                ..test_request.to_owned()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_conflict.code(), tonic::Code::AlreadyExists);
+        let test_taken = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                request_id: String::new(),
+                ..test_request.to_owned()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_taken.code(), tonic::Code::AlreadyExists);
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        test_response_sender.send(MicroVmLaunch {
+            uuid: test_task.id.to_owned(),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        })?;
+        let test_launched = test_launch.await??;
+        let test_retried = test_retry.await??;
+        assert_eq!(test_launched.get_ref().uuid, test_task.id);
+        assert_eq!(test_retried.get_ref().uuid, test_task.id);
+        test_vm_registry.launched(test_launched.get_ref()).await?;
+        let test_repeat = test_external.launch_vm(Request::new(test_request)).await?;
+        assert_eq!(test_repeat.get_ref().uuid, test_task.id);
+        assert_eq!(test_repeat.get_ref().launched.as_str(), "true");
+        assert_eq!(test_vm_registry.list().await.len(), 1);
+        assert_eq!(test_task_queues.depth().await, 1);
+        let test_list = test_external
+            .list_v_ms(Request::new(ListFilter::default()))
+            .await?;
+        assert_eq!(test_list.get_ref().vms[0].name.as_str(), "test_name");
+        assert_eq!(
+            test_list.get_ref().vms[0].request_id.as_str(),
+            "test_request"
+        );
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn shutdown_vm() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _rx) = tokio::sync::broadcast::channel(1);
-        let (test_response_sender, _) = tokio::sync::broadcast::channel(1);
-        let test_response_sender_clone = test_response_sender.clone();
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(1);
         let test_shutdown_result_sender_clone = test_shutdown_result_sender.clone();
-        let test_external = External::init(
-            test_tx,
-            test_response_sender_clone,
-            test_shutdown_result_sender_clone,
-        )
-        .await?;
-        let test_request = Request::new(MicroVm {
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = External {
+            task_queues: test_task_queues.to_owned(),
+            shutdown_result_sender_clone: test_shutdown_result_sender_clone,
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        for test_node in ["test_node_a", "test_node_b"] {
+            test_task_queues.open(test_node).await?;
+            test_task_queues.connect(test_node).await;
+        }
+        test_vm_registry
+            .pending("tester", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("tester"),
+                launched: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_node_b"),
+            })
+            .await?;
+        let mut test_request = Request::new(MicroVm {
             name: String::from("tester"),
         });
+        telemetry::inject(test_request.metadata_mut(), "test_trace_id").await;
         let test_result = tokio::spawn(async move {
             let test_external_shutdown_vm = test_external.shutdown_vm(test_request).await.unwrap();
+            assert_eq!(
+                telemetry::trace_id(test_external_shutdown_vm.metadata())
+                    .await
+                    .as_str(),
+                "test_trace_id",
+            );
             assert_eq!(
                 test_external_shutdown_vm.get_ref().shutdown.as_str(),
                 "true",
@@ -260,14 +3070,1479 @@ mod tests {
             tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         }
         let test_instance_shutdown = MicroVmShutdown {
-            uuid: "test_uuid".to_string(),
+            uuid: "tester".to_string(),
             shutdown: true.to_string(),
             details: String::from("test_uuid"),
+            node_id: String::from("test_node_b"),
         };
         test_shutdown_result_sender
             .send(test_instance_shutdown)
             .expect("could not send!");
         assert!(test_result.await.is_ok());
+        assert_eq!(
+            test_task_queues.connected().await,
+            vec![
+                (String::from("test_node_a"), 0),
+                (String::from("test_node_b"), 1),
+            ],
+        );
+        let test_task = test_task_queues.next("test_node_b", 1).await.unwrap();
+        assert_eq!(test_task.id.as_str(), "tester");
+        assert_eq!(test_task.trace_id.as_str(), "test_trace_id");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dispatch_shutdown() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = External {
+            task_queues: test_task_queues.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        for test_node in ["test_node_a", "test_node_b"] {
+            test_task_queues.open(test_node).await?;
+        }
+        test_task_queues.connect("test_node_a").await;
+        for test_uuid in ["test_uuid_held", "test_uuid_unplaced"] {
+            test_vm_registry
+                .pending(test_uuid, "", VmSpec::default(), &Claim::default())
+                .await?;
+        }
+        test_vm_registry
+            .assign("test_uuid_held", "test_node_b")
+            .await?;
+        let test_task = |test_uuid: &str| Task {
+            action: 2,
+            id: test_uuid.to_string(),
+            trace_id: String::from("test_trace_id"),
+            machine: None,
+        };
+        assert_eq!(
+            test_external
+                .dispatch_shutdown(&test_task("test_uuid_held"))
+                .await?,
+            1,
+        );
+        assert_eq!(
+            test_task_queues.outstanding("test_node_b").await,
+            vec![String::from("test_uuid_held")],
+        );
+        assert!(test_task_queues.outstanding("test_node_a").await.is_empty());
+        assert_eq!(
+            test_external
+                .dispatch_shutdown(&test_task("test_uuid_unplaced"))
+                .await?,
+            1,
+        );
+        assert_eq!(
+            test_task_queues.outstanding("test_node_a").await,
+            vec![String::from("test_uuid_unplaced")],
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn await_shutdown_lagged_and_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let mut test_external = External {
+            shutdown_result_sender_clone: test_shutdown_result_sender.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        test_external.result_timeout = Duration::from_millis(200);
+        let test_shutdown = MicroVmShutdown {
+            uuid: String::from("test_uuid"),
+            shutdown: true.to_string(),
+            details: String::from("stopped"),
+            node_id: String::from("test_node"),
+        };
+        test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_uuid"),
+                launched: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        let test_receiver = test_shutdown_result_sender.subscribe();
+        for test_index in 0..6 {
+            test_shutdown_result_sender.send(MicroVmShutdown {
+                uuid: format!("test_other_uuid_{}", test_index),
+                ..test_shutdown.to_owned()
+            })?;
+        }
+        let test_timeout = test_external
+            .await_shutdown(test_receiver, "test_uuid", "test_trace_id", Instant::now())
+            .await
+            .unwrap_err();
+        assert_eq!(test_timeout.code(), tonic::Code::DeadlineExceeded);
+        let test_receiver = test_shutdown_result_sender.subscribe();
+        for test_index in 0..6 {
+            test_shutdown_result_sender.send(MicroVmShutdown {
+                uuid: format!("test_other_uuid_{}", test_index),
+                ..test_shutdown.to_owned()
+            })?;
+        }
+        assert!(test_vm_registry.shutdown(&test_shutdown).await?.is_some());
+        let test_response = test_external
+            .await_shutdown(test_receiver, "test_uuid", "test_trace_id", Instant::now())
+            .await?;
+        assert_eq!(test_response.get_ref().shutdown.as_str(), "true");
+        assert_eq!(test_response.get_ref().details.as_str(), "stopped");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_nodes() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_inventory = NodeInventory {
+            cpu_count: 8,
+            firecracker_version: String::from("Firecracker v1.4.1"),
+            ..NodeInventory::default()
+        };
+        test_node_registry
+            .register(
+                "test_node",
+                test_inventory,
+                HashMap::from([(String::from("zone"), String::from("a"))]),
+            )
+            .await?;
+        let test_external = External {
+            node_registry: test_node_registry,
+            ..test_external().await?
+        };
+        let test_request = Request::new(ListFilter::default());
+        let test_external_list_nodes = test_external.list_nodes(test_request).await?;
+        let test_nodes = &test_external_list_nodes.get_ref().nodes;
+        assert_eq!(test_nodes.len(), 1);
+        assert_eq!(test_nodes[0].node_id.as_str(), "test_node");
+        assert!(test_nodes[0].registered_at > 0);
+        let test_node_inventory = test_nodes[0].inventory.as_ref().unwrap();
+        assert_eq!(test_node_inventory.cpu_count, 8);
+        assert_eq!(
+            test_node_inventory.firecracker_version.as_str(),
+            "Firecracker v1.4.1",
+        );
+        assert_eq!(test_nodes[0].labels["zone"], "a");
+        let test_request = Request::new(ListFilter {
+            selector: String::from("zone in (b,c)"),
+        });
+        let test_external_list_nodes = test_external.list_nodes(test_request).await?;
+        assert!(test_external_list_nodes.get_ref().nodes.is_empty());
+        let test_request = Request::new(ListFilter {
+            selector: String::from("zone in (a"),
+        });
+        let test_invalid = test_external.list_nodes(test_request).await.unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_vms() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_spec = VmSpec {
+            labels: HashMap::from([(String::from("env"), String::from("prod"))]),
+            annotations: HashMap::from([(String::from("note"), String::from("test note"))]),
+            ..VmSpec::default()
+        };
+        test_vm_registry
+            .pending("test_uuid", "", test_spec, &Claim::default())
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_uuid"),
+                launched: true.to_string(),
+                details: String::from("success!"),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        let test_external = External {
+            vm_registry: test_vm_registry,
+            ..test_external().await?
+        };
+        let test_request = Request::new(ListFilter::default());
+        let test_external_list_vms = test_external.list_v_ms(test_request).await?;
+        let test_vms = &test_external_list_vms.get_ref().vms;
+        assert_eq!(test_vms.len(), 1);
+        assert_eq!(test_vms[0].uuid.as_str(), "test_uuid");
+        assert_eq!(test_vms[0].node_id.as_str(), "test_node");
+        assert_eq!(test_vms[0].state(), State::Running);
+        assert!(test_vms[0].created_at > 0);
+        assert_eq!(test_vms[0].labels["env"], "prod");
+        assert_eq!(test_vms[0].annotations["note"], "test note");
+        let test_request = Request::new(ListFilter {
+            selector: String::from("env!=prod"),
+        });
+        let test_external_list_vms = test_external.list_v_ms(test_request).await?;
+        assert!(test_external_list_vms.get_ref().vms.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn schedule_placement() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        for (test_node, test_zone) in [("test_node_a", "a"), ("test_node_b", "b")] {
+            test_node_registry
+                .register(
+                    test_node,
+                    NodeInventory::default(),
+                    HashMap::from([(String::from("zone"), String::from(test_zone))]),
+                )
+                .await?;
+            test_task_queues.open(test_node).await?;
+            test_task_queues.connect(test_node).await;
+        }
+        let test_external = External {
+            task_queues: test_task_queues,
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        let test_spread = VmSpec {
+            labels: HashMap::from([(String::from("app"), String::from("web"))]),
+            placement: PlacementSpec {
+                anti_affinity: vec![String::from("app=web")],
+                ..PlacementSpec::default()
+            },
+            ..VmSpec::default()
+        };
+        let mut test_placed = Vec::new();
+        for test_uuid in ["test_uuid_a", "test_uuid_b"] {
+            test_vm_registry
+                .pending(test_uuid, "", test_spread.to_owned(), &Claim::default())
+                .await?;
+            let test_record = test_vm_registry.get(test_uuid).await.unwrap();
+            let test_node = test_external.schedule(&test_record).await?;
+            test_vm_registry.assign(test_uuid, &test_node).await?;
+            test_placed.push(test_node);
+        }
+        assert_eq!(test_placed, ["test_node_a", "test_node_b"]);
+        test_vm_registry
+            .pending("test_uuid_c", "", test_spread, &Claim::default())
+            .await?;
+        let test_record = test_vm_registry.get("test_uuid_c").await.unwrap();
+        let test_blocked = test_external.schedule(&test_record).await.unwrap_err();
+        assert_eq!(test_blocked.code(), tonic::Code::FailedPrecondition);
+        assert!(test_blocked
+            .message()
+            .contains("test_node_a: anti-affinity app=web matched MicroVM test_uuid_a"));
+        assert!(test_blocked
+            .message()
+            .contains("test_node_b: anti-affinity app=web matched MicroVM test_uuid_b"));
+        let test_pinned = VmSpec {
+            placement: PlacementSpec {
+                node_selector: String::from("zone=b"),
+                affinity: vec![String::from("app=web")],
+                ..PlacementSpec::default()
+            },
+            ..VmSpec::default()
+        };
+        test_vm_registry
+            .pending("test_uuid_d", "", test_pinned, &Claim::default())
+            .await?;
+        let test_record = test_vm_registry.get("test_uuid_d").await.unwrap();
+        assert_eq!(
+            test_external.schedule(&test_record).await?.as_str(),
+            "test_node_b"
+        );
+        let test_invalid = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                placement: Some(Placement {
+                    node_selector: String::from("zone in (a"),
+                    ..Placement::default()
+                }),
+                ..LaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        for test_node in ["test_node_a", "test_node_b"] {
+            test_node_registry.drain(test_node, true).await?;
+        }
+        let test_unavailable = test_external.schedule(&test_record).await.unwrap_err();
+        assert_eq!(test_unavailable.code(), tonic::Code::Unavailable);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn label_and_shutdown_vms() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        test_task_queues.open("test_node").await?;
+        test_task_queues.connect("test_node").await;
+        for (test_uuid, test_env) in [
+            ("test_uuid_a", "prod"),
+            ("test_uuid_b", "prod"),
+            ("test_uuid_c", "dev"),
+        ] {
+            let test_spec = VmSpec {
+                labels: HashMap::from([(String::from("env"), String::from(test_env))]),
+                ..VmSpec::default()
+            };
+            test_vm_registry
+                .pending(test_uuid, "", test_spec, &Claim::default())
+                .await?;
+            test_vm_registry
+                .launched(&MicroVmLaunch {
+                    uuid: String::from(test_uuid),
+                    launched: true.to_string(),
+                    details: String::new(),
+                    node_id: String::from("test_node"),
+                })
+                .await?;
+        }
+        let test_external = Arc::new(External {
+            task_queues: test_task_queues.to_owned(),
+            shutdown_result_sender_clone: test_shutdown_result_sender.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        });
+        let test_request = Request::new(LabelUpdate {
+            selector: String::new(),
+            labels: HashMap::from([(String::from("owner"), String::from("ops"))]),
+            remove: Vec::new(),
+        });
+        let test_unscoped = test_external.label_v_ms(test_request).await.unwrap_err();
+        assert_eq!(test_unscoped.code(), tonic::Code::InvalidArgument);
+        let test_request = Request::new(LabelUpdate {
+            selector: String::from("env=prod"),
+            labels: HashMap::from([(String::from("owner"), String::from("ops"))]),
+            remove: Vec::new(),
+        });
+        let test_labeled = test_external.label_v_ms(test_request).await?.into_inner();
+        assert_eq!(test_labeled.results.len(), 2);
+        assert!(test_labeled
+            .results
+            .iter()
+            .all(|test_result| test_result.ok));
+        let test_request = Request::new(VmSelector {
+            selector: String::from("owner=ops"),
+        });
+        let test_shutdown_external = test_external.to_owned();
+        let test_result = tokio::spawn(async move {
+            test_shutdown_external
+                .shutdown_v_ms(test_request)
+                .await
+                .unwrap()
+                .into_inner()
+        });
+        while test_shutdown_result_sender.receiver_count() == 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+        for (test_uuid, test_shutdown) in [("test_uuid_a", true), ("test_uuid_b", false)] {
+            test_shutdown_result_sender
+                .send(MicroVmShutdown {
+                    uuid: String::from(test_uuid),
+                    shutdown: test_shutdown.to_string(),
+                    details: String::from("test_details"),
+                    node_id: String::from("test_node"),
+                })
+                .expect("could not send!");
+        }
+        let test_bulk = test_result.await?;
+        assert_eq!(test_bulk.results.len(), 2);
+        assert_eq!(test_bulk.results[0].uuid.as_str(), "test_uuid_a");
+        assert!(test_bulk.results[0].ok);
+        assert_eq!(test_bulk.results[1].uuid.as_str(), "test_uuid_b");
+        assert!(!test_bulk.results[1].ok);
+        assert_eq!(test_bulk.results[1].details.as_str(), "test_details");
+        assert_eq!(test_task_queues.connected().await[0].1, 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bulk_shutdown_lagged_and_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let mut test_awaiting = HashMap::with_capacity(2);
+        for test_uuid in ["test_uuid_a", "test_uuid_b"] {
+            test_vm_registry
+                .pending(test_uuid, "", VmSpec::default(), &Claim::default())
+                .await?;
+            test_vm_registry
+                .launched(&MicroVmLaunch {
+                    uuid: String::from(test_uuid),
+                    launched: true.to_string(),
+                    details: String::new(),
+                    node_id: String::from("test_node"),
+                })
+                .await?;
+            test_awaiting.insert(
+                test_uuid.to_string(),
+                test_vm_registry.get(test_uuid).await.unwrap(),
+            );
+        }
+        let mut test_external = External {
+            shutdown_result_sender_clone: test_shutdown_result_sender.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        test_external.result_timeout = Duration::from_millis(200);
+        let test_shutdown = MicroVmShutdown {
+            uuid: String::from("test_uuid_a"),
+            shutdown: true.to_string(),
+            details: String::from("stopped"),
+            node_id: String::from("test_node"),
+        };
+        test_vm_registry.shutdown(&test_shutdown).await?;
+        let test_receiver = test_shutdown_result_sender.subscribe();
+        for test_index in 0..6 {
+            test_shutdown_result_sender.send(MicroVmShutdown {
+                uuid: format!("test_other_uuid_{}", test_index),
+                ..test_shutdown.to_owned()
+            })?;
+        }
+        let mut test_results = test_external
+            .await_shutdowns(test_receiver, test_awaiting, Instant::now())
+            .await;
+        test_results.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+        assert_eq!(test_results.len(), 2);
+        assert_eq!(test_results[0].uuid.as_str(), "test_uuid_a");
+        assert!(test_results[0].ok);
+        assert_eq!(test_results[0].details.as_str(), "stopped");
+        assert_eq!(test_results[1].uuid.as_str(), "test_uuid_b");
+        assert!(!test_results[1].ok);
+        assert!(test_results[1].details.contains("in time"));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn authorize() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_policy_config = PolicyConfig {
+            anonymous_role: Role::Reader,
+            default_role: Role::Reader,
+            principals: vec![PrincipalConfig {
+                name: String::from("test_operator"),
+                token: String::from("test_operator_token"),
+                role: Role::Operator,
+                tenant: None,
+            }],
+            ..PolicyConfig::default()
+        };
+        let test_policy = Arc::new(Policy::init(&test_policy_config).await?);
+        let test_audit_log = Arc::new(AuditLog::init(&TEST_AUDIT_CONFIG).await?);
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_external = External {
+            node_registry: test_node_registry,
+            policy: test_policy,
+            audit_log: test_audit_log.to_owned(),
+            ..test_external().await?
+        };
+        assert!(test_external
+            .list_v_ms(Request::new(ListFilter::default()))
+            .await
+            .is_ok());
+        let test_denied = test_external
+            .launch_vm(Request::new(LaunchRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        let mut test_request = Request::new(NodeDrain {
+            node_id: String::from("test_node"),
+            draining: true,
+        });
+        test_request
+            .metadata_mut()
+            .insert("authorization", "Bearer test_operator_token".parse()?);
+        let test_denied = test_external.drain_node(test_request).await.unwrap_err();
+        assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        let test_entries = test_audit_log.recent().await;
+        assert_eq!(test_entries.len(), 3);
+        assert_eq!(test_entries[0].operation, Operation::ListVms);
+        assert!(test_entries[0].allowed);
+        assert_eq!(test_entries[1].principal.as_str(), "anonymous");
+        assert!(!test_entries[1].allowed);
+        assert_eq!(test_entries[2].principal.as_str(), "test_operator");
+        assert_eq!(test_entries[2].operation, Operation::DrainNode);
+        assert!(!test_entries[2].allowed);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tenant_isolation() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_event_log = Arc::new(EventLog::init(&EventsConfig::default()).await?);
+        let test_policy_config = PolicyConfig {
+            principals: vec![
+                PrincipalConfig {
+                    name: String::from("test_operator"),
+                    token: String::from("test_operator_token"),
+                    role: Role::Operator,
+                    tenant: Some(String::from("test_team")),
+                },
+                PrincipalConfig {
+                    name: String::from("test_other"),
+                    token: String::from("test_other_token"),
+                    role: Role::Operator,
+                    tenant: Some(String::from("test_other_team")),
+                },
+            ],
+            ..PolicyConfig::default()
+        };
+        let test_claim = Claim {
+            tenant: String::from("test_team"),
+            ..Claim::default()
+        };
+        test_vm_registry
+            .pending(
+                "test_uuid",
+                "",
+                VmSpec {
+                    name: String::from("test_vm"),
+                    labels: HashMap::from([(String::from("env"), String::from("prod"))]),
+                    ..VmSpec::default()
+                },
+                &test_claim,
+            )
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_uuid"),
+                launched: true.to_string(),
+                details: String::from("success!"),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        let test_external = External {
+            vm_registry: test_vm_registry.to_owned(),
+            policy: Arc::new(Policy::init(&test_policy_config).await?),
+            event_log: test_event_log.to_owned(),
+            ..test_external().await?
+        };
+        fn test_as<T>(token: &str, message: T) -> Request<T> {
+            let mut test_request = Request::new(message);
+            test_request.metadata_mut().insert(
+                "authorization",
+                format!("Bearer {}", token).parse().unwrap(),
+            );
+            test_request
+        }
+        let test_owned = test_external
+            .list_v_ms(test_as("test_operator_token", ListFilter::default()))
+            .await?;
+        assert_eq!(test_owned.get_ref().vms.len(), 1);
+        let test_foreign = test_external
+            .list_v_ms(test_as("test_other_token", ListFilter::default()))
+            .await?;
+        assert!(test_foreign.get_ref().vms.is_empty());
+        let test_denied = test_external
+            .shutdown_vm(test_as(
+                "test_other_token",
+                MicroVm {
+                    name: String::from("test_uuid"),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        let test_unresolved = test_external
+            .shutdown_vm(test_as(
+                "test_other_token",
+                MicroVm {
+                    name: String::from("test_vm"),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(test_unresolved.code(), tonic::Code::Unavailable);
+        let test_bulk = test_external
+            .shutdown_v_ms(test_as(
+                "test_other_token",
+                VmSelector {
+                    selector: String::from("env=prod"),
+                },
+            ))
+            .await?;
+        assert!(test_bulk.get_ref().results.is_empty());
+        let test_relabeled = test_external
+            .label_v_ms(test_as(
+                "test_other_token",
+                LabelUpdate {
+                    selector: String::from("env=prod"),
+                    labels: HashMap::from([(String::from("owner"), String::from("test"))]),
+                    remove: Vec::with_capacity(0),
+                },
+            ))
+            .await?;
+        assert!(test_relabeled.get_ref().results.is_empty());
+        let test_record = test_vm_registry.get("test_uuid").await.unwrap();
+        assert_eq!(test_record.state, VmState::Running);
+        assert!(!test_record.spec.labels.contains_key("owner"));
+        test_external
+            .create_group(test_as(
+                "test_operator_token",
+                Group {
+                    name: String::from("test_web"),
+                    replicas: 1,
+                    template: Some(LaunchRequest::default()),
+                    ..Group::default()
+                },
+            ))
+            .await?;
+        let test_group = test_external
+            .get_group(test_as(
+                "test_operator_token",
+                GroupSelector {
+                    name: String::from("test_web"),
+                },
+            ))
+            .await?;
+        assert_eq!(test_group.get_ref().tenant.as_str(), "test_team");
+        let test_hidden = test_external
+            .get_group(test_as(
+                "test_other_token",
+                GroupSelector {
+                    name: String::from("test_web"),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(test_hidden.code(), tonic::Code::PermissionDenied);
+        let test_groups = test_external
+            .list_groups(test_as("test_operator_token", Empty {}))
+            .await?;
+        assert_eq!(test_groups.get_ref().groups.len(), 1);
+        let test_groups = test_external
+            .list_groups(test_as("test_other_token", Empty {}))
+            .await?;
+        assert!(test_groups.get_ref().groups.is_empty());
+        test_event_log.node_joined("test_node").await;
+        test_event_log
+            .vm_state("test_uuid", "test_node", State::Running, "success!")
+            .await;
+        test_event_log
+            .vm_state("test_removed", "test_node", State::Failed, "failed!")
+            .await;
+        test_event_log.node_left("test_node", "test_left").await;
+        let test_filter = EventFilter {
+            resume_token: 1,
+            ..EventFilter::default()
+        };
+        let mut test_stream = test_external
+            .watch_events(test_as("test_operator_token", test_filter.to_owned()))
+            .await?
+            .into_inner();
+        let test_owned = test_stream.next().await.unwrap()?;
+        assert_eq!(test_owned.vm_id.as_str(), "test_uuid");
+        let test_left = test_stream.next().await.unwrap()?;
+        assert_eq!(test_left.kind, Kind::NodeLeft as i32);
+        let mut test_stream = test_external
+            .watch_events(test_as("test_other_token", test_filter))
+            .await?
+            .into_inner();
+        let test_left = test_stream.next().await.unwrap()?;
+        assert_eq!(test_left.kind, Kind::NodeLeft as i32);
+        test_event_log
+            .task_failed("test_uuid", "test_node", "failed!")
+            .await;
+        test_event_log.node_joined("test_node").await;
+        let test_joined = test_stream.next().await.unwrap()?;
+        assert_eq!(test_joined.kind, Kind::NodeJoined as i32);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tenant_quotas() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_policy_config = PolicyConfig {
+            anonymous_role: Role::Admin,
+            principals: vec![PrincipalConfig {
+                name: String::from("test_operator"),
+                token: String::from("test_operator_token"),
+                role: Role::Operator,
+                tenant: Some(String::from("test_team")),
+            }],
+            tenants: TenantsConfig {
+                quotas: HashMap::from([(
+                    String::from("test_team"),
+                    Quota {
+                        vms: Some(1),
+                        ..Quota::default()
+                    },
+                )]),
+                ..TenantsConfig::default()
+            },
+            ..PolicyConfig::default()
+        };
+        let test_external = Arc::new(External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            policy: Arc::new(Policy::init(&test_policy_config).await?),
+            ..test_external().await?
+        });
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        fn test_operator<T>(message: T) -> Request<T> {
+            let mut test_request = Request::new(message);
+            test_request.metadata_mut().insert(
+                "authorization",
+                "Bearer test_operator_token".parse().unwrap(),
+            );
+            test_request
+        }
+        let test_launch = tokio::spawn({
+            let test_external = test_external.to_owned();
+            let test_request = test_operator(LaunchRequest::default());
+            async move { test_external.launch_vm(test_request).await }
+        });
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        test_response_sender.send(MicroVmLaunch {
+            uuid: test_task.id.to_owned(),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        })?;
+        test_launch.await??;
+        let test_exhausted = test_external
+            .launch_vm(test_operator(LaunchRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(test_exhausted.code(), tonic::Code::ResourceExhausted);
+        assert!(test_exhausted
+            .message()
+            .contains("test_team would use 2 of 1 vms"));
+        assert_eq!(test_vm_registry.list().await.len(), 1);
+        assert_eq!(
+            test_vm_registry.list().await[0].tenant.as_str(),
+            "test_team"
+        );
+        let test_usage = test_external
+            .query_usage(test_operator(UsageQuery::default()))
+            .await?;
+        assert_eq!(test_usage.get_ref().tenants.len(), 1);
+        assert_eq!(test_usage.get_ref().tenants[0].tenant.as_str(), "test_team");
+        assert_eq!(test_usage.get_ref().tenants[0].vms, 1);
+        assert_eq!(test_usage.get_ref().tenants[0].vcpus, 2);
+        assert_eq!(test_usage.get_ref().tenants[0].memory_mib, 1024);
+        let test_denied = test_external
+            .query_usage(test_operator(UsageQuery {
+                all: true,
+                ..UsageQuery::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        let test_usage = test_external
+            .query_usage(Request::new(UsageQuery {
+                all: true,
+                ..UsageQuery::default()
+            }))
+            .await?;
+        assert_eq!(test_usage.get_ref().tenants.len(), 1);
+        assert_eq!(
+            test_usage.get_ref().tenants[0].quota,
+            Some(TenantQuota {
+                vms: Some(1),
+                ..TenantQuota::default()
+            }),
+        );
+        let test_usage = test_external
+            .query_usage(Request::new(UsageQuery::default()))
+            .await?;
+        assert_eq!(test_usage.get_ref().tenants[0].tenant.as_str(), "default");
+        assert_eq!(test_usage.get_ref().tenants[0].vms, 0);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let (test_shutdown_result_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_policy_config = PolicyConfig {
+            anonymous_role: Role::Admin,
+            tenants: TenantsConfig {
+                default_quota: Quota {
+                    vms: Some(3),
+                    ..Quota::default()
+                },
+                ..TenantsConfig::default()
+            },
+            ..PolicyConfig::default()
+        };
+        let test_external = External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            shutdown_result_sender_clone: test_shutdown_result_sender.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            policy: Arc::new(Policy::init(&test_policy_config).await?),
+            ..test_external().await?
+        };
+        let test_template = LaunchRequest {
+            name: String::from("test_ci"),
+            ..LaunchRequest::default()
+        };
+        let test_invalid = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest {
+                template: Some(test_template.to_owned()),
+                count: 2,
+                launches: vec![LaunchRequest::default()],
+                atomic: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        let test_empty = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(test_empty.code(), tonic::Code::InvalidArgument);
+        let test_exhausted = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest {
+                template: Some(test_template.to_owned()),
+                count: 4,
+                atomic: true,
+                ..BatchLaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_exhausted.code(), tonic::Code::ResourceExhausted);
+        assert!(test_exhausted.message().contains("MicroVM 3 of the batch"));
+        assert!(test_vm_registry.list().await.is_empty());
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let mut test_results = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest {
+                template: Some(test_template.to_owned()),
+                count: 4,
+                ..BatchLaunchRequest::default()
+            }))
+            .await?
+            .into_inner();
+        let test_rejected = test_results.next().await.unwrap()?;
+        assert_eq!(test_rejected.index, 3);
+        assert_eq!(test_rejected.name.as_str(), "test_ci-3");
+        assert!(!test_rejected.launched);
+        for test_launched in [true, false, true] {
+            let test_task = test_task_queues
+                .next("test_node", test_session)
+                .await
+                .unwrap();
+            test_task_queues
+                .acknowledge("test_node", &test_task.id)
+                .await?;
+            test_response_sender.send(MicroVmLaunch {
+                uuid: test_task.id.to_owned(),
+                launched: test_launched.to_string(),
+                details: String::from("test_details"),
+                node_id: String::from("test_node"),
+            })?;
+        }
+        let mut test_launched = Vec::with_capacity(3);
+        while let Some(test_result) = test_results.next().await {
+            let test_result = test_result?;
+            assert_eq!(test_result.node_id.as_str(), "test_node");
+            assert!(!test_result.rolled_back);
+            test_launched.push((test_result.index, test_result.launched));
+        }
+        assert_eq!(test_launched, vec![(0, true), (1, false), (2, true)]);
+        for test_record in test_vm_registry.list().await {
+            test_vm_registry.remove(&test_record.uuid).await?;
+        }
+        let mut test_results = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest {
+                launches: vec![
+                    LaunchRequest {
+                        name: String::from("test_first"),
+                        ..LaunchRequest::default()
+                    },
+                    LaunchRequest {
+                        name: String::from("test_second"),
+                        ..LaunchRequest::default()
+                    },
+                ],
+                atomic: true,
+                ..BatchLaunchRequest::default()
+            }))
+            .await?
+            .into_inner();
+        let mut test_uuids = Vec::with_capacity(2);
+        for test_launched in [true, false] {
+            let test_task = test_task_queues
+                .next("test_node", test_session)
+                .await
+                .unwrap();
+            test_task_queues
+                .acknowledge("test_node", &test_task.id)
+                .await?;
+            test_uuids.push(test_task.id.to_owned());
+            test_response_sender.send(MicroVmLaunch {
+                uuid: test_task.id.to_owned(),
+                launched: test_launched.to_string(),
+                details: String::from("test_details"),
+                node_id: String::from("test_node"),
+            })?;
+        }
+        let test_first = test_results.next().await.unwrap()?;
+        assert!(test_first.launched);
+        assert_eq!(test_first.name.as_str(), "test_first");
+        let test_second = test_results.next().await.unwrap()?;
+        assert!(!test_second.launched);
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        assert_eq!(test_task.action, 2);
+        assert_eq!(test_task.id, test_uuids[0]);
+        test_shutdown_result_sender.send(MicroVmShutdown {
+            uuid: test_task.id.to_owned(),
+            shutdown: true.to_string(),
+            details: String::from("test_details"),
+            node_id: String::from("test_node"),
+        })?;
+        let test_rolled_back = test_results.next().await.unwrap()?;
+        assert_eq!(test_rolled_back.index, 0);
+        assert!(test_rolled_back.rolled_back);
+        assert!(!test_rolled_back.launched);
+        assert!(test_results.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_batch_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let mut test_external = External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            ..test_external().await?
+        };
+        test_external.result_timeout = Duration::from_millis(300);
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let mut test_results = test_external
+            .launch_v_ms(Request::new(BatchLaunchRequest {
+                template: Some(LaunchRequest::default()),
+                count: 2,
+                atomic: true,
+                ..BatchLaunchRequest::default()
+            }))
+            .await?
+            .into_inner();
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        test_response_sender.send(MicroVmLaunch {
+            uuid: test_task.id.to_owned(),
+            launched: true.to_string(),
+            details: String::from("test_details"),
+            node_id: String::from("test_node"),
+        })?;
+        let test_first = test_results.next().await.unwrap()?;
+        assert!(test_first.launched);
+        let test_second = test_results.next().await.unwrap()?;
+        assert!(!test_second.launched);
+        assert!(test_second.details.contains("launch result in time"));
+        let test_rollback = test_results.next().await.unwrap()?;
+        assert_eq!(test_rollback.index, test_first.index);
+        assert!(!test_rollback.rolled_back);
+        assert!(test_rollback
+            .details
+            .starts_with("Rollback failed: MicroVM did not report a shutdown result in time"));
+        assert!(test_results.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reconcile_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_external = External {
+            task_queues: test_task_queues.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            ..test_external().await?
+        };
+        let test_template = LaunchRequest {
+            labels: HashMap::from([(String::from("tier"), String::from("web"))]),
+            ..LaunchRequest::default()
+        };
+        let test_named = test_external
+            .create_group(Request::new(Group {
+                name: String::from("test_web"),
+                replicas: 2,
+                template: Some(LaunchRequest {
+                    name: String::from("test_vm"),
+                    ..test_template.to_owned()
+                }),
+                ..Group::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_named.code(), tonic::Code::InvalidArgument);
+        let test_group = test_external
+            .create_group(Request::new(Group {
+                name: String::from("test_web"),
+                replicas: 2,
+                max_surge: 1,
+                template: Some(test_template.to_owned()),
+                ..Group::default()
+            }))
+            .await?
+            .into_inner();
+        assert_eq!(test_group.generation, 1);
+        assert_eq!(test_group.tenant.as_str(), "default");
+        test_external.reconcile().await;
+        assert!(test_vm_registry.list().await.is_empty());
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        test_external.reconcile().await;
+        test_external.reconcile().await;
+        let test_members = test_vm_registry.list().await;
+        assert_eq!(test_members.len(), 2);
+        for test_member in &test_members {
+            let test_task = test_task_queues
+                .next("test_node", test_session)
+                .await
+                .unwrap();
+            test_task_queues
+                .acknowledge("test_node", &test_task.id)
+                .await?;
+            assert_eq!(test_task.action, 1);
+            assert_eq!(test_member.spec.group.as_str(), "test_web");
+            assert!(test_member.spec.name.starts_with("test_web-"));
+            assert_eq!(test_member.spec.labels["tier"], "web");
+            test_vm_registry
+                .launched(&MicroVmLaunch {
+                    uuid: test_member.uuid.to_owned(),
+                    launched: true.to_string(),
+                    details: String::from("test_details"),
+                    node_id: String::from("test_node"),
+                })
+                .await?;
+        }
+        test_external.reconcile().await;
+        assert_eq!(test_task_queues.depth().await, 0);
+        let test_status = test_external
+            .get_group(Request::new(GroupSelector {
+                name: String::from("test_web"),
+            }))
+            .await?
+            .into_inner()
+            .status
+            .unwrap();
+        assert_eq!((test_status.running, test_status.updated), (2, 2));
+        test_external
+            .scale_group(Request::new(GroupScale {
+                name: String::from("test_web"),
+                replicas: 1,
+            }))
+            .await?;
+        test_external.reconcile().await;
+        test_external.reconcile().await;
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        test_task_queues
+            .acknowledge("test_node", &test_task.id)
+            .await?;
+        assert_eq!(test_task.action, 2);
+        assert_eq!(test_task.id, test_members[1].uuid);
+        assert_eq!(test_task_queues.depth().await, 0);
+        test_vm_registry
+            .shutdown(&MicroVmShutdown {
+                uuid: test_task.id.to_owned(),
+                shutdown: true.to_string(),
+                details: String::from("test_details"),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        let test_updated = test_external
+            .update_group(Request::new(Group {
+                name: String::from("test_web"),
+                replicas: 1,
+                max_surge: 1,
+                template: Some(LaunchRequest {
+                    profile: String::new(),
+                    annotations: HashMap::from([(String::from("release"), String::from("2"))]),
+                    ..test_template
+                }),
+                ..Group::default()
+            }))
+            .await?
+            .into_inner();
+        assert_eq!(test_updated.generation, 2);
+        test_external.reconcile().await;
+        test_external.reconcile().await;
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        test_task_queues
+            .acknowledge("test_node", &test_task.id)
+            .await?;
+        assert_eq!(test_task.action, 1);
+        assert_eq!(test_task_queues.depth().await, 0);
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: test_task.id.to_owned(),
+                launched: true.to_string(),
+                details: String::from("test_details"),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        test_external.reconcile().await;
+        let test_retired = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        test_task_queues
+            .acknowledge("test_node", &test_retired.id)
+            .await?;
+        assert_eq!(test_retired.action, 2);
+        assert_eq!(test_retired.id, test_members[0].uuid);
+        test_external
+            .delete_group(Request::new(GroupSelector {
+                name: String::from("test_web"),
+            }))
+            .await?;
+        test_external.reconcile().await;
+        let test_orphan = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        assert_eq!(test_orphan.action, 2);
+        assert_eq!(test_orphan.id, test_task.id);
+        let test_groups = test_external
+            .list_groups(Request::new(Empty {}))
+            .await?
+            .into_inner();
+        assert!(test_groups.groups.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _) = tokio::sync::broadcast::channel(4);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_profiles = HashMap::from([(
+            String::from("test_small"),
+            ProfileConfig {
+                description: String::from("Small test MicroVM"),
+                machine: MachineSpec {
+                    vcpus: 1,
+                    memory_mib: 512,
+                    drives: vec![Drive {
+                        drive_id: String::from("data"),
+                        image: String::from("data.ext4"),
+                        read_only: true,
+                    }],
+                    ..MachineSpec::default()
+                },
+            },
+        )]);
+        let test_external = Arc::new(External {
+            task_queues: test_task_queues.to_owned(),
+            launch_result_sender_clone: test_response_sender.to_owned(),
+            node_registry: test_node_registry.to_owned(),
+            vm_registry: test_vm_registry.to_owned(),
+            profile_registry: Arc::new(ProfileRegistry::init(&test_profiles).await?),
+            ..test_external().await?
+        });
+        let test_created = test_external
+            .create_profile(Request::new(Profile {
+                name: String::from("test_api"),
+                machine: Some(MachineSpec {
+                    vcpus: 2,
+                    ..MachineSpec::default()
+                }),
+                ..Profile::default()
+            }))
+            .await?;
+        assert!(!test_created.get_ref().configured);
+        let test_duplicate = test_external
+            .create_profile(Request::new(Profile {
+                name: String::from("test_small"),
+                ..Profile::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_duplicate.code(), tonic::Code::AlreadyExists);
+        let test_configured = test_external
+            .update_profile(Request::new(Profile {
+                name: String::from("test_small"),
+                ..Profile::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_configured.code(), tonic::Code::FailedPrecondition);
+        let test_updated = test_external
+            .update_profile(Request::new(Profile {
+                name: String::from("test_api"),
+                description: String::from("Updated"),
+                machine: Some(MachineSpec {
+                    vcpus: 4,
+                    ..MachineSpec::default()
+                }),
+                ..Profile::default()
+            }))
+            .await?;
+        assert_eq!(test_updated.get_ref().machine.to_owned().unwrap().vcpus, 4);
+        let test_list = test_external.list_profiles(Request::new(Empty {})).await?;
+        assert_eq!(test_list.get_ref().profiles.len(), 2);
+        assert_eq!(test_list.get_ref().profiles[0].name.as_str(), "test_api");
+        assert!(test_list.get_ref().profiles[1].configured);
+        test_external
+            .delete_profile(Request::new(ProfileSelector {
+                name: String::from("test_api"),
+            }))
+            .await?;
+        let test_missing = test_external
+            .get_profile(Request::new(ProfileSelector {
+                name: String::from("test_api"),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        let test_unknown = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                profile: String::from("test_api"),
+                ..LaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_unknown.code(), tonic::Code::NotFound);
+        let test_invalid = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                overrides: Some(MachineSpec {
+                    root_fs: String::from("../rootfs.ext4"),
+                    ..MachineSpec::default()
+                }),
+                ..LaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        assert!(test_vm_registry.list().await.is_empty());
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let test_missing_image = test_external
+            .launch_vm(Request::new(LaunchRequest {
+                profile: String::from("test_small"),
+                ..LaunchRequest::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing_image.code(), tonic::Code::FailedPrecondition);
+        assert!(test_missing_image
+            .message()
+            .contains("test_node: image data.ext4 not found"));
+        test_node_registry
+            .update_inventory(
+                "test_node",
+                NodeInventory {
+                    images: vec![String::from("data.ext4")],
+                    ..NodeInventory::default()
+                },
+            )
+            .await?;
+        let test_launch = tokio::spawn({
+            let test_external = test_external.to_owned();
+            let test_request = Request::new(LaunchRequest {
+                profile: String::from("test_small"),
+                overrides: Some(MachineSpec {
+                    vcpus: 3,
+                    ..MachineSpec::default()
+                }),
+                ..LaunchRequest::default()
+            });
+            async move { test_external.launch_vm(test_request).await }
+        });
+        let test_task = test_task_queues
+            .next("test_node", test_session)
+            .await
+            .unwrap();
+        let test_machine = test_task.machine.to_owned().unwrap();
+        assert_eq!(test_machine.vcpus, 3);
+        assert_eq!(test_machine.memory_mib, 512);
+        assert_eq!(test_machine.drives[0].drive_id.as_str(), "data");
+        test_response_sender.send(MicroVmLaunch {
+            uuid: test_task.id.to_owned(),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        })?;
+        test_launch.await??;
+        let test_vms = test_external
+            .list_v_ms(Request::new(ListFilter::default()))
+            .await?;
+        assert_eq!(test_vms.get_ref().vms[0].profile.as_str(), "test_small");
+        assert_eq!(
+            test_vms.get_ref().vms[0].machine,
+            Some(test_machine.to_owned())
+        );
+        let test_usage = test_external
+            .query_usage(Request::new(UsageQuery::default()))
+            .await?;
+        assert_eq!(test_usage.get_ref().tenants[0].vcpus, 3);
+        assert_eq!(test_usage.get_ref().tenants[0].memory_mib, 512);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drain_and_delist_node() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_external = External {
+            node_registry: test_node_registry.to_owned(),
+            ..test_external().await?
+        };
+        let test_request = Request::new(NodeDrain {
+            node_id: String::from("test_node"),
+            draining: true,
+        });
+        let test_drained = test_external.drain_node(test_request).await?;
+        assert!(test_drained.get_ref().draining);
+        assert!(test_node_registry.get("test_node").await.unwrap().draining);
+        let test_request = Request::new(NodeSelector {
+            node_id: String::from("test_node"),
+        });
+        let test_delisted = test_external.delist_node(test_request).await?;
+        assert_eq!(test_delisted.get_ref().node_id.as_str(), "test_node");
+        assert!(!test_node_registry.contains("test_node").await);
+        let test_request = Request::new(NodeSelector {
+            node_id: String::from("test_node"),
+        });
+        let test_missing = test_external.delist_node(test_request).await.unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn query_audit() -> Result<(), Box<dyn std::error::Error>> {
+        let test_external = test_external().await?;
+        test_external
+            .list_v_ms(Request::new(ListFilter::default()))
+            .await?;
+        let test_request = Request::new(NodeDrain {
+            node_id: String::from("test_missing_node"),
+            draining: true,
+        });
+        assert!(test_external.drain_node(test_request).await.is_err());
+        let test_request = Request::new(AuditQuery {
+            vm_id: String::new(),
+            principal: String::from("anonymous"),
+            limit: 0,
+        });
+        let test_entries = test_external.query_audit(test_request).await?.into_inner();
+        assert_eq!(test_entries.entries.len(), 2);
+        let test_drain = &test_entries.entries[0];
+        assert_eq!(test_drain.operation.as_str(), "drain_node");
+        assert_eq!(test_drain.node_id.as_str(), "test_missing_node");
+        assert!(test_drain.allowed);
+        assert_eq!(test_drain.result.as_str(), "NotFound");
+        assert!(test_drain.arguments.contains("\"draining\":true"));
+        assert_eq!(test_entries.entries[1].operation.as_str(), "list_vms");
+        let test_request = Request::new(AuditQuery {
+            vm_id: String::from("test_uuid"),
+            principal: String::new(),
+            limit: 10,
+        });
+        let test_entries = test_external.query_audit(test_request).await?.into_inner();
+        assert!(test_entries.entries.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn watch_events() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_event_log = Arc::new(EventLog::init(&EventsConfig::default()).await?);
+        let test_external = External {
+            node_registry: test_node_registry.to_owned(),
+            event_log: test_event_log.to_owned(),
+            ..test_external().await?
+        };
+        test_event_log.node_joined("test_node").await;
+        test_event_log
+            .vm_state("test_uuid", "test_node", State::Running, "success!")
+            .await;
+        let test_request = Request::new(EventFilter {
+            vm_id: String::from("test_uuid"),
+            resume_token: 1,
+            ..EventFilter::default()
+        });
+        let mut test_stream = test_external.watch_events(test_request).await?.into_inner();
+        let test_replayed = test_stream.next().await.unwrap()?;
+        assert_eq!(test_replayed.resume_token, 2);
+        assert_eq!(test_replayed.state, State::Running as i32);
+        test_event_log.node_joined("test_other_node").await;
+        test_event_log
+            .task_failed("test_uuid", "test_node", "failed!")
+            .await;
+        let test_live = test_stream.next().await.unwrap()?;
+        assert_eq!(test_live.resume_token, 4);
+        assert_eq!(test_live.kind, Kind::TaskFailed as i32);
+        assert_eq!(test_live.details.as_str(), "failed!");
+
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_request = Request::new(EventFilter {
+            kinds: vec![Kind::NodeLeft as i32],
+            ..EventFilter::default()
+        });
+        let mut test_stream = test_external.watch_events(test_request).await?.into_inner();
+        let test_request = Request::new(NodeSelector {
+            node_id: String::from("test_node"),
+        });
+        test_external.delist_node(test_request).await?;
+        let test_left = test_stream.next().await.unwrap()?;
+        assert_eq!(test_left.node_id.as_str(), "test_node");
+        assert_eq!(test_left.details.as_str(), "Node delisted by admin");
+
+        let test_request = Request::new(EventFilter {
+            resume_token: 42,
+            ..EventFilter::default()
+        });
+        let test_status = test_external.watch_events(test_request).await.unwrap_err();
+        assert_eq!(test_status.code(), tonic::Code::OutOfRange);
         Ok(())
     }
 }
@@ -0,0 +1,539 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use crate::impulse::shared::v010::MachineSpec;
+use crate::placement::PlacementSpec;
+use crate::profiles;
+use crate::store::Store;
+use crate::vm_registry::{VmRecord, VmState};
+
+const MAX_REPLICAS: u32 = 256;
+const RETIRE_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub(crate) struct GroupTemplate {
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+    #[serde(default)]
+    pub placement: PlacementSpec,
+    #[serde(default)]
+    pub profile: String,
+    #[serde(default)]
+    pub overrides: MachineSpec,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct GroupRecord {
+    pub name: String,
+    pub tenant: String,
+    pub replicas: u32,
+    pub max_surge: u32,
+    pub template: GroupTemplate,
+    pub generation: u64,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+impl GroupRecord {
+    async fn init(
+        name: &str,
+        tenant: &str,
+        replicas: u32,
+        max_surge: u32,
+        template: GroupTemplate,
+    ) -> GroupRecord {
+        let now = SystemTime::now();
+
+        GroupRecord {
+            name: name.to_string(),
+            tenant: tenant.to_string(),
+            replicas,
+            max_surge: max_surge.max(1),
+            template,
+            generation: 1,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Plan {
+    pub lost: Vec<String>,
+    pub launch: u32,
+    pub stop: Vec<String>,
+}
+
+pub struct GroupRegistry {
+    groups: Mutex<HashMap<String, GroupRecord>>,
+    retiring: Mutex<HashMap<String, Instant>>,
+    store: Option<Arc<Store>>,
+}
+
+impl GroupRegistry {
+    pub async fn init() -> Result<GroupRegistry, Box<dyn std::error::Error>> {
+        let groups = Mutex::new(HashMap::with_capacity(8));
+        let retiring = Mutex::new(HashMap::with_capacity(8));
+
+        Ok(GroupRegistry {
+            groups,
+            retiring,
+            store: None,
+        })
+    }
+
+    pub async fn restore(store: Arc<Store>) -> Result<GroupRegistry, Box<dyn std::error::Error>> {
+        let groups = store
+            .groups()
+            .await?
+            .into_iter()
+            .map(|record| (record.name.to_owned(), record))
+            .collect();
+
+        Ok(GroupRegistry {
+            groups: Mutex::new(groups),
+            retiring: Mutex::new(HashMap::with_capacity(8)),
+            store: Some(store),
+        })
+    }
+
+    pub(crate) async fn reload(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut groups = self.groups.lock().await;
+
+        if let Some(store) = &self.store {
+            *groups = store
+                .groups()
+                .await?
+                .into_iter()
+                .map(|record| (record.name.to_owned(), record))
+                .collect();
+        }
+
+        self.retiring.lock().await.clear();
+
+        Ok(groups.len())
+    }
+
+    pub(crate) async fn create(
+        &self,
+        name: &str,
+        tenant: &str,
+        replicas: u32,
+        max_surge: u32,
+        template: GroupTemplate,
+    ) -> Result<GroupRecord, Status> {
+        Self::check(name, replicas).await?;
+
+        let mut groups = self.groups.lock().await;
+
+        if groups.contains_key(name) {
+            let message = format!("Group {} already exists!", name);
+
+            return Err(Status::already_exists(message));
+        }
+
+        let record = GroupRecord::init(name, tenant, replicas, max_surge, template).await;
+
+        self.persist(&record).await?;
+
+        groups.insert(name.to_string(), record.to_owned());
+
+        Ok(record)
+    }
+
+    pub(crate) async fn update(
+        &self,
+        name: &str,
+        replicas: u32,
+        max_surge: u32,
+        template: GroupTemplate,
+    ) -> Result<GroupRecord, Status> {
+        Self::check(name, replicas).await?;
+
+        let mut groups = self.groups.lock().await;
+
+        match groups.get_mut(name) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                if updated.template != template {
+                    updated.template = template;
+                    updated.generation += 1;
+                }
+
+                updated.replicas = replicas;
+                updated.max_surge = max_surge.max(1);
+                updated.updated_at = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated.to_owned();
+
+                Ok(updated)
+            }
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn scale(&self, name: &str, replicas: u32) -> Result<GroupRecord, Status> {
+        Self::check(name, replicas).await?;
+
+        let mut groups = self.groups.lock().await;
+
+        match groups.get_mut(name) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                updated.replicas = replicas;
+                updated.updated_at = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated.to_owned();
+
+                Ok(updated)
+            }
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn remove(&self, name: &str) -> Result<GroupRecord, Status> {
+        let mut groups = self.groups.lock().await;
+
+        if !groups.contains_key(name) {
+            return Err(Self::not_found(name).await);
+        }
+
+        if let Some(store) = &self.store {
+            store.remove_group(name).await?;
+        }
+
+        match groups.remove(name) {
+            Some(record) => Ok(record),
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn get(&self, name: &str) -> Result<GroupRecord, Status> {
+        let groups = self.groups.lock().await;
+
+        match groups.get(name) {
+            Some(record) => Ok(record.to_owned()),
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn list(&self) -> Vec<GroupRecord> {
+        let groups = self.groups.lock().await;
+        let mut list: Vec<GroupRecord> = groups.values().cloned().collect();
+
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+
+        list
+    }
+
+    pub(crate) async fn retire(&self, uuid: &str) {
+        let mut retiring = self.retiring.lock().await;
+
+        retiring.insert(uuid.to_string(), Instant::now());
+    }
+
+    pub(crate) async fn retiring(&self, live: &HashSet<String>) -> HashSet<String> {
+        let mut retiring = self.retiring.lock().await;
+
+        retiring.retain(|uuid, since| live.contains(uuid) && since.elapsed() < RETIRE_TIMEOUT);
+
+        retiring.keys().cloned().collect()
+    }
+
+    async fn check(name: &str, replicas: u32) -> Result<(), Status> {
+        if let Err(error) = profiles::validate_name(name) {
+            return Err(Status::invalid_argument(error.to_string()));
+        }
+
+        match replicas > MAX_REPLICAS {
+            true => {
+                let message = format!(
+                    "Group {} asks for {} replicas but at most {} are allowed!",
+                    name, replicas, MAX_REPLICAS,
+                );
+
+                Err(Status::invalid_argument(message))
+            }
+            false => Ok(()),
+        }
+    }
+
+    async fn persist(&self, record: &GroupRecord) -> Result<(), Status> {
+        match &self.store {
+            Some(store) => store.put_group(record).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn not_found(name: &str) -> Status {
+        let message = format!("Group {} was not found!", name);
+
+        Status::not_found(message)
+    }
+}
+
+pub(crate) fn plan(
+    group: &GroupRecord,
+    members: &[VmRecord],
+    healthy: &HashSet<String>,
+    retiring: &HashSet<String>,
+) -> Plan {
+    let mut lost = Vec::with_capacity(members.len());
+    let mut live = Vec::with_capacity(members.len());
+
+    for record in members
+        .iter()
+        .filter(|record| record.live() && !retiring.contains(&record.uuid))
+    {
+        match record.node_id.is_empty() || healthy.contains(&record.node_id) {
+            true => live.push(record),
+            false => lost.push(record.uuid.to_owned()),
+        }
+    }
+
+    let desired = group.replicas as usize;
+    let surge = group.max_surge.max(1) as usize;
+    let total = live.len();
+    let outdated = live
+        .iter()
+        .filter(|record| record.spec.generation < group.generation)
+        .count();
+
+    let mut running: Vec<&VmRecord> = live
+        .into_iter()
+        .filter(|record| record.state == VmState::Running)
+        .collect();
+
+    let launch = match total < desired {
+        true => (desired - total).min(surge),
+        false => outdated.min((desired + surge).saturating_sub(total)),
+    };
+    let extras = total
+        .saturating_sub(desired)
+        .min(surge)
+        .min(running.len().saturating_sub(desired));
+
+    running.sort_by_key(|record| {
+        (
+            record.spec.generation >= group.generation,
+            Reverse(record.created_at),
+        )
+    });
+
+    Plan {
+        lost,
+        launch: launch as u32,
+        stop: running
+            .into_iter()
+            .take(extras)
+            .map(|record| record.uuid.to_owned())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::StoreConfig;
+    use crate::vm_registry::VmSpec;
+
+    fn test_member(uuid: &str, generation: u64, state: VmState, age: u64) -> VmRecord {
+        let test_created = SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age);
+
+        VmRecord {
+            uuid: uuid.to_string(),
+            node_id: String::from("test_node"),
+            state,
+            details: String::new(),
+            created_at: test_created,
+            updated_at: test_created,
+            request_id: String::new(),
+            spec: VmSpec {
+                group: String::from("test_group"),
+                generation,
+                ..VmSpec::default()
+            },
+            tenant: String::from("default"),
+            resources: Default::default(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn crud() -> Result<(), Box<dyn std::error::Error>> {
+        let test_registry = GroupRegistry::init().await?;
+        let test_template = GroupTemplate {
+            profile: String::from("small"),
+            ..GroupTemplate::default()
+        };
+        let test_group = test_registry
+            .create("test_group", "default", 3, 0, test_template.to_owned())
+            .await?;
+        assert_eq!(test_group.generation, 1);
+        assert_eq!(test_group.max_surge, 1);
+        let test_duplicate = test_registry
+            .create("test_group", "default", 1, 1, GroupTemplate::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_duplicate.code(), tonic::Code::AlreadyExists);
+        let test_invalid = test_registry
+            .create("test/group", "default", 1, 1, GroupTemplate::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        assert!(test_registry
+            .create("test_huge", "default", 1000, 1, GroupTemplate::default())
+            .await
+            .is_err());
+        let test_scaled = test_registry.scale("test_group", 5).await?;
+        assert_eq!(test_scaled.replicas, 5);
+        assert_eq!(test_scaled.generation, 1);
+        let test_updated = test_registry
+            .update("test_group", 5, 2, test_template)
+            .await?;
+        assert_eq!(test_updated.generation, 1);
+        assert_eq!(test_updated.max_surge, 2);
+        let test_updated = test_registry
+            .update("test_group", 5, 2, GroupTemplate::default())
+            .await?;
+        assert_eq!(test_updated.generation, 2);
+        assert_eq!(test_registry.list().await.len(), 1);
+        test_registry.remove("test_group").await?;
+        assert_eq!(
+            test_registry.get("test_group").await.unwrap_err().code(),
+            tonic::Code::NotFound,
+        );
+        assert!(test_registry.scale("test_group", 1).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retiring() -> Result<(), Box<dyn std::error::Error>> {
+        let test_registry = GroupRegistry::init().await?;
+        test_registry.retire("test_a").await;
+        test_registry.retire("test_b").await;
+        let test_live = HashSet::from([String::from("test_a")]);
+        assert_eq!(
+            test_registry.retiring(&test_live).await,
+            HashSet::from([String::from("test_a")]),
+        );
+        assert!(test_registry.retiring(&HashSet::new()).await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Arc::new(Store::init(&StoreConfig { path: None }).await?);
+        let test_registry = GroupRegistry::restore(test_store.clone()).await?;
+        test_registry
+            .create("test_group", "test_team", 2, 1, GroupTemplate::default())
+            .await?;
+        let test_restored = GroupRegistry::restore(test_store).await?;
+        assert_eq!(
+            test_restored.get("test_group").await?.tenant.as_str(),
+            "test_team"
+        );
+        assert_eq!(test_restored.reload().await?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn reconcile_plan() {
+        let mut test_group = GroupRecord {
+            name: String::from("test_group"),
+            tenant: String::from("default"),
+            replicas: 3,
+            max_surge: 2,
+            template: GroupTemplate::default(),
+            generation: 1,
+            created_at: SystemTime::UNIX_EPOCH,
+            updated_at: SystemTime::UNIX_EPOCH,
+        };
+        let test_healthy = HashSet::from([String::from("test_node")]);
+        let test_none = HashSet::new();
+        assert_eq!(
+            plan(&test_group, &[], &test_healthy, &test_none),
+            Plan {
+                launch: 2,
+                ..Plan::default()
+            },
+        );
+        let mut test_lost = test_member("test_lost", 1, VmState::Running, 9);
+        test_lost.node_id = String::from("test_gone");
+        let test_members = vec![
+            test_member("test_a", 1, VmState::Running, 3),
+            test_member("test_failed", 1, VmState::Failed, 2),
+            test_lost,
+        ];
+        let test_plan = plan(&test_group, &test_members, &test_healthy, &test_none);
+        assert_eq!(test_plan.lost, vec![String::from("test_lost")]);
+        assert_eq!(test_plan.launch, 2);
+        assert!(test_plan.stop.is_empty());
+        test_group.replicas = 1;
+        test_group.max_surge = 1;
+        let test_members = vec![
+            test_member("test_old", 1, VmState::Running, 3),
+            test_member("test_new", 1, VmState::Running, 1),
+            test_member("test_pending", 1, VmState::Pending, 0),
+        ];
+        assert_eq!(
+            plan(&test_group, &test_members, &test_healthy, &test_none).stop,
+            vec![String::from("test_new")],
+        );
+        let test_retiring = HashSet::from([String::from("test_new")]);
+        assert_eq!(
+            plan(&test_group, &test_members, &test_healthy, &test_retiring),
+            Plan::default(),
+        );
+        test_group.replicas = 2;
+        test_group.generation = 2;
+        let test_members = vec![
+            test_member("test_old_a", 1, VmState::Running, 3),
+            test_member("test_old_b", 1, VmState::Running, 2),
+        ];
+        assert_eq!(
+            plan(&test_group, &test_members, &test_healthy, &test_none),
+            Plan {
+                launch: 1,
+                ..Plan::default()
+            },
+        );
+        let test_members = vec![
+            test_member("test_old_a", 1, VmState::Running, 3),
+            test_member("test_old_b", 1, VmState::Running, 2),
+            test_member("test_new", 2, VmState::Pending, 1),
+        ];
+        assert_eq!(
+            plan(&test_group, &test_members, &test_healthy, &test_none),
+            Plan::default(),
+        );
+        let test_members = vec![
+            test_member("test_old_a", 1, VmState::Running, 3),
+            test_member("test_old_b", 1, VmState::Running, 2),
+            test_member("test_new", 2, VmState::Running, 1),
+        ];
+        assert_eq!(
+            plan(&test_group, &test_members, &test_healthy, &test_none),
+            Plan {
+                stop: vec![String::from("test_old_b")],
+                ..Plan::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tokio::time::{interval, Duration};
+
+use tonic::server::NamedService;
+
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use tonic_reflection::server::{Builder, ServerReflection, ServerReflectionServer};
+
+use tracing::info;
+
+use crate::external_interface::{External, InterfaceServer as ExternalInterfaceServer};
+use crate::internal_interface::{InterfaceServer as InternalInterfaceServer, Internal};
+use crate::node_registry::NodeRegistry;
+use crate::task_queue::TaskQueues;
+
+const EXTERNAL: &str = <ExternalInterfaceServer<External> as NamedService>::NAME;
+const INTERNAL: &str = <InternalInterfaceServer<Internal> as NamedService>::NAME;
+
+pub struct Readiness {
+    reporter: HealthReporter,
+    node_registry: Arc<NodeRegistry>,
+    task_queues: Arc<TaskQueues>,
+    ready: Option<bool>,
+}
+
+impl Readiness {
+    pub async fn init(
+        reporter: HealthReporter,
+        node_registry: Arc<NodeRegistry>,
+        task_queues: Arc<TaskQueues>,
+    ) -> Result<Readiness, Box<dyn std::error::Error>> {
+        Ok(Readiness {
+            reporter,
+            node_registry,
+            task_queues,
+            ready: None,
+        })
+    }
+
+    pub async fn ready(&self) -> bool {
+        let nodes_registered = !self.node_registry.list().await.is_empty();
+        let scheduler_up = !self.task_queues.connected().await.is_empty();
+
+        nodes_registered && scheduler_up
+    }
+
+    pub async fn report(&mut self) {
+        let ready = self.ready().await;
+
+        if self.ready == Some(ready) {
+            return;
+        }
+
+        let status = match ready {
+            true => ServingStatus::Serving,
+            false => ServingStatus::NotServing,
+        };
+
+        info!(ready, "Readiness changed");
+
+        self.reporter.set_service_status("", status).await;
+        self.reporter.set_service_status(EXTERNAL, status).await;
+        self.reporter
+            .set_service_status(INTERNAL, ServingStatus::Serving)
+            .await;
+
+        self.ready = Some(ready);
+    }
+
+    pub async fn run(
+        mut self,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut probe_interval = interval(Duration::from_secs(1));
+
+        while !*shutdown.borrow_and_update() {
+            tokio::select! {
+                _ = probe_interval.tick() => self.report().await,
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        for service in ["", EXTERNAL, INTERNAL] {
+            self.reporter
+                .set_service_status(service, ServingStatus::NotServing)
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn reflection(
+    descriptors: &'static [&'static [u8]],
+) -> Result<ServerReflectionServer<impl ServerReflection>, Box<dyn std::error::Error>> {
+    let mut builder = Builder::configure()
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET);
+
+    for descriptor in descriptors {
+        builder = builder.register_encoded_file_descriptor_set(descriptor);
+    }
+
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use tonic::transport::{Channel, Server};
+
+    use tonic_health::pb::health_check_response::ServingStatus as Status;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    use tonic_reflection::pb::server_reflection_client::ServerReflectionClient;
+    use tonic_reflection::pb::server_reflection_request::MessageRequest;
+    use tonic_reflection::pb::server_reflection_response::MessageResponse;
+    use tonic_reflection::pb::ServerReflectionRequest;
+
+    use crate::config::TaskQueueConfig;
+    use crate::impulse::shared::v010::NodeInventory;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn report() -> Result<(), Box<dyn std::error::Error>> {
+        let (test_reporter, test_health) = tonic_health::server::health_reporter();
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let mut test_readiness = Readiness::init(
+            test_reporter,
+            test_node_registry.to_owned(),
+            test_task_queues.to_owned(),
+        )
+        .await?;
+        let (test_shutdown, _) = watch::channel(false);
+        let test_signal = test_shutdown.subscribe();
+        let test_server = tokio::spawn(async move {
+            let mut test_signal = test_signal;
+            Server::builder()
+                .add_service(test_health)
+                .serve_with_shutdown("127.0.0.1:48314".parse().unwrap(), async move {
+                    test_signal.changed().await.ok();
+                })
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut test_client = HealthClient::new(
+            Channel::from_static("http://127.0.0.1:48314")
+                .connect()
+                .await?,
+        );
+        let test_check = |service: &str| HealthCheckRequest {
+            service: String::from(service),
+        };
+
+        test_readiness.report().await;
+        assert!(!test_readiness.ready().await);
+        let test_status = test_client.check(test_check("")).await?.into_inner().status;
+        assert_eq!(test_status, Status::NotServing as i32);
+        let test_status = test_client
+            .check(test_check(INTERNAL))
+            .await?
+            .into_inner()
+            .status;
+        assert_eq!(test_status, Status::Serving as i32);
+
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_readiness.report().await;
+        assert!(!test_readiness.ready().await);
+        test_task_queues.open("test_node").await?;
+        test_task_queues.connect("test_node").await;
+        test_readiness.report().await;
+        assert!(test_readiness.ready().await);
+        let test_status = test_client
+            .check(test_check(EXTERNAL))
+            .await?
+            .into_inner()
+            .status;
+        assert_eq!(test_status, Status::Serving as i32);
+
+        test_shutdown.send_replace(true);
+        assert!(test_readiness.run(test_shutdown.subscribe()).await.is_ok());
+        let test_status = test_client.check(test_check("")).await?.into_inner().status;
+        assert_eq!(test_status, Status::NotServing as i32);
+        test_server.await??;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reflection() -> Result<(), Box<dyn std::error::Error>> {
+        let test_reflection = super::reflection(&[
+            crate::impulse::external::v010::FILE_DESCRIPTOR_SET,
+            crate::impulse::shared::v010::FILE_DESCRIPTOR_SET,
+        ])
+        .await?;
+        let (test_shutdown, mut test_signal) = watch::channel(false);
+        let test_server = tokio::spawn(async move {
+            Server::builder()
+                .add_service(test_reflection)
+                .serve_with_shutdown("127.0.0.1:48315".parse().unwrap(), async move {
+                    test_signal.changed().await.ok();
+                })
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut test_client = ServerReflectionClient::new(
+            Channel::from_static("http://127.0.0.1:48315")
+                .connect()
+                .await?,
+        );
+        let test_request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        let mut test_responses = test_client
+            .server_reflection_info(tokio_stream::once(test_request))
+            .await?
+            .into_inner();
+        let test_response = test_responses.message().await?.unwrap();
+        let test_services = match test_response.message_response {
+            Some(MessageResponse::ListServicesResponse(list)) => list
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect::<Vec<String>>(),
+            _ => Vec::with_capacity(0),
+        };
+        assert!(test_services.contains(&String::from(EXTERNAL)));
+        assert!(test_services.contains(&String::from("grpc.health.v1.Health")));
+        assert!(!test_services.contains(&String::from(INTERNAL)));
+        test_shutdown.send_replace(true);
+        test_server.await??;
+        Ok(())
+    }
+}
@@ -1,59 +1,139 @@
+use std::sync::Arc;
+
 use tonic::{Request, Response, Status};
 
 use tokio::sync::broadcast::Sender;
 
 use tokio_stream::wrappers::ReceiverStream;
 
+use tracing::{debug, info, info_span, warn, Instrument};
+
 use uuid::Uuid;
 
-use crate::impulse::internal::v010::{NodeId, SystemId};
+use crate::auth::authorize_node;
+use crate::events::EventLog;
+use crate::impulse::internal::v010::{MicroVmExit, NodeId, NodeRegistration, SystemId, TaskAck};
 use crate::impulse::shared::v010::{MicroVmLaunch, MicroVmShutdown, Task};
-use crate::IMPULSE_INTERFACE;
+use crate::labels;
+use crate::node_registry::NodeRegistry;
+use crate::task_queue::TaskQueues;
+use crate::telemetry;
+use crate::vm_registry::{VmRegistry, VmState};
 
 pub use crate::impulse::internal::v010::interface_server::{Interface, InterfaceServer};
 
 pub struct Internal {
     pub system_id: Uuid,
-    nodes: tokio::sync::Mutex<Vec<String>>,
-    task_sender_clone: Sender<Task>,
+    node_registry: Arc<NodeRegistry>,
+    vm_registry: Arc<VmRegistry>,
+    task_queues: Arc<TaskQueues>,
     launch_result_sender: Sender<MicroVmLaunch>,
     shutdown_result_sender: Sender<MicroVmShutdown>,
+    event_log: Arc<EventLog>,
 }
 
 impl Internal {
     pub async fn init(
-        task_sender_clone: Sender<Task>,
+        task_queues: Arc<TaskQueues>,
         launch_result_sender: Sender<MicroVmLaunch>,
         shutdown_result_sender: Sender<MicroVmShutdown>,
+        node_registry: Arc<NodeRegistry>,
+        vm_registry: Arc<VmRegistry>,
+        event_log: Arc<EventLog>,
     ) -> Result<Internal, Box<dyn std::error::Error>> {
         let system_id = Uuid::new_v4();
-        let nodes = tokio::sync::Mutex::new(Vec::with_capacity(20));
 
         Ok(Internal {
             system_id,
-            nodes,
-            task_sender_clone,
+            node_registry,
+            vm_registry,
+            task_queues,
             launch_result_sender,
             shutdown_result_sender,
+            event_log,
         })
     }
+
+    async fn validate_node_id(node_id: &str) -> Result<(), Status> {
+        match node_id.trim().is_empty() {
+            true => {
+                let message = String::from("Node id is empty... please provide a node id!");
+                let status = Status::new(tonic::Code::InvalidArgument, message);
+                Err(status)
+            }
+            false => Ok(()),
+        }
+    }
+
+    async fn not_registered(node_id: &str) -> Status {
+        let message = format!("Node {} was not found... please register first!", node_id);
+        Status::new(tonic::Code::NotFound, message)
+    }
 }
 
 #[tonic::async_trait]
 impl Interface for Internal {
-    async fn register(&self, request: Request<NodeId>) -> Result<Response<SystemId>, Status> {
-        println!(
-            "{} New register request! | {}",
-            IMPULSE_INTERFACE,
-            request.get_ref().node_id,
-        );
+    async fn register(
+        &self,
+        request: Request<NodeRegistration>,
+    ) -> Result<Response<SystemId>, Status> {
+        info!(node_id = %request.get_ref().node_id, "New register request");
+
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let registration = request.into_inner();
 
-        let mut nodes = self.nodes.lock().await;
-        let node = request.into_inner().node_id;
+        Self::validate_node_id(&registration.node_id).await?;
 
-        nodes.push(node);
+        if let Err(error) = labels::validate(&registration.labels) {
+            return Err(Status::invalid_argument(error.to_string()));
+        }
+
+        let inventory = registration.inventory.unwrap_or_default();
+        let vm_ids = inventory.vm_ids.to_owned();
+        let refreshed = self.node_registry.contains(&registration.node_id).await;
+        let record = self
+            .node_registry
+            .register(&registration.node_id, inventory, registration.labels)
+            .await?;
+
+        match refreshed {
+            true => info!(
+                node_id = %record.node_id,
+                session_id = %record.session_id,
+                "Node session refreshed",
+            ),
+            false => info!(
+                node_id = %record.node_id,
+                session_id = %record.session_id,
+                "Node registered",
+            ),
+        }
+
+        self.task_queues.open(&record.node_id).await?;
+        self.event_log.node_joined(&record.node_id).await;
+
+        let outstanding = self.task_queues.outstanding(&record.node_id).await;
+        let confirmation = self
+            .vm_registry
+            .confirm(&record.node_id, &vm_ids, &outstanding)
+            .await?;
+
+        for vm in confirmation.changed {
+            info!(vm_uuid = %vm.uuid, state = ?vm.state, "MicroVM reconciled with node");
+
+            self.event_log
+                .vm_state(&vm.uuid, &vm.node_id, vm.state.into(), &vm.details)
+                .await;
+        }
 
-        println!("{} Node Registered!", IMPULSE_INTERFACE);
+        for uuid in confirmation.unknown {
+            warn!(
+                vm_uuid = %uuid,
+                node_id = %record.node_id,
+                "Node reported a MicroVM it does not hold... leaving it for cleanup",
+            );
+        }
 
         let system_id = SystemId {
             system_id: self.system_id.to_string(),
@@ -63,57 +143,198 @@ impl Interface for Internal {
         Ok(response)
     }
 
+    async fn update_inventory(
+        &self,
+        request: Request<NodeRegistration>,
+    ) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let registration = request.into_inner();
+
+        Self::validate_node_id(&registration.node_id).await?;
+
+        let inventory = registration.inventory.unwrap_or_default();
+
+        match self
+            .node_registry
+            .update_inventory(&registration.node_id, inventory)
+            .await?
+        {
+            true => {
+                let system_id = SystemId {
+                    system_id: self.system_id.to_string(),
+                };
+
+                Ok(Response::new(system_id))
+            }
+            false => Err(Self::not_registered(&registration.node_id).await),
+        }
+    }
+
     type ControllerStream = ReceiverStream<Result<Task, Status>>;
 
     async fn controller(
         &self,
         request: tonic::Request<NodeId>,
     ) -> Result<tonic::Response<Self::ControllerStream>, tonic::Status> {
-        println!(
-            "{} Node connected and awaiting tasks | {}",
-            IMPULSE_INTERFACE,
-            request.get_ref().node_id,
-        );
+        info!(node_id = %request.get_ref().node_id, "Node connected and awaiting tasks");
 
-        let nodes = self.nodes.lock().await;
+        let node_id = &request.get_ref().node_id;
 
-        if nodes.contains(&request.get_ref().node_id) {
-            let (tx, rx) = tokio::sync::mpsc::channel(4);
-            let mut receiver = self.task_sender_clone.subscribe();
+        Self::validate_node_id(node_id).await?;
+        authorize_node(&request, node_id).await?;
 
-            tokio::spawn(async move {
-                while let Ok(task) = receiver.recv().await {
-                    match task.action {
-                        1 => {
-                            println!("{} Received start instance request", IMPULSE_INTERFACE);
+        let session = match self.node_registry.contains(node_id).await {
+            true => self.task_queues.connect(node_id).await,
+            false => None,
+        };
 
-                            tx.send(Ok(task)).await.unwrap();
+        match session {
+            Some(session) => {
+                let (tx, rx) = tokio::sync::mpsc::channel(4);
+                let task_queues = self.task_queues.clone();
+                let event_log = self.event_log.clone();
+                let node_id = node_id.to_owned();
+                let span = info_span!("controller", node_id = %node_id, session);
+
+                tokio::spawn(
+                    async move {
+                        loop {
+                            let permit = tokio::select! {
+                                _ = tx.closed() => break,
+                                permit = tx.reserve() => match permit {
+                                    Ok(permit) => permit,
+                                    Err(_) => break,
+                                },
+                            };
+
+                            let task = tokio::select! {
+                                _ = tx.closed() => break,
+                                task = task_queues.next(&node_id, session) => match task {
+                                    Some(task) => task,
+                                    None => {
+                                        info!("Node delisted or reconnected... closing controller");
+
+                                        break;
+                                    }
+                                },
+                            };
+
+                            match task.action {
+                                1 => info!(
+                                    task_id = %task.id,
+                                    trace_id = %task.trace_id,
+                                    "Dispatching start instance request",
+                                ),
+                                2 => info!(
+                                    task_id = %task.id,
+                                    trace_id = %task.trace_id,
+                                    "Dispatching shutdown instance request",
+                                ),
+                                _ => (),
+                            }
+
+                            permit.send(Ok(task));
                         }
-                        2 => {
-                            println!("{}Received shutdown instance request", IMPULSE_INTERFACE);
 
-                            tx.send(Ok(task)).await.unwrap();
+                        if task_queues.disconnect(&node_id, session).await {
+                            info!("Controller stream closed");
+
+                            event_log
+                                .node_left(&node_id, "Controller stream closed")
+                                .await;
                         }
-                        _ => (),
                     }
-                }
-            });
+                    .instrument(span),
+                );
 
-            Ok(Response::new(ReceiverStream::new(rx)))
-        } else {
-            let message = String::from("Node was not found... please register first!");
-            let status = Status::new(tonic::Code::NotFound, message);
-            Err(status)
+                Ok(Response::new(ReceiverStream::new(rx)))
+            }
+            None => Err(Self::not_registered(node_id).await),
         }
     }
 
+    async fn acknowledge(&self, request: Request<TaskAck>) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let ack = request.into_inner();
+
+        Self::validate_node_id(&ack.node_id).await?;
+
+        match self
+            .task_queues
+            .acknowledge(&ack.node_id, &ack.task_id)
+            .await?
+        {
+            true => debug!(node_id = %ack.node_id, task_id = %ack.task_id, "Task acknowledged"),
+            false => debug!(
+                node_id = %ack.node_id,
+                task_id = %ack.task_id,
+                "Task was already acknowledged",
+            ),
+        }
+
+        let system_id = SystemId {
+            system_id: self.system_id.to_string(),
+        };
+
+        Ok(Response::new(system_id))
+    }
+
     async fn launch_result(
         &self,
         request: Request<MicroVmLaunch>,
     ) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let trace_id = telemetry::trace_id(request.metadata()).await;
         let task_result = request.into_inner();
+        let span = info_span!(
+            "launch_result",
+            vm_uuid = %task_result.uuid,
+            node_id = %task_result.node_id,
+            %trace_id,
+        );
+
+        async {
+            info!(launched = %task_result.launched, "Launch result received");
 
-        self.launch_result_sender.send(task_result).unwrap();
+            let state = match self.vm_registry.launched(&task_result).await? {
+                Some(state) => state,
+                None => {
+                    debug!("Launch result did not match a live record");
+
+                    return Ok(());
+                }
+            };
+
+            self.event_log
+                .vm_state(
+                    &task_result.uuid,
+                    &task_result.node_id,
+                    state.into(),
+                    &task_result.details,
+                )
+                .await;
+
+            if state == VmState::Failed {
+                self.event_log
+                    .task_failed(
+                        &task_result.uuid,
+                        &task_result.node_id,
+                        &task_result.details,
+                    )
+                    .await;
+            }
+
+            if let Err(error) = self.launch_result_sender.send(task_result) {
+                debug!(result = ?error.0, "No pending request for launch result");
+            }
+
+            Ok::<(), Status>(())
+        }
+        .instrument(span)
+        .await?;
 
         let system_id = SystemId {
             system_id: self.system_id.to_string(),
@@ -127,9 +348,53 @@ impl Interface for Internal {
         &self,
         request: Request<MicroVmShutdown>,
     ) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let trace_id = telemetry::trace_id(request.metadata()).await;
         let task_result = request.into_inner();
+        let span = info_span!(
+            "shutdown_result",
+            vm_uuid = %task_result.uuid,
+            node_id = %task_result.node_id,
+            %trace_id,
+        );
+
+        async {
+            info!(shutdown = %task_result.shutdown, "Shutdown result received");
+
+            match self.vm_registry.shutdown(&task_result).await? {
+                Some(state) => {
+                    self.event_log
+                        .vm_state(
+                            &task_result.uuid,
+                            &task_result.node_id,
+                            state.into(),
+                            &task_result.details,
+                        )
+                        .await;
+                }
+                None if task_result.shutdown == "true" => {
+                    debug!("Shutdown result did not match a live record");
+                }
+                None => {
+                    self.event_log
+                        .task_failed(
+                            &task_result.uuid,
+                            &task_result.node_id,
+                            &task_result.details,
+                        )
+                        .await;
+                }
+            }
 
-        self.shutdown_result_sender.send(task_result).unwrap();
+            if let Err(error) = self.shutdown_result_sender.send(task_result) {
+                debug!(result = ?error.0, "No pending request for shutdown result");
+            }
+
+            Ok::<(), Status>(())
+        }
+        .instrument(span)
+        .await?;
 
         let system_id = SystemId {
             system_id: self.system_id.to_string(),
@@ -139,131 +404,403 @@ impl Interface for Internal {
         Ok(response)
     }
 
-    async fn delist(&self, request: Request<NodeId>) -> Result<Response<SystemId>, Status> {
-        let mut nodes = self.nodes.lock().await;
-        let node_id = request.into_inner().node_id;
+    async fn exit_report(
+        &self,
+        request: Request<MicroVmExit>,
+    ) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
 
-        match nodes.binary_search(&node_id) {
-            Ok(node) => {
-                let element = &nodes.get(node).unwrap();
-                println!("removing node... {}", element);
-                nodes.remove(node);
-                println!("node removed...");
+        let trace_id = telemetry::trace_id(request.metadata()).await;
+        let exit = request.into_inner();
 
-                let response = SystemId {
-                    system_id: self.system_id.to_string(),
-                };
+        Self::validate_node_id(&exit.node_id).await?;
 
-                Ok(Response::new(response))
+        let span = info_span!(
+            "exit_report",
+            vm_uuid = %exit.uuid,
+            node_id = %exit.node_id,
+            %trace_id,
+        );
+
+        async {
+            info!(
+                reason = %exit.reason,
+                exit_code = exit.exit_code,
+                failed = exit.failed,
+                restarting = exit.restarting,
+                "MicroVM exit reported",
+            );
+
+            match self.vm_registry.exited(&exit).await? {
+                Some(record) => {
+                    self.event_log
+                        .vm_state(
+                            &record.uuid,
+                            &record.node_id,
+                            record.state.into(),
+                            &record.details,
+                        )
+                        .await;
+                }
+                None => debug!("MicroVM exit did not match a live record"),
             }
-            Err(error) => {
-                println!("{}", error);
-                let message = format!("Node {} was not found... please try again!", &node_id);
-                let status = Status::new(tonic::Code::NotFound, message);
-                Err(status)
+
+            Ok::<(), Status>(())
+        }
+        .instrument(span)
+        .await?;
+
+        let system_id = SystemId {
+            system_id: self.system_id.to_string(),
+        };
+
+        Ok(Response::new(system_id))
+    }
+
+    async fn delist(&self, request: Request<NodeId>) -> Result<Response<SystemId>, Status> {
+        authorize_node(&request, &request.get_ref().node_id).await?;
+
+        let node_id = request.into_inner().node_id;
+
+        Self::validate_node_id(&node_id).await?;
+
+        match self.node_registry.remove(&node_id).await? {
+            Some(record) => {
+                info!(
+                    node_id = %record.node_id,
+                    session_id = %record.session_id,
+                    "Node delisted",
+                );
+
+                for task in self.task_queues.close(&node_id).await? {
+                    self.event_log
+                        .task_failed(
+                            &task.id,
+                            &node_id,
+                            "Node delisted before acknowledging task",
+                        )
+                        .await;
+                }
+
+                self.event_log.node_left(&node_id, "Node delisted").await;
             }
+            None => warn!(%node_id, "Node was already delisted"),
         }
+
+        let response = SystemId {
+            system_id: self.system_id.to_string(),
+        };
+
+        Ok(Response::new(response))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{EventsConfig, TaskQueueConfig};
+    use crate::impulse::shared::v010::NodeInventory;
+    use crate::tenants::Claim;
+    use crate::vm_registry::VmSpec;
+    use std::collections::HashMap;
     use std::str::FromStr;
 
     #[tokio::test(flavor = "multi_thread")]
     async fn init() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_internal =
-            Internal::init(test_tx, test_response_sender, test_shutdown_result_sender).await?;
-        let test_nodes = test_internal.nodes.lock().await;
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
         assert_eq!(test_internal.system_id.get_version_num(), 4);
-        assert_eq!(test_nodes.len(), 0);
-        assert_eq!(test_nodes.capacity(), 20);
+        assert!(test_internal.node_registry.list().await.is_empty());
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn register() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_internal =
-            Internal::init(test_tx, test_response_sender, test_shutdown_result_sender).await?;
-        let test_nodes = test_internal.nodes.lock().await;
-        assert_eq!(test_nodes.len(), 0);
-        drop(test_nodes);
-        let test_request = Request::new(NodeId {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        assert!(test_internal.node_registry.list().await.is_empty());
+        let test_request = Request::new(NodeRegistration {
             node_id: String::from("test_uuid"),
+            labels: HashMap::from([(String::from("zone"), String::from("a"))]),
+            inventory: Some(NodeInventory {
+                cpu_count: 2,
+                ..NodeInventory::default()
+            }),
         });
         let test_internal_register = test_internal.register(test_request).await?;
         let test_internal_register_uuid =
             Uuid::from_str(test_internal_register.get_ref().system_id.as_str()).unwrap();
         assert_eq!(test_internal_register_uuid.get_version_num(), 4);
-        let test_nodes = test_internal.nodes.lock().await;
+        let test_nodes = test_internal.node_registry.list().await;
         assert_eq!(test_nodes.len(), 1);
+        assert_eq!(test_nodes[0].inventory.cpu_count, 2);
+        assert_eq!(test_nodes[0].labels["zone"], "a");
+        assert!(test_internal
+            .task_queues
+            .connect("test_uuid")
+            .await
+            .is_some());
+        test_internal
+            .vm_registry
+            .pending("test_vm_lost", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_internal
+            .vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_vm_lost"),
+                launched: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_uuid"),
+            })
+            .await?;
+        test_internal
+            .vm_registry
+            .pending("test_vm_kept", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_internal
+            .vm_registry
+            .assign("test_vm_kept", "test_uuid")
+            .await?;
+        let test_request = Request::new(NodeRegistration {
+            node_id: String::from("test_uuid"),
+            labels: HashMap::new(),
+            inventory: Some(NodeInventory {
+                cpu_count: 4,
+                vm_ids: vec![String::from("test_vm_kept"), String::from("test_vm_stray")],
+                ..NodeInventory::default()
+            }),
+        });
+        test_internal.register(test_request).await?;
+        let test_refreshed_nodes = test_internal.node_registry.list().await;
+        assert_eq!(test_refreshed_nodes.len(), 1);
+        assert_eq!(test_refreshed_nodes[0].inventory.cpu_count, 4);
+        assert_ne!(test_refreshed_nodes[0].session_id, test_nodes[0].session_id,);
+        let test_lost = test_internal.vm_registry.get("test_vm_lost").await.unwrap();
+        assert_eq!(test_lost.state, VmState::Failed);
+        let test_kept = test_internal.vm_registry.get("test_vm_kept").await.unwrap();
+        assert_eq!(test_kept.state, VmState::Running);
+        assert!(test_internal
+            .vm_registry
+            .get("test_vm_stray")
+            .await
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_status() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let test_request = Request::new(NodeRegistration {
+            node_id: String::from(" "),
+            labels: HashMap::new(),
+            inventory: None,
+        });
+        let test_internal_register = test_internal.register(test_request).await;
+        assert_eq!(
+            test_internal_register.as_ref().unwrap_err().code(),
+            tonic::Code::InvalidArgument,
+        );
+        assert_eq!(
+            test_internal_register.as_ref().unwrap_err().message(),
+            "Node id is empty... please provide a node id!",
+        );
+        let test_request = Request::new(NodeRegistration {
+            node_id: String::from("test_uuid"),
+            labels: HashMap::from([(String::from("zone"), String::from("a b"))]),
+            inventory: None,
+        });
+        let test_internal_register = test_internal.register(test_request).await;
+        assert_eq!(
+            test_internal_register.unwrap_err().code(),
+            tonic::Code::InvalidArgument,
+        );
+        assert!(test_internal.node_registry.list().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn update_inventory() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let test_request = Request::new(NodeRegistration {
+            node_id: String::from("test_uuid"),
+            labels: HashMap::new(),
+            inventory: Some(NodeInventory::default()),
+        });
+        let test_internal_update = test_internal.update_inventory(test_request).await;
+        assert_eq!(
+            test_internal_update.as_ref().unwrap_err().code(),
+            tonic::Code::NotFound,
+        );
+        test_internal
+            .node_registry
+            .register("test_uuid", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_request = Request::new(NodeRegistration {
+            node_id: String::from("test_uuid"),
+            labels: HashMap::new(),
+            inventory: Some(NodeInventory {
+                images: vec![String::from("test_root_fs")],
+                ..NodeInventory::default()
+            }),
+        });
+        test_internal.update_inventory(test_request).await?;
+        let test_nodes = test_internal.node_registry.list().await;
+        assert_eq!(
+            test_nodes[0].inventory.images,
+            vec![String::from("test_root_fs")]
+        );
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn controller_response() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_tx_clone = test_tx.clone();
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_task_queues_clone = test_task_queues.clone();
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
         let test_internal = Internal::init(
-            test_tx_clone,
+            test_task_queues_clone,
             test_response_sender,
             test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
         )
         .await?;
-        let mut test_nodes = test_internal.nodes.lock().await;
-        test_nodes.push(String::from("test_uuid"));
-        drop(test_nodes);
+        test_internal
+            .node_registry
+            .register("test_uuid", NodeInventory::default(), HashMap::new())
+            .await?;
         let test_request = Request::new(NodeId {
             node_id: String::from("test_uuid"),
         });
+        test_task_queues.open("test_uuid").await?;
         let test_internal_controller = test_internal.controller(test_request).await?;
         let test_task = Task {
             action: 1,
             id: Uuid::new_v4().simple().to_string(),
+            trace_id: String::from("test_trace_id"),
+            machine: None,
         };
-        test_tx.send(test_task).unwrap();
-        drop(test_tx);
-        drop(test_internal);
+        test_task_queues.enqueue("test_uuid", test_task).await?;
         let mut test_internal_controller_receiver = test_internal_controller.into_inner();
-        while let Some(task) = test_internal_controller_receiver.as_mut().recv().await {
-            match task.as_ref().unwrap().id.as_str() {
-                "1" => assert_eq!(task.unwrap().action, 1),
-                "2" => assert_eq!(task.unwrap().action, 2),
-                _ => (),
-            }
-        }
+        let test_received = test_internal_controller_receiver
+            .as_mut()
+            .recv()
+            .await
+            .unwrap()?;
+        assert_eq!(test_received.action, 1);
+        assert_eq!(test_received.trace_id.as_str(), "test_trace_id");
+        let test_request = Request::new(TaskAck {
+            node_id: String::from("test_uuid"),
+            task_id: test_received.id,
+        });
+        test_internal.acknowledge(test_request).await?;
+        assert_eq!(test_task_queues.depth().await, 0);
+
+        let test_task = Task {
+            action: 2,
+            id: String::from("test_vm"),
+            trace_id: String::from("test_trace_id"),
+            machine: None,
+        };
+        test_task_queues.enqueue("test_uuid", test_task).await?;
+        let test_received = test_internal_controller_receiver
+            .as_mut()
+            .recv()
+            .await
+            .unwrap()?;
+        assert_eq!(test_received.id.as_str(), "test_vm");
+        drop(test_internal_controller_receiver);
+        let test_request = Request::new(NodeId {
+            node_id: String::from("test_uuid"),
+        });
+        let test_internal_controller = test_internal.controller(test_request).await?;
+        let mut test_internal_controller_receiver = test_internal_controller.into_inner();
+        let test_redelivered = test_internal_controller_receiver
+            .as_mut()
+            .recv()
+            .await
+            .unwrap()?;
+        assert_eq!(test_redelivered.id.as_str(), "test_vm");
+        assert_eq!(test_redelivered.action, 2);
+        assert_eq!(test_task_queues.depth().await, 1);
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn controller_status() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_tx_clone = test_tx.clone();
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_task_queues_clone = test_task_queues.clone();
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
         let test_internal = Internal::init(
-            test_tx_clone,
+            test_task_queues_clone,
             test_response_sender,
             test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
         )
         .await?;
         let test_request = Request::new(NodeId {
             node_id: String::from("test_uuid"),
         });
-        let test_task = Task {
-            action: 1,
-            id: Uuid::new_v4().simple().to_string(),
-        };
-        test_tx.send(test_task).unwrap();
+        test_task_queues.open("test_uuid").await?;
         let test_internal_controller = test_internal.controller(test_request).await;
         assert_eq!(
             test_internal_controller.as_ref().unwrap_err().code(),
@@ -271,11 +808,119 @@ mod tests {
         );
         assert_eq!(
             test_internal_controller.as_ref().unwrap_err().message(),
-            "Node was not found... please register first!",
+            "Node test_uuid was not found... please register first!",
         );
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_result_without_request() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, test_response_rx) = tokio::sync::broadcast::channel(1);
+        let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        drop(test_response_rx);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let test_launch = MicroVmLaunch {
+            uuid: String::from("test_uuid"),
+            launched: true.to_string(),
+            details: String::from("held while disconnected"),
+            node_id: String::from("test_node"),
+        };
+        let test_unknown = test_internal
+            .launch_result(Request::new(test_launch.to_owned()))
+            .await
+            .unwrap_err();
+        assert_eq!(test_unknown.code(), tonic::Code::NotFound);
+        assert!(test_internal.vm_registry.list().await.is_empty());
+        test_internal
+            .vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_internal
+            .vm_registry
+            .assign("test_uuid", "test_node")
+            .await?;
+        let test_foreign = test_internal
+            .launch_result(Request::new(MicroVmLaunch {
+                node_id: String::from("test_other_node"),
+                ..test_launch.to_owned()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(test_foreign.code(), tonic::Code::PermissionDenied);
+        let test_request = Request::new(test_launch);
+        let test_internal_launch_result = test_internal.launch_result(test_request).await?;
+        let test_internal_launch_result_uuid =
+            Uuid::from_str(test_internal_launch_result.get_ref().system_id.as_str()).unwrap();
+        assert_eq!(test_internal_launch_result_uuid.get_version_num(), 4);
+        let test_record = test_internal.vm_registry.list().await.remove(0);
+        assert_eq!(test_record.node_id.as_str(), "test_node");
+        assert_eq!(test_record.state, VmState::Running);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exit_report() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_event_log = Arc::new(EventLog::init(&EventsConfig::default()).await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            test_event_log,
+        )
+        .await?;
+        test_internal
+            .vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_internal
+            .launch_result(Request::new(MicroVmLaunch {
+                uuid: String::from("test_uuid"),
+                launched: true.to_string(),
+                details: String::from("launched"),
+                node_id: String::from("test_node"),
+            }))
+            .await?;
+        let test_request = Request::new(MicroVmExit {
+            node_id: String::from("test_node"),
+            uuid: String::from("test_uuid"),
+            reason: String::from("was killed by signal 9"),
+            exit_code: 9,
+            failed: true,
+            restarting: false,
+            restarts: 0,
+        });
+        test_internal.exit_report(test_request).await?;
+        let test_record = test_internal.vm_registry.get("test_uuid").await.unwrap();
+        assert_eq!(test_record.state, VmState::Failed);
+        assert_eq!(
+            test_record.details.as_str(),
+            "MicroVM was killed by signal 9"
+        );
+        let test_empty = test_internal
+            .exit_report(Request::new(MicroVmExit::default()))
+            .await;
+        assert_eq!(test_empty.unwrap_err().code(), tonic::Code::InvalidArgument);
+        Ok(())
+    }
+
     // #[tokio::test(flavor = "multi_thread")]
     // async fn result() -> Result<(), Box<dyn std::error::Error>> {
     //     let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
@@ -296,52 +941,83 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn delist_response() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_internal =
-            Internal::init(test_tx, test_response_sender, test_shutdown_result_sender).await?;
-        let mut test_nodes = test_internal.nodes.lock().await;
-        test_nodes.push(String::from("test_uuid"));
-        assert_eq!(test_nodes.len(), 1);
-        drop(test_nodes);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        for test_node in ["test_uuid_c", "test_uuid_a", "test_uuid_b"] {
+            test_internal
+                .node_registry
+                .register(test_node, NodeInventory::default(), HashMap::new())
+                .await?;
+        }
+        assert_eq!(test_internal.node_registry.list().await.len(), 3);
         let test_request = Request::new(NodeId {
-            node_id: String::from("test_uuid"),
+            node_id: String::from("test_uuid_c"),
         });
         let test_internal_delist = test_internal.delist(test_request).await?;
         let test_internal_delist_uuid =
             Uuid::from_str(test_internal_delist.get_ref().system_id.as_str()).unwrap();
         assert_eq!(test_internal_delist_uuid.get_version_num(), 4);
-        let test_nodes = test_internal.nodes.lock().await;
-        assert_eq!(test_nodes.len(), 0);
+        let test_nodes = test_internal.node_registry.list().await;
+        assert_eq!(test_nodes.len(), 2);
+        assert!(!test_internal.node_registry.contains("test_uuid_c").await);
+        let test_request = Request::new(NodeId {
+            node_id: String::from("test_uuid_c"),
+        });
+        assert!(test_internal.delist(test_request).await.is_ok());
+        assert_eq!(test_internal.node_registry.list().await.len(), 2);
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn delist_status() -> Result<(), Box<dyn std::error::Error>> {
-        let (test_tx, _test_rx) = tokio::sync::broadcast::channel(1);
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
         let (test_response_sender, _test_rx) = tokio::sync::broadcast::channel(1);
         let (test_shutdown_result_sender, _test_rx) = tokio::sync::broadcast::channel(1);
-        let test_internal =
-            Internal::init(test_tx, test_response_sender, test_shutdown_result_sender).await?;
-        let mut test_nodes = test_internal.nodes.lock().await;
-        test_nodes.push(String::from("test_uuid"));
-        assert_eq!(test_nodes.len(), 1);
-        drop(test_nodes);
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_internal = Internal::init(
+            test_task_queues,
+            test_response_sender,
+            test_shutdown_result_sender,
+            test_node_registry,
+            test_vm_registry,
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        test_internal
+            .node_registry
+            .register("test_uuid", NodeInventory::default(), HashMap::new())
+            .await?;
         let test_request = Request::new(NodeId {
-            node_id: String::from("not test_uuid"),
+            node_id: String::from(""),
         });
         let test_internal_delist = test_internal.delist(test_request).await;
         assert_eq!(
             test_internal_delist.as_ref().unwrap_err().code(),
-            tonic::Code::NotFound,
+            tonic::Code::InvalidArgument,
         );
         assert_eq!(
             test_internal_delist.as_ref().unwrap_err().message(),
-            "Node not test_uuid was not found... please try again!",
+            "Node id is empty... please provide a node id!",
         );
-        let test_nodes = test_internal.nodes.lock().await;
-        assert_eq!(test_nodes.len(), 1);
+        let test_request = Request::new(NodeId {
+            node_id: String::from("not test_uuid"),
+        });
+        assert!(test_internal.delist(test_request).await.is_ok());
+        assert!(test_internal.node_registry.contains("test_uuid").await);
+        assert_eq!(test_internal.node_registry.list().await.len(), 1);
         Ok(())
     }
 }
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::system_error::SystemError;
+
+const MAX_KEY_LENGTH: usize = 253;
+const MAX_VALUE_LENGTH: usize = 63;
+const MAX_ANNOTATION_LENGTH: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Requirement {
+    Equals(String, String),
+    NotEquals(String, String),
+    In(String, Vec<String>),
+    NotIn(String, Vec<String>),
+    Exists(String),
+    Absent(String),
+}
+
+impl Requirement {
+    fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        match self {
+            Requirement::Equals(key, value) => labels.get(key) == Some(value),
+            Requirement::NotEquals(key, value) => labels.get(key) != Some(value),
+            Requirement::In(key, values) => labels.get(key).is_some_and(|v| values.contains(v)),
+            Requirement::NotIn(key, values) => !labels.get(key).is_some_and(|v| values.contains(v)),
+            Requirement::Exists(key) => labels.contains_key(key),
+            Requirement::Absent(key) => !labels.contains_key(key),
+        }
+    }
+}
+
+impl FromStr for Requirement {
+    type Err = SystemError;
+
+    fn from_str(requirement: &str) -> Result<Requirement, SystemError> {
+        let requirement = requirement.trim();
+
+        if let Some(key) = requirement.strip_prefix('!') {
+            return Ok(Requirement::Absent(valid_key(key.trim())?));
+        }
+
+        if let Some((key, values)) = requirement.split_once('(') {
+            let (key, operator) = match key.trim().rsplit_once(char::is_whitespace) {
+                Some((key, operator)) => (key.trim(), operator),
+                None => {
+                    let message = format!("Selector {} is missing in or notin!", requirement);
+
+                    return Err(SystemError::new(&message));
+                }
+            };
+            let values = match values.trim().strip_suffix(')') {
+                Some(values) => values
+                    .split(',')
+                    .map(|v| valid_value(v.trim()))
+                    .collect::<Result<Vec<String>, SystemError>>()?,
+                None => {
+                    let message = format!("Selector {} is missing a closing )!", requirement);
+
+                    return Err(SystemError::new(&message));
+                }
+            };
+
+            return match operator {
+                "in" => Ok(Requirement::In(valid_key(key)?, values)),
+                "notin" => Ok(Requirement::NotIn(valid_key(key)?, values)),
+                _ => {
+                    let message = format!("Selector operator {} is not in or notin!", operator);
+
+                    Err(SystemError::new(&message))
+                }
+            };
+        }
+
+        if let Some((k, v)) = requirement.split_once("!=") {
+            return Ok(Requirement::NotEquals(
+                valid_key(k.trim())?,
+                valid_value(v.trim())?,
+            ));
+        }
+
+        if let Some((k, v)) = requirement
+            .split_once("==")
+            .or_else(|| requirement.split_once('='))
+        {
+            return Ok(Requirement::Equals(
+                valid_key(k.trim())?,
+                valid_value(v.trim())?,
+            ));
+        }
+
+        Ok(Requirement::Exists(valid_key(requirement)?))
+    }
+}
+
+impl Display for Requirement {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Requirement::Equals(key, value) => write!(f, "{}={}", key, value),
+            Requirement::NotEquals(key, value) => write!(f, "{}!={}", key, value),
+            Requirement::In(key, values) => write!(f, "{} in ({})", key, values.join(",")),
+            Requirement::NotIn(key, values) => write!(f, "{} notin ({})", key, values.join(",")),
+            Requirement::Exists(key) => write!(f, "{}", key),
+            Requirement::Absent(key) => write!(f, "!{}", key),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selector {
+    requirements: Vec<Requirement>,
+}
+
+impl Selector {
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+    }
+
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.requirements
+            .iter()
+            .all(|requirement| requirement.matches(labels))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SystemError;
+
+    fn from_str(selector: &str) -> Result<Selector, SystemError> {
+        let mut requirements = Vec::with_capacity(4);
+        let mut depth = 0;
+        let mut start = 0;
+
+        for (index, character) in selector.char_indices() {
+            match character {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    requirements.push(selector[start..index].parse()?);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+
+        if !selector[start..].trim().is_empty() {
+            requirements.push(selector[start..].parse()?);
+        } else if start > 0 {
+            let message = format!("Selector {} ends with an empty requirement!", selector);
+
+            return Err(SystemError::new(&message));
+        }
+
+        Ok(Selector { requirements })
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let requirements: Vec<String> = self
+            .requirements
+            .iter()
+            .map(Requirement::to_string)
+            .collect();
+
+        write!(f, "{}", requirements.join(","))
+    }
+}
+
+pub fn validate(labels: &HashMap<String, String>) -> Result<(), SystemError> {
+    for (k, v) in labels {
+        valid_key(k)?;
+        valid_value(v)?;
+    }
+
+    Ok(())
+}
+
+pub fn validate_keys(keys: &[String]) -> Result<(), SystemError> {
+    for k in keys {
+        valid_key(k)?;
+    }
+
+    Ok(())
+}
+
+pub fn validate_annotations(annotations: &HashMap<String, String>) -> Result<(), SystemError> {
+    for (k, v) in annotations {
+        valid_key(k)?;
+
+        if v.len() > MAX_ANNOTATION_LENGTH {
+            let message = format!(
+                "Annotation {} is longer than {} bytes!",
+                k, MAX_ANNOTATION_LENGTH,
+            );
+
+            return Err(SystemError::new(&message));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn parse_label(label: &str) -> Result<(String, String), SystemError> {
+    match label.split_once('=') {
+        Some((k, v)) => Ok((valid_key(k.trim())?, valid_value(v.trim())?)),
+        None => {
+            let message = format!("Label {} is not key=value!", label);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+pub fn parse_annotation(annotation: &str) -> Result<(String, String), SystemError> {
+    match annotation.split_once('=') {
+        Some((k, v)) => Ok((valid_key(k.trim())?, v.to_string())),
+        None => {
+            let message = format!("Annotation {} is not key=value!", annotation);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+fn valid_key(key: &str) -> Result<String, SystemError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+
+    match valid {
+        true => Ok(key.to_string()),
+        false => {
+            let message = format!("Label key {:?} is not a valid key!", key);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+fn valid_value(value: &str) -> Result<String, SystemError> {
+    let valid = value.len() <= MAX_VALUE_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    match valid {
+        true => Ok(value.to_string()),
+        false => {
+            let message = format!("Label value {:?} is not a valid value!", value);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse() -> Result<(), Box<dyn std::error::Error>> {
+        let test_selector: Selector =
+            "env=prod, tier!=db,zone in (a, b),region notin (x),gpu,!legacy".parse()?;
+        assert_eq!(test_selector.requirements.len(), 6);
+        assert_eq!(
+            test_selector.to_string(),
+            "env=prod,tier!=db,zone in (a,b),region notin (x),gpu,!legacy",
+        );
+        let test_selector: Selector = "env==prod".parse()?;
+        assert_eq!(test_selector.to_string(), "env=prod");
+        assert!("".parse::<Selector>()?.is_empty());
+        assert!("env=prod,".parse::<Selector>().is_err());
+        assert!("zone in (a,b".parse::<Selector>().is_err());
+        assert!("zone within (a)".parse::<Selector>().is_err());
+        assert!("(a)".parse::<Selector>().is_err());
+        assert!("env=pr od".parse::<Selector>().is_err());
+        assert!("=prod".parse::<Selector>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn matches() -> Result<(), Box<dyn std::error::Error>> {
+        let test_prod = test_labels(&[("env", "prod"), ("zone", "a"), ("gpu", "")]);
+        let test_dev = test_labels(&[("env", "dev"), ("zone", "c"), ("legacy", "true")]);
+        let test_selector: Selector = "env=prod".parse()?;
+        assert!(test_selector.matches(&test_prod));
+        assert!(!test_selector.matches(&test_dev));
+        let test_selector: Selector = "env!=prod".parse()?;
+        assert!(test_selector.matches(&test_dev));
+        assert!(test_selector.matches(&HashMap::new()));
+        let test_selector: Selector = "zone in (a,b),gpu".parse()?;
+        assert!(test_selector.matches(&test_prod));
+        assert!(!test_selector.matches(&test_dev));
+        let test_selector: Selector = "zone notin (a,b),!gpu".parse()?;
+        assert!(!test_selector.matches(&test_prod));
+        assert!(test_selector.matches(&test_dev));
+        let test_selector: Selector = "!legacy".parse()?;
+        assert!(test_selector.matches(&test_prod));
+        assert!(!test_selector.matches(&test_dev));
+        assert!(Selector::default().matches(&test_dev));
+        Ok(())
+    }
+
+    #[test]
+    fn labels() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            parse_label("example.com/tier=web")?,
+            (String::from("example.com/tier"), String::from("web")),
+        );
+        assert_eq!(parse_label("gpu=")?, (String::from("gpu"), String::new()));
+        assert!(parse_label("gpu").is_err());
+        assert!(parse_label("tier=a/b").is_err());
+        assert_eq!(
+            parse_annotation("note=free text, a=b")?,
+            (String::from("note"), String::from("free text, a=b")),
+        );
+        assert!(parse_annotation("free text").is_err());
+        assert!(validate(&test_labels(&[("env", "prod")])).is_ok());
+        assert!(validate(&test_labels(&[("", "prod")])).is_err());
+        assert!(validate(&test_labels(&[("env", &"x".repeat(64))])).is_err());
+        assert!(validate_keys(&[String::from("env"), String::from("tier")]).is_ok());
+        assert!(validate_keys(&[String::from("env tier")]).is_err());
+        assert!(validate_annotations(&test_labels(&[("note", "free text, any=chars")])).is_ok());
+        assert!(validate_annotations(&test_labels(&[("note", &"x".repeat(4097))])).is_err());
+        Ok(())
+    }
+}
@@ -1,28 +1,63 @@
 pub mod actuator_client;
 pub mod actuator_engine;
+pub mod audit;
+pub mod auth;
+pub mod cluster;
+pub mod config;
+pub mod events;
+pub mod external_client;
 pub mod external_interface;
+pub mod groups;
+pub mod health;
 pub mod internal_interface;
+pub mod labels;
+pub mod metrics;
+pub mod node_registry;
+pub(crate) mod placement;
+pub mod policy;
+pub mod profiles;
+pub mod runtime;
+pub mod store;
 pub(crate) mod system_error;
+pub mod task_queue;
+pub mod telemetry;
+pub mod tenants;
+pub mod vm_registry;
 
-pub const IMPULSE_ACTUATOR: &str = ":: i m p u l s e _ a c t u a t o r >";
-pub const IMPULSE_INTERFACE: &str = ":: i m p u l s e _ i n t e r f a c e >";
+pub mod impulse {
+    pub mod cluster {
+        pub mod v010 {
+            include!("../../proto/impulse.cluster.v010.rs");
 
-pub(crate) mod impulse {
-    pub(crate) mod external {
-        pub(crate) mod v010 {
+            pub const FILE_DESCRIPTOR_SET: &[u8] =
+                include_bytes!(concat!(env!("OUT_DIR"), "/impulse_cluster_v010.bin"));
+        }
+    }
+
+    pub mod external {
+        pub mod v010 {
             include!("../../proto/impulse.external.v010.rs");
+
+            pub const FILE_DESCRIPTOR_SET: &[u8] =
+                include_bytes!(concat!(env!("OUT_DIR"), "/impulse_external_v010.bin"));
         }
     }
 
-    pub(crate) mod internal {
-        pub(crate) mod v010 {
+    pub mod internal {
+        pub mod v010 {
             include!("../../proto/impulse.internal.v010.rs");
+
+            pub const FILE_DESCRIPTOR_SET: &[u8] =
+                include_bytes!(concat!(env!("OUT_DIR"), "/impulse_internal_v010.bin"));
         }
     }
 
-    pub(crate) mod shared {
-        pub(crate) mod v010 {
+    pub mod shared {
+        pub mod v010 {
             include!("../../proto/impulse.shared.v010.rs");
+
+            pub const FILE_DESCRIPTOR_SET: &[u8] =
+                include_bytes!(concat!(env!("OUT_DIR"), "/impulse_shared_v010.bin"));
         }
     }
 }
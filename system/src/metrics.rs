@@ -0,0 +1,428 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use tracing::{error, info};
+
+use crate::node_registry::NodeRegistry;
+use crate::vm_registry::{VmRegistry, VmState};
+
+pub static INTERFACE: LazyLock<InterfaceMetrics> =
+    LazyLock::new(|| InterfaceMetrics::init().expect("interface metrics are valid"));
+
+pub static ACTUATOR: LazyLock<ActuatorMetrics> =
+    LazyLock::new(|| ActuatorMetrics::init().expect("actuator metrics are valid"));
+
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+pub struct InterfaceMetrics {
+    registry: Registry,
+    pub launches: IntCounterVec,
+    pub launch_duration: Histogram,
+    pub shutdowns: IntCounterVec,
+    pub shutdown_duration: Histogram,
+    pub running_vms: IntGauge,
+    pub connected_nodes: IntGauge,
+    pub node_cpus: IntGaugeVec,
+    pub node_memory_available: IntGaugeVec,
+    pub node_disk_free: IntGaugeVec,
+    pub node_running_vms: IntGaugeVec,
+    pub node_draining: IntGaugeVec,
+}
+
+impl InterfaceMetrics {
+    fn init() -> Result<InterfaceMetrics, prometheus::Error> {
+        let registry = Registry::new_custom(Some(String::from("impulse_interface")), None)?;
+
+        let launches = IntCounterVec::new(
+            Opts::new("launches_total", "MicroVM launch requests by result"),
+            &["result"],
+        )?;
+        let launch_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "launch_duration_seconds",
+                "Time from launch request to node result",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        let shutdowns = IntCounterVec::new(
+            Opts::new("shutdowns_total", "MicroVM shutdown requests by result"),
+            &["result"],
+        )?;
+        let shutdown_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "shutdown_duration_seconds",
+                "Time from shutdown request to node result",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        let running_vms = IntGauge::new("running_vms", "MicroVMs in the running state")?;
+        let connected_nodes = IntGauge::new("connected_nodes", "Registered actuator nodes")?;
+        let node_cpus = IntGaugeVec::new(
+            Opts::new("node_cpus", "CPUs reported by the node"),
+            &["node_id"],
+        )?;
+        let node_memory_available = IntGaugeVec::new(
+            Opts::new(
+                "node_memory_available_bytes",
+                "Available memory reported by the node",
+            ),
+            &["node_id"],
+        )?;
+        let node_disk_free = IntGaugeVec::new(
+            Opts::new("node_disk_free_bytes", "Free disk reported by the node"),
+            &["node_id"],
+        )?;
+        let node_running_vms = IntGaugeVec::new(
+            Opts::new("node_running_vms", "MicroVMs running on the node"),
+            &["node_id"],
+        )?;
+        let node_draining = IntGaugeVec::new(
+            Opts::new("node_draining", "Whether the node is draining"),
+            &["node_id"],
+        )?;
+
+        registry.register(Box::new(launches.to_owned()))?;
+        registry.register(Box::new(launch_duration.to_owned()))?;
+        registry.register(Box::new(shutdowns.to_owned()))?;
+        registry.register(Box::new(shutdown_duration.to_owned()))?;
+        registry.register(Box::new(running_vms.to_owned()))?;
+        registry.register(Box::new(connected_nodes.to_owned()))?;
+        registry.register(Box::new(node_cpus.to_owned()))?;
+        registry.register(Box::new(node_memory_available.to_owned()))?;
+        registry.register(Box::new(node_disk_free.to_owned()))?;
+        registry.register(Box::new(node_running_vms.to_owned()))?;
+        registry.register(Box::new(node_draining.to_owned()))?;
+
+        Ok(InterfaceMetrics {
+            registry,
+            launches,
+            launch_duration,
+            shutdowns,
+            shutdown_duration,
+            running_vms,
+            connected_nodes,
+            node_cpus,
+            node_memory_available,
+            node_disk_free,
+            node_running_vms,
+            node_draining,
+        })
+    }
+
+    pub async fn observe_launch(&self, result: &str, latency: Duration) {
+        self.launches.with_label_values(&[result]).inc();
+        self.launch_duration.observe(latency.as_secs_f64());
+    }
+
+    pub async fn observe_shutdown(&self, result: &str, latency: Duration) {
+        self.shutdowns.with_label_values(&[result]).inc();
+        self.shutdown_duration.observe(latency.as_secs_f64());
+    }
+
+    async fn refresh(&self, node_registry: &NodeRegistry, vm_registry: &VmRegistry) {
+        let nodes = node_registry.list().await;
+        let vms = vm_registry.list().await;
+
+        self.connected_nodes.set(nodes.len() as i64);
+        self.node_cpus.reset();
+        self.node_memory_available.reset();
+        self.node_disk_free.reset();
+        self.node_running_vms.reset();
+        self.node_draining.reset();
+
+        for node in &nodes {
+            let labels = [node.node_id.as_str()];
+
+            self.node_cpus
+                .with_label_values(&labels)
+                .set(node.inventory.cpu_count as i64);
+            self.node_memory_available
+                .with_label_values(&labels)
+                .set(node.inventory.memory_available as i64);
+            self.node_disk_free
+                .with_label_values(&labels)
+                .set(node.inventory.disk_free as i64);
+            self.node_running_vms.with_label_values(&labels).set(0);
+            self.node_draining
+                .with_label_values(&labels)
+                .set(node.draining as i64);
+        }
+
+        let running: Vec<_> = vms
+            .iter()
+            .filter(|vm| vm.state == VmState::Running)
+            .collect();
+
+        self.running_vms.set(running.len() as i64);
+
+        for vm in running {
+            if nodes.iter().any(|node| node.node_id == vm.node_id) {
+                self.node_running_vms
+                    .with_label_values(&[vm.node_id.as_str()])
+                    .inc();
+            }
+        }
+    }
+}
+
+pub struct ActuatorMetrics {
+    registry: Registry,
+    pub launches: IntCounterVec,
+    pub launch_duration: Histogram,
+    pub shutdowns: IntCounterVec,
+    pub shutdown_duration: Histogram,
+    pub running_vms: IntGauge,
+    pub exits: IntCounterVec,
+    pub free_ip_addresses: IntGauge,
+    pub free_mac_addresses: IntGauge,
+    pub address_pool_exhausted: IntCounter,
+}
+
+impl ActuatorMetrics {
+    fn init() -> Result<ActuatorMetrics, prometheus::Error> {
+        let registry = Registry::new_custom(Some(String::from("impulse_actuator")), None)?;
+
+        let launches = IntCounterVec::new(
+            Opts::new("launches_total", "MicroVM launches by result"),
+            &["result"],
+        )?;
+        let launch_duration = Histogram::with_opts(
+            HistogramOpts::new("launch_duration_seconds", "Time spent launching a MicroVM")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        let shutdowns = IntCounterVec::new(
+            Opts::new("shutdowns_total", "MicroVM shutdowns by result"),
+            &["result"],
+        )?;
+        let shutdown_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "shutdown_duration_seconds",
+                "Time spent shutting down a MicroVM",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        let running_vms = IntGauge::new("running_vms", "MicroVMs launched on this node")?;
+        let exits = IntCounterVec::new(
+            Opts::new("exits_total", "Unexpected MicroVM exits by outcome"),
+            &["outcome"],
+        )?;
+        let free_ip_addresses =
+            IntGauge::new("free_ip_addresses", "Unassigned addresses in the pool")?;
+        let free_mac_addresses = IntGauge::new("free_mac_addresses", "Unassigned MAC addresses")?;
+        let address_pool_exhausted = IntCounter::new(
+            "address_pool_exhausted_total",
+            "Address allocations that found the pool exhausted",
+        )?;
+
+        registry.register(Box::new(launches.to_owned()))?;
+        registry.register(Box::new(launch_duration.to_owned()))?;
+        registry.register(Box::new(shutdowns.to_owned()))?;
+        registry.register(Box::new(shutdown_duration.to_owned()))?;
+        registry.register(Box::new(running_vms.to_owned()))?;
+        registry.register(Box::new(exits.to_owned()))?;
+        registry.register(Box::new(free_ip_addresses.to_owned()))?;
+        registry.register(Box::new(free_mac_addresses.to_owned()))?;
+        registry.register(Box::new(address_pool_exhausted.to_owned()))?;
+
+        Ok(ActuatorMetrics {
+            registry,
+            launches,
+            launch_duration,
+            shutdowns,
+            shutdown_duration,
+            running_vms,
+            exits,
+            free_ip_addresses,
+            free_mac_addresses,
+            address_pool_exhausted,
+        })
+    }
+
+    pub async fn observe_launch(&self, result: &str, latency: Duration) {
+        self.launches.with_label_values(&[result]).inc();
+        self.launch_duration.observe(latency.as_secs_f64());
+    }
+
+    pub async fn observe_shutdown(&self, result: &str, latency: Duration) {
+        self.shutdowns.with_label_values(&[result]).inc();
+        self.shutdown_duration.observe(latency.as_secs_f64());
+    }
+}
+
+pub enum Exporter {
+    Interface {
+        node_registry: Arc<NodeRegistry>,
+        vm_registry: Arc<VmRegistry>,
+    },
+    Actuator,
+}
+
+impl Exporter {
+    pub async fn render(&self) -> Result<Vec<u8>, prometheus::Error> {
+        let families = match self {
+            Exporter::Interface {
+                node_registry,
+                vm_registry,
+            } => {
+                INTERFACE.refresh(node_registry, vm_registry).await;
+                INTERFACE.registry.gather()
+            }
+            Exporter::Actuator => ACTUATOR.registry.gather(),
+        };
+
+        let mut buffer = Vec::with_capacity(4096);
+
+        TextEncoder::new().encode(&families, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    async fn respond(&self, request: Request<Body>) -> Response<Body> {
+        let mut response = Response::new(Body::empty());
+
+        if request.method() != Method::GET || request.uri().path() != "/metrics" {
+            *response.status_mut() = StatusCode::NOT_FOUND;
+
+            return response;
+        }
+
+        match self.render().await {
+            Ok(buffer) => {
+                if let Ok(content_type) = TextEncoder::new().format_type().parse() {
+                    response.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+
+                *response.body_mut() = Body::from(buffer);
+            }
+            Err(error) => {
+                error!(%error, "Unable to render metrics");
+
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            }
+        }
+
+        response
+    }
+}
+
+pub async fn serve(
+    address: SocketAddr,
+    exporter: Exporter,
+    signal: impl Future<Output = ()>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let exporter = Arc::new(exporter);
+    let make_service = make_service_fn(move |_| {
+        let exporter = exporter.to_owned();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let exporter = exporter.to_owned();
+
+                async move { Ok::<_, Infallible>(exporter.respond(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&address)?.serve(make_service);
+
+    info!(%address, "Serving metrics");
+
+    server.with_graceful_shutdown(signal).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::impulse::shared::v010::{MicroVmLaunch, NodeInventory};
+    use crate::tenants::Claim;
+    use crate::vm_registry::VmSpec;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interface_render() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = Arc::new(NodeRegistry::init().await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let test_inventory = NodeInventory {
+            cpu_count: 8,
+            ..NodeInventory::default()
+        };
+        test_node_registry
+            .register("test_metrics_node", test_inventory, HashMap::new())
+            .await?;
+        test_vm_registry
+            .pending(
+                "test_metrics_uuid",
+                "",
+                VmSpec::default(),
+                &Claim::default(),
+            )
+            .await?;
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                uuid: String::from("test_metrics_uuid"),
+                launched: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_metrics_node"),
+            })
+            .await?;
+        INTERFACE
+            .observe_launch("launched", Duration::from_millis(250))
+            .await;
+        let test_exporter = Exporter::Interface {
+            node_registry: test_node_registry,
+            vm_registry: test_vm_registry,
+        };
+        let test_rendered = String::from_utf8(test_exporter.render().await?)?;
+        assert!(
+            test_rendered.contains("impulse_interface_node_cpus{node_id=\"test_metrics_node\"} 8")
+        );
+        assert!(test_rendered
+            .contains("impulse_interface_node_running_vms{node_id=\"test_metrics_node\"} 1"));
+        assert!(test_rendered.contains("impulse_interface_launches_total{result=\"launched\"}"));
+        assert!(test_rendered.contains("impulse_interface_launch_duration_seconds_bucket"));
+        assert!(!test_rendered.contains("impulse_actuator_"));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve_metrics() -> Result<(), Box<dyn std::error::Error>> {
+        let test_address: SocketAddr = "127.0.0.1:48311".parse()?;
+        let (test_shutdown, test_signal) = tokio::sync::oneshot::channel::<()>();
+        ACTUATOR.address_pool_exhausted.inc();
+        let test_server = tokio::spawn(serve(test_address, Exporter::Actuator, async {
+            test_signal.await.ok();
+        }));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let test_client = hyper::Client::new();
+        let test_response = test_client
+            .get("http://127.0.0.1:48311/metrics".parse()?)
+            .await?;
+        assert_eq!(test_response.status(), StatusCode::OK);
+        let test_body = hyper::body::to_bytes(test_response.into_body()).await?;
+        let test_body = String::from_utf8(test_body.to_vec())?;
+        assert!(test_body.contains("impulse_actuator_address_pool_exhausted_total"));
+        let test_missing = test_client
+            .get("http://127.0.0.1:48311/missing".parse()?)
+            .await?;
+        assert_eq!(test_missing.status(), StatusCode::NOT_FOUND);
+        test_shutdown.send(()).ok();
+        assert!(test_server.await.is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use uuid::Uuid;
+
+use crate::impulse::shared::v010::NodeInventory;
+use crate::store::Store;
+
+const STALE_AFTER: Duration = Duration::from_secs(90);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct NodeRecord {
+    pub node_id: String,
+    pub session_id: Uuid,
+    pub registered_at: SystemTime,
+    pub last_seen: SystemTime,
+    pub inventory: NodeInventory,
+    pub draining: bool,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl NodeRecord {
+    async fn init(
+        node_id: &str,
+        inventory: NodeInventory,
+        labels: HashMap<String, String>,
+    ) -> NodeRecord {
+        let now = SystemTime::now();
+
+        NodeRecord {
+            node_id: node_id.to_string(),
+            session_id: Uuid::new_v4(),
+            registered_at: now,
+            last_seen: now,
+            inventory,
+            draining: false,
+            labels,
+        }
+    }
+
+    pub(crate) async fn stale(&self) -> bool {
+        self.last_seen
+            .elapsed()
+            .is_ok_and(|elapsed| elapsed > STALE_AFTER)
+    }
+}
+
+pub struct NodeRegistry {
+    nodes: Mutex<HashMap<String, NodeRecord>>,
+    store: Option<Arc<Store>>,
+}
+
+impl NodeRegistry {
+    pub async fn init() -> Result<NodeRegistry, Box<dyn std::error::Error>> {
+        let nodes = Mutex::new(HashMap::with_capacity(20));
+
+        Ok(NodeRegistry { nodes, store: None })
+    }
+
+    pub async fn restore(store: Arc<Store>) -> Result<NodeRegistry, Box<dyn std::error::Error>> {
+        let nodes = store
+            .nodes()
+            .await?
+            .into_iter()
+            .map(|record| (record.node_id.to_owned(), record))
+            .collect();
+
+        Ok(NodeRegistry {
+            nodes: Mutex::new(nodes),
+            store: Some(store),
+        })
+    }
+
+    pub(crate) async fn reload(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut nodes = self.nodes.lock().await;
+
+        if let Some(store) = &self.store {
+            *nodes = store
+                .nodes()
+                .await?
+                .into_iter()
+                .map(|record| (record.node_id.to_owned(), record))
+                .collect();
+        }
+
+        Ok(nodes.len())
+    }
+
+    async fn persist(&self, record: &NodeRecord) -> Result<(), Status> {
+        match &self.store {
+            Some(store) => store.put_node(record).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn register(
+        &self,
+        node_id: &str,
+        inventory: NodeInventory,
+        labels: HashMap<String, String>,
+    ) -> Result<NodeRecord, Status> {
+        let mut nodes = self.nodes.lock().await;
+        let mut record = NodeRecord::init(node_id, inventory, labels).await;
+
+        if let Some(existing) = nodes.get(node_id) {
+            record.draining = existing.draining;
+        }
+
+        self.persist(&record).await?;
+
+        nodes.insert(node_id.to_string(), record.to_owned());
+
+        Ok(record)
+    }
+
+    pub(crate) async fn update_inventory(
+        &self,
+        node_id: &str,
+        inventory: NodeInventory,
+    ) -> Result<bool, Status> {
+        let mut nodes = self.nodes.lock().await;
+
+        match nodes.get_mut(node_id) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                updated.inventory = inventory;
+                updated.last_seen = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated;
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) async fn drain(
+        &self,
+        node_id: &str,
+        draining: bool,
+    ) -> Result<Option<NodeRecord>, Status> {
+        let mut nodes = self.nodes.lock().await;
+
+        match nodes.get_mut(node_id) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                updated.draining = draining;
+
+                self.persist(&updated).await?;
+
+                *record = updated.to_owned();
+
+                Ok(Some(updated))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn get(&self, node_id: &str) -> Option<NodeRecord> {
+        let nodes = self.nodes.lock().await;
+
+        nodes.get(node_id).cloned()
+    }
+
+    pub(crate) async fn contains(&self, node_id: &str) -> bool {
+        let nodes = self.nodes.lock().await;
+
+        nodes.contains_key(node_id)
+    }
+
+    pub(crate) async fn remove(&self, node_id: &str) -> Result<Option<NodeRecord>, Status> {
+        let mut nodes = self.nodes.lock().await;
+
+        if let (Some(store), true) = (&self.store, nodes.contains_key(node_id)) {
+            store.remove_node(node_id).await?;
+        }
+
+        Ok(nodes.remove(node_id))
+    }
+
+    pub(crate) async fn list(&self) -> Vec<NodeRecord> {
+        let nodes = self.nodes.lock().await;
+        let mut list: Vec<NodeRecord> = nodes.values().cloned().collect();
+
+        list.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::StoreConfig;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        assert!(test_node_registry.list().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        let test_inventory = NodeInventory {
+            cpu_count: 2,
+            ..NodeInventory::default()
+        };
+        let test_record = test_node_registry
+            .register("test_node_b", test_inventory, HashMap::new())
+            .await?;
+        assert_eq!(test_record.session_id.get_version_num(), 4);
+        test_node_registry
+            .register("test_node_a", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_updated_inventory = NodeInventory {
+            cpu_count: 4,
+            ..NodeInventory::default()
+        };
+        let test_refreshed_record = test_node_registry
+            .register("test_node_b", test_updated_inventory, HashMap::new())
+            .await?;
+        assert_ne!(test_refreshed_record.session_id, test_record.session_id);
+        let test_list = test_node_registry.list().await;
+        assert_eq!(test_list.len(), 2);
+        assert_eq!(test_list[0].node_id.as_str(), "test_node_a");
+        assert_eq!(test_list[1].node_id.as_str(), "test_node_b");
+        assert_eq!(test_list[1].inventory.cpu_count, 4);
+        assert_eq!(test_list[1].session_id, test_refreshed_record.session_id);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn update_inventory() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        let test_inventory = NodeInventory {
+            cpu_count: 2,
+            ..NodeInventory::default()
+        };
+        assert!(
+            !test_node_registry
+                .update_inventory("test_node", test_inventory.to_owned())
+                .await?
+        );
+        let test_record = test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        assert!(
+            test_node_registry
+                .update_inventory("test_node", test_inventory)
+                .await?
+        );
+        let test_list = test_node_registry.list().await;
+        assert_eq!(test_list[0].inventory.cpu_count, 2);
+        assert_eq!(test_list[0].session_id, test_record.session_id);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remove() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        assert!(test_node_registry.contains("test_node").await);
+        assert!(test_node_registry.remove("test_node").await?.is_some());
+        assert!(test_node_registry.remove("test_node").await?.is_none());
+        assert!(!test_node_registry.contains("test_node").await);
+        assert!(test_node_registry.list().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drain() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        assert!(test_node_registry.drain("test_node", true).await?.is_none());
+        test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        let test_record = test_node_registry.drain("test_node", true).await?.unwrap();
+        assert!(test_record.draining);
+        let test_refreshed_record = test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        assert!(test_refreshed_record.draining);
+        test_node_registry.drain("test_node", false).await?;
+        assert!(!test_node_registry.get("test_node").await.unwrap().draining);
+        assert!(test_node_registry.get("test_other_node").await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stale() -> Result<(), Box<dyn std::error::Error>> {
+        let test_node_registry = NodeRegistry::init().await?;
+        let mut test_record = test_node_registry
+            .register("test_node", NodeInventory::default(), HashMap::new())
+            .await?;
+        assert!(!test_record.stale().await);
+        test_record.last_seen = SystemTime::now() - Duration::from_secs(120);
+        assert!(test_record.stale().await);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Arc::new(Store::init(&StoreConfig { path: None }).await?);
+        let test_node_registry = NodeRegistry::restore(test_store.to_owned()).await?;
+        let test_inventory = NodeInventory {
+            cpu_count: 2,
+            images: vec![String::from("test_root_fs")],
+            ..NodeInventory::default()
+        };
+        let test_record = test_node_registry
+            .register("test_node_a", test_inventory, HashMap::new())
+            .await?;
+        test_node_registry
+            .register("test_node_b", NodeInventory::default(), HashMap::new())
+            .await?;
+        test_node_registry.drain("test_node_a", true).await?;
+        test_node_registry.remove("test_node_b").await?;
+        drop(test_node_registry);
+        let test_node_registry = NodeRegistry::restore(test_store).await?;
+        let test_list = test_node_registry.list().await;
+        assert_eq!(test_list.len(), 1);
+        assert_eq!(test_list[0].node_id.as_str(), "test_node_a");
+        assert_eq!(test_list[0].session_id, test_record.session_id);
+        assert_eq!(test_list[0].inventory.cpu_count, 2);
+        assert_eq!(test_list[0].inventory.images.len(), 1);
+        assert!(test_list[0].draining);
+        Ok(())
+    }
+}
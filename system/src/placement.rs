@@ -0,0 +1,319 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::labels::Selector;
+use crate::system_error::SystemError;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub(crate) struct PlacementSpec {
+    pub node_selector: String,
+    pub preferences: Vec<(String, u32)>,
+    pub affinity: Vec<String>,
+    pub anti_affinity: Vec<String>,
+}
+
+pub(crate) struct Candidate<'a> {
+    pub node_id: &'a str,
+    pub labels: &'a HashMap<String, String>,
+    pub depth: Option<usize>,
+    pub draining: bool,
+    pub images: &'a [String],
+}
+
+pub(crate) struct Resident<'a> {
+    pub uuid: &'a str,
+    pub node_id: &'a str,
+    pub labels: &'a HashMap<String, String>,
+}
+
+pub(crate) struct Constraints {
+    node_selector: Selector,
+    preferences: Vec<(Selector, u32)>,
+    affinity: Vec<Selector>,
+    anti_affinity: Vec<Selector>,
+    images: Vec<String>,
+}
+
+impl Constraints {
+    pub(crate) fn parse(spec: &PlacementSpec) -> Result<Constraints, SystemError> {
+        let mut preferences = Vec::with_capacity(spec.preferences.len());
+
+        for (selector, weight) in &spec.preferences {
+            preferences.push((selector.parse()?, *weight));
+        }
+
+        Ok(Constraints {
+            node_selector: spec.node_selector.parse()?,
+            preferences,
+            affinity: parse_all(&spec.affinity)?,
+            anti_affinity: parse_all(&spec.anti_affinity)?,
+            images: Vec::with_capacity(0),
+        })
+    }
+
+    pub(crate) fn require_images(mut self, images: Vec<String>) -> Constraints {
+        self.images = images;
+
+        self
+    }
+
+    pub(crate) fn place(
+        &self,
+        candidates: &[Candidate],
+        residents: &[Resident],
+        labels: &HashMap<String, String>,
+    ) -> Result<String, Vec<(String, String)>> {
+        let mut eligible = Vec::with_capacity(candidates.len());
+        let mut blocked = Vec::with_capacity(candidates.len());
+
+        for candidate in candidates {
+            match self.blocker(candidate, residents, labels) {
+                Some(reason) => blocked.push((candidate.node_id.to_string(), reason)),
+                None => eligible.push(candidate),
+            }
+        }
+
+        eligible.sort_by_key(|candidate| {
+            (
+                Reverse(self.score(candidate.labels)),
+                candidate.depth,
+                candidate.node_id,
+            )
+        });
+
+        match eligible.first() {
+            Some(candidate) => Ok(candidate.node_id.to_string()),
+            None => Err(blocked),
+        }
+    }
+
+    fn blocker(
+        &self,
+        candidate: &Candidate,
+        residents: &[Resident],
+        labels: &HashMap<String, String>,
+    ) -> Option<String> {
+        if candidate.depth.is_none() {
+            return Some(String::from("not connected"));
+        }
+
+        if candidate.draining {
+            return Some(String::from("draining"));
+        }
+
+        if let Some(image) = self
+            .images
+            .iter()
+            .find(|image| !candidate.images.contains(image))
+        {
+            return Some(format!("image {} not found", image));
+        }
+
+        if !self.node_selector.matches(candidate.labels) {
+            return Some(format!("node selector {} not matched", self.node_selector));
+        }
+
+        for selector in &self.anti_affinity {
+            if let Some(resident) = residents.iter().find(|resident| {
+                resident.node_id == candidate.node_id && selector.matches(resident.labels)
+            }) {
+                return Some(format!(
+                    "anti-affinity {} matched MicroVM {}",
+                    selector, resident.uuid,
+                ));
+            }
+        }
+
+        for selector in &self.affinity {
+            let mut matching = residents
+                .iter()
+                .filter(|resident| selector.matches(resident.labels))
+                .peekable();
+
+            if matching.peek().is_none() && selector.matches(labels) {
+                continue;
+            }
+
+            if !matching.any(|resident| resident.node_id == candidate.node_id) {
+                return Some(format!(
+                    "affinity {} not matched by any MicroVM here",
+                    selector
+                ));
+            }
+        }
+
+        None
+    }
+
+    fn score(&self, labels: &HashMap<String, String>) -> u32 {
+        self.preferences
+            .iter()
+            .filter(|(selector, _)| selector.matches(labels))
+            .map(|(_, weight)| *weight)
+            .sum()
+    }
+}
+
+fn parse_all(selectors: &[String]) -> Result<Vec<Selector>, SystemError> {
+    selectors.iter().map(|selector| selector.parse()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse() -> Result<(), Box<dyn std::error::Error>> {
+        let test_spec = PlacementSpec {
+            node_selector: String::from("zone in (a,b)"),
+            preferences: vec![(String::from("gpu"), 5)],
+            affinity: vec![String::from("app=db")],
+            anti_affinity: vec![String::from("app=web")],
+        };
+        let test_constraints = Constraints::parse(&test_spec)?;
+        assert_eq!(test_constraints.preferences.len(), 1);
+        assert_eq!(test_constraints.affinity.len(), 1);
+        assert_eq!(test_constraints.anti_affinity.len(), 1);
+        let test_spec = PlacementSpec {
+            anti_affinity: vec![String::from("app in (web")],
+            ..PlacementSpec::default()
+        };
+        assert!(Constraints::parse(&test_spec).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn place() -> Result<(), Box<dyn std::error::Error>> {
+        let test_zone_a = test_labels(&[("zone", "a")]);
+        let test_zone_b = test_labels(&[("zone", "b"), ("gpu", "")]);
+        let test_web = test_labels(&[("app", "web")]);
+        let test_images = [String::from("test_root_fs")];
+        let test_candidates = [
+            Candidate {
+                node_id: "test_node_a",
+                labels: &test_zone_a,
+                depth: Some(0),
+                draining: false,
+                images: &test_images,
+            },
+            Candidate {
+                node_id: "test_node_b",
+                labels: &test_zone_b,
+                depth: Some(3),
+                draining: false,
+                images: &[],
+            },
+            Candidate {
+                node_id: "test_node_c",
+                labels: &test_zone_b,
+                depth: Some(0),
+                draining: true,
+                images: &test_images,
+            },
+            Candidate {
+                node_id: "test_node_d",
+                labels: &test_zone_a,
+                depth: None,
+                draining: false,
+                images: &test_images,
+            },
+        ];
+        let test_residents = [Resident {
+            uuid: "test_uuid_web",
+            node_id: "test_node_a",
+            labels: &test_web,
+        }];
+        let test_constraints = Constraints::parse(&PlacementSpec::default())?;
+        assert_eq!(
+            test_constraints.place(&test_candidates, &test_residents, &test_web),
+            Ok(String::from("test_node_a")),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec {
+            preferences: vec![(String::from("gpu"), 1)],
+            ..PlacementSpec::default()
+        })?;
+        assert_eq!(
+            test_constraints.place(&test_candidates, &test_residents, &test_web),
+            Ok(String::from("test_node_b")),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec {
+            anti_affinity: vec![String::from("app=web")],
+            ..PlacementSpec::default()
+        })?;
+        assert_eq!(
+            test_constraints.place(&test_candidates, &test_residents, &test_web),
+            Ok(String::from("test_node_b")),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec {
+            affinity: vec![String::from("app=web")],
+            ..PlacementSpec::default()
+        })?;
+        assert_eq!(
+            test_constraints.place(&test_candidates[1..], &test_residents, &test_web),
+            Err(vec![
+                (
+                    String::from("test_node_b"),
+                    String::from("affinity app=web not matched by any MicroVM here"),
+                ),
+                (String::from("test_node_c"), String::from("draining")),
+                (String::from("test_node_d"), String::from("not connected")),
+            ]),
+        );
+        assert_eq!(
+            test_constraints.place(&test_candidates[1..], &[], &test_web),
+            Ok(String::from("test_node_b")),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec {
+            node_selector: String::from("zone=a"),
+            anti_affinity: vec![String::from("app=web")],
+            ..PlacementSpec::default()
+        })?;
+        assert_eq!(
+            test_constraints.place(&test_candidates[..2], &test_residents, &test_web),
+            Err(vec![
+                (
+                    String::from("test_node_a"),
+                    String::from("anti-affinity app=web matched MicroVM test_uuid_web"),
+                ),
+                (
+                    String::from("test_node_b"),
+                    String::from("node selector zone=a not matched"),
+                ),
+            ]),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec {
+            preferences: vec![(String::from("gpu"), 1)],
+            ..PlacementSpec::default()
+        })?
+        .require_images(vec![String::from("test_root_fs")]);
+        assert_eq!(
+            test_constraints.place(&test_candidates, &test_residents, &test_web),
+            Ok(String::from("test_node_a")),
+        );
+        let test_constraints = Constraints::parse(&PlacementSpec::default())?
+            .require_images(vec![String::from("test_missing")]);
+        assert_eq!(
+            test_constraints.place(&test_candidates[..2], &test_residents, &test_web),
+            Err(vec![
+                (
+                    String::from("test_node_a"),
+                    String::from("image test_missing not found"),
+                ),
+                (
+                    String::from("test_node_b"),
+                    String::from("image test_missing not found"),
+                ),
+            ]),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use tonic::{Request, Status};
+
+use crate::config::PolicyConfig;
+use crate::tenants::Tenants;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Reader,
+    Operator,
+    Admin,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Role::Reader => write!(f, "reader"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    SystemStatus,
+    SystemVersion,
+    ListNodes,
+    ListVms,
+    LaunchVm,
+    LaunchVms,
+    ShutdownVm,
+    ShutdownVms,
+    LabelVms,
+    QueryUsage,
+    ListProfiles,
+    GetProfile,
+    CreateProfile,
+    UpdateProfile,
+    DeleteProfile,
+    ListGroups,
+    GetGroup,
+    CreateGroup,
+    UpdateGroup,
+    ScaleGroup,
+    DeleteGroup,
+    DelistNode,
+    DrainNode,
+    QueryAudit,
+    WatchEvents,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Operation::SystemStatus => write!(f, "system_status"),
+            Operation::SystemVersion => write!(f, "system_version"),
+            Operation::ListNodes => write!(f, "list_nodes"),
+            Operation::ListVms => write!(f, "list_vms"),
+            Operation::LaunchVm => write!(f, "launch_vm"),
+            Operation::LaunchVms => write!(f, "launch_vms"),
+            Operation::ShutdownVm => write!(f, "shutdown_vm"),
+            Operation::ShutdownVms => write!(f, "shutdown_vms"),
+            Operation::LabelVms => write!(f, "label_vms"),
+            Operation::QueryUsage => write!(f, "query_usage"),
+            Operation::ListProfiles => write!(f, "list_profiles"),
+            Operation::GetProfile => write!(f, "get_profile"),
+            Operation::CreateProfile => write!(f, "create_profile"),
+            Operation::UpdateProfile => write!(f, "update_profile"),
+            Operation::DeleteProfile => write!(f, "delete_profile"),
+            Operation::ListGroups => write!(f, "list_groups"),
+            Operation::GetGroup => write!(f, "get_group"),
+            Operation::CreateGroup => write!(f, "create_group"),
+            Operation::UpdateGroup => write!(f, "update_group"),
+            Operation::ScaleGroup => write!(f, "scale_group"),
+            Operation::DeleteGroup => write!(f, "delete_group"),
+            Operation::DelistNode => write!(f, "delist_node"),
+            Operation::DrainNode => write!(f, "drain_node"),
+            Operation::QueryAudit => write!(f, "query_audit"),
+            Operation::WatchEvents => write!(f, "watch_events"),
+        }
+    }
+}
+
+impl Operation {
+    pub fn role(&self) -> Role {
+        match self {
+            Operation::SystemStatus
+            | Operation::SystemVersion
+            | Operation::ListNodes
+            | Operation::ListVms
+            | Operation::QueryUsage
+            | Operation::ListProfiles
+            | Operation::GetProfile
+            | Operation::ListGroups
+            | Operation::GetGroup
+            | Operation::WatchEvents => Role::Reader,
+            Operation::LaunchVm
+            | Operation::LaunchVms
+            | Operation::ShutdownVm
+            | Operation::ShutdownVms
+            | Operation::LabelVms
+            | Operation::CreateGroup
+            | Operation::UpdateGroup
+            | Operation::ScaleGroup
+            | Operation::DeleteGroup => Role::Operator,
+            Operation::CreateProfile
+            | Operation::UpdateProfile
+            | Operation::DeleteProfile
+            | Operation::DelistNode
+            | Operation::DrainNode
+            | Operation::QueryAudit => Role::Admin,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Principal {
+    pub name: String,
+    pub role: Role,
+    pub tenant: String,
+}
+
+pub struct Policy {
+    anonymous_role: Role,
+    default_role: Role,
+    tokens: HashMap<String, Principal>,
+    pub tenants: Tenants,
+}
+
+impl Policy {
+    pub async fn init(config: &PolicyConfig) -> Result<Policy, Box<dyn std::error::Error>> {
+        let tenants = Tenants::init(&config.tenants).await?;
+        let mut tokens = HashMap::with_capacity(config.principals.len());
+
+        for principal in &config.principals {
+            tokens.insert(
+                principal.token.to_owned(),
+                Principal {
+                    name: principal.name.to_owned(),
+                    role: principal.role,
+                    tenant: principal
+                        .tenant
+                        .to_owned()
+                        .unwrap_or_else(|| tenants.default_tenant().to_string()),
+                },
+            );
+        }
+
+        Ok(Policy {
+            anonymous_role: config.anonymous_role,
+            default_role: config.default_role,
+            tokens,
+            tenants,
+        })
+    }
+
+    pub async fn principal<T>(&self, request: &Request<T>) -> Principal {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match token {
+            Some(token) => match self.tokens.get(token) {
+                Some(principal) => principal.to_owned(),
+                None => Principal {
+                    name: String::from("unbound_token"),
+                    role: self.default_role,
+                    tenant: self.tenants.default_tenant().to_string(),
+                },
+            },
+            None => Principal {
+                name: String::from("anonymous"),
+                role: self.anonymous_role,
+                tenant: self.tenants.default_tenant().to_string(),
+            },
+        }
+    }
+
+    pub async fn authorize(principal: &Principal, operation: Operation) -> Result<(), Status> {
+        match principal.role >= operation.role() {
+            true => Ok(()),
+            false => {
+                let message = format!(
+                    "{} with role {} may not call {:?}... {} role required!",
+                    principal.name,
+                    principal.role,
+                    operation,
+                    operation.role(),
+                );
+
+                Err(Status::permission_denied(message))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::PrincipalConfig;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn principal() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = PolicyConfig {
+            anonymous_role: Role::Admin,
+            default_role: Role::Reader,
+            principals: vec![PrincipalConfig {
+                name: String::from("test_operator"),
+                token: String::from("test_operator_token"),
+                role: Role::Operator,
+                tenant: Some(String::from("test_team")),
+            }],
+            ..PolicyConfig::default()
+        };
+        let test_default = Policy::init(&PolicyConfig::default()).await?;
+        let test_anonymous = test_default.principal(&Request::new(())).await;
+        assert_eq!(test_anonymous.role, Role::Reader);
+        assert!(Policy::authorize(&test_anonymous, Operation::LaunchVm)
+            .await
+            .is_err());
+        let test_policy = Policy::init(&test_config).await?;
+        let test_anonymous = test_policy.principal(&Request::new(())).await;
+        assert_eq!(test_anonymous.name.as_str(), "anonymous");
+        assert_eq!(test_anonymous.role, Role::Admin);
+        assert_eq!(test_anonymous.tenant.as_str(), "default");
+        let mut test_request = Request::new(());
+        test_request
+            .metadata_mut()
+            .insert("authorization", "Bearer test_operator_token".parse()?);
+        let test_operator = test_policy.principal(&test_request).await;
+        assert_eq!(test_operator.name.as_str(), "test_operator");
+        assert_eq!(test_operator.role, Role::Operator);
+        assert_eq!(test_operator.tenant.as_str(), "test_team");
+        let mut test_request = Request::new(());
+        test_request
+            .metadata_mut()
+            .insert("authorization", "Bearer test_unbound_token".parse()?);
+        let test_unbound = test_policy.principal(&test_request).await;
+        assert_eq!(test_unbound.role, Role::Reader);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn authorize() -> Result<(), Box<dyn std::error::Error>> {
+        let test_reader = Principal {
+            name: String::from("test_reader"),
+            role: Role::Reader,
+            tenant: String::from("default"),
+        };
+        assert!(Policy::authorize(&test_reader, Operation::QueryUsage)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_reader, Operation::ListVms)
+            .await
+            .is_ok());
+        let test_denied = Policy::authorize(&test_reader, Operation::LaunchVm)
+            .await
+            .unwrap_err();
+        assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        let test_operator = Principal {
+            name: String::from("test_operator"),
+            role: Role::Operator,
+            tenant: String::from("default"),
+        };
+        assert!(Policy::authorize(&test_operator, Operation::ShutdownVm)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_operator, Operation::ShutdownVms)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_operator, Operation::LaunchVms)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_reader, Operation::LaunchVms)
+            .await
+            .is_err());
+        assert!(Policy::authorize(&test_reader, Operation::LabelVms)
+            .await
+            .is_err());
+        assert!(Policy::authorize(&test_operator, Operation::DrainNode)
+            .await
+            .is_err());
+        assert!(Policy::authorize(&test_operator, Operation::CreateProfile)
+            .await
+            .is_err());
+        assert!(Policy::authorize(&test_reader, Operation::GetProfile)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_operator, Operation::ScaleGroup)
+            .await
+            .is_ok());
+        assert!(Policy::authorize(&test_reader, Operation::CreateGroup)
+            .await
+            .is_err());
+        assert!(Policy::authorize(&test_reader, Operation::ListGroups)
+            .await
+            .is_ok());
+        let test_admin = Principal {
+            name: String::from("test_admin"),
+            role: Role::Admin,
+            tenant: String::from("default"),
+        };
+        assert!(Policy::authorize(&test_admin, Operation::DelistNode)
+            .await
+            .is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,719 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use crate::config::ProfileConfig;
+use crate::impulse::shared::v010::{MachineSpec, RestartPolicy};
+use crate::store::Store;
+use crate::system_error::SystemError;
+
+const MAX_NAME_LENGTH: usize = 63;
+const MAX_VCPUS: u32 = 32;
+pub const ROOT_DRIVE_ID: &str = "rootfs";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct ProfileRecord {
+    pub name: String,
+    pub description: String,
+    pub machine: MachineSpec,
+    #[serde(skip)]
+    pub configured: bool,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+impl ProfileRecord {
+    pub(crate) async fn init(name: &str, description: &str, machine: MachineSpec) -> ProfileRecord {
+        let now = SystemTime::now();
+
+        ProfileRecord {
+            name: name.to_string(),
+            description: description.to_string(),
+            machine,
+            configured: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+pub struct ProfileRegistry {
+    configured: HashMap<String, ProfileRecord>,
+    profiles: Mutex<HashMap<String, ProfileRecord>>,
+    store: Option<Arc<Store>>,
+}
+
+impl ProfileRegistry {
+    pub async fn init(
+        config: &HashMap<String, ProfileConfig>,
+    ) -> Result<ProfileRegistry, Box<dyn std::error::Error>> {
+        let configured = Self::configured(config).await?;
+        let profiles = Mutex::new(HashMap::with_capacity(8));
+
+        Ok(ProfileRegistry {
+            configured,
+            profiles,
+            store: None,
+        })
+    }
+
+    pub async fn restore(
+        config: &HashMap<String, ProfileConfig>,
+        store: Arc<Store>,
+    ) -> Result<ProfileRegistry, Box<dyn std::error::Error>> {
+        let configured = Self::configured(config).await?;
+        let profiles = store
+            .profiles()
+            .await?
+            .into_iter()
+            .filter(|record| !configured.contains_key(&record.name))
+            .map(|record| (record.name.to_owned(), record))
+            .collect();
+
+        Ok(ProfileRegistry {
+            configured,
+            profiles: Mutex::new(profiles),
+            store: Some(store),
+        })
+    }
+
+    async fn configured(
+        config: &HashMap<String, ProfileConfig>,
+    ) -> Result<HashMap<String, ProfileRecord>, Box<dyn std::error::Error>> {
+        let mut configured = HashMap::with_capacity(config.len());
+
+        for (name, profile) in config {
+            validate_name(name)?;
+            validate(&profile.machine)?;
+
+            let mut record =
+                ProfileRecord::init(name, &profile.description, profile.machine.to_owned()).await;
+
+            record.configured = true;
+
+            configured.insert(name.to_owned(), record);
+        }
+
+        Ok(configured)
+    }
+
+    pub(crate) async fn reload(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut profiles = self.profiles.lock().await;
+
+        if let Some(store) = &self.store {
+            *profiles = store
+                .profiles()
+                .await?
+                .into_iter()
+                .filter(|record| !self.configured.contains_key(&record.name))
+                .map(|record| (record.name.to_owned(), record))
+                .collect();
+        }
+
+        Ok(profiles.len() + self.configured.len())
+    }
+
+    pub(crate) async fn create(
+        &self,
+        name: &str,
+        description: &str,
+        machine: MachineSpec,
+    ) -> Result<ProfileRecord, Status> {
+        Self::check(name, &machine).await?;
+
+        let mut profiles = self.profiles.lock().await;
+
+        if self.configured.contains_key(name) || profiles.contains_key(name) {
+            let message = format!("Profile {} already exists!", name);
+
+            return Err(Status::already_exists(message));
+        }
+
+        let record = ProfileRecord::init(name, description, machine).await;
+
+        if let Some(store) = &self.store {
+            store.put_profile(&record).await?;
+        }
+
+        profiles.insert(name.to_string(), record.to_owned());
+
+        Ok(record)
+    }
+
+    pub(crate) async fn update(
+        &self,
+        name: &str,
+        description: &str,
+        machine: MachineSpec,
+    ) -> Result<ProfileRecord, Status> {
+        Self::check(name, &machine).await?;
+        self.writable(name).await?;
+
+        let mut profiles = self.profiles.lock().await;
+
+        match profiles.get_mut(name) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                updated.description = description.to_string();
+                updated.machine = machine;
+                updated.updated_at = SystemTime::now();
+
+                if let Some(store) = &self.store {
+                    store.put_profile(&updated).await?;
+                }
+
+                *record = updated.to_owned();
+
+                Ok(updated)
+            }
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn remove(&self, name: &str) -> Result<ProfileRecord, Status> {
+        self.writable(name).await?;
+
+        let mut profiles = self.profiles.lock().await;
+
+        if !profiles.contains_key(name) {
+            return Err(Self::not_found(name).await);
+        }
+
+        if let Some(store) = &self.store {
+            store.remove_profile(name).await?;
+        }
+
+        match profiles.remove(name) {
+            Some(record) => Ok(record),
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn get(&self, name: &str) -> Result<ProfileRecord, Status> {
+        if let Some(record) = self.configured.get(name) {
+            return Ok(record.to_owned());
+        }
+
+        let profiles = self.profiles.lock().await;
+
+        match profiles.get(name) {
+            Some(record) => Ok(record.to_owned()),
+            None => Err(Self::not_found(name).await),
+        }
+    }
+
+    pub(crate) async fn list(&self) -> Vec<ProfileRecord> {
+        let profiles = self.profiles.lock().await;
+        let mut list: Vec<ProfileRecord> = self
+            .configured
+            .values()
+            .chain(profiles.values())
+            .cloned()
+            .collect();
+
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+
+        list
+    }
+
+    pub(crate) async fn resolve(
+        &self,
+        profile: &str,
+        overrides: &MachineSpec,
+    ) -> Result<MachineSpec, Status> {
+        let base = match profile.is_empty() {
+            true => MachineSpec::default(),
+            false => self.get(profile).await?.machine,
+        };
+        let machine = overlay(&base, overrides);
+
+        match validate(&machine) {
+            Ok(()) => Ok(machine),
+            Err(error) => Err(Status::invalid_argument(error.to_string())),
+        }
+    }
+
+    async fn check(name: &str, machine: &MachineSpec) -> Result<(), Status> {
+        validate_name(name)
+            .and_then(|_| validate(machine))
+            .map_err(|error| Status::invalid_argument(error.to_string()))
+    }
+
+    async fn writable(&self, name: &str) -> Result<(), Status> {
+        match self.configured.contains_key(name) {
+            true => {
+                let message = format!(
+                    "Profile {} is defined in the interface config... edit the config instead!",
+                    name,
+                );
+
+                Err(Status::failed_precondition(message))
+            }
+            false => Ok(()),
+        }
+    }
+
+    async fn not_found(name: &str) -> Status {
+        let message = format!("Profile {} was not found!", name);
+
+        Status::not_found(message)
+    }
+}
+
+pub fn overlay(base: &MachineSpec, overrides: &MachineSpec) -> MachineSpec {
+    let mut machine = base.to_owned();
+
+    for (field, value) in [
+        (&mut machine.kernel_image, &overrides.kernel_image),
+        (&mut machine.initrd, &overrides.initrd),
+        (&mut machine.root_fs, &overrides.root_fs),
+        (&mut machine.boot_args, &overrides.boot_args),
+    ] {
+        if !value.is_empty() {
+            *field = value.to_owned();
+        }
+    }
+
+    if overrides.vcpus > 0 {
+        machine.vcpus = overrides.vcpus;
+    }
+
+    if overrides.memory_mib > 0 {
+        machine.memory_mib = overrides.memory_mib;
+    }
+
+    if overrides.restart_policy != RestartPolicy::Unspecified as i32 {
+        machine.restart_policy = overrides.restart_policy;
+    }
+
+    for drive in &overrides.drives {
+        match machine
+            .drives
+            .iter_mut()
+            .find(|existing| existing.drive_id == drive.drive_id)
+        {
+            Some(existing) => *existing = drive.to_owned(),
+            None => machine.drives.push(drive.to_owned()),
+        }
+    }
+
+    for interface in &overrides.network_interfaces {
+        match machine
+            .network_interfaces
+            .iter_mut()
+            .find(|existing| existing.iface_id == interface.iface_id)
+        {
+            Some(existing) => *existing = interface.to_owned(),
+            None => machine.network_interfaces.push(interface.to_owned()),
+        }
+    }
+
+    machine
+}
+
+pub fn validate(machine: &MachineSpec) -> Result<(), SystemError> {
+    for image in [&machine.kernel_image, &machine.initrd, &machine.root_fs] {
+        if !image.is_empty() {
+            validate_image(image)?;
+        }
+    }
+
+    if machine.vcpus > MAX_VCPUS {
+        let message = format!(
+            "MicroVM asks for {} vCPUs but at most {} are supported!",
+            machine.vcpus, MAX_VCPUS,
+        );
+
+        return Err(SystemError::new(&message));
+    }
+
+    if RestartPolicy::from_i32(machine.restart_policy).is_none() {
+        let message = format!("Restart policy {} is not known!", machine.restart_policy);
+
+        return Err(SystemError::new(&message));
+    }
+
+    let mut drive_ids = Vec::with_capacity(machine.drives.len());
+
+    for drive in &machine.drives {
+        validate_name(&drive.drive_id)?;
+        validate_image(&drive.image)?;
+
+        if drive.drive_id == ROOT_DRIVE_ID || drive_ids.contains(&&drive.drive_id) {
+            let message = format!("Drive id {} is reserved or used twice!", drive.drive_id);
+
+            return Err(SystemError::new(&message));
+        }
+
+        drive_ids.push(&drive.drive_id);
+    }
+
+    let mut iface_ids = Vec::with_capacity(machine.network_interfaces.len());
+
+    for interface in &machine.network_interfaces {
+        validate_name(&interface.iface_id)?;
+        validate_name(&interface.host_dev_name)?;
+
+        if !interface.guest_mac.is_empty() && !valid_mac(&interface.guest_mac) {
+            let message = format!(
+                "Guest MAC {} is not a valid MAC address!",
+                interface.guest_mac
+            );
+
+            return Err(SystemError::new(&message));
+        }
+
+        if iface_ids.contains(&&interface.iface_id) {
+            let message = format!("Interface id {} is used twice!", interface.iface_id);
+
+            return Err(SystemError::new(&message));
+        }
+
+        iface_ids.push(&interface.iface_id);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn images(machine: &MachineSpec) -> Vec<String> {
+    let mut images: Vec<String> = [&machine.kernel_image, &machine.initrd, &machine.root_fs]
+        .into_iter()
+        .chain(machine.drives.iter().map(|drive| &drive.image))
+        .filter(|image| !image.is_empty())
+        .cloned()
+        .collect();
+
+    images.sort();
+    images.dedup();
+
+    images
+}
+
+pub(crate) fn validate_name(name: &str) -> Result<(), SystemError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    match valid {
+        true => Ok(()),
+        false => {
+            let message = format!("Name {:?} is not a valid name!", name);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+fn validate_image(image: &str) -> Result<(), SystemError> {
+    match validate_name(image) {
+        Ok(()) if !image.starts_with('.') => Ok(()),
+        _ => {
+            let message = format!("Image {:?} is not a plain file name!", image);
+
+            Err(SystemError::new(&message))
+        }
+    }
+}
+
+pub(crate) mod restart_policy {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::impulse::shared::v010::RestartPolicy;
+
+    pub fn serialize<S: Serializer>(policy: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&name(*policy))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        if name.is_empty() {
+            return Ok(RestartPolicy::Unspecified as i32);
+        }
+
+        match RestartPolicy::from_str_name(&format!("RESTART_POLICY_{}", name.to_uppercase())) {
+            Some(policy) => Ok(policy as i32),
+            None => {
+                let message = format!(
+                    "Restart policy {} is not never, on_failure or always!",
+                    name
+                );
+
+                Err(D::Error::custom(message))
+            }
+        }
+    }
+
+    pub fn name(policy: i32) -> String {
+        match RestartPolicy::from_i32(policy) {
+            Some(RestartPolicy::Unspecified) | None => String::new(),
+            Some(policy) => policy
+                .as_str_name()
+                .trim_start_matches("RESTART_POLICY_")
+                .to_lowercase(),
+        }
+    }
+}
+
+fn valid_mac(mac: &str) -> bool {
+    let octets: Vec<&str> = mac.split(':').collect();
+
+    octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::StoreConfig;
+    use crate::impulse::shared::v010::{Drive, NetworkInterface};
+
+    fn test_config() -> HashMap<String, ProfileConfig> {
+        HashMap::from([(
+            String::from("small"),
+            ProfileConfig {
+                description: String::from("test_small"),
+                machine: MachineSpec {
+                    root_fs: String::from("test_root_fs"),
+                    vcpus: 1,
+                    memory_mib: 512,
+                    ..MachineSpec::default()
+                },
+            },
+        )])
+    }
+
+    #[test]
+    fn overlay_and_validate() -> Result<(), Box<dyn std::error::Error>> {
+        let test_base = MachineSpec {
+            kernel_image: String::from("test_kernel"),
+            vcpus: 2,
+            memory_mib: 1024,
+            drives: vec![Drive {
+                drive_id: String::from("data"),
+                image: String::from("test_data"),
+                read_only: false,
+            }],
+            ..MachineSpec::default()
+        };
+        let test_overrides = MachineSpec {
+            memory_mib: 4096,
+            restart_policy: RestartPolicy::OnFailure as i32,
+            drives: vec![
+                Drive {
+                    drive_id: String::from("data"),
+                    image: String::from("test_data"),
+                    read_only: true,
+                },
+                Drive {
+                    drive_id: String::from("scratch"),
+                    image: String::from("test_scratch"),
+                    read_only: false,
+                },
+            ],
+            ..MachineSpec::default()
+        };
+        let test_machine = overlay(&test_base, &test_overrides);
+        assert_eq!(test_machine.kernel_image.as_str(), "test_kernel");
+        assert_eq!(test_machine.vcpus, 2);
+        assert_eq!(test_machine.memory_mib, 4096);
+        assert_eq!(test_machine.drives.len(), 2);
+        assert!(test_machine.drives[0].read_only);
+        assert_eq!(test_machine.restart_policy, RestartPolicy::OnFailure as i32);
+        assert_eq!(
+            overlay(&test_machine, &MachineSpec::default()).restart_policy,
+            RestartPolicy::OnFailure as i32,
+        );
+        validate(&test_machine)?;
+        assert!(validate(&MachineSpec {
+            restart_policy: 7,
+            ..MachineSpec::default()
+        })
+        .is_err());
+        assert!(validate(&MachineSpec {
+            root_fs: String::from("../etc/shadow"),
+            ..MachineSpec::default()
+        })
+        .is_err());
+        assert!(validate(&MachineSpec {
+            vcpus: 64,
+            ..MachineSpec::default()
+        })
+        .is_err());
+        assert!(validate(&MachineSpec {
+            drives: vec![Drive {
+                drive_id: String::from(ROOT_DRIVE_ID),
+                image: String::from("test_data"),
+                read_only: false,
+            }],
+            ..MachineSpec::default()
+        })
+        .is_err());
+        assert!(validate(&MachineSpec {
+            network_interfaces: vec![NetworkInterface {
+                iface_id: String::from("eth0"),
+                host_dev_name: String::from("tap0"),
+                guest_mac: String::from("06:00:ac:10:00:02"),
+            }],
+            ..MachineSpec::default()
+        })
+        .is_ok());
+        assert!(validate(&MachineSpec {
+            network_interfaces: vec![NetworkInterface {
+                iface_id: String::from("eth0"),
+                host_dev_name: String::from("tap0"),
+                guest_mac: String::from("not a mac"),
+            }],
+            ..MachineSpec::default()
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn restart_policy_serde() -> Result<(), Box<dyn std::error::Error>> {
+        let test_machine = MachineSpec {
+            restart_policy: RestartPolicy::OnFailure as i32,
+            ..MachineSpec::default()
+        };
+        let test_json = serde_json::to_value(&test_machine)?;
+        assert_eq!(test_json["restart_policy"], "on_failure");
+        let test_parsed: MachineSpec = serde_json::from_value(test_json)?;
+        assert_eq!(test_parsed, test_machine);
+        let test_default: MachineSpec = serde_json::from_str("{}")?;
+        assert_eq!(
+            test_default.restart_policy,
+            RestartPolicy::Unspecified as i32
+        );
+        assert_eq!(serde_json::to_value(&test_default)?["restart_policy"], "");
+        assert!(serde_json::from_str::<MachineSpec>(r#"{"restart_policy":"sometimes"}"#).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn crud() -> Result<(), Box<dyn std::error::Error>> {
+        let test_registry = ProfileRegistry::init(&test_config()).await?;
+        assert_eq!(test_registry.list().await.len(), 1);
+        assert!(test_registry.get("small").await?.configured);
+        let test_machine = MachineSpec {
+            vcpus: 4,
+            memory_mib: 8192,
+            ..MachineSpec::default()
+        };
+        let test_created = test_registry
+            .create("db", "test_db", test_machine.to_owned())
+            .await?;
+        assert!(!test_created.configured);
+        let test_exists = test_registry
+            .create("small", "", MachineSpec::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_exists.code(), tonic::Code::AlreadyExists);
+        let test_invalid = test_registry
+            .create("not valid", "", MachineSpec::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        let test_updated = test_registry
+            .update(
+                "db",
+                "test_db_large",
+                MachineSpec {
+                    vcpus: 8,
+                    ..test_machine
+                },
+            )
+            .await?;
+        assert_eq!(test_updated.machine.vcpus, 8);
+        assert_eq!(test_updated.created_at, test_created.created_at);
+        let test_configured = test_registry
+            .update("small", "", MachineSpec::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_configured.code(), tonic::Code::FailedPrecondition);
+        let test_configured = test_registry.remove("small").await.unwrap_err();
+        assert_eq!(test_configured.code(), tonic::Code::FailedPrecondition);
+        let test_names: Vec<String> = test_registry
+            .list()
+            .await
+            .into_iter()
+            .map(|record| record.name)
+            .collect();
+        assert_eq!(test_names, vec![String::from("db"), String::from("small")]);
+        test_registry.remove("db").await?;
+        let test_missing = test_registry.get("db").await.unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        let test_missing = test_registry
+            .update("db", "", MachineSpec::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolve() -> Result<(), Box<dyn std::error::Error>> {
+        let test_registry = ProfileRegistry::init(&test_config()).await?;
+        let test_machine = test_registry
+            .resolve(
+                "small",
+                &MachineSpec {
+                    memory_mib: 2048,
+                    ..MachineSpec::default()
+                },
+            )
+            .await?;
+        assert_eq!(test_machine.root_fs.as_str(), "test_root_fs");
+        assert_eq!(test_machine.vcpus, 1);
+        assert_eq!(test_machine.memory_mib, 2048);
+        let test_machine = test_registry.resolve("", &MachineSpec::default()).await?;
+        assert_eq!(test_machine, MachineSpec::default());
+        let test_missing = test_registry
+            .resolve("large", &MachineSpec::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        let test_invalid = test_registry
+            .resolve(
+                "small",
+                &MachineSpec {
+                    kernel_image: String::from("/boot/vmlinux"),
+                    ..MachineSpec::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Arc::new(Store::init(&StoreConfig { path: None }).await?);
+        let test_registry = ProfileRegistry::restore(&test_config(), test_store.clone()).await?;
+        test_registry
+            .create("db", "test_db", MachineSpec::default())
+            .await?;
+        let test_restored = ProfileRegistry::restore(&HashMap::new(), test_store).await?;
+        assert_eq!(
+            test_restored.get("db").await?.description.as_str(),
+            "test_db"
+        );
+        assert!(test_restored.get("small").await.is_err());
+        assert_eq!(test_restored.reload().await?, 1);
+        Ok(())
+    }
+}
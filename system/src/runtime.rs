@@ -0,0 +1,1007 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::fs::{create_dir_all, remove_file, symlink_metadata};
+use tokio::net::UnixListener;
+use tokio::sync::broadcast::channel;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{interval, sleep, Duration, Instant};
+
+use tokio_stream::wrappers::UnixListenerStream;
+
+use tonic::body::BoxBody;
+use tonic::codegen::{http, InterceptedService, Service};
+use tonic::server::NamedService;
+use tonic::transport::{Body, Server};
+use tonic::Status;
+
+use tonic_health::server::health_reporter;
+
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::actuator_client::{rejected, Internal as InternalClient};
+use crate::actuator_engine::{Engine, Inventory, Supervised};
+use crate::audit::AuditLog;
+use crate::auth::{server_tls, ListenerAuth};
+use crate::cluster::{ClusterServer, LeaderGate, Leadership};
+use crate::config::{ActuatorConfig, InterfaceConfig, ListenAddress, ListenerConfig};
+use crate::events::EventLog;
+use crate::external_interface::{External, InterfaceServer as ExternalInterfaceServer};
+use crate::groups::GroupRegistry;
+use crate::health::{self, Readiness};
+use crate::impulse;
+use crate::impulse::shared::v010::Task;
+use crate::internal_interface::{InterfaceServer as InternalInterfaceServer, Internal};
+use crate::metrics::{self, Exporter, ACTUATOR};
+use crate::node_registry::NodeRegistry;
+use crate::policy::Policy;
+use crate::profiles::ProfileRegistry;
+use crate::store::Store;
+use crate::system_error::SystemError;
+use crate::task_queue::TaskQueues;
+use crate::telemetry;
+use crate::vm_registry::VmRegistry;
+
+use uuid::Uuid;
+
+const INVENTORY_RETRY: Duration = Duration::from_secs(5);
+const CLUSTER_DESCRIPTORS: &[&[u8]] = &[impulse::cluster::v010::FILE_DESCRIPTOR_SET];
+const EXTERNAL_DESCRIPTORS: &[&[u8]] = &[
+    impulse::external::v010::FILE_DESCRIPTOR_SET,
+    impulse::shared::v010::FILE_DESCRIPTOR_SET,
+];
+const INTERNAL_DESCRIPTORS: &[&[u8]] = &[
+    impulse::internal::v010::FILE_DESCRIPTOR_SET,
+    impulse::shared::v010::FILE_DESCRIPTOR_SET,
+];
+
+pub async fn interface(config: InterfaceConfig) -> Result<(), Box<dyn std::error::Error>> {
+    telemetry::init(&config.log).await?;
+
+    let (launch_result_sender, _) = channel(4);
+    let launch_result_sender_clone = launch_result_sender.clone();
+
+    let (shutdown_result_sender, _) = channel(4);
+    let shutdown_result_sender_clone = shutdown_result_sender.clone();
+
+    let cluster_auth = match &config.cluster {
+        Some(cluster) => Some(ListenerAuth::cluster(cluster)?),
+        None => None,
+    };
+
+    let store = match &config.cluster {
+        Some(cluster) => Arc::new(Store::clustered(&config.store, cluster).await?),
+        None => Arc::new(Store::init(&config.store).await?),
+    };
+
+    let task_queues = Arc::new(TaskQueues::restore(&config.tasks, store.clone()).await?);
+
+    let node_registry = Arc::new(NodeRegistry::restore(store.clone()).await?);
+    let node_registry_clone = node_registry.clone();
+
+    let vm_registry = Arc::new(VmRegistry::restore(store.clone()).await?);
+    let vm_registry_clone = vm_registry.clone();
+
+    let profile_registry =
+        Arc::new(ProfileRegistry::restore(&config.profiles, store.clone()).await?);
+
+    let group_registry = Arc::new(GroupRegistry::restore(store.clone()).await?);
+
+    info!(
+        nodes = node_registry.list().await.len(),
+        vms = vm_registry.list().await.len(),
+        profiles = profile_registry.list().await.len(),
+        groups = group_registry.list().await.len(),
+        tasks = task_queues.depth().await,
+        "Interface state restored",
+    );
+
+    let (health_reporter, health_server) = health_reporter();
+    let readiness =
+        Readiness::init(health_reporter, node_registry.clone(), task_queues.clone()).await?;
+
+    let exporter = Exporter::Interface {
+        node_registry: node_registry.clone(),
+        vm_registry: vm_registry.clone(),
+    };
+
+    let policy = Arc::new(Policy::init(&config.policy).await?);
+    let audit_log = Arc::new(AuditLog::init(&config.audit).await?);
+    let event_log = Arc::new(EventLog::init(&config.events).await?);
+
+    let leadership = store.leadership();
+    let following = (
+        node_registry.clone(),
+        vm_registry.clone(),
+        task_queues.clone(),
+        profile_registry.clone(),
+        group_registry.clone(),
+    );
+
+    let internal_interface = Internal::init(
+        task_queues.clone(),
+        launch_result_sender,
+        shutdown_result_sender,
+        node_registry_clone,
+        vm_registry_clone,
+        event_log.clone(),
+    )
+    .await?;
+
+    let external_interface = External::init(
+        internal_interface.system_id,
+        task_queues,
+        launch_result_sender_clone,
+        shutdown_result_sender_clone,
+        node_registry,
+        vm_registry,
+        profile_registry,
+        group_registry,
+        policy,
+        audit_log,
+        event_log,
+    )
+    .await?;
+
+    info!(
+        version = %external_interface.version,
+        system_id = %internal_interface.system_id,
+        "Launching system",
+    );
+
+    if config.external.is_empty() || config.internal.is_empty() {
+        let error = SystemError::new("Both the external and internal interface need a listener!");
+
+        return Err(Box::new(error));
+    }
+
+    let (shutdown, _) = watch::channel(false);
+    let mut listeners = JoinSet::new();
+
+    let reconciler = external_interface.to_owned();
+    let external_server = ExternalInterfaceServer::new(external_interface);
+    let internal_server = InternalInterfaceServer::new(internal_interface);
+
+    let peers = store.raft().map(|raft| raft.peers()).unwrap_or_default();
+    let external_redirects: HashMap<String, String> = peers
+        .iter()
+        .map(|peer| (peer.id.to_owned(), peer.external.to_owned()))
+        .collect();
+    let internal_redirects: HashMap<String, String> = peers
+        .iter()
+        .map(|peer| (peer.id.to_owned(), peer.internal.to_owned()))
+        .collect();
+
+    for listener in &config.external {
+        let auth = ListenerAuth::init(listener, false);
+        let gate = LeaderGate::init(leadership.clone(), external_redirects.to_owned());
+        let service = InterceptedService::new(
+            InterceptedService::new(external_server.to_owned(), gate),
+            auth,
+        );
+
+        serve(
+            &mut listeners,
+            "external",
+            listener,
+            service,
+            health_server.to_owned(),
+            EXTERNAL_DESCRIPTORS,
+            &shutdown,
+        )
+        .await?;
+    }
+
+    for listener in &config.internal {
+        let auth = ListenerAuth::init(listener, true);
+        let gate = LeaderGate::init(leadership.clone(), internal_redirects.to_owned());
+        let service = InterceptedService::new(
+            InterceptedService::new(internal_server.to_owned(), gate),
+            auth,
+        );
+
+        serve(
+            &mut listeners,
+            "internal",
+            listener,
+            service,
+            health_server.to_owned(),
+            INTERNAL_DESCRIPTORS,
+            &shutdown,
+        )
+        .await?;
+    }
+
+    if let (Some(raft), Some(cluster), Some(auth)) = (store.raft(), &config.cluster, cluster_auth) {
+        let service = InterceptedService::new(ClusterServer::from_arc(raft.clone()), auth);
+
+        serve(
+            &mut listeners,
+            "cluster",
+            &cluster.listener,
+            service,
+            health_server.to_owned(),
+            CLUSTER_DESCRIPTORS,
+            &shutdown,
+        )
+        .await?;
+
+        let (node_registry, vm_registry, task_queues, profile_registry, group_registry) = following;
+
+        listeners.spawn(raft.run(shutdown.subscribe()));
+        listeners.spawn(follow_leadership(
+            store.leadership(),
+            node_registry,
+            vm_registry,
+            task_queues,
+            profile_registry,
+            group_registry,
+            shutdown.subscribe(),
+        ));
+    }
+
+    listeners.spawn(readiness.run(shutdown.subscribe()));
+    listeners.spawn(reconcile_groups(
+        reconciler,
+        leadership,
+        Duration::from_millis(config.groups.reconcile_ms),
+        shutdown.subscribe(),
+    ));
+
+    if let Some(address) = config.metrics {
+        let signal = shutdown_signal(&shutdown).await;
+
+        listeners.spawn(metrics::serve(address, exporter, signal));
+    }
+
+    info!("Running...");
+
+    let mut served = Ok(());
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c(), if !*shutdown.borrow() => {
+                info!("Shutting down...");
+
+                shutdown.send_replace(true);
+            }
+            listener = listeners.join_next() => match listener {
+                Some(Ok(Ok(()))) => {
+                    shutdown.send_replace(true);
+                }
+                Some(Ok(Err(error))) => {
+                    error!(%error, "Listener failed");
+
+                    shutdown.send_replace(true);
+                    served = Err(error as Box<dyn std::error::Error>);
+                }
+                Some(Err(error)) => {
+                    shutdown.send_replace(true);
+                    served = Err(Box::new(error) as Box<dyn std::error::Error>);
+                }
+                None => break,
+            },
+        }
+    }
+
+    served
+}
+
+async fn follow_leadership(
+    mut leadership: watch::Receiver<Leadership>,
+    node_registry: Arc<NodeRegistry>,
+    vm_registry: Arc<VmRegistry>,
+    task_queues: Arc<TaskQueues>,
+    profile_registry: Arc<ProfileRegistry>,
+    group_registry: Arc<GroupRegistry>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    loop {
+        tokio::select! {
+            changed = leadership.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+            }
+            _ = shutdown.changed() => return Ok(()),
+        }
+
+        let leader = match &*leadership.borrow_and_update() {
+            Leadership::Leader => None,
+            Leadership::Follower(leader) => Some(leader.to_owned().unwrap_or_default()),
+        };
+
+        let nodes = node_registry
+            .reload()
+            .await
+            .map_err(|error| error.to_string());
+        let vms = vm_registry
+            .reload()
+            .await
+            .map_err(|error| error.to_string());
+        let tasks = task_queues
+            .reload()
+            .await
+            .map_err(|error| error.to_string());
+        let profiles = profile_registry
+            .reload()
+            .await
+            .map_err(|error| error.to_string());
+        let groups = group_registry
+            .reload()
+            .await
+            .map_err(|error| error.to_string());
+
+        match (nodes, vms, tasks, profiles, groups, leader) {
+            (Ok(nodes), Ok(vms), Ok(tasks), Ok(profiles), Ok(groups), None) => {
+                info!(
+                    nodes,
+                    vms, tasks, profiles, groups, "Leading cluster... state reloaded"
+                );
+            }
+            (Ok(_), Ok(_), Ok(_), Ok(_), Ok(_), Some(leader)) => {
+                info!(%leader, "Following cluster... state reloaded");
+            }
+            (nodes, vms, tasks, profiles, groups, _) => {
+                let error = [
+                    nodes.err(),
+                    vms.err(),
+                    tasks.err(),
+                    profiles.err(),
+                    groups.err(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(", ");
+
+                error!(%error, "Unable to reload cluster state");
+            }
+        }
+    }
+}
+
+async fn reconcile_groups(
+    external: External,
+    leadership: watch::Receiver<Leadership>,
+    period: Duration,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut reconcile_interval = interval(period.max(Duration::from_millis(100)));
+
+    while !*shutdown.borrow_and_update() {
+        tokio::select! {
+            _ = reconcile_interval.tick() => {
+                let leading = matches!(&*leadership.borrow(), Leadership::Leader);
+
+                if leading {
+                    external.reconcile().await;
+                }
+            }
+            changed = shutdown.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn serve<S, H>(
+    listeners: &mut JoinSet<Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+    name: &str,
+    listener: &ListenerConfig,
+    service: S,
+    health: H,
+    descriptors: &'static [&'static [u8]],
+    shutdown: &watch::Sender<bool>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: Service<http::Request<Body>, Response = http::Response<BoxBody>, Error = Infallible>
+        + NamedService
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    H: Service<http::Request<Body>, Response = http::Response<BoxBody>, Error = Infallible>
+        + NamedService
+        + Clone
+        + Send
+        + 'static,
+    H::Future: Send + 'static,
+{
+    let mut server = Server::builder();
+
+    match &listener.tls {
+        Some(tls) => server = server.tls_config(server_tls(tls).await?)?,
+        None => warn!(
+            interface = name,
+            address = %listener.address,
+            "TLS is not configured... serving plaintext",
+        ),
+    }
+
+    info!(
+        interface = name,
+        address = %listener.address,
+        bearer_tokens = listener.tokens.len(),
+        "Serving interface",
+    );
+
+    let reflection = health::reflection(descriptors).await?;
+    let router = server
+        .add_service(service)
+        .add_service(health)
+        .add_service(reflection);
+    let signal = shutdown_signal(shutdown).await;
+
+    match &listener.address {
+        ListenAddress::Tcp(socket_addr) => {
+            let socket_addr = socket_addr.to_owned();
+
+            listeners.spawn(async move {
+                router.serve_with_shutdown(socket_addr, signal).await?;
+
+                Ok(())
+            });
+        }
+        ListenAddress::Unix(path) => {
+            let incoming = UnixListenerStream::new(bind_unix(path).await?);
+            let path = path.to_owned();
+
+            listeners.spawn(async move {
+                let served = router.serve_with_incoming_shutdown(incoming, signal).await;
+
+                remove_file(&path).await.ok();
+
+                served?;
+
+                Ok(())
+            });
+        }
+    }
+
+    Ok(())
+}
+
+async fn shutdown_signal(shutdown: &watch::Sender<bool>) -> impl Future<Output = ()> {
+    let mut shutdown = shutdown.subscribe();
+
+    async move {
+        while !*shutdown.borrow_and_update() {
+            if shutdown.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn bind_unix(path: &Path) -> Result<UnixListener, Box<dyn std::error::Error>> {
+    if let Ok(metadata) = symlink_metadata(path).await {
+        match metadata.file_type().is_socket() {
+            true => remove_file(path).await?,
+            false => {
+                let message = format!("{} exists and is not a socket!", path.display());
+
+                return Err(Box::new(SystemError::new(&message)));
+            }
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).await?;
+    }
+
+    Ok(UnixListener::bind(path)?)
+}
+
+pub async fn actuator(config: ActuatorConfig) -> Result<(), Box<dyn std::error::Error>> {
+    telemetry::init(&config.log).await?;
+
+    info!(
+        endpoint = %config.endpoint,
+        failover_endpoints = config.failover_endpoints.len(),
+        "Connecting",
+    );
+
+    let mut internal_client = InternalClient::init(
+        &config.endpoint,
+        &config.failover_endpoints,
+        config.tls.as_ref(),
+        config.token.as_deref(),
+    )
+    .await?;
+
+    internal_client.labels = config.labels.to_owned();
+
+    if let Some(address) = config.metrics {
+        tokio::spawn(async move {
+            if let Err(error) =
+                metrics::serve(address, Exporter::Actuator, std::future::pending()).await
+            {
+                error!(%error, "Metrics listener failed");
+            }
+        });
+    }
+
+    let span = info_span!("actuator", node_id = %internal_client.node_id);
+
+    actuate(config, internal_client).instrument(span).await
+}
+
+async fn actuate(
+    config: ActuatorConfig,
+    mut internal_client: InternalClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut engine = Engine::init(&config).await?;
+    info!(active = engine.active, "Engine initialized");
+
+    let mut last_inventory = None;
+    let mut held = Vec::with_capacity(0);
+
+    loop {
+        let inventory = match collect_inventory(&engine, &mut last_inventory).await {
+            Some(inventory) => inventory,
+            None => {
+                sleep(INVENTORY_RETRY).await;
+
+                continue;
+            }
+        };
+        info!(
+            cpus = inventory.cpu_count,
+            images = inventory.images.len(),
+            "Node inventory collected",
+        );
+
+        let mut controller = {
+            let mut outage_interval = interval(Duration::from_millis(config.restart.watch_ms));
+            let session = internal_client.session(inventory);
+
+            tokio::pin!(session);
+
+            loop {
+                tokio::select! {
+                    controller = &mut session => break controller,
+                    _ = outage_interval.tick() => held.extend(engine.supervise().await),
+                }
+            }
+        };
+        info!(held = held.len(), "Awaiting tasks...");
+
+        let mut inventory_interval = interval(Duration::from_secs(30));
+        let mut watch_interval = interval(Duration::from_millis(config.restart.watch_ms));
+
+        loop {
+            tokio::select! {
+                _ = inventory_interval.tick() => {
+                    let inventory = match collect_inventory(&engine, &mut last_inventory).await {
+                        Some(inventory) => inventory,
+                        None => continue,
+                    };
+
+                    if let Err(error) = internal_client.update_inventory(inventory).await {
+                        warn!(error = error.message(), "Connection lost");
+                        break;
+                    }
+                }
+                _ = watch_interval.tick() => {
+                    let mut supervised = std::mem::take(&mut held);
+
+                    supervised.extend(engine.supervise().await);
+
+                    if let Err(error) = report_supervised(&mut internal_client, supervised).await {
+                        warn!(error = error.message(), "Connection lost");
+                        break;
+                    }
+                }
+                message = controller.message() => {
+                    let task = match message {
+                        Ok(Some(task)) => task,
+                        Ok(None) => {
+                            warn!("Controller stream closed");
+                            break;
+                        }
+                        Err(error) => {
+                            warn!(error = error.message(), "Connection lost");
+                            break;
+                        }
+                    };
+
+                    if let Err(error) = perform(&mut engine, &mut internal_client, &task).await {
+                        warn!(error = error.message(), "Connection lost");
+                        break;
+                    }
+                }
+            }
+        }
+
+        info!("Reconnecting...");
+    }
+}
+
+async fn collect_inventory(engine: &Engine, last: &mut Option<Inventory>) -> Option<Inventory> {
+    match engine.inventory().await {
+        Ok(inventory) => {
+            *last = Some(inventory.to_owned());
+
+            Some(inventory)
+        }
+        Err(error) => {
+            warn!(%error, "Unable to collect node inventory... reusing the last one");
+
+            last.to_owned().map(|mut inventory| {
+                inventory.vm_ids = engine.vm_ids();
+
+                inventory
+            })
+        }
+    }
+}
+
+async fn report_supervised(
+    internal_client: &mut InternalClient,
+    supervised: Vec<Supervised>,
+) -> Result<(), Status> {
+    let mut reported = Ok(());
+
+    for supervised in supervised {
+        let trace_id = Uuid::new_v4().simple().to_string();
+        let report = match supervised {
+            Supervised::Exited(exit) => internal_client.exit_report(&exit, &trace_id).await,
+            Supervised::Restarted { uuid, details } => {
+                internal_client
+                    .launch_result(&uuid, true, details, &trace_id)
+                    .await
+            }
+        };
+
+        match report {
+            Err(error) if !rejected(&error) => reported = Err(error),
+            _ => (),
+        }
+    }
+
+    reported
+}
+
+async fn perform(
+    engine: &mut Engine,
+    internal_client: &mut InternalClient,
+    task: &Task,
+) -> Result<(), Status> {
+    let span = info_span!(
+        "task",
+        task_id = %task.id,
+        vm_uuid = %task.id,
+        trace_id = %task.trace_id,
+    );
+
+    let report = async {
+        match task.action {
+            1 => {
+                info!("Start instance task received");
+                let started = Instant::now();
+                let machine = task.machine.to_owned().unwrap_or_default();
+                let launch = engine.launch_vm(&task.id, &machine).await;
+                let result = match &launch {
+                    Ok((true, _)) => "launched",
+                    Ok((false, _)) => "failed",
+                    Err(_) => "error",
+                };
+                ACTUATOR.observe_launch(result, started.elapsed()).await;
+                let (launched, details) = match launch {
+                    Ok(launch) => launch,
+                    Err(error) => {
+                        warn!(%error, "Launch failed");
+
+                        (false, error.to_string())
+                    }
+                };
+
+                Some(
+                    internal_client
+                        .launch_result(&task.id, launched, details, &task.trace_id)
+                        .await,
+                )
+            }
+            2 => {
+                info!("Shutdown instance task received");
+                let started = Instant::now();
+                let shutdown = engine.shutdown_vm(&task.id).await;
+                let result = match &shutdown {
+                    Ok((true, _)) => "shutdown",
+                    Ok((false, _)) => "failed",
+                    Err(_) => "error",
+                };
+                ACTUATOR.observe_shutdown(result, started.elapsed()).await;
+                let (shutdown, details) = match shutdown {
+                    Ok(shutdown) => shutdown,
+                    Err(error) => {
+                        warn!(%error, "Shutdown failed");
+
+                        (false, error.to_string())
+                    }
+                };
+
+                Some(
+                    internal_client
+                        .shutdown_result(&task.id, shutdown, details, &task.trace_id)
+                        .await,
+                )
+            }
+            _ => None,
+        }
+    }
+    .instrument(span)
+    .await;
+
+    let acknowledged = internal_client.acknowledge(&task.id).await;
+
+    if let Some(Err(error)) = report {
+        if !rejected(&error) {
+            return Err(error);
+        }
+    }
+
+    acknowledged?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{AuditConfig, EventsConfig, PolicyConfig, TaskQueueConfig};
+    use crate::external_client::External as ExternalClient;
+
+    use tonic::transport::Channel;
+
+    use uuid::Uuid;
+
+    use tonic_health::pb::health_check_response::ServingStatus;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve_listeners() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let (test_launch_result_sender, _) = channel(4);
+        let (test_shutdown_result_sender, _) = channel(4);
+        let test_external = External::init(
+            Uuid::new_v4(),
+            test_task_queues,
+            test_launch_result_sender,
+            test_shutdown_result_sender,
+            Arc::new(NodeRegistry::init().await?),
+            Arc::new(VmRegistry::init().await?),
+            Arc::new(ProfileRegistry::init(&HashMap::new()).await?),
+            Arc::new(GroupRegistry::init().await?),
+            Arc::new(Policy::init(&PolicyConfig::default()).await?),
+            Arc::new(
+                AuditLog::init(&AuditConfig {
+                    path: None,
+                    capacity: 10,
+                    max_bytes: 0,
+                    max_files: 0,
+                })
+                .await?,
+            ),
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let test_external_server = ExternalInterfaceServer::new(test_external);
+        let test_socket = "unix:/tmp/test_impulse/runtime/external.sock";
+        let test_uds_listener = ListenerConfig::from(test_socket.parse::<ListenAddress>()?);
+        let mut test_tcp_listener =
+            ListenerConfig::from("127.0.0.1:48212".parse::<ListenAddress>()?);
+        test_tcp_listener.tokens = vec![String::from("test_token")];
+        let (_test_reporter, test_health) = health_reporter();
+        let (test_shutdown, _) = watch::channel(false);
+        let mut test_listeners = JoinSet::new();
+        for test_listener in [&test_uds_listener, &test_tcp_listener] {
+            let test_service = InterceptedService::new(
+                test_external_server.to_owned(),
+                ListenerAuth::init(test_listener, false),
+            );
+            serve(
+                &mut test_listeners,
+                "external",
+                test_listener,
+                test_service,
+                test_health.to_owned(),
+                EXTERNAL_DESCRIPTORS,
+                &test_shutdown,
+            )
+            .await?;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let mut test_local = ExternalClient::init(test_socket, None, None).await?;
+        assert_eq!(
+            test_local.system_status().await?.status.as_str(),
+            "not_ready"
+        );
+        let mut test_remote = ExternalClient::init("http://127.0.0.1:48212", None, None).await?;
+        let test_unauthenticated = test_remote.system_status().await.unwrap_err();
+        assert_eq!(test_unauthenticated.code(), tonic::Code::Unauthenticated);
+        let mut test_remote =
+            ExternalClient::init("http://127.0.0.1:48212", None, Some("test_token")).await?;
+        assert!(test_remote.system_status().await.is_ok());
+        let mut test_health_client = HealthClient::new(
+            Channel::from_static("http://127.0.0.1:48212")
+                .connect()
+                .await?,
+        );
+        let test_health_check = test_health_client
+            .check(HealthCheckRequest {
+                service: String::new(),
+            })
+            .await?;
+        assert_eq!(
+            test_health_check.into_inner().status,
+            ServingStatus::Serving as i32,
+        );
+
+        test_shutdown.send_replace(true);
+        while let Some(test_listener) = test_listeners.join_next().await {
+            assert!(test_listener?.is_ok());
+        }
+        assert!(symlink_metadata("/tmp/test_impulse/runtime/external.sock")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn perform_failed_launch() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TaskQueueConfig::default()).await?);
+        let test_vm_registry = Arc::new(VmRegistry::init().await?);
+        let (test_launch_result_sender, _) = channel(4);
+        let (test_shutdown_result_sender, _) = channel(4);
+        let test_internal = Internal::init(
+            test_task_queues.to_owned(),
+            test_launch_result_sender,
+            test_shutdown_result_sender,
+            Arc::new(NodeRegistry::init().await?),
+            test_vm_registry.to_owned(),
+            Arc::new(EventLog::init(&EventsConfig::default()).await?),
+        )
+        .await?;
+        let (test_shutdown, test_shutdown_signal) = tokio::sync::oneshot::channel::<()>();
+        let test_server = Server::builder()
+            .add_service(InternalInterfaceServer::new(test_internal))
+            .serve_with_shutdown("127.0.0.1:48213".parse()?, async {
+                test_shutdown_signal.await.ok();
+            });
+        let test_server = tokio::spawn(test_server);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let mut test_internal_client =
+            InternalClient::init("http://127.0.0.1:48213", &[], None, None).await?;
+        let test_node_id = test_internal_client.node_id.to_owned();
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await?;
+        let test_launch = Task {
+            action: 1,
+            id: Uuid::new_v4().simple().to_string(),
+            trace_id: String::from("test_trace_id"),
+            machine: Some(impulse::shared::v010::MachineSpec {
+                kernel_image: String::from("test_missing_kernel"),
+                ..Default::default()
+            }),
+        };
+        let test_shutdown_task = Task {
+            action: 2,
+            id: Uuid::new_v4().simple().to_string(),
+            trace_id: String::from("test_trace_id"),
+            machine: None,
+        };
+        test_task_queues.open(&test_node_id).await?;
+        let test_session = test_task_queues.connect(&test_node_id).await.unwrap();
+        for test_task in [&test_launch, &test_shutdown_task] {
+            test_task_queues
+                .enqueue(&test_node_id, test_task.to_owned())
+                .await?;
+            test_task_queues.next(&test_node_id, test_session).await;
+        }
+        assert_eq!(test_task_queues.depth().await, 2);
+        test_vm_registry
+            .pending(
+                &test_launch.id,
+                "",
+                crate::vm_registry::VmSpec::default(),
+                &crate::tenants::Claim::default(),
+            )
+            .await?;
+
+        perform(&mut test_engine, &mut test_internal_client, &test_launch).await?;
+        let test_record = test_vm_registry.get(&test_launch.id).await.unwrap();
+        assert_eq!(test_record.state, crate::vm_registry::VmState::Failed);
+        assert!(!test_record.details.is_empty());
+        assert_eq!(test_task_queues.depth().await, 1);
+        perform(
+            &mut test_engine,
+            &mut test_internal_client,
+            &test_shutdown_task,
+        )
+        .await?;
+        assert_eq!(test_task_queues.depth().await, 0);
+
+        test_shutdown.send(()).ok();
+        test_server.await??;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn collect_inventory_fallback() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_engine = Engine::init(&ActuatorConfig::default()).await?;
+        let mut test_last = None;
+        let test_inventory = collect_inventory(&test_engine, &mut test_last)
+            .await
+            .unwrap();
+        assert_eq!(test_last.as_ref(), Some(&test_inventory));
+        test_engine.images_base = Path::new("/var/lib/test_impulse_actuator/missing").into();
+        assert_eq!(
+            collect_inventory(&test_engine, &mut test_last).await,
+            Some(test_inventory),
+        );
+        assert!(collect_inventory(&test_engine, &mut None).await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn report_supervised_unreachable() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_internal_client =
+            InternalClient::init("http://127.0.0.1:1", &[], None, None).await?;
+        assert!(report_supervised(&mut test_internal_client, Vec::new())
+            .await
+            .is_ok());
+        let test_supervised = vec![
+            Supervised::Exited(crate::actuator_engine::Exit {
+                uuid: String::from("test_uuid"),
+                reason: String::from("exited with status 1"),
+                exit_code: 1,
+                failed: true,
+                restarting: false,
+                restarts: 0,
+            }),
+            Supervised::Restarted {
+                uuid: String::from("test_other_uuid"),
+                details: String::from("MicroVM restarted (restart 1)"),
+            },
+        ];
+        let test_lost = report_supervised(&mut test_internal_client, test_supervised)
+            .await
+            .unwrap_err();
+        assert_eq!(test_lost.code(), tonic::Code::Unavailable);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bind_unix_not_socket() -> Result<(), Box<dyn std::error::Error>> {
+        let test_path = Path::new("/tmp/test_impulse/runtime/not_a_socket");
+        create_dir_all("/tmp/test_impulse/runtime").await?;
+        tokio::fs::write(test_path, b"test").await?;
+        assert!(bind_unix(test_path).await.is_err());
+        assert!(symlink_metadata(test_path).await?.is_file());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interface_without_listeners() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = InterfaceConfig {
+            internal: Vec::with_capacity(0),
+            ..InterfaceConfig::default()
+        };
+        assert!(interface(test_config).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::sync::Arc;
+
+use sled::{Db, Tree};
+
+use tokio::sync::watch;
+
+use tonic::Status;
+
+use tracing::{error, info};
+
+use crate::cluster::{Leadership, Raft, RAFT, RAFT_LOG};
+use crate::config::{ClusterConfig, StoreConfig};
+use crate::groups::GroupRecord;
+use crate::impulse::cluster::v010::Command;
+use crate::impulse::shared::v010::Task;
+use crate::node_registry::NodeRecord;
+use crate::profiles::ProfileRecord;
+use crate::system_error::SystemError;
+use crate::vm_registry::VmRecord;
+
+const SCHEMA_VERSION: &[u8] = b"schema_version";
+const NODES: &str = "nodes";
+const VMS: &str = "vms";
+const TASKS: &str = "tasks";
+const PROFILES: &str = "profiles";
+const GROUPS: &str = "groups";
+
+pub(crate) const STATE_TREES: &[&str] = &[NODES, VMS, TASKS, PROFILES, GROUPS];
+
+type Migration = fn(&Db) -> sled::Result<()>;
+
+const MIGRATIONS: &[Migration] = &[
+    create_trees,
+    create_raft_trees,
+    create_profile_tree,
+    create_group_tree,
+];
+
+fn create_trees(db: &Db) -> sled::Result<()> {
+    for tree in [NODES, VMS, TASKS] {
+        db.open_tree(tree)?;
+    }
+
+    Ok(())
+}
+
+fn create_raft_trees(db: &Db) -> sled::Result<()> {
+    for tree in [RAFT, RAFT_LOG] {
+        db.open_tree(tree)?;
+    }
+
+    Ok(())
+}
+
+fn create_profile_tree(db: &Db) -> sled::Result<()> {
+    db.open_tree(PROFILES)?;
+
+    Ok(())
+}
+
+fn create_group_tree(db: &Db) -> sled::Result<()> {
+    db.open_tree(GROUPS)?;
+
+    Ok(())
+}
+
+pub struct Store {
+    db: Db,
+    nodes: Tree,
+    vms: Tree,
+    tasks: Tree,
+    profiles: Tree,
+    groups: Tree,
+    raft: Option<Arc<Raft>>,
+}
+
+impl Store {
+    pub async fn init(config: &StoreConfig) -> Result<Store, Box<dyn std::error::Error>> {
+        let db = match &config.path {
+            Some(path) => sled::Config::new().path(path).open()?,
+            None => sled::Config::new().temporary(true).open()?,
+        };
+
+        Self::migrate(&db).await?;
+
+        let nodes = db.open_tree(NODES)?;
+        let vms = db.open_tree(VMS)?;
+        let tasks = db.open_tree(TASKS)?;
+        let profiles = db.open_tree(PROFILES)?;
+        let groups = db.open_tree(GROUPS)?;
+
+        Ok(Store {
+            db,
+            nodes,
+            vms,
+            tasks,
+            profiles,
+            groups,
+            raft: None,
+        })
+    }
+
+    pub async fn clustered(
+        config: &StoreConfig,
+        cluster: &ClusterConfig,
+    ) -> Result<Store, Box<dyn std::error::Error>> {
+        let mut store = Self::init(config).await?;
+        let raft = Raft::init(cluster, store.db.clone()).await?;
+
+        store.raft = Some(Arc::new(raft));
+
+        Ok(store)
+    }
+
+    pub fn raft(&self) -> Option<Arc<Raft>> {
+        self.raft.clone()
+    }
+
+    pub fn leadership(&self) -> watch::Receiver<Leadership> {
+        match &self.raft {
+            Some(raft) => raft.leadership(),
+            None => watch::channel(Leadership::Leader).1,
+        }
+    }
+
+    async fn schema_version(db: &Db) -> Result<usize, Box<dyn std::error::Error>> {
+        match db.get(SCHEMA_VERSION)? {
+            Some(version) => Ok(serde_json::from_slice(&version)?),
+            None => Ok(0),
+        }
+    }
+
+    async fn migrate(db: &Db) -> Result<(), Box<dyn std::error::Error>> {
+        let mut version = Self::schema_version(db).await?;
+
+        if version > MIGRATIONS.len() {
+            let message = format!(
+                "Store schema version {} is newer than the supported version {}!",
+                version,
+                MIGRATIONS.len(),
+            );
+
+            return Err(Box::new(SystemError::new(&message)));
+        }
+
+        for migration in &MIGRATIONS[version..] {
+            migration(db)?;
+
+            version += 1;
+
+            db.insert(SCHEMA_VERSION, serde_json::to_vec(&version)?)?;
+            db.flush_async().await?;
+
+            info!(version, "Store schema migrated");
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn put_node(&self, record: &NodeRecord) -> Result<(), Status> {
+        self.put(&self.nodes, &record.node_id, record).await
+    }
+
+    pub(crate) async fn remove_node(&self, node_id: &str) -> Result<(), Status> {
+        self.remove(&self.nodes, node_id).await
+    }
+
+    pub(crate) async fn nodes(&self) -> Result<Vec<NodeRecord>, Box<dyn std::error::Error>> {
+        Self::load(&self.nodes).await
+    }
+
+    pub(crate) async fn put_vm(&self, record: &VmRecord) -> Result<(), Status> {
+        self.put(&self.vms, &record.uuid, record).await
+    }
+
+    pub(crate) async fn remove_vm(&self, uuid: &str) -> Result<(), Status> {
+        self.remove(&self.vms, uuid).await
+    }
+
+    pub(crate) async fn vms(&self) -> Result<Vec<VmRecord>, Box<dyn std::error::Error>> {
+        Self::load(&self.vms).await
+    }
+
+    pub(crate) async fn put_tasks(&self, node_id: &str, tasks: &[Task]) -> Result<(), Status> {
+        self.put(&self.tasks, node_id, &(node_id, tasks)).await
+    }
+
+    pub(crate) async fn remove_tasks(&self, node_id: &str) -> Result<(), Status> {
+        self.remove(&self.tasks, node_id).await
+    }
+
+    pub(crate) async fn tasks(
+        &self,
+    ) -> Result<Vec<(String, Vec<Task>)>, Box<dyn std::error::Error>> {
+        Self::load(&self.tasks).await
+    }
+
+    pub(crate) async fn put_profile(&self, record: &ProfileRecord) -> Result<(), Status> {
+        self.put(&self.profiles, &record.name, record).await
+    }
+
+    pub(crate) async fn remove_profile(&self, name: &str) -> Result<(), Status> {
+        self.remove(&self.profiles, name).await
+    }
+
+    pub(crate) async fn profiles(&self) -> Result<Vec<ProfileRecord>, Box<dyn std::error::Error>> {
+        Self::load(&self.profiles).await
+    }
+
+    pub(crate) async fn put_group(&self, record: &GroupRecord) -> Result<(), Status> {
+        self.put(&self.groups, &record.name, record).await
+    }
+
+    pub(crate) async fn remove_group(&self, name: &str) -> Result<(), Status> {
+        self.remove(&self.groups, name).await
+    }
+
+    pub(crate) async fn groups(&self) -> Result<Vec<GroupRecord>, Box<dyn std::error::Error>> {
+        Self::load(&self.groups).await
+    }
+
+    async fn put<T: Serialize>(&self, tree: &Tree, key: &str, value: &T) -> Result<(), Status> {
+        let value = match serde_json::to_vec(value) {
+            Ok(value) => value,
+            Err(error) => {
+                error!(key, %error, "Unable to encode store record");
+
+                return Err(Status::internal(format!(
+                    "Unable to encode store record {}",
+                    key,
+                )));
+            }
+        };
+
+        if let Some(raft) = &self.raft {
+            return self.replicate(raft, tree, key, value, false).await;
+        }
+
+        if let Err(error) = tree.insert(key, value) {
+            error!(key, %error, "Unable to write store record");
+
+            return Err(Status::unavailable(format!(
+                "Unable to write store record {}... please retry later!",
+                key,
+            )));
+        }
+
+        self.flush(key).await
+    }
+
+    async fn remove(&self, tree: &Tree, key: &str) -> Result<(), Status> {
+        if let Some(raft) = &self.raft {
+            return self
+                .replicate(raft, tree, key, Vec::with_capacity(0), true)
+                .await;
+        }
+
+        if let Err(error) = tree.remove(key) {
+            error!(key, %error, "Unable to remove store record");
+
+            return Err(Status::unavailable(format!(
+                "Unable to remove store record {}... please retry later!",
+                key,
+            )));
+        }
+
+        self.flush(key).await
+    }
+
+    async fn replicate(
+        &self,
+        raft: &Raft,
+        tree: &Tree,
+        key: &str,
+        value: Vec<u8>,
+        remove: bool,
+    ) -> Result<(), Status> {
+        let command = Command {
+            tree: String::from_utf8_lossy(&tree.name()).into_owned(),
+            key: key.as_bytes().to_vec(),
+            value,
+            remove,
+        };
+
+        if let Err(error) = raft.propose(vec![command]).await {
+            error!(
+                key,
+                error = error.message(),
+                "Unable to replicate store record"
+            );
+
+            return Err(Status::unavailable(error.message()));
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&self, key: &str) -> Result<(), Status> {
+        if let Err(error) = self.db.flush_async().await {
+            error!(key, %error, "Unable to flush store");
+
+            return Err(Status::unavailable(format!(
+                "Unable to flush store record {}... please retry later!",
+                key,
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn load<T: DeserializeOwned>(tree: &Tree) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        let mut records = Vec::with_capacity(tree.len());
+
+        for entry in tree.iter() {
+            let (_, value) = entry?;
+
+            records.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn migrate() -> Result<(), Box<dyn std::error::Error>> {
+        let test_db = sled::Config::new().temporary(true).open()?;
+        assert_eq!(Store::schema_version(&test_db).await?, 0);
+        Store::migrate(&test_db).await?;
+        assert_eq!(Store::schema_version(&test_db).await?, MIGRATIONS.len());
+        assert!(test_db.tree_names().contains(&sled::IVec::from(TASKS)));
+        assert!(test_db.tree_names().contains(&sled::IVec::from(PROFILES)));
+        assert!(test_db.tree_names().contains(&sled::IVec::from(GROUPS)));
+        Store::migrate(&test_db).await?;
+        assert_eq!(Store::schema_version(&test_db).await?, MIGRATIONS.len());
+        test_db.insert(SCHEMA_VERSION, serde_json::to_vec(&(MIGRATIONS.len() + 1))?)?;
+        assert!(Store::migrate(&test_db).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn records() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Store::init(&StoreConfig { path: None }).await?;
+        assert!(test_store.nodes().await?.is_empty());
+        test_store
+            .put_tasks("test_node", &[Task::default(), Task::default()])
+            .await?;
+        let test_tasks = test_store.tasks().await?;
+        assert_eq!(test_tasks.len(), 1);
+        assert_eq!(test_tasks[0].0.as_str(), "test_node");
+        assert_eq!(test_tasks[0].1.len(), 2);
+        test_store.remove_tasks("test_node").await?;
+        assert!(test_store.tasks().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn temporary() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Store::init(&StoreConfig { path: None }).await?;
+        assert_eq!(
+            Store::schema_version(&test_store.db).await?,
+            MIGRATIONS.len()
+        );
+        assert!(test_store.vms().await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, Notify};
+
+use tonic::Status;
+
+use crate::config::TaskQueueConfig;
+use crate::impulse::shared::v010::Task;
+use crate::store::Store;
+
+struct NodeQueue {
+    queued: VecDeque<Task>,
+    unacked: VecDeque<Task>,
+    session: u64,
+    connected: bool,
+    notify: Arc<Notify>,
+}
+
+impl NodeQueue {
+    async fn init() -> NodeQueue {
+        NodeQueue {
+            queued: VecDeque::with_capacity(20),
+            unacked: VecDeque::with_capacity(20),
+            session: 0,
+            connected: false,
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.queued.len() + self.unacked.len()
+    }
+
+    fn tasks(&self) -> Vec<Task> {
+        self.unacked.iter().chain(&self.queued).cloned().collect()
+    }
+}
+
+pub struct TaskQueues {
+    capacity: usize,
+    queues: Mutex<HashMap<String, NodeQueue>>,
+    store: Option<Arc<Store>>,
+}
+
+impl TaskQueues {
+    pub async fn init(config: &TaskQueueConfig) -> Result<TaskQueues, Box<dyn std::error::Error>> {
+        let capacity = config.capacity.max(1);
+        let queues = Mutex::new(HashMap::with_capacity(20));
+
+        Ok(TaskQueues {
+            capacity,
+            queues,
+            store: None,
+        })
+    }
+
+    pub async fn restore(
+        config: &TaskQueueConfig,
+        store: Arc<Store>,
+    ) -> Result<TaskQueues, Box<dyn std::error::Error>> {
+        let capacity = config.capacity.max(1);
+        let queues = Self::load(&store).await?;
+
+        Ok(TaskQueues {
+            capacity,
+            queues: Mutex::new(queues),
+            store: Some(store),
+        })
+    }
+
+    async fn load(store: &Store) -> Result<HashMap<String, NodeQueue>, Box<dyn std::error::Error>> {
+        let stored = store.tasks().await?;
+        let mut queues = HashMap::with_capacity(stored.len());
+
+        for (node_id, tasks) in stored {
+            let mut queue = NodeQueue::init().await;
+
+            queue.queued.extend(tasks);
+            queues.insert(node_id, queue);
+        }
+
+        Ok(queues)
+    }
+
+    pub(crate) async fn reload(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut queues = self.queues.lock().await;
+
+        let mut reloaded = match &self.store {
+            Some(store) => Self::load(store).await?,
+            None => HashMap::with_capacity(0),
+        };
+
+        for (node_id, queue) in queues.iter_mut() {
+            queue.notify.notify_waiters();
+
+            while let Some(task) = queue.unacked.pop_back() {
+                queue.queued.push_front(task);
+            }
+
+            queue.session += 1;
+            queue.connected = false;
+
+            if let Some(reloaded) = reloaded.get_mut(node_id) {
+                reloaded.session = queue.session;
+            }
+        }
+
+        if self.store.is_some() {
+            *queues = reloaded;
+        }
+
+        Ok(queues.values().map(NodeQueue::depth).sum())
+    }
+
+    async fn persist(&self, node_id: &str, queue: &NodeQueue) -> Result<(), Status> {
+        match &self.store {
+            Some(store) => store.put_tasks(node_id, &queue.tasks()).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn open(&self, node_id: &str) -> Result<(), Status> {
+        let mut queues = self.queues.lock().await;
+
+        if !queues.contains_key(node_id) {
+            let queue = NodeQueue::init().await;
+
+            self.persist(node_id, &queue).await?;
+
+            queues.insert(node_id.to_owned(), queue);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn close(&self, node_id: &str) -> Result<Vec<Task>, Status> {
+        let mut queues = self.queues.lock().await;
+
+        if let (Some(store), true) = (&self.store, queues.contains_key(node_id)) {
+            store.remove_tasks(node_id).await?;
+        }
+
+        match queues.remove(node_id) {
+            Some(queue) => {
+                queue.notify.notify_waiters();
+
+                Ok(queue.tasks())
+            }
+            None => Ok(Vec::with_capacity(0)),
+        }
+    }
+
+    pub(crate) async fn connect(&self, node_id: &str) -> Option<u64> {
+        let mut queues = self.queues.lock().await;
+        let queue = queues.get_mut(node_id)?;
+
+        while let Some(task) = queue.unacked.pop_back() {
+            queue.queued.push_front(task);
+        }
+
+        queue.session += 1;
+        queue.connected = true;
+        queue.notify.notify_waiters();
+
+        Some(queue.session)
+    }
+
+    pub(crate) async fn disconnect(&self, node_id: &str, session: u64) -> bool {
+        let mut queues = self.queues.lock().await;
+
+        match queues.get_mut(node_id) {
+            Some(queue) if queue.session == session => {
+                queue.connected = false;
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) async fn enqueue(&self, node_id: &str, task: Task) -> Result<usize, Status> {
+        let mut queues = self.queues.lock().await;
+
+        let queue = match queues.get_mut(node_id) {
+            Some(queue) => queue,
+            None => {
+                let message = format!(
+                    "Node {} has no task queue... please register first!",
+                    node_id
+                );
+
+                return Err(Status::not_found(message));
+            }
+        };
+
+        if queue.depth() >= self.capacity {
+            let message = format!(
+                "Task queue for node {} is full ({} tasks)... please retry later!",
+                node_id, self.capacity,
+            );
+
+            return Err(Status::resource_exhausted(message));
+        }
+
+        queue.queued.push_back(task);
+
+        if let Err(status) = self.persist(node_id, queue).await {
+            queue.queued.pop_back();
+
+            return Err(status);
+        }
+
+        queue.notify.notify_one();
+
+        Ok(queue.depth())
+    }
+
+    pub(crate) async fn next(&self, node_id: &str, session: u64) -> Option<Task> {
+        loop {
+            let notify = self.queues.lock().await.get(node_id)?.notify.clone();
+            let notified = notify.notified();
+
+            tokio::pin!(notified);
+
+            notified.as_mut().enable();
+
+            {
+                let mut queues = self.queues.lock().await;
+                let queue = queues.get_mut(node_id)?;
+
+                if queue.session != session {
+                    return None;
+                }
+
+                if let Some(task) = queue.queued.pop_front() {
+                    queue.unacked.push_back(task.to_owned());
+
+                    return Some(task);
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    pub(crate) async fn acknowledge(&self, node_id: &str, task_id: &str) -> Result<bool, Status> {
+        let mut queues = self.queues.lock().await;
+
+        let queue = match queues.get_mut(node_id) {
+            Some(queue) => queue,
+            None => return Ok(false),
+        };
+
+        match queue.unacked.iter().position(|task| task.id == task_id) {
+            Some(position) => {
+                let task = queue.unacked.remove(position);
+
+                if let Err(status) = self.persist(node_id, queue).await {
+                    if let Some(task) = task {
+                        queue.unacked.insert(position, task);
+                    }
+
+                    return Err(status);
+                }
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) async fn outstanding(&self, node_id: &str) -> Vec<String> {
+        let queues = self.queues.lock().await;
+
+        match queues.get(node_id) {
+            Some(queue) => queue.tasks().into_iter().map(|task| task.id).collect(),
+            None => Vec::with_capacity(0),
+        }
+    }
+
+    pub(crate) async fn connected(&self) -> Vec<(String, usize)> {
+        let queues = self.queues.lock().await;
+        let mut connected: Vec<(String, usize)> = queues
+            .iter()
+            .filter(|(_, queue)| queue.connected)
+            .map(|(node_id, queue)| (node_id.to_owned(), queue.depth()))
+            .collect();
+
+        connected.sort();
+
+        connected
+    }
+
+    pub(crate) async fn depth(&self) -> usize {
+        let queues = self.queues.lock().await;
+
+        queues.values().map(NodeQueue::depth).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::time::{timeout, Duration};
+
+    use crate::config::StoreConfig;
+
+    const TEST_CONFIG: TaskQueueConfig = TaskQueueConfig { capacity: 2 };
+
+    fn test_task(id: &str) -> Task {
+        Task {
+            action: 1,
+            id: id.to_owned(),
+            trace_id: String::new(),
+            machine: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn enqueue() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = TaskQueues::init(&TEST_CONFIG).await?;
+        let test_missing = test_task_queues
+            .enqueue("test_node", test_task("test_uuid_a"))
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        test_task_queues.open("test_node").await?;
+        assert_eq!(
+            test_task_queues
+                .enqueue("test_node", test_task("test_uuid_a"))
+                .await?,
+            1,
+        );
+        assert_eq!(
+            test_task_queues
+                .enqueue("test_node", test_task("test_uuid_b"))
+                .await?,
+            2,
+        );
+        let test_full = test_task_queues
+            .enqueue("test_node", test_task("test_uuid_c"))
+            .await
+            .unwrap_err();
+        assert_eq!(test_full.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(test_task_queues.depth().await, 2);
+        assert!(test_task_queues.connected().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn acknowledge() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = TaskQueues::init(&TEST_CONFIG).await?;
+        test_task_queues.open("test_node").await?;
+        test_task_queues
+            .enqueue("test_node", test_task("test_uuid_a"))
+            .await?;
+        test_task_queues
+            .enqueue("test_node", test_task("test_uuid_b"))
+            .await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        assert_eq!(
+            test_task_queues.connected().await,
+            vec![(String::from("test_node"), 2)],
+        );
+        let test_next = test_task_queues.next("test_node", test_session).await;
+        assert_eq!(test_next.unwrap().id.as_str(), "test_uuid_a");
+        assert_eq!(
+            test_task_queues.outstanding("test_node").await,
+            vec![String::from("test_uuid_a"), String::from("test_uuid_b")],
+        );
+        assert!(
+            test_task_queues
+                .acknowledge("test_node", "test_uuid_a")
+                .await?
+        );
+        assert!(
+            !test_task_queues
+                .acknowledge("test_node", "test_uuid_a")
+                .await?
+        );
+        assert_eq!(test_task_queues.depth().await, 1);
+        assert_eq!(
+            test_task_queues.outstanding("test_node").await,
+            vec![String::from("test_uuid_b")],
+        );
+        assert!(test_task_queues
+            .outstanding("test_other_node")
+            .await
+            .is_empty());
+        let test_next = test_task_queues.next("test_node", test_session).await;
+        assert_eq!(test_next.unwrap().id.as_str(), "test_uuid_b");
+        assert_eq!(test_task_queues.depth().await, 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn redeliver() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TEST_CONFIG).await?);
+        test_task_queues.open("test_node").await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let test_waiter = tokio::spawn({
+            let test_task_queues = test_task_queues.clone();
+            async move { test_task_queues.next("test_node", test_session).await }
+        });
+        test_task_queues
+            .enqueue("test_node", test_task("test_uuid_a"))
+            .await?;
+        let test_delivered = timeout(Duration::from_secs(1), test_waiter).await??;
+        assert_eq!(test_delivered.unwrap().id.as_str(), "test_uuid_a");
+        assert!(test_task_queues.disconnect("test_node", test_session).await);
+        assert!(test_task_queues.connected().await.is_empty());
+        let test_stale = tokio::spawn({
+            let test_task_queues = test_task_queues.clone();
+            async move { test_task_queues.next("test_node", test_session).await }
+        });
+        let test_stale_session = test_session;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        assert!(
+            !test_task_queues
+                .disconnect("test_node", test_stale_session)
+                .await
+        );
+        assert!(timeout(Duration::from_secs(1), test_stale)
+            .await??
+            .is_none());
+        let test_redelivered = test_task_queues.next("test_node", test_session).await;
+        assert_eq!(test_redelivered.unwrap().id.as_str(), "test_uuid_a");
+        test_task_queues
+            .enqueue("test_node", test_task("test_uuid_b"))
+            .await?;
+        let test_dropped = test_task_queues.close("test_node").await?;
+        assert_eq!(test_dropped.len(), 2);
+        assert!(test_task_queues
+            .next("test_node", test_session)
+            .await
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reload() -> Result<(), Box<dyn std::error::Error>> {
+        let test_task_queues = Arc::new(TaskQueues::init(&TEST_CONFIG).await?);
+        test_task_queues.open("test_node").await?;
+        test_task_queues
+            .enqueue("test_node", test_task("test_uuid_a"))
+            .await?;
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        test_task_queues.next("test_node", test_session).await;
+        let test_stale = tokio::spawn({
+            let test_task_queues = test_task_queues.clone();
+            async move { test_task_queues.next("test_node", test_session).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(test_task_queues.reload().await?, 1);
+        assert!(timeout(Duration::from_secs(1), test_stale)
+            .await??
+            .is_none());
+        assert!(test_task_queues.connected().await.is_empty());
+        let test_session = test_task_queues.connect("test_node").await.unwrap();
+        let test_redelivered = test_task_queues.next("test_node", test_session).await;
+        assert_eq!(test_redelivered.unwrap().id.as_str(), "test_uuid_a");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Arc::new(Store::init(&StoreConfig { path: None }).await?);
+        let test_task_queues = TaskQueues::restore(&TEST_CONFIG, test_store.to_owned()).await?;
+        for test_node in ["test_node_a", "test_node_b"] {
+            test_task_queues.open(test_node).await?;
+        }
+        test_task_queues
+            .enqueue("test_node_a", test_task("test_uuid_a"))
+            .await?;
+        test_task_queues
+            .enqueue("test_node_a", test_task("test_uuid_b"))
+            .await?;
+        let test_session = test_task_queues.connect("test_node_a").await.unwrap();
+        test_task_queues.next("test_node_a", test_session).await;
+        test_task_queues.next("test_node_a", test_session).await;
+        test_task_queues
+            .acknowledge("test_node_a", "test_uuid_a")
+            .await?;
+        test_task_queues.close("test_node_b").await?;
+        drop(test_task_queues);
+        let test_task_queues = TaskQueues::restore(&TEST_CONFIG, test_store).await?;
+        assert_eq!(test_task_queues.depth().await, 1);
+        assert!(test_task_queues.connected().await.is_empty());
+        assert!(test_task_queues
+            .enqueue("test_node_b", test_task("test_uuid_c"))
+            .await
+            .is_err());
+        let test_session = test_task_queues.connect("test_node_a").await.unwrap();
+        let test_redelivered = test_task_queues.next("test_node_a", test_session).await;
+        assert_eq!(test_redelivered.unwrap().id.as_str(), "test_uuid_b");
+        Ok(())
+    }
+}
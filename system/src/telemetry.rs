@@ -0,0 +1,84 @@
+use tonic::metadata::{MetadataMap, MetadataValue};
+
+use tracing::debug;
+use tracing_subscriber::EnvFilter;
+
+use uuid::Uuid;
+
+use crate::config::{LogConfig, LogFormat};
+
+pub const TRACE_ID: &str = "x-impulse-trace-id";
+
+pub async fn init(config: &LogConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = EnvFilter::try_new(&config.level)?;
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let installed = match config.format {
+        LogFormat::Text => subscriber.try_init(),
+        LogFormat::Json => subscriber
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init(),
+    };
+
+    if installed.is_err() {
+        debug!("Subscriber already installed... keeping it");
+    }
+
+    Ok(())
+}
+
+pub async fn trace_id(metadata: &MetadataMap) -> String {
+    metadata
+        .get(TRACE_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.trim().is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string())
+}
+
+pub async fn inject(metadata: &mut MetadataMap, trace_id: &str) {
+    if let Ok(value) = MetadataValue::try_from(trace_id) {
+        metadata.insert(TRACE_ID, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = LogConfig {
+            level: String::from("system=debug,warn"),
+            format: LogFormat::Json,
+        };
+        assert!(super::init(&test_config).await.is_ok());
+        assert!(super::init(&test_config).await.is_ok());
+        let test_invalid = LogConfig {
+            level: String::from("system=not_a_level"),
+            format: LogFormat::Text,
+        };
+        assert!(super::init(&test_invalid).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trace_id() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_metadata = MetadataMap::new();
+        let test_generated = super::trace_id(&test_metadata).await;
+        assert_eq!(Uuid::parse_str(&test_generated)?.get_version_num(), 4);
+        inject(&mut test_metadata, "test_trace_id").await;
+        assert_eq!(
+            super::trace_id(&test_metadata).await.as_str(),
+            "test_trace_id"
+        );
+        inject(&mut test_metadata, "not\na valid value").await;
+        assert_eq!(
+            super::trace_id(&test_metadata).await.as_str(),
+            "test_trace_id"
+        );
+        Ok(())
+    }
+}
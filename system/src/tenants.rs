@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::TenantsConfig;
+use crate::system_error::SystemError;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Resources {
+    pub vcpus: u64,
+    pub memory_mib: u64,
+    pub disk_mib: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Quota {
+    pub vms: Option<u64>,
+    pub vcpus: Option<u64>,
+    pub memory_mib: Option<u64>,
+    pub disk_mib: Option<u64>,
+}
+
+impl Quota {
+    pub fn admit(
+        &self,
+        tenant: &str,
+        usage: &Usage,
+        resources: &Resources,
+    ) -> Result<(), SystemError> {
+        let requested = [
+            ("vms", self.vms, usage.vms + 1),
+            ("vcpus", self.vcpus, usage.vcpus + resources.vcpus),
+            (
+                "memory_mib",
+                self.memory_mib,
+                usage.memory_mib + resources.memory_mib,
+            ),
+            (
+                "disk_mib",
+                self.disk_mib,
+                usage.disk_mib + resources.disk_mib,
+            ),
+        ];
+
+        for (resource, limit, total) in requested {
+            if let Some(limit) = limit.filter(|limit| total > *limit) {
+                let message = format!(
+                    "Tenant {} would use {} of {} {}... please shut down MicroVMs or raise the quota!",
+                    tenant, total, limit, resource,
+                );
+
+                return Err(SystemError::new(&message));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    pub vms: u64,
+    pub vcpus: u64,
+    pub memory_mib: u64,
+    pub disk_mib: u64,
+}
+
+impl Usage {
+    pub fn add(&mut self, resources: &Resources) {
+        self.vms += 1;
+        self.vcpus += resources.vcpus;
+        self.memory_mib += resources.memory_mib;
+        self.disk_mib += resources.disk_mib;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Claim {
+    pub tenant: String,
+    pub resources: Resources,
+    pub quota: Quota,
+}
+
+impl Claim {
+    pub fn admit(&self, usage: &Usage) -> Result<(), SystemError> {
+        self.quota.admit(&self.tenant, usage, &self.resources)
+    }
+}
+
+pub struct Tenants {
+    default: String,
+    footprint: Resources,
+    default_quota: Quota,
+    quotas: HashMap<String, Quota>,
+}
+
+impl Tenants {
+    pub async fn init(config: &TenantsConfig) -> Result<Tenants, Box<dyn std::error::Error>> {
+        Ok(Tenants {
+            default: config.default.to_owned(),
+            footprint: config.footprint,
+            default_quota: config.default_quota.to_owned(),
+            quotas: config.quotas.to_owned(),
+        })
+    }
+
+    pub fn default_tenant(&self) -> &str {
+        &self.default
+    }
+
+    pub fn footprint(&self) -> Resources {
+        self.footprint
+    }
+
+    pub fn quota(&self, tenant: &str) -> &Quota {
+        self.quotas.get(tenant).unwrap_or(&self.default_quota)
+    }
+
+    pub fn claim(&self, tenant: &str) -> Claim {
+        Claim {
+            tenant: tenant.to_string(),
+            resources: self.footprint,
+            quota: self.quota(tenant).to_owned(),
+        }
+    }
+
+    pub fn configured(&self) -> impl Iterator<Item = &String> {
+        self.quotas.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_config = TenantsConfig {
+            quotas: HashMap::from([(
+                String::from("test_team"),
+                Quota {
+                    vms: Some(2),
+                    ..Quota::default()
+                },
+            )]),
+            ..TenantsConfig::default()
+        };
+        let test_tenants = Tenants::init(&test_config).await?;
+        assert_eq!(test_tenants.default_tenant(), "default");
+        assert_eq!(test_tenants.footprint().vcpus, 2);
+        assert_eq!(test_tenants.footprint().memory_mib, 1024);
+        assert_eq!(test_tenants.quota("test_team").vms, Some(2));
+        assert_eq!(test_tenants.quota("test_other"), &Quota::default());
+        let test_claim = test_tenants.claim("test_team");
+        assert_eq!(test_claim.tenant.as_str(), "test_team");
+        assert_eq!(test_claim.resources, test_tenants.footprint());
+        assert_eq!(test_claim.quota.vms, Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn admit() -> Result<(), Box<dyn std::error::Error>> {
+        let test_footprint = Resources {
+            vcpus: 2,
+            memory_mib: 1024,
+            disk_mib: 512,
+        };
+        let mut test_usage = Usage::default();
+        assert!(Quota::default()
+            .admit("test_team", &test_usage, &test_footprint)
+            .is_ok());
+        let test_quota = Quota {
+            vms: Some(3),
+            vcpus: Some(4),
+            memory_mib: None,
+            disk_mib: Some(1024),
+        };
+        test_quota.admit("test_team", &test_usage, &test_footprint)?;
+        test_usage.add(&test_footprint);
+        assert_eq!(test_usage.vms, 1);
+        assert_eq!(test_usage.vcpus, 2);
+        test_quota.admit("test_team", &test_usage, &test_footprint)?;
+        test_usage.add(&test_footprint);
+        let test_error = test_quota
+            .admit("test_team", &test_usage, &test_footprint)
+            .unwrap_err();
+        assert_eq!(
+            test_error.to_string(),
+            "Tenant test_team would use 6 of 4 vcpus... please shut down MicroVMs or raise the quota!",
+        );
+        let test_quota = Quota {
+            vms: Some(2),
+            ..Quota::default()
+        };
+        assert!(test_quota
+            .admit("test_team", &test_usage, &test_footprint)
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,985 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::Mutex;
+
+use tonic::Status;
+
+use crate::impulse::internal::v010::MicroVmExit;
+use crate::impulse::shared::v010::{MachineSpec, MicroVmLaunch, MicroVmShutdown};
+use crate::labels::Selector;
+use crate::placement::PlacementSpec;
+use crate::store::Store;
+use crate::tenants::{Claim, Resources, Usage};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) enum VmState {
+    Pending,
+    Running,
+    Failed,
+    Shutdown,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub(crate) struct VmSpec {
+    pub name: String,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+    #[serde(default)]
+    pub placement: PlacementSpec,
+    #[serde(default)]
+    pub profile: String,
+    #[serde(default)]
+    pub machine: MachineSpec,
+    #[serde(default)]
+    pub group: String,
+    #[serde(default)]
+    pub generation: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct VmRecord {
+    pub uuid: String,
+    pub node_id: String,
+    pub state: VmState,
+    pub details: String,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+    #[serde(default)]
+    pub request_id: String,
+    #[serde(default)]
+    pub spec: VmSpec,
+    #[serde(default)]
+    pub tenant: String,
+    #[serde(default)]
+    pub resources: Resources,
+}
+
+impl VmRecord {
+    async fn init(uuid: &str) -> VmRecord {
+        let now = SystemTime::now();
+
+        VmRecord {
+            uuid: uuid.to_string(),
+            node_id: String::new(),
+            state: VmState::Pending,
+            details: String::new(),
+            created_at: now,
+            updated_at: now,
+            request_id: String::new(),
+            spec: VmSpec::default(),
+            tenant: String::new(),
+            resources: Resources::default(),
+        }
+    }
+
+    pub(crate) fn live(&self) -> bool {
+        matches!(self.state, VmState::Pending | VmState::Running)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum Reservation {
+    Created(VmRecord),
+    Existing(VmRecord),
+}
+
+#[derive(Debug)]
+pub(crate) struct Confirmation {
+    pub changed: Vec<VmRecord>,
+    pub unknown: Vec<String>,
+}
+
+pub struct VmRegistry {
+    vms: Mutex<HashMap<String, VmRecord>>,
+    store: Option<Arc<Store>>,
+}
+
+impl VmRegistry {
+    pub async fn init() -> Result<VmRegistry, Box<dyn std::error::Error>> {
+        let vms = Mutex::new(HashMap::with_capacity(20));
+
+        Ok(VmRegistry { vms, store: None })
+    }
+
+    pub async fn restore(store: Arc<Store>) -> Result<VmRegistry, Box<dyn std::error::Error>> {
+        let vms = store
+            .vms()
+            .await?
+            .into_iter()
+            .map(|record| (record.uuid.to_owned(), record))
+            .collect();
+
+        Ok(VmRegistry {
+            vms: Mutex::new(vms),
+            store: Some(store),
+        })
+    }
+
+    pub(crate) async fn reload(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut vms = self.vms.lock().await;
+
+        if let Some(store) = &self.store {
+            *vms = store
+                .vms()
+                .await?
+                .into_iter()
+                .map(|record| (record.uuid.to_owned(), record))
+                .collect();
+        }
+
+        Ok(vms.len())
+    }
+
+    async fn persist(&self, record: &VmRecord) -> Result<(), Status> {
+        match &self.store {
+            Some(store) => store.put_vm(record).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn pending(
+        &self,
+        uuid: &str,
+        request_id: &str,
+        spec: VmSpec,
+        claim: &Claim,
+    ) -> Result<Reservation, Status> {
+        let mut vms = self.vms.lock().await;
+
+        if !request_id.is_empty() {
+            if let Some(record) = vms
+                .values()
+                .find(|record| record.request_id == request_id && record.tenant == claim.tenant)
+            {
+                if record.spec != spec {
+                    let message = format!(
+                        "Request id {} was already used to launch MicroVM {} with a different spec!",
+                        request_id, record.uuid,
+                    );
+
+                    return Err(Status::already_exists(message));
+                }
+
+                return Ok(Reservation::Existing(record.to_owned()));
+            }
+        }
+
+        if !spec.name.is_empty() {
+            let taken = vms.values().any(|record| {
+                record.spec.name == spec.name && record.tenant == claim.tenant && record.live()
+            });
+
+            if taken {
+                let message = format!("MicroVM named {} already exists!", spec.name);
+
+                return Err(Status::already_exists(message));
+            }
+        }
+
+        let usage = usage(vms.values(), &claim.tenant);
+
+        if let Err(error) = claim.admit(&usage) {
+            return Err(Status::resource_exhausted(error.to_string()));
+        }
+
+        let mut record = VmRecord::init(uuid).await;
+
+        record.request_id = request_id.to_string();
+        record.spec = spec;
+        record.tenant = claim.tenant.to_owned();
+        record.resources = claim.resources;
+
+        self.persist(&record).await?;
+
+        vms.insert(uuid.to_string(), record.to_owned());
+
+        Ok(Reservation::Created(record))
+    }
+
+    pub(crate) async fn resolve(&self, name: &str, tenant: &str) -> String {
+        let vms = self.vms.lock().await;
+
+        vms.values()
+            .find(|record| record.spec.name == name && record.tenant == tenant && record.live())
+            .map(|record| record.uuid.to_owned())
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    pub(crate) async fn launched(&self, result: &MicroVmLaunch) -> Result<Option<VmState>, Status> {
+        let state = match result.launched.as_str() {
+            "true" => VmState::Running,
+            _ => VmState::Failed,
+        };
+
+        let updated = self
+            .update(&result.uuid, &result.node_id, Some(state), &result.details)
+            .await?;
+
+        Ok(updated.map(|record| record.state))
+    }
+
+    pub(crate) async fn shutdown(
+        &self,
+        result: &MicroVmShutdown,
+    ) -> Result<Option<VmState>, Status> {
+        let state = match result.shutdown.as_str() {
+            "true" => Some(VmState::Shutdown),
+            _ => None,
+        };
+
+        let updated = self
+            .update(&result.uuid, &result.node_id, state, &result.details)
+            .await?;
+
+        Ok(updated.and(state))
+    }
+
+    pub(crate) async fn get(&self, uuid: &str) -> Option<VmRecord> {
+        let vms = self.vms.lock().await;
+
+        vms.get(uuid).cloned()
+    }
+
+    pub(crate) async fn assign(&self, uuid: &str, node_id: &str) -> Result<bool, Status> {
+        let mut vms = self.vms.lock().await;
+
+        match vms.get_mut(uuid) {
+            Some(record) => {
+                let mut updated = record.to_owned();
+
+                updated.node_id = node_id.to_string();
+                updated.updated_at = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated;
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) async fn remove(&self, uuid: &str) -> Result<Option<VmRecord>, Status> {
+        let mut vms = self.vms.lock().await;
+
+        if let (Some(store), true) = (&self.store, vms.contains_key(uuid)) {
+            store.remove_vm(uuid).await?;
+        }
+
+        Ok(vms.remove(uuid))
+    }
+
+    pub(crate) async fn fail(&self, uuid: &str, details: &str) -> Result<Option<VmRecord>, Status> {
+        let mut vms = self.vms.lock().await;
+
+        match vms.get_mut(uuid) {
+            Some(record) if record.live() => {
+                let mut updated = record.to_owned();
+
+                updated.state = VmState::Failed;
+                updated.details = details.to_string();
+                updated.updated_at = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated.to_owned();
+
+                Ok(Some(updated))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) async fn exited(&self, exit: &MicroVmExit) -> Result<Option<VmRecord>, Status> {
+        let mut vms = self.vms.lock().await;
+
+        match vms.get_mut(&exit.uuid) {
+            Some(record) if record.live() && record.node_id == exit.node_id => {
+                let (state, details) = match (exit.restarting, exit.failed) {
+                    (true, _) => (
+                        VmState::Pending,
+                        format!(
+                            "MicroVM {}... restarting (restart {})",
+                            exit.reason, exit.restarts,
+                        ),
+                    ),
+                    (false, true) => (VmState::Failed, format!("MicroVM {}", exit.reason)),
+                    (false, false) => (VmState::Shutdown, format!("MicroVM {}", exit.reason)),
+                };
+
+                let mut updated = record.to_owned();
+
+                updated.state = state;
+                updated.details = details;
+                updated.updated_at = SystemTime::now();
+
+                self.persist(&updated).await?;
+
+                *record = updated.to_owned();
+
+                Ok(Some(updated))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) async fn confirm(
+        &self,
+        node_id: &str,
+        vm_ids: &[String],
+        outstanding: &[String],
+    ) -> Result<Confirmation, Status> {
+        let mut vms = self.vms.lock().await;
+        let mut changed = Vec::with_capacity(vm_ids.len());
+
+        for record in vms.values() {
+            if record.node_id != node_id || !record.live() {
+                continue;
+            }
+
+            let (state, details) = match (record.state, vm_ids.contains(&record.uuid)) {
+                (VmState::Pending, true) => (VmState::Running, "MicroVM was confirmed by its node"),
+                (VmState::Running, false) => {
+                    (VmState::Failed, "MicroVM was not confirmed by its node")
+                }
+                (VmState::Pending, false) if !outstanding.contains(&record.uuid) => {
+                    (VmState::Failed, "MicroVM was not found on its node")
+                }
+                _ => continue,
+            };
+
+            let mut updated = record.to_owned();
+
+            updated.state = state;
+            updated.details = details.to_string();
+            updated.updated_at = SystemTime::now();
+
+            changed.push(updated);
+        }
+
+        let unknown = vm_ids
+            .iter()
+            .filter(|uuid| {
+                !vms.get(uuid.as_str())
+                    .is_some_and(|record| record.node_id == node_id && record.live())
+            })
+            .cloned()
+            .collect();
+
+        for record in &changed {
+            self.persist(record).await?;
+
+            vms.insert(record.uuid.to_owned(), record.to_owned());
+        }
+
+        Ok(Confirmation { changed, unknown })
+    }
+
+    pub(crate) async fn list(&self) -> Vec<VmRecord> {
+        let vms = self.vms.lock().await;
+        let mut list: Vec<VmRecord> = vms.values().cloned().collect();
+
+        list.sort_by_key(|record| record.created_at);
+
+        list
+    }
+
+    pub(crate) async fn usage(&self) -> HashMap<String, Usage> {
+        let vms = self.vms.lock().await;
+        let mut usage: HashMap<String, Usage> = HashMap::with_capacity(4);
+
+        for record in vms.values().filter(|record| record.live()) {
+            usage
+                .entry(record.tenant.to_owned())
+                .or_default()
+                .add(&record.resources);
+        }
+
+        usage
+    }
+
+    pub(crate) async fn select(&self, selector: &Selector) -> Vec<VmRecord> {
+        let mut list = self.list().await;
+
+        list.retain(|record| selector.matches(&record.spec.labels));
+
+        list
+    }
+
+    pub(crate) async fn relabel(
+        &self,
+        selector: &Selector,
+        labels: &HashMap<String, String>,
+        remove: &[String],
+        tenant: Option<&str>,
+    ) -> Result<Vec<VmRecord>, Status> {
+        let mut vms = self.vms.lock().await;
+        let mut changed = Vec::with_capacity(vms.len());
+
+        for record in vms.values() {
+            let owned = tenant.is_none_or(|tenant| record.tenant == tenant);
+
+            if owned && selector.matches(&record.spec.labels) {
+                let mut updated = record.to_owned();
+
+                for key in remove {
+                    updated.spec.labels.remove(key);
+                }
+
+                updated.spec.labels.extend(labels.to_owned());
+                updated.updated_at = SystemTime::now();
+
+                changed.push(updated);
+            }
+        }
+
+        for record in &changed {
+            self.persist(record).await?;
+
+            vms.insert(record.uuid.to_owned(), record.to_owned());
+        }
+
+        changed.sort_by_key(|record| record.created_at);
+
+        Ok(changed)
+    }
+
+    async fn update(
+        &self,
+        uuid: &str,
+        node_id: &str,
+        state: Option<VmState>,
+        details: &str,
+    ) -> Result<Option<VmRecord>, Status> {
+        let mut vms = self.vms.lock().await;
+
+        let record = match vms.get_mut(uuid) {
+            Some(record) => record,
+            None => {
+                let message = format!("MicroVM {} was not found!", uuid);
+
+                return Err(Status::not_found(message));
+            }
+        };
+
+        if !record.node_id.is_empty() && record.node_id != node_id {
+            let message = format!(
+                "Node {} does not hold MicroVM {}... result rejected!",
+                node_id, uuid,
+            );
+
+            return Err(Status::permission_denied(message));
+        }
+
+        let state = match state {
+            Some(state) if record.live() => state,
+            _ => return Ok(None),
+        };
+
+        let mut updated = record.to_owned();
+
+        updated.node_id = node_id.to_string();
+        updated.state = state;
+        updated.details = details.to_string();
+        updated.updated_at = SystemTime::now();
+
+        self.persist(&updated).await?;
+
+        *record = updated.to_owned();
+
+        Ok(Some(updated))
+    }
+}
+
+fn usage<'a>(records: impl Iterator<Item = &'a VmRecord>, tenant: &str) -> Usage {
+    let mut usage = Usage::default();
+
+    for record in records.filter(|record| record.live() && record.tenant == tenant) {
+        usage.add(&record.resources);
+    }
+
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{ClusterConfig, PeerConfig, StoreConfig};
+
+    fn test_labeled(name: &str, labels: &[(&str, &str)]) -> VmSpec {
+        VmSpec {
+            name: name.to_string(),
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..VmSpec::default()
+        }
+    }
+
+    fn test_launch(uuid: &str, node_id: &str) -> MicroVmLaunch {
+        MicroVmLaunch {
+            uuid: uuid.to_string(),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: node_id.to_string(),
+        }
+    }
+
+    async fn test_assigned(
+        test_vm_registry: &VmRegistry,
+        uuid: &str,
+        node_id: &str,
+    ) -> Result<(), Status> {
+        test_vm_registry
+            .pending(uuid, "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_vm_registry.assign(uuid, node_id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        assert!(test_vm_registry.list().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launched() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        let test_reservation = test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        assert!(
+            matches!(test_reservation, Reservation::Created(test_record) if test_record.state == VmState::Pending)
+        );
+        let test_launch = MicroVmLaunch {
+            uuid: String::from("test_uuid"),
+            launched: true.to_string(),
+            details: String::from("success!"),
+            node_id: String::from("test_node"),
+        };
+        test_vm_registry.launched(&test_launch).await?;
+        let test_record = test_vm_registry.list().await.remove(0);
+        assert_eq!(test_record.state, VmState::Running);
+        assert_eq!(test_record.node_id.as_str(), "test_node");
+        let test_failed_launch = MicroVmLaunch {
+            uuid: String::from("test_failed_uuid"),
+            launched: false.to_string(),
+            details: String::from("failed!"),
+            node_id: String::from("test_node"),
+        };
+        let test_error = test_vm_registry
+            .launched(&test_failed_launch)
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.code(), tonic::Code::NotFound);
+        assert_eq!(test_vm_registry.list().await.len(), 1);
+        test_assigned(&test_vm_registry, "test_failed_uuid", "test_node").await?;
+        let test_error = test_vm_registry
+            .launched(&MicroVmLaunch {
+                node_id: String::from("test_other_node"),
+                ..test_failed_launch.to_owned()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.code(), tonic::Code::PermissionDenied);
+        assert_eq!(
+            test_vm_registry.launched(&test_failed_launch).await?,
+            Some(VmState::Failed),
+        );
+        let test_record = test_vm_registry.list().await.remove(1);
+        assert_eq!(test_record.state, VmState::Failed);
+        assert!(test_vm_registry
+            .launched(&MicroVmLaunch {
+                launched: true.to_string(),
+                ..test_failed_launch.to_owned()
+            })
+            .await?
+            .is_none());
+        let test_record = test_vm_registry.get("test_failed_uuid").await.unwrap();
+        assert_eq!(test_record.state, VmState::Failed);
+        assert_eq!(test_vm_registry.list().await.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shutdown() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        let test_failed_shutdown = MicroVmShutdown {
+            uuid: String::from("test_uuid"),
+            shutdown: false.to_string(),
+            details: String::from("MicroVM was not found!"),
+            node_id: String::from("test_node"),
+        };
+        test_vm_registry.shutdown(&test_failed_shutdown).await?;
+        let test_record = test_vm_registry.list().await.remove(0);
+        assert_eq!(test_record.state, VmState::Pending);
+        let test_shutdown = MicroVmShutdown {
+            shutdown: true.to_string(),
+            ..test_failed_shutdown
+        };
+        test_vm_registry.shutdown(&test_shutdown).await?;
+        let test_record = test_vm_registry.list().await.remove(0);
+        assert_eq!(test_record.state, VmState::Shutdown);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn confirm() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        for test_uuid in ["test_uuid_a", "test_uuid_b", "test_uuid_h"] {
+            test_assigned(&test_vm_registry, test_uuid, "test_node").await?;
+            test_vm_registry
+                .launched(&test_launch(test_uuid, "test_node"))
+                .await?;
+        }
+        test_vm_registry
+            .shutdown(&MicroVmShutdown {
+                uuid: String::from("test_uuid_h"),
+                shutdown: true.to_string(),
+                details: String::new(),
+                node_id: String::from("test_node"),
+            })
+            .await?;
+        test_assigned(&test_vm_registry, "test_uuid_c", "test_other_node").await?;
+        test_vm_registry
+            .launched(&test_launch("test_uuid_c", "test_other_node"))
+            .await?;
+        for test_uuid in ["test_uuid_e", "test_uuid_f", "test_uuid_g"] {
+            test_assigned(&test_vm_registry, test_uuid, "test_node").await?;
+        }
+        let test_confirmed: Vec<String> = ["a", "c", "d", "e", "h"]
+            .iter()
+            .map(|test_suffix| format!("test_uuid_{}", test_suffix))
+            .collect();
+        let test_outstanding = vec![String::from("test_uuid_g")];
+        let test_confirmation = test_vm_registry
+            .confirm("test_node", &test_confirmed, &test_outstanding)
+            .await?;
+        assert_eq!(test_confirmation.changed.len(), 3);
+        assert_eq!(
+            test_confirmation.unknown,
+            vec![
+                String::from("test_uuid_c"),
+                String::from("test_uuid_d"),
+                String::from("test_uuid_h"),
+            ],
+        );
+        let test_lost = test_vm_registry.get("test_uuid_b").await.unwrap();
+        assert_eq!(test_lost.state, VmState::Failed);
+        let test_started = test_vm_registry.get("test_uuid_e").await.unwrap();
+        assert_eq!(test_started.state, VmState::Running);
+        let test_missing = test_vm_registry.get("test_uuid_f").await.unwrap();
+        assert_eq!(test_missing.state, VmState::Failed);
+        let test_queued = test_vm_registry.get("test_uuid_g").await.unwrap();
+        assert_eq!(test_queued.state, VmState::Pending);
+        let test_stopped = test_vm_registry.get("test_uuid_h").await.unwrap();
+        assert_eq!(test_stopped.state, VmState::Shutdown);
+        let test_untouched = test_vm_registry.get("test_uuid_c").await.unwrap();
+        assert_eq!(test_untouched.state, VmState::Running);
+        assert_eq!(test_untouched.node_id.as_str(), "test_other_node");
+        assert!(test_vm_registry.get("test_uuid_d").await.is_none());
+        assert!(test_vm_registry
+            .confirm("test_node", &test_confirmed, &test_outstanding)
+            .await?
+            .changed
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pending() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        let test_spec = test_labeled("test_name", &[]);
+        let test_created = test_vm_registry
+            .pending(
+                "test_uuid_a",
+                "test_request",
+                test_spec.to_owned(),
+                &Claim::default(),
+            )
+            .await?;
+        assert!(matches!(test_created, Reservation::Created(_)));
+        let test_repeat = test_vm_registry
+            .pending(
+                "test_uuid_b",
+                "test_request",
+                test_spec.to_owned(),
+                &Claim::default(),
+            )
+            .await?;
+        assert!(
+            matches!(test_repeat, Reservation::Existing(test_record) if test_record.uuid == "test_uuid_a")
+        );
+        let test_conflict = test_vm_registry
+            .pending(
+                "test_uuid_b",
+                "test_request",
+                VmSpec::default(),
+                &Claim::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(test_conflict.code(), tonic::Code::AlreadyExists);
+        let test_taken = test_vm_registry
+            .pending(
+                "test_uuid_b",
+                "test_other_request",
+                test_spec.to_owned(),
+                &Claim::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(test_taken.code(), tonic::Code::AlreadyExists);
+        assert_eq!(
+            test_vm_registry.resolve("test_name", "").await.as_str(),
+            "test_uuid_a"
+        );
+        test_vm_registry
+            .launched(&MicroVmLaunch {
+                launched: false.to_string(),
+                ..test_launch("test_uuid_a", "test_node")
+            })
+            .await?;
+        assert_eq!(
+            test_vm_registry.resolve("test_name", "").await.as_str(),
+            "test_name"
+        );
+        let test_reused = test_vm_registry
+            .pending("test_uuid_b", "", test_spec.to_owned(), &Claim::default())
+            .await?;
+        assert!(matches!(test_reused, Reservation::Created(_)));
+        assert_eq!(test_vm_registry.list().await.len(), 2);
+        let test_tenant = Claim {
+            tenant: String::from("test_team"),
+            ..Claim::default()
+        };
+        let test_other_request = test_vm_registry
+            .pending(
+                "test_uuid_c",
+                "test_request",
+                VmSpec::default(),
+                &test_tenant,
+            )
+            .await?;
+        assert!(matches!(test_other_request, Reservation::Created(_)));
+        let test_other_name = test_vm_registry
+            .pending("test_uuid_d", "", test_spec.to_owned(), &test_tenant)
+            .await?;
+        assert!(matches!(test_other_name, Reservation::Created(_)));
+        let test_taken = test_vm_registry
+            .pending("test_uuid_e", "", test_spec, &test_tenant)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            test_taken.message(),
+            "MicroVM named test_name already exists!"
+        );
+        assert_eq!(
+            test_vm_registry
+                .resolve("test_name", "test_team")
+                .await
+                .as_str(),
+            "test_uuid_d"
+        );
+        assert_eq!(
+            test_vm_registry.resolve("test_name", "").await.as_str(),
+            "test_uuid_b"
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn assign() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        assert!(!test_vm_registry.assign("test_uuid", "test_node").await?);
+        test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        assert!(test_vm_registry.assign("test_uuid", "test_node").await?);
+        let test_record = test_vm_registry.get("test_uuid").await.unwrap();
+        assert_eq!(test_record.node_id.as_str(), "test_node");
+        assert_eq!(test_record.state, VmState::Pending);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fail() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        assert!(test_vm_registry.fail("test_uuid", "lost").await?.is_none());
+        test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await?;
+        let test_record = test_vm_registry.fail("test_uuid", "lost").await?.unwrap();
+        assert_eq!(test_record.state, VmState::Failed);
+        assert_eq!(test_record.details.as_str(), "lost");
+        assert!(test_vm_registry.fail("test_uuid", "lost").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exited() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        let mut test_exit = MicroVmExit {
+            node_id: String::from("test_node"),
+            uuid: String::from("test_uuid"),
+            reason: String::from("exited with status 1"),
+            exit_code: 1,
+            failed: true,
+            restarting: true,
+            restarts: 1,
+        };
+        assert!(test_vm_registry.exited(&test_exit).await?.is_none());
+        test_assigned(&test_vm_registry, "test_uuid", "test_node").await?;
+        test_vm_registry
+            .launched(&test_launch("test_uuid", "test_node"))
+            .await?;
+        let test_record = test_vm_registry.exited(&test_exit).await?.unwrap();
+        assert_eq!(test_record.state, VmState::Pending);
+        assert_eq!(
+            test_record.details.as_str(),
+            "MicroVM exited with status 1... restarting (restart 1)",
+        );
+        test_exit.node_id = String::from("test_other_node");
+        assert!(test_vm_registry.exited(&test_exit).await?.is_none());
+        test_exit.node_id = String::from("test_node");
+        test_exit.restarting = false;
+        let test_record = test_vm_registry.exited(&test_exit).await?.unwrap();
+        assert_eq!(test_record.state, VmState::Failed);
+        assert_eq!(test_record.details.as_str(), "MicroVM exited with status 1");
+        assert!(test_vm_registry.exited(&test_exit).await?.is_none());
+        test_assigned(&test_vm_registry, "test_clean_uuid", "test_node").await?;
+        test_vm_registry
+            .launched(&test_launch("test_clean_uuid", "test_node"))
+            .await?;
+        test_exit.uuid = String::from("test_clean_uuid");
+        test_exit.failed = false;
+        test_exit.reason = String::from("exited cleanly");
+        let test_record = test_vm_registry.exited(&test_exit).await?.unwrap();
+        assert_eq!(test_record.state, VmState::Shutdown);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn select_and_relabel() -> Result<(), Box<dyn std::error::Error>> {
+        let test_vm_registry = VmRegistry::init().await?;
+        test_vm_registry
+            .pending(
+                "test_uuid_a",
+                "",
+                test_labeled("", &[("env", "prod"), ("tier", "web")]),
+                &Claim::default(),
+            )
+            .await?;
+        test_vm_registry
+            .pending(
+                "test_uuid_b",
+                "",
+                test_labeled("", &[("env", "prod"), ("tier", "db")]),
+                &Claim::default(),
+            )
+            .await?;
+        test_vm_registry
+            .pending(
+                "test_uuid_c",
+                "",
+                test_labeled("", &[("env", "dev")]),
+                &Claim::default(),
+            )
+            .await?;
+        let test_selector: Selector = "env=prod".parse()?;
+        assert_eq!(test_vm_registry.select(&test_selector).await.len(), 2);
+        let test_selector: Selector = "env=prod,tier notin (db)".parse()?;
+        let test_selected = test_vm_registry.select(&test_selector).await;
+        assert_eq!(test_selected.len(), 1);
+        assert_eq!(test_selected[0].uuid.as_str(), "test_uuid_a");
+        assert_eq!(test_vm_registry.select(&Selector::default()).await.len(), 3);
+        let test_selector: Selector = "env=prod".parse()?;
+        let test_labels = HashMap::from([(String::from("owner"), String::from("ops"))]);
+        let test_changed = test_vm_registry
+            .relabel(&test_selector, &test_labels, &[], Some("test_team"))
+            .await?;
+        assert!(test_changed.is_empty());
+        let test_changed = test_vm_registry
+            .relabel(&test_selector, &test_labels, &[String::from("tier")], None)
+            .await?;
+        assert_eq!(test_changed.len(), 2);
+        assert!(test_changed
+            .iter()
+            .all(|test_record| test_record.spec.labels.len() == 2
+                && test_record.spec.labels["owner"] == "ops"));
+        let test_selector: Selector = "owner=ops,!tier".parse()?;
+        assert_eq!(test_vm_registry.select(&test_selector).await.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error>> {
+        let test_store = Arc::new(Store::init(&StoreConfig { path: None }).await?);
+        let test_vm_registry = VmRegistry::restore(test_store.to_owned()).await?;
+        test_vm_registry
+            .pending("test_uuid_a", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_assigned(&test_vm_registry, "test_uuid_b", "test_node").await?;
+        test_vm_registry
+            .launched(&test_launch("test_uuid_b", "test_node"))
+            .await?;
+        test_vm_registry
+            .pending("test_uuid_c", "", VmSpec::default(), &Claim::default())
+            .await?;
+        test_vm_registry.remove("test_uuid_c").await?;
+        drop(test_vm_registry);
+        let test_vm_registry = VmRegistry::restore(test_store).await?;
+        let test_list = test_vm_registry.list().await;
+        assert_eq!(test_list.len(), 2);
+        assert_eq!(test_list[0].state, VmState::Pending);
+        assert_eq!(test_list[1].state, VmState::Running);
+        assert_eq!(test_list[1].node_id.as_str(), "test_node");
+        assert_eq!(test_list[1].details.as_str(), "success!");
+        Ok(())
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unreplicated() -> Result<(), Box<dyn std::error::Error>> {
+        let test_cluster = ClusterConfig {
+            id: String::from("test_interface_a"),
+            peers: vec![PeerConfig {
+                id: String::from("test_interface_b"),
+                endpoint: String::from("http://127.0.0.1:1"),
+                external: String::from("http://127.0.0.1:1/external"),
+                internal: String::from("http://127.0.0.1:1/internal"),
+            }],
+            ..ClusterConfig::default()
+        };
+        let test_store =
+            Arc::new(Store::clustered(&StoreConfig { path: None }, &test_cluster).await?);
+        let test_vm_registry = VmRegistry::restore(test_store.to_owned()).await?;
+        let test_unavailable = test_vm_registry
+            .pending("test_uuid", "", VmSpec::default(), &Claim::default())
+            .await
+            .unwrap_err();
+        assert_eq!(test_unavailable.code(), tonic::Code::Unavailable);
+        assert!(test_vm_registry.list().await.is_empty());
+        let test_missing = test_vm_registry
+            .launched(&test_launch("test_uuid", "test_node"))
+            .await
+            .unwrap_err();
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        assert!(test_vm_registry.get("test_uuid").await.is_none());
+        assert!(test_store.vms().await?.is_empty());
+        Ok(())
+    }
+}